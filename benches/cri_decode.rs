@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Benchmarks for decode functions (Criterion)
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -10,8 +10,8 @@ use criterion::{Criterion, criterion_group, criterion_main};
 use gufo_snmp::ber::BerDecoder;
 use gufo_snmp::ber::{
     BerHeader, SnmpBool, SnmpCounter32, SnmpCounter64, SnmpGauge32, SnmpInt, SnmpIpAddress,
-    SnmpNull, SnmpObjectDescriptor, SnmpOctetString, SnmpOid, SnmpOpaque, SnmpReal,
-    SnmpRelativeOid, SnmpTimeTicks, SnmpUInteger32,
+    SnmpNsapAddress, SnmpNull, SnmpObjectDescriptor, SnmpOctetString, SnmpOid, SnmpOpaque,
+    SnmpReal, SnmpRelativeOid, SnmpTimeTicks, SnmpUInteger32,
 };
 use gufo_snmp::snmp::msg::SnmpV2cMessage;
 
@@ -114,6 +114,13 @@ pub fn bench_octetstring(c: &mut Criterion) {
     });
 }
 
+pub fn bench_nsapaddress(c: &mut Criterion) {
+    let data = [0x45, 5, 0x47, 0, 5, 0x80, 0xff];
+    c.bench_function("decode NsapAddress", |b| {
+        b.iter(|| SnmpNsapAddress::from_ber(&data))
+    });
+}
+
 pub fn bench_opaque(c: &mut Criterion) {
     let data = [0x44, 5, 0, 1, 2, 3, 4];
     c.bench_function("decode Opaque", |b| b.iter(|| SnmpOpaque::from_ber(&data)));
@@ -165,6 +172,7 @@ criterion_group!(
     bench_gauge32,
     bench_int,
     bench_ipaddress,
+    bench_nsapaddress,
     bench_null,
     bench_objectdescriptor,
     bench_oid,
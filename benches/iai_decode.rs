@@ -1,15 +1,15 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Benchmarks for decode functions (Iai)
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use gufo_snmp::ber::BerDecoder;
 use gufo_snmp::ber::{
     BerHeader, SnmpBool, SnmpCounter32, SnmpCounter64, SnmpGauge32, SnmpInt, SnmpIpAddress,
-    SnmpNull, SnmpObjectDescriptor, SnmpOctetString, SnmpOid, SnmpOpaque, SnmpReal,
-    SnmpRelativeOid, SnmpTimeTicks, SnmpUInteger32,
+    SnmpNsapAddress, SnmpNull, SnmpObjectDescriptor, SnmpOctetString, SnmpOid, SnmpOpaque,
+    SnmpReal, SnmpRelativeOid, SnmpTimeTicks, SnmpUInteger32,
 };
 use gufo_snmp::snmp::msg::SnmpV2cMessage;
 use iai::black_box;
@@ -97,6 +97,11 @@ pub fn decode_octetstring() {
     let _ = SnmpOctetString::from_ber(black_box(&data));
 }
 
+pub fn decode_nsapaddress() {
+    let data = [0x45, 5, 0x47, 0, 5, 0x80, 0xff];
+    let _ = SnmpNsapAddress::from_ber(black_box(&data));
+}
+
 pub fn decode_opaque() {
     let data = [0x44, 5, 0, 1, 2, 3, 4];
     let _ = SnmpOpaque::from_ber(black_box(&data));
@@ -141,6 +146,7 @@ iai::main!(
     decode_gauge32,
     decode_int,
     decode_ipaddress,
+    decode_nsapaddress,
     decode_null,
     decode_objectdescriptor,
     decode_oid,
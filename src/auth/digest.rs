@@ -1,11 +1,11 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Generic HMAC implementation
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 use super::SnmpAuth;
-use crate::error::SnmpResult;
+use crate::error::{SnmpError, SnmpResult};
 use digest::Digest;
 use std::marker::PhantomData;
 
@@ -122,4 +122,18 @@ impl<D: Digest, const KS: usize, const SS: usize> SnmpAuth for DigestAuth<D, KS,
         data[offset..offset + SS].copy_from_slice(&d2[0..SS]);
         Ok(())
     }
+    fn verify(&self, data: &[u8], offset: usize) -> SnmpResult<()> {
+        if data.len() < offset + SS {
+            return Err(SnmpError::AuthenticationFailed);
+        }
+        // RFC-3414, pp. 6.3.2. Processing an incoming message
+        // Replace msgAuthenticationParameters with zeroes and calculate MAC
+        let mut msg = data.to_vec();
+        msg[offset..offset + SS].copy_from_slice(&ZEROES[..SS]);
+        self.sign(&mut msg, offset)?;
+        if msg[offset..offset + SS] != data[offset..offset + SS] {
+            return Err(SnmpError::AuthenticationFailed);
+        }
+        Ok(())
+    }
 }
@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP v3 Auth primitives
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -51,6 +51,8 @@ pub trait SnmpAuth {
     fn placeholder(&self) -> &'static [u8];
     // Sign data in buffer
     fn sign(&self, data: &mut [u8], offset: usize) -> SnmpResult<()>;
+    // Verify signature of the received message
+    fn verify(&self, data: &[u8], offset: usize) -> SnmpResult<()>;
 }
 
 // - - X X    X X X X
@@ -118,6 +120,30 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn test_md5_verify() -> SnmpResult<()> {
+        let mut whole_msg = [
+            48u8, 119, 2, 1, 3, 48, 16, 2, 4, 31, 120, 150, 153, 2, 2, 5, 220, 4, 1, 1, 2, 1, 3, 4,
+            47, 48, 45, 4, 13, 128, 0, 31, 136, 4, 50, 55, 103, 83, 56, 54, 116, 100, 2, 1, 0, 2,
+            1, 0, 4, 6, 117, 115, 101, 114, 49, 48, 4, 12, 18, 138, 173, 156, 223, 188, 26, 178,
+            137, 113, 25, 22, 4, 0, 48, 47, 4, 13, 128, 0, 31, 136, 4, 50, 55, 103, 83, 56, 54,
+            116, 100, 4, 0, 160, 28, 2, 4, 80, 85, 225, 64, 2, 1, 0, 2, 1, 0, 48, 14, 48, 12, 6, 8,
+            43, 6, 1, 2, 1, 1, 4, 0, 5, 0,
+        ];
+        let offset = 58;
+        let master_key = [117u8, 115, 101, 114, 49, 48, 107, 101, 121]; // user10key
+        let engine_id = [128, 0, 31, 136, 4, 50, 55, 103, 83, 56, 54, 116, 100];
+        let mut auth_key = Md5AuthKey::default();
+        auth_key.as_master(&master_key, &engine_id);
+        auth_key.verify(&whole_msg, offset)?;
+        // Tamper request-id
+        whole_msg[94] ^= 1;
+        assert!(matches!(
+            auth_key.verify(&whole_msg, offset),
+            Err(SnmpError::AuthenticationFailed)
+        ));
+        Ok(())
+    }
+    #[test]
     fn test_sha1_sign() -> SnmpResult<()> {
         let mut whole_msg = [
             48, 119, 2, 1, 3, 48, 16, 2, 4, 31, 120, 150, 153, 2, 2, 5, 220, 4, 1, 1, 2, 1, 3, 4,
@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP v3 No Auth
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -34,4 +34,7 @@ impl SnmpAuth for NoAuth {
     fn sign(&self, _data: &mut [u8], _offset: usize) -> SnmpResult<()> {
         Ok(())
     }
+    fn verify(&self, _data: &[u8], _offset: usize) -> SnmpResult<()> {
+        Ok(())
+    }
 }
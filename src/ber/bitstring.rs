@@ -1,14 +1,17 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BIT STRING type
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_BIT_STRING, Tag};
+use super::{BerDecoder, BerEncoder, BerHeader, TAG_BIT_STRING, Tag};
+use crate::buf::Buffer;
 use crate::error::{SnmpError, SnmpResult};
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 
+#[derive(Clone)]
 pub struct SnmpBitString(u64);
 
 impl<'a> BerDecoder<'a> for SnmpBitString {
@@ -37,6 +40,21 @@ impl<'a> BerDecoder<'a> for SnmpBitString {
     }
 }
 
+impl BerEncoder for SnmpBitString {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        // X.690 pp 8.6.2: <unused bits> <payload>
+        let start = buf.len();
+        let mut left = self.0;
+        while left > 0 {
+            buf.push_u8((left & 0xff) as u8)?;
+            left >>= 8;
+        }
+        buf.push_u8(0)?;
+        buf.push_tag_len(TAG_BIT_STRING, buf.len() - start)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpBitString {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -47,6 +65,12 @@ impl<'py> IntoPyObject<'py> for &SnmpBitString {
     }
 }
 
+impl From<u64> for SnmpBitString {
+    fn from(value: u64) -> Self {
+        SnmpBitString(value)
+    }
+}
+
 impl From<SnmpBitString> for u64 {
     fn from(value: SnmpBitString) -> Self {
         value.0
@@ -73,4 +97,14 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpBitString(0x0A3B5F291CD).push_ber(&mut buf)?;
+        let (tail, v) = SnmpBitString::from_ber(buf.data())?;
+        assert_eq!(tail.len(), 0);
+        assert_eq!(v.0, 0x0A3B5F291CD);
+        Ok(())
+    }
 }
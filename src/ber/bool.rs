@@ -1,14 +1,17 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BER BOOLEAN class
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_BOOL, Tag};
+use super::{BerDecoder, BerEncoder, BerHeader, TAG_BOOL, Tag};
+use crate::buf::Buffer;
 use crate::error::{SnmpError, SnmpResult};
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python, types::PyBool};
 
+#[derive(Clone)]
 pub struct SnmpBool(bool);
 
 impl<'a> BerDecoder<'a> for SnmpBool {
@@ -25,12 +28,25 @@ impl<'a> BerDecoder<'a> for SnmpBool {
     }
 }
 
+impl BerEncoder for SnmpBool {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        buf.push(&[TAG_BOOL, 1, if self.0 { 0xff } else { 0 }])
+    }
+}
+
+impl From<bool> for SnmpBool {
+    fn from(value: bool) -> Self {
+        SnmpBool(value)
+    }
+}
+
 impl From<SnmpBool> for bool {
     fn from(value: SnmpBool) -> Self {
         value.0
     }
 }
 
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpBool {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -63,4 +79,12 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpBool(true).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &[1, 1, 0xff]);
+        Ok(())
+    }
 }
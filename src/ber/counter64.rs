@@ -1,14 +1,21 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Application Class Counter64
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_APP_COUNTER64, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{
+    BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_COUNTER64, Tag, push_unsigned,
+};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 
+#[derive(Clone)]
 pub struct SnmpCounter64(pub(crate) u64);
 
 impl<'a> BerDecoder<'a> for SnmpCounter64 {
@@ -28,6 +35,13 @@ impl<'a> BerDecoder<'a> for SnmpCounter64 {
     }
 }
 
+impl BerEncoder for SnmpCounter64 {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        push_unsigned(buf, BER_CLASS_APPLICATION | TAG_APP_COUNTER64, self.0)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpCounter64 {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -58,4 +72,15 @@ mod tests {
         assert_eq!(tt.0, 0x013510AB);
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpCounter64(0x0102030405060708).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &[0x46, 8, 1, 2, 3, 4, 5, 6, 7, 8]);
+        let (tail, v) = SnmpCounter64::from_ber(buf.data())?;
+        assert_eq!(tail.len(), 0);
+        assert_eq!(v.0, 0x0102030405060708);
+        Ok(())
+    }
 }
@@ -1,14 +1,21 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Application Class Gauge32
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_APP_GAUGE32, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{
+    BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_GAUGE32, Tag, push_unsigned,
+};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 
+#[derive(Clone)]
 pub struct SnmpGauge32(pub(crate) u32);
 
 impl<'a> BerDecoder<'a> for SnmpGauge32 {
@@ -28,6 +35,13 @@ impl<'a> BerDecoder<'a> for SnmpGauge32 {
     }
 }
 
+impl BerEncoder for SnmpGauge32 {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        push_unsigned(buf, BER_CLASS_APPLICATION | TAG_APP_GAUGE32, self.0 as u64)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpGauge32 {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -58,4 +72,15 @@ mod tests {
         assert_eq!(tt.0, 0x013510AB);
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpGauge32(0x013510AB).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &[0x42, 4, 1, 53, 16, 171]);
+        let (tail, v) = SnmpGauge32::from_ber(buf.data())?;
+        assert_eq!(tail.len(), 0);
+        assert_eq!(v.0, 0x013510AB);
+        Ok(())
+    }
 }
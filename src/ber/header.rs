@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BerHeader class
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -66,12 +66,15 @@ impl BerHeader {
         // bits 5 - 1 tag number
         let tag = match id_octets & 0x1f {
             0x1f => {
-                // > 30
+                // > 30, X.690 pp 8.1.2.4
                 let mut n = 0 as Tag;
                 loop {
-                    // @todo: check size
-                    let t = i[current];
+                    let t = next_octet(i, current)?;
                     current += 1;
+                    if n > Tag::MAX >> 7 {
+                        // Tag number does not fit
+                        return Err(Err::Failure(SnmpError::InvalidTagFormat));
+                    }
                     n = (n << 7) | ((t & 0x7f) as Tag);
                     if t & 0x80 == 0 {
                         break;
@@ -84,16 +87,19 @@ impl BerHeader {
         // Parse length offset
         // X.690 8.3.1.4-8.3.1.5
         // @todo: Indefinite length
-        let n = i[current];
+        let n = next_octet(i, current)?;
         current += 1;
         let length = if n & 0x80 == 0 {
             // Short form, X.690 pp 8.3.1.4
             n as usize
         } else {
             // Long form, X.690 pp 8.1.3.5
-            let mut ln = 0;
+            let mut ln = 0usize;
             for _ in 0..n & 0x7f {
-                ln = (ln << 8) + (i[current] as usize);
+                ln = ln
+                    .checked_mul(256)
+                    .ok_or(Err::Failure(SnmpError::InvalidTagFormat))?
+                    + (next_octet(i, current)? as usize);
                 current += 1;
             }
             ln
@@ -117,6 +123,12 @@ impl BerHeader {
     }
 }
 
+// Get octet at position, or report truncated header
+#[inline]
+fn next_octet(i: &[u8], pos: usize) -> Result<u8, Err<SnmpError>> {
+    i.get(pos).copied().ok_or(Err::Incomplete(Needed::new(1)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,6 +142,34 @@ mod tests {
         assert!(r.is_err());
     }
 
+    // Truncated long tag and long length
+    #[test]
+    fn test_truncated_header() {
+        assert!(BerHeader::from_ber(&[0x1f, 0x81]).is_err());
+        assert!(BerHeader::from_ber(&[0x04, 0x82, 0x01]).is_err());
+        assert!(BerHeader::from_ber(&[0x04, 0x89, 1, 2, 3, 4, 5, 6, 7, 8, 9]).is_err());
+    }
+
+    // Long-form tag, net-snmp Opaque Float
+    #[test]
+    fn test_header_long_tag() -> SnmpResult<()> {
+        let data = [0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a];
+        let (tail, hdr) = BerHeader::from_ber(&data)?;
+        assert_eq!(hdr.class, BerClass::Context);
+        assert!(!hdr.constructed);
+        assert_eq!(hdr.tag, 0x78);
+        assert_eq!(hdr.length, 4);
+        assert_eq!(tail, &data[3..]);
+        Ok(())
+    }
+
+    // Tag number overflow
+    #[test]
+    fn test_header_long_tag_overflow() {
+        let r = BerHeader::from_ber(&[0x9f, 0x82, 0x00, 0]);
+        assert!(matches!(r, Err(Err::Failure(SnmpError::InvalidTagFormat))));
+    }
+
     // Null, zero-length content
     #[test]
     fn test_header_null() -> SnmpResult<()> {
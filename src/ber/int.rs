@@ -1,16 +1,20 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BER INTEGER Class
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use super::{BerDecoder, BerEncoder, BerHeader, TAG_INT, Tag};
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 use std::cmp::Ordering;
 
+#[derive(Clone)]
 pub struct SnmpInt(i64);
 
 impl<'a> BerDecoder<'a> for SnmpInt {
@@ -125,6 +129,7 @@ impl SnmpInt {
     }
 }
 
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpInt {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
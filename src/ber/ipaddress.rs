@@ -1,14 +1,18 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Application Class IpAddress
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_APP_IPADDRESS, Tag};
+use super::{BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_IPADDRESS, Tag};
+use crate::buf::Buffer;
 use crate::error::{SnmpError, SnmpResult};
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python, types::PyString};
+use std::net::Ipv4Addr;
 
+#[derive(Clone)]
 pub struct SnmpIpAddress(u8, u8, u8, u8);
 
 impl<'a> BerDecoder<'a> for SnmpIpAddress {
@@ -25,12 +29,39 @@ impl<'a> BerDecoder<'a> for SnmpIpAddress {
     }
 }
 
+impl BerEncoder for SnmpIpAddress {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        buf.push(&[
+            BER_CLASS_APPLICATION | TAG_APP_IPADDRESS,
+            4,
+            self.0,
+            self.1,
+            self.2,
+            self.3,
+        ])
+    }
+}
+
+impl From<Ipv4Addr> for SnmpIpAddress {
+    fn from(value: Ipv4Addr) -> Self {
+        let [a, b, c, d] = value.octets();
+        SnmpIpAddress(a, b, c, d)
+    }
+}
+
 impl From<&SnmpIpAddress> for String {
     fn from(value: &SnmpIpAddress) -> Self {
         format!("{}.{}.{}.{}", value.0, value.1, value.2, value.3)
     }
 }
 
+impl From<&SnmpIpAddress> for Ipv4Addr {
+    fn from(value: &SnmpIpAddress) -> Self {
+        Ipv4Addr::new(value.0, value.1, value.2, value.3)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpIpAddress {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -64,4 +95,12 @@ mod tests {
         let s: String = ip.into();
         assert_eq!(s, "127.0.0.1");
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpIpAddress(127, 0, 0, 1).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &[0x40, 0x4, 127, 0, 0, 1]);
+        Ok(())
+    }
 }
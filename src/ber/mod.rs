@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BER module definition
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -17,6 +17,10 @@ pub enum BerClass {
 
 pub type Tag = u8;
 
+// Identifier octets class bits, X.690 pp 8.1.2.2
+pub const BER_CLASS_APPLICATION: u8 = 0x40;
+pub const BER_CLASS_CONTEXT: u8 = 0x80;
+
 // BER Tags
 // pub const TAG_END_OF_CONTENTS: Tag = 0x0;
 pub const TAG_BOOL: Tag = 0x1;
@@ -35,13 +39,20 @@ pub const TAG_APP_COUNTER32: Tag = 1;
 pub const TAG_APP_GAUGE32: Tag = 2;
 pub const TAG_APP_TIMETICKS: Tag = 3;
 pub const TAG_APP_OPAQUE: Tag = 4;
-// pub const TAG_APP_NSAPADDRESS: Tag = 5;
+pub const TAG_APP_NSAPADDRESS: Tag = 5;
 pub const TAG_APP_COUNTER64: Tag = 6;
 pub const TAG_APP_UINTEGER32: Tag = 7;
 // SNMP Context Tags
 pub const TAG_CTX_NO_SUCH_OBJECT: Tag = 0;
 pub const TAG_CTX_NO_SUCH_INSTANCE: Tag = 1;
 pub const TAG_CTX_END_OF_MIB_VIEW: Tag = 2;
+// net-snmp Opaque-wrapped types, context-specific long-form tags.
+// 0x30 + application tag of the type, see net-snmp's asn1.h
+pub const TAG_OPAQUE_COUNTER64: Tag = 0x76;
+pub const TAG_OPAQUE_FLOAT: Tag = 0x78;
+pub const TAG_OPAQUE_DOUBLE: Tag = 0x79;
+pub const TAG_OPAQUE_INT64: Tag = 0x7a;
+pub const TAG_OPAQUE_UINT64: Tag = 0x7b;
 
 pub mod header;
 pub use header::BerHeader;
@@ -78,6 +89,10 @@ pub mod timeticks;
 pub use timeticks::SnmpTimeTicks;
 pub mod opaque;
 pub use opaque::SnmpOpaque;
+pub mod opaquevalue;
+pub use opaquevalue::SnmpOpaqueValue;
+pub mod nsapaddress;
+pub use nsapaddress::SnmpNsapAddress;
 pub mod counter64;
 pub use counter64::SnmpCounter64;
 pub mod uinteger32;
@@ -97,19 +112,24 @@ where
 
     fn from_ber(i: &'a [u8]) -> IResult<&'a [u8], Self, SnmpError> {
         if i.len() < 2 {
-            return Err(Err::Failure(SnmpError::Incomplete));
+            return Err(Err::Failure(SnmpError::Incomplete.at(i)));
         }
-        let (tail, hdr) = BerHeader::from_ber(i)?;
+        let (tail, hdr) =
+            BerHeader::from_ber(i).map_err(|e| Err::Failure(SnmpError::from(e).at(i)))?;
         if hdr.tag != Self::TAG
             || (hdr.constructed && !Self::ALLOW_CONSTRUCTED)
             || (!hdr.constructed && !Self::ALLOW_PRIMITIVE)
         {
-            return Err(Err::Failure(SnmpError::UnexpectedTag));
+            return Err(Err::Failure(
+                SnmpError::UnexpectedTag
+                    .at(i)
+                    .with_tags(Some(Self::TAG), hdr.tag),
+            ));
         }
         //
         Ok((
             &tail[hdr.length..],
-            Self::decode(tail, &hdr).map_err(Err::Failure)?,
+            Self::decode(tail, &hdr).map_err(|e| Err::Failure(e.at(i)))?,
         ))
     }
 }
@@ -117,3 +137,22 @@ where
 pub trait BerEncoder {
     fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()>;
 }
+
+// Push unsigned integer with given identifier octet.
+// Leading zero octet is added when the highest bit is set,
+// to prevent the value to be treated as negative.
+pub(crate) fn push_unsigned(buf: &mut Buffer, id: u8, value: u64) -> SnmpResult<()> {
+    let start = buf.len();
+    let mut left = value;
+    loop {
+        buf.push_u8((left & 0xff) as u8)?;
+        left >>= 8;
+        if left == 0 {
+            break;
+        }
+    }
+    if buf.data()[0] & 0x80 != 0 {
+        buf.push_u8(0)?;
+    }
+    buf.push_tag_len(id, buf.len() - start)
+}
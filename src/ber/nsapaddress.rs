@@ -0,0 +1,69 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: SNMP Application Class NsapAddress
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::{BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_NSAPADDRESS, Tag};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
+use pyo3::{Bound, IntoPyObject, PyAny, Python, types::PyBytes};
+
+#[derive(Clone)]
+pub struct SnmpNsapAddress<'a>(pub(crate) &'a [u8]);
+
+impl<'a> BerDecoder<'a> for SnmpNsapAddress<'a> {
+    const ALLOW_PRIMITIVE: bool = true;
+    const ALLOW_CONSTRUCTED: bool = false;
+    const TAG: Tag = TAG_APP_NSAPADDRESS;
+
+    // Implement RFC-1442 pp 7.1.10: OCTET STRING (SIZE (1 | 4..21)).
+    // Size is not enforced to tolerate the legacy agents.
+    fn decode(i: &'a [u8], h: &BerHeader) -> SnmpResult<Self> {
+        Ok(SnmpNsapAddress(&i[..h.length]))
+    }
+}
+
+impl BerEncoder for SnmpNsapAddress<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        buf.push_tagged(BER_CLASS_APPLICATION | TAG_APP_NSAPADDRESS, self.0)
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for &SnmpNsapAddress<'_> {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = SnmpError;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(PyBytes::new(py, self.0).into_any())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ber() -> SnmpResult<()> {
+        let data = [0x45, 5, 0x47, 0, 5, 0x80, 0xff];
+        let (tail, s) = SnmpNsapAddress::from_ber(&data)?;
+        assert_eq!(tail.len(), 0);
+        assert_eq!(s.0, &data[2..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let data = [0x45, 5, 0x47, 0, 5, 0x80, 0xff];
+        let mut buf = Buffer::default();
+        SnmpNsapAddress(&data[2..]).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &data);
+        Ok(())
+    }
+}
@@ -1,14 +1,19 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: OBJECT DESCRIPTOR type
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_OBJECT_DESCRIPTOR, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{BerDecoder, BerEncoder, BerHeader, TAG_OBJECT_DESCRIPTOR, Tag};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python, types::PyBytes};
 
+#[derive(Clone)]
 pub struct SnmpObjectDescriptor<'a>(pub(crate) &'a [u8]);
 
 impl<'a> BerDecoder<'a> for SnmpObjectDescriptor<'a> {
@@ -22,6 +27,13 @@ impl<'a> BerDecoder<'a> for SnmpObjectDescriptor<'a> {
     }
 }
 
+impl BerEncoder for SnmpObjectDescriptor<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        buf.push_tagged(TAG_OBJECT_DESCRIPTOR, self.0)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'a, 'py> IntoPyObject<'py> for &'a SnmpObjectDescriptor<'a> {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
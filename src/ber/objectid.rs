@@ -1,14 +1,16 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BER OBJECT IDENTIFIER Class
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use super::{BerDecoder, BerEncoder, BerHeader, TAG_OBJECT_ID, Tag};
 use crate::buf::Buffer;
 use crate::error::{SnmpError, SnmpResult};
+#[cfg(feature = "python")]
 use pyo3::types::PyString;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 use std::borrow::Cow;
 use std::fmt::Write;
@@ -52,6 +54,7 @@ impl BerEncoder for SnmpOid<'_> {
     }
 }
 
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpOid<'_> {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -1,14 +1,19 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: OCTET STRING type
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_OCTET_STRING, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{BerDecoder, BerEncoder, BerHeader, TAG_OCTET_STRING, Tag};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python, types::PyBytes};
 
+#[derive(Clone)]
 pub struct SnmpOctetString<'a>(pub(crate) &'a [u8]);
 
 impl<'a> BerDecoder<'a> for SnmpOctetString<'a> {
@@ -22,6 +27,13 @@ impl<'a> BerDecoder<'a> for SnmpOctetString<'a> {
     }
 }
 
+impl BerEncoder for SnmpOctetString<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        buf.push_tagged(TAG_OCTET_STRING, self.0)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'a, 'py> IntoPyObject<'py> for &'a SnmpOctetString<'a> {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -44,4 +56,13 @@ mod tests {
         assert_eq!(s.0, &data[2..]);
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let data = [4u8, 5, 0, 1, 2, 3, 4];
+        let mut buf = Buffer::default();
+        SnmpOctetString(&data[2..]).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &data);
+        Ok(())
+    }
 }
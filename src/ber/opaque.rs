@@ -1,14 +1,19 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Application Class Opaque
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_APP_OPAQUE, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_OPAQUE, Tag};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python, types::PyBytes};
 
+#[derive(Clone)]
 pub struct SnmpOpaque<'a>(pub(crate) &'a [u8]);
 
 impl<'a> BerDecoder<'a> for SnmpOpaque<'a> {
@@ -22,6 +27,13 @@ impl<'a> BerDecoder<'a> for SnmpOpaque<'a> {
     }
 }
 
+impl BerEncoder for SnmpOpaque<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        buf.push_tagged(BER_CLASS_APPLICATION | TAG_APP_OPAQUE, self.0)
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpOpaque<'_> {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -44,4 +56,13 @@ mod tests {
         assert_eq!(s.0, &data[2..]);
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let data = [0x44, 5, 0, 1, 2, 3, 4];
+        let mut buf = Buffer::default();
+        SnmpOpaque(&data[2..]).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &data);
+        Ok(())
+    }
 }
@@ -0,0 +1,258 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: net-snmp Opaque-wrapped values
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::{
+    BER_CLASS_APPLICATION, BER_CLASS_CONTEXT, BerClass, BerEncoder, BerHeader, TAG_APP_OPAQUE,
+    TAG_OPAQUE_COUNTER64, TAG_OPAQUE_DOUBLE, TAG_OPAQUE_FLOAT, TAG_OPAQUE_INT64, TAG_OPAQUE_UINT64,
+    Tag,
+};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
+use pyo3::{Bound, IntoPyObject, PyAny, Python};
+
+// Identifier octet of the long-form context-specific tag
+const OPAQUE_TAG1: u8 = BER_CLASS_CONTEXT | 0x1f;
+
+/// Number, wrapped into Opaque by net-snmp agents,
+/// like UCD-SNMP-MIB::laLoadFloat.
+///
+/// Opaque content is the nested primitive with long-form
+/// context-specific tag: `9f 78 04 3e 19 99 9a` for Float 0.15.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnmpOpaqueValue {
+    Counter64(u64),
+    Float(f32),
+    Double(f64),
+    Int64(i64),
+    UInt64(u64),
+}
+
+impl SnmpOpaqueValue {
+    /// Decode the Opaque content.
+    /// Returns None if content is not the known encoding.
+    pub fn from_opaque(i: &[u8]) -> Option<SnmpOpaqueValue> {
+        if i.first() != Some(&OPAQUE_TAG1) {
+            return None;
+        }
+        let (tail, hdr) = BerHeader::from_ber(i).ok()?;
+        if hdr.class != BerClass::Context || hdr.constructed || tail.len() != hdr.length {
+            return None;
+        }
+        Some(match hdr.tag {
+            TAG_OPAQUE_COUNTER64 => SnmpOpaqueValue::Counter64(parse_unsigned(tail)?),
+            TAG_OPAQUE_FLOAT => SnmpOpaqueValue::Float(f32::from_be_bytes(tail.try_into().ok()?)),
+            TAG_OPAQUE_DOUBLE => SnmpOpaqueValue::Double(f64::from_be_bytes(tail.try_into().ok()?)),
+            TAG_OPAQUE_INT64 => SnmpOpaqueValue::Int64(parse_signed(tail)?),
+            TAG_OPAQUE_UINT64 => SnmpOpaqueValue::UInt64(parse_unsigned(tail)?),
+            _ => return None,
+        })
+    }
+
+    fn tag(&self) -> Tag {
+        match self {
+            SnmpOpaqueValue::Counter64(_) => TAG_OPAQUE_COUNTER64,
+            SnmpOpaqueValue::Float(_) => TAG_OPAQUE_FLOAT,
+            SnmpOpaqueValue::Double(_) => TAG_OPAQUE_DOUBLE,
+            SnmpOpaqueValue::Int64(_) => TAG_OPAQUE_INT64,
+            SnmpOpaqueValue::UInt64(_) => TAG_OPAQUE_UINT64,
+        }
+    }
+}
+
+// Unsigned integer, leading zero octet is allowed
+fn parse_unsigned(i: &[u8]) -> Option<u64> {
+    let i = match i {
+        [0, rest @ ..] if rest.len() == 8 => rest,
+        _ => i,
+    };
+    if i.len() > 8 {
+        return None;
+    }
+    Some(i.iter().fold(0, |acc, x| (acc << 8) | (*x as u64)))
+}
+
+// Two's complement integer
+fn parse_signed(i: &[u8]) -> Option<i64> {
+    if i.len() > 8 {
+        return None;
+    }
+    let init: i64 = match i.first() {
+        Some(x) if x & 0x80 != 0 => -1,
+        _ => 0,
+    };
+    Some(i.iter().fold(init, |acc, x| (acc << 8) | (*x as i64)))
+}
+
+impl BerEncoder for SnmpOpaqueValue {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        let start = buf.len();
+        match self {
+            SnmpOpaqueValue::Counter64(x) | SnmpOpaqueValue::UInt64(x) => {
+                // Leading zero octet for the highest bit set
+                let mut v = [0u8; 9];
+                v[1..].copy_from_slice(&x.to_be_bytes());
+                let mut pos = v[1..8].iter().take_while(|x| **x == 0).count() + 1;
+                if v[pos] & 0x80 != 0 {
+                    pos -= 1;
+                }
+                buf.push(&v[pos..])?;
+            }
+            SnmpOpaqueValue::Float(x) => buf.push(&x.to_be_bytes())?,
+            SnmpOpaqueValue::Double(x) => buf.push(&x.to_be_bytes())?,
+            SnmpOpaqueValue::Int64(x) => {
+                // Strip redundant sign octets
+                let v = x.to_be_bytes();
+                let pos = v
+                    .windows(2)
+                    .take_while(|w| {
+                        (w[0] == 0 && w[1] & 0x80 == 0) || (w[0] == 0xff && w[1] & 0x80 != 0)
+                    })
+                    .count();
+                buf.push(&v[pos..])?;
+            }
+        }
+        // Nested header: 9f <tag> <len>
+        let ln = buf.len() - start;
+        buf.push(&[OPAQUE_TAG1, self.tag(), ln as u8])?;
+        buf.push_tag_len(BER_CLASS_APPLICATION | TAG_APP_OPAQUE, buf.len() - start)
+    }
+}
+
+/// Widen Opaque Float to f64, keeping its shortest decimal representation,
+/// i.e. 0.15 instead of 0.15000000596046448.
+#[cfg(feature = "python")]
+pub(crate) fn float_to_f64(x: f32) -> f64 {
+    format!("{x}").parse().unwrap_or(x as f64)
+}
+
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for &SnmpOpaqueValue {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = SnmpError;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(match self {
+            SnmpOpaqueValue::Counter64(x) | SnmpOpaqueValue::UInt64(x) => {
+                x.into_pyobject(py)?.into_any()
+            }
+            SnmpOpaqueValue::Float(x) => float_to_f64(*x).into_pyobject(py)?.into_any(),
+            SnmpOpaqueValue::Double(x) => x.into_pyobject(py)?.into_any(),
+            SnmpOpaqueValue::Int64(x) => x.into_pyobject(py)?.into_any(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_opaque() {
+        let samples: [(&[u8], SnmpOpaqueValue); 8] = [
+            (
+                &[0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a],
+                SnmpOpaqueValue::Float(0.15),
+            ),
+            (
+                &[0x9f, 0x79, 8, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+                SnmpOpaqueValue::Double(1.5),
+            ),
+            (
+                &[0x9f, 0x76, 5, 1, 0, 0, 0, 0],
+                SnmpOpaqueValue::Counter64(0x100000000),
+            ),
+            (&[0x9f, 0x7a, 1, 0x7f], SnmpOpaqueValue::Int64(127)),
+            (&[0x9f, 0x7a, 1, 0xff], SnmpOpaqueValue::Int64(-1)),
+            (&[0x9f, 0x7a, 2, 0xff, 0x7f], SnmpOpaqueValue::Int64(-129)),
+            (&[0x9f, 0x7b, 2, 0, 0x80], SnmpOpaqueValue::UInt64(128)),
+            (
+                &[
+                    0x9f, 0x7b, 9, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                ],
+                SnmpOpaqueValue::UInt64(u64::MAX),
+            ),
+        ];
+        for (data, expected) in samples {
+            assert_eq!(SnmpOpaqueValue::from_opaque(data), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_from_opaque_raw() {
+        let samples: [&[u8]; 6] = [
+            // Arbitrary bytes
+            &[0, 1, 2, 3],
+            // Unknown tag, not in net-snmp's asn1.h
+            &[0x9f, 0x7c, 1, 0],
+            // Invalid float size
+            &[0x9f, 0x78, 2, 0, 0],
+            // Trailing data
+            &[0x9f, 0x7a, 1, 0, 0],
+            // Truncated
+            &[0x9f, 0x79, 8, 0],
+            // Too long integer
+            &[0x9f, 0x7a, 9, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        for data in samples {
+            assert_eq!(SnmpOpaqueValue::from_opaque(data), None);
+        }
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_float_to_f64() {
+        assert_eq!(float_to_f64(0.15), 0.15);
+        assert_eq!(float_to_f64(0.5), 0.5);
+        assert_eq!(float_to_f64(-1.25e-3), -1.25e-3);
+    }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let samples: [(SnmpOpaqueValue, &[u8]); 9] = [
+            (
+                SnmpOpaqueValue::Float(0.15),
+                &[0x44, 7, 0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a],
+            ),
+            (
+                SnmpOpaqueValue::Double(1.5),
+                &[0x44, 11, 0x9f, 0x79, 8, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+            ),
+            (
+                SnmpOpaqueValue::Counter64(0x100000000),
+                &[0x44, 8, 0x9f, 0x76, 5, 1, 0, 0, 0, 0],
+            ),
+            (SnmpOpaqueValue::Int64(0), &[0x44, 4, 0x9f, 0x7a, 1, 0]),
+            (SnmpOpaqueValue::Int64(-1), &[0x44, 4, 0x9f, 0x7a, 1, 0xff]),
+            (
+                SnmpOpaqueValue::Int64(128),
+                &[0x44, 5, 0x9f, 0x7a, 2, 0, 0x80],
+            ),
+            (
+                SnmpOpaqueValue::Int64(-129),
+                &[0x44, 5, 0x9f, 0x7a, 2, 0xff, 0x7f],
+            ),
+            (SnmpOpaqueValue::UInt64(0), &[0x44, 4, 0x9f, 0x7b, 1, 0]),
+            (
+                SnmpOpaqueValue::UInt64(u64::MAX),
+                &[
+                    0x44, 12, 0x9f, 0x7b, 9, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                ],
+            ),
+        ];
+        for (value, expected) in samples {
+            let mut buf = Buffer::default();
+            value.push_ber(&mut buf)?;
+            assert_eq!(buf.data(), expected);
+            assert_eq!(SnmpOpaqueValue::from_opaque(&expected[2..]), Some(value));
+        }
+        Ok(())
+    }
+}
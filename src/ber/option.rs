@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BER Option Class
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -28,17 +28,20 @@ impl<'a> BerDecoder<'a> for SnmpOption<'a> {
 
     fn from_ber(i: &'a [u8]) -> IResult<&'a [u8], Self, SnmpError> {
         if i.len() < 3 {
-            return Err(Err::Failure(SnmpError::Incomplete));
+            return Err(Err::Failure(SnmpError::Incomplete.at(i)));
         }
-        let (tail, hdr) = BerHeader::from_ber(i)?;
+        let (tail, hdr) =
+            BerHeader::from_ber(i).map_err(|e| Err::Failure(SnmpError::from(e).at(i)))?;
         if !hdr.constructed || (hdr.class != BerClass::Context && hdr.class != BerClass::Universal)
         {
-            return Err(Err::Failure(SnmpError::UnexpectedTag));
+            return Err(Err::Failure(
+                SnmpError::UnexpectedTag.at(i).with_tags(None, hdr.tag),
+            ));
         }
         //
         Ok((
             &tail[hdr.length..],
-            Self::decode(tail, &hdr).map_err(Err::Failure)?,
+            Self::decode(tail, &hdr).map_err(|e| Err::Failure(e.at(i)))?,
         ))
     }
 }
@@ -1,15 +1,18 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: BER REAL Class
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_REAL, Tag};
+use super::{BerDecoder, BerEncoder, BerHeader, TAG_REAL, Tag};
+use crate::buf::Buffer;
 use crate::error::{SnmpError, SnmpResult};
 use core::str::from_utf8;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 
+#[derive(Clone)]
 pub struct SnmpReal(f64);
 
 impl<'a> BerDecoder<'a> for SnmpReal {
@@ -111,7 +114,71 @@ impl<'a> BerDecoder<'a> for SnmpReal {
     }
 }
 
+impl BerEncoder for SnmpReal {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        let v = self.0;
+        // 8.5.2: Plus zero has no contents octets
+        if v == 0.0 && v.is_sign_positive() {
+            return buf.push(&[TAG_REAL, 0]);
+        }
+        // 8.5.9: Special real values
+        if v.is_nan() {
+            return buf.push(&[TAG_REAL, 1, 0b01000010]);
+        }
+        if v.is_infinite() {
+            return buf.push(&[
+                TAG_REAL,
+                1,
+                if v.is_sign_positive() {
+                    0b01000000
+                } else {
+                    0b01000001
+                },
+            ]);
+        }
+        if v == 0.0 {
+            return buf.push(&[TAG_REAL, 1, 0b01000011]);
+        }
+        // 8.5.8: Decimal encoding, ISO 6093 NR3
+        let s = SnmpReal::to_nr3(v);
+        buf.push(s.as_bytes())?;
+        buf.push_u8(3)?;
+        buf.push_tag_len(TAG_REAL, s.len() + 1)
+    }
+}
+
+impl From<f64> for SnmpReal {
+    fn from(value: f64) -> Self {
+        SnmpReal(value)
+    }
+}
+
 impl SnmpReal {
+    // Format finite value as ISO 6093 NR3 in X.690 pp 11.3.1 canonical form:
+    // integer mantissa without trailing zeros, the full stop,
+    // and the exponent, zero one written as `+0`. I.e. 15.E-1
+    fn to_nr3(v: f64) -> String {
+        // Shortest representation, i.e. -1.5E-10
+        let s = format!("{:E}", v);
+        let (mantissa, exp) = s.split_once('E').unwrap_or((&s, "0"));
+        let mut exp = exp.parse::<i32>().unwrap_or_default();
+        let (sign, mantissa) = match mantissa.strip_prefix('-') {
+            Some(x) => ("-", x),
+            None => ("", mantissa),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        exp -= frac.len() as i32;
+        let mut digits = format!("{int}{frac}");
+        while digits.len() > 1 && digits.ends_with('0') {
+            digits.pop();
+            exp += 1;
+        }
+        if exp == 0 {
+            format!("{sign}{digits}.E+0")
+        } else {
+            format!("{sign}{digits}.E{exp}")
+        }
+    }
     fn parse_u32(i: &[u8]) -> u32 {
         let mut v = 0u32;
         for &n in i.iter() {
@@ -121,6 +188,7 @@ impl SnmpReal {
     }
 }
 
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpReal {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -155,8 +223,11 @@ mod tests {
             vec![9u8, 9, 0x03, 0x2d, 0x34, 0x35, 0x36, 0x37, 0x65, 0x2d, 0x31], // NR3
             vec![9u8, 5, 0x03, 0x31, 0x45, 0x2b, 0x30], // NR3
             vec![9u8, 6, 0x03, 0x31, 0x35, 0x45, 0x2d, 0x31], // NR3
+            vec![9u8, 6, 0x03, 0x31, 0x2e, 0x45, 0x2b, 0x30], // NR3, canonical
+        ];
+        let expected = [
+            0.0, 456.0, -456.0, 456.7, -456.7, 456.7, -456.7, 1.0, 1.5, 1.0,
         ];
-        let expected = [0.0, 456.0, -456.0, 456.7, -456.7, 456.7, -456.7, 1.0, 1.5];
         for i in 0..data.len() {
             let (tail, v) = SnmpReal::from_ber(&data[i])?;
             let diff = (v.0 - expected[i]).abs();
@@ -198,4 +269,48 @@ mod tests {
         assert_eq!(tail.len(), 0);
         Ok(())
     }
+    #[test]
+    fn test_to_nr3() {
+        for (v, expected) in [
+            (1.0, "1.E+0"),
+            (1.5, "15.E-1"),
+            (-456.7, "-4567.E-1"),
+            (100.0, "1.E2"),
+            (1e10, "1.E10"),
+            (-1.5e-10, "-15.E-11"),
+        ] {
+            assert_eq!(SnmpReal::to_nr3(v), expected);
+        }
+    }
+    #[test]
+    fn test_encode_nr3() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpReal(1.0).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), b"\x09\x06\x031.E+0");
+        Ok(())
+    }
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        for v in [
+            0.0,
+            -0.0,
+            1.0,
+            100.0,
+            456.7,
+            -1.5e-10,
+            0.1,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ] {
+            let mut buf = Buffer::default();
+            SnmpReal(v).push_ber(&mut buf)?;
+            let (tail, r) = SnmpReal::from_ber(buf.data())?;
+            assert_eq!(tail.len(), 0);
+            assert_eq!(r.0, v);
+            assert_eq!(r.0.is_sign_negative(), v.is_sign_negative());
+        }
+        Ok(())
+    }
 }
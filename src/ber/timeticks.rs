@@ -1,14 +1,21 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Application Class TimeTicks
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_APP_TIMETICKS, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{
+    BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_TIMETICKS, Tag, push_unsigned,
+};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 
+#[derive(Clone)]
 pub struct SnmpTimeTicks(pub(crate) u32);
 
 impl<'a> BerDecoder<'a> for SnmpTimeTicks {
@@ -28,6 +35,17 @@ impl<'a> BerDecoder<'a> for SnmpTimeTicks {
     }
 }
 
+impl BerEncoder for SnmpTimeTicks {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        push_unsigned(
+            buf,
+            BER_CLASS_APPLICATION | TAG_APP_TIMETICKS,
+            self.0 as u64,
+        )
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpTimeTicks {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -51,4 +69,15 @@ mod tests {
         assert_eq!(tt.0, expected);
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpTimeTicks(0).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &[0x43, 1, 0]);
+        let (tail, v) = SnmpTimeTicks::from_ber(buf.data())?;
+        assert_eq!(tail.len(), 0);
+        assert_eq!(v.0, 0);
+        Ok(())
+    }
 }
@@ -1,14 +1,22 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Application Class UInteger32
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::{BerDecoder, BerHeader, TAG_APP_UINTEGER32, Tag};
-use crate::error::{SnmpError, SnmpResult};
+use super::{
+    BER_CLASS_APPLICATION, BerDecoder, BerEncoder, BerHeader, TAG_APP_UINTEGER32, Tag,
+    push_unsigned,
+};
+use crate::buf::Buffer;
+#[cfg(feature = "python")]
+use crate::error::SnmpError;
+use crate::error::SnmpResult;
+#[cfg(feature = "python")]
 use pyo3::{Bound, IntoPyObject, PyAny, Python};
 
+#[derive(Clone)]
 pub struct SnmpUInteger32(pub(crate) u32);
 
 impl<'a> BerDecoder<'a> for SnmpUInteger32 {
@@ -28,6 +36,17 @@ impl<'a> BerDecoder<'a> for SnmpUInteger32 {
     }
 }
 
+impl BerEncoder for SnmpUInteger32 {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        push_unsigned(
+            buf,
+            BER_CLASS_APPLICATION | TAG_APP_UINTEGER32,
+            self.0 as u64,
+        )
+    }
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpUInteger32 {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -58,4 +77,15 @@ mod tests {
         assert_eq!(tt.0, 0x013510AB);
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let mut buf = Buffer::default();
+        SnmpUInteger32(0xFFFFFFFF).push_ber(&mut buf)?;
+        assert_eq!(buf.data(), &[0x47, 5, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        let (tail, v) = SnmpUInteger32::from_ber(buf.data())?;
+        assert_eq!(tail.len(), 0);
+        assert_eq!(v.0, 0xFFFFFFFF);
+        Ok(())
+    }
 }
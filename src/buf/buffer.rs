@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Buffer implementation
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -10,7 +10,7 @@ use std::mem::MaybeUninit;
 use std::ptr;
 use std::slice;
 
-const MAX_SIZE: usize = 4080; // 4096 - other fields
+pub const MAX_SIZE: usize = 4080; // 4096 - other fields
 
 // SNMP message is build starting from the end,
 // So we use stack-like buffer.
@@ -18,6 +18,8 @@ pub struct Buffer {
     pos: usize,
     bookmark: usize,
     data: [MaybeUninit<u8>; MAX_SIZE],
+    // Received message, which doesn't fit `data`
+    large: Vec<u8>,
 }
 
 impl Default for Buffer {
@@ -28,6 +30,7 @@ impl Default for Buffer {
             pos: MAX_SIZE,
             bookmark: 0,
             data: unsafe { MaybeUninit::uninit().assume_init() },
+            large: Vec::new(),
         }
     }
 }
@@ -168,8 +171,23 @@ impl Buffer {
     #[inline]
     pub fn reset(&mut self) {
         self.pos = MAX_SIZE;
+        self.large = Vec::new();
+    }
+    // Place received message at the beginning of the buffer.
+    // Messages, larger than the buffer, are kept on the heap.
+    pub fn put_received(&mut self, msg: &[u8]) {
+        if msg.len() > MAX_SIZE {
+            self.large = msg.to_vec();
+            return;
+        }
+        for (d, x) in self.data.iter_mut().zip(msg) {
+            d.write(*x);
+        }
     }
     pub fn as_slice(&self, len: usize) -> &[u8] {
+        if len > MAX_SIZE {
+            return &self.large[..len];
+        }
         unsafe { slice::from_raw_parts(self.data.as_ptr() as *const u8, len) }
     }
 }
@@ -269,4 +287,16 @@ mod tests {
         assert_eq!(b.data(), &expected);
         Ok(())
     }
+
+    #[test_case(10)]
+    #[test_case(MAX_SIZE)]
+    #[test_case(MAX_SIZE + 1)]
+    fn test_put_received(len: usize) {
+        let msg: Vec<u8> = (0..len).map(|x| x as u8).collect();
+        let mut b = Buffer::default();
+        b.put_received(&msg);
+        assert_eq!(b.as_slice(len), msg.as_slice());
+        b.reset();
+        assert!(b.large.is_empty());
+    }
 }
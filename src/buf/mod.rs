@@ -8,5 +8,5 @@
 pub mod buffer;
 pub mod pool;
 
-pub use buffer::Buffer;
+pub use buffer::{Buffer, MAX_SIZE};
 pub use pool::get_buffer_pool;
@@ -0,0 +1,712 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Async Rust client
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::{SnmpClientConfig, UsmUser, op, walk::Scope};
+use crate::{
+    ber::{BerDecoder, BerHeader, SnmpInt, SnmpOctetString, SnmpOid, SnmpSequence},
+    buf::{Buffer, MAX_SIZE, get_buffer_pool},
+    error::{SnmpError, SnmpResult},
+    snmp::{
+        SNMP_V1, SNMP_V2C, SNMP_V3, getresponse::SnmpGetResponse, pdu::SnmpPdu, value::OwnedValue,
+    },
+    socket::{
+        Attempts, Proto, RetryPolicy, SnmpIo, SnmpV1Proto, SnmpV2cProto, SnmpV3Proto, SocketConfig,
+        fit_max_size, resolve, target_key,
+    },
+};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::{
+    net::UdpSocket,
+    runtime::Handle,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+    time,
+};
+
+// Delay after the receive error, doubled on each consecutive one
+const MIN_RECV_BACKOFF: Duration = Duration::from_millis(1);
+const MAX_RECV_BACKOFF: Duration = Duration::from_secs(1);
+
+// Waiting requests by agent's address and routing id
+type Routes = Mutex<HashMap<(SocketAddr, i64), UnboundedSender<Vec<u8>>>>;
+
+/// UDP socket, shared by async clients.
+///
+/// Replies are dispatched to the waiting requests by the background task,
+/// so any number of clients and concurrent requests may share one socket.
+/// Cloning is cheap, the socket is closed when the last clone
+/// and the last client are dropped.
+#[derive(Clone)]
+pub struct AsyncSnmpSocket {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    io: Arc<UdpSocket>,
+    routes: Arc<Routes>,
+    dispatcher: JoinHandle<()>,
+    ipv6: bool,
+    cfg: SocketConfig,
+    timeout: Duration,
+    max_size: i64,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        self.dispatcher.abort();
+    }
+}
+
+impl AsyncSnmpSocket {
+    /// Bind socket for IPv4 or IPv6 agents.
+    /// Must be called within the tokio runtime.
+    ///
+    /// Settings are applied to every client, using the socket.
+    /// Only UDP transport is supported.
+    pub fn bind(ipv6: bool, cfg: SnmpClientConfig) -> SnmpResult<Self> {
+        if cfg.timeout.is_zero() {
+            return Err(SnmpError::SocketError("invalid timeout".into()));
+        }
+        let max_size = fit_max_size(cfg.max_msg_size)
+            .ok_or_else(|| SnmpError::SocketError("invalid max message size".into()))?;
+        let handle = Handle::try_current().map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        let timeout = cfg.timeout;
+        let cfg = SocketConfig::from(cfg);
+        let io = std::net::UdpSocket::from(SnmpIo::unconnected(ipv6, cfg.clone())?.into_socket());
+        let io = io
+            .set_nonblocking(true)
+            .and_then(|_| {
+                let _guard = handle.enter();
+                UdpSocket::from_std(io)
+            })
+            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        let io = Arc::new(io);
+        let routes = Arc::new(Routes::default());
+        let dispatcher = handle.spawn(dispatch(Arc::clone(&io), Arc::clone(&routes)));
+        Ok(Self {
+            inner: Arc::new(Shared {
+                io,
+                routes,
+                dispatcher,
+                ipv6,
+                cfg,
+                timeout,
+                max_size,
+            }),
+        })
+    }
+    /// Local address of the socket
+    pub fn local_addr(&self) -> SnmpResult<SocketAddr> {
+        self.inner
+            .io
+            .local_addr()
+            .map_err(|e| SnmpError::SocketError(e.to_string()))
+    }
+    /// SNMPv1 client. `addr` is `host:port`.
+    pub async fn v1(&self, addr: &str, community: &str) -> SnmpResult<AsyncSnmpClient> {
+        self.client(addr, Proto::V1(SnmpV1Proto::new(community.into())))
+            .await
+    }
+    /// SNMPv2c client. `addr` is `host:port`.
+    pub async fn v2c(&self, addr: &str, community: &str) -> SnmpResult<AsyncSnmpClient> {
+        self.client(addr, Proto::V2c(SnmpV2cProto::new(community.into())))
+            .await
+    }
+    /// SNMPv3 client. `addr` is `host:port`.
+    ///
+    /// Engine id is discovered when not set. Engine boots and time
+    /// are requested before returning the client.
+    pub async fn v3(
+        &self,
+        addr: &str,
+        engine_id: Option<Vec<u8>>,
+        user: UsmUser,
+    ) -> SnmpResult<AsyncSnmpClient> {
+        let discover = engine_id.is_none();
+        // Engine id discovery is not authenticated
+        let initial = if discover {
+            UsmUser::default()
+        } else {
+            user.clone()
+        };
+        let proto = SnmpV3Proto::new(
+            engine_id.unwrap_or_default(),
+            initial.name.clone(),
+            initial.get_auth(),
+            initial.get_privacy(),
+            self.inner.max_size,
+        )?;
+        let client = self.client(addr, Proto::V3(Box::new(proto))).await?;
+        if discover {
+            client.refresh().await?;
+            if let Proto::V3(proto) = &mut *client.lock() {
+                proto.set_keys(user.name.clone(), user.get_auth(), user.get_privacy())?;
+            }
+        }
+        if discover || user.require_auth() {
+            client.refresh().await?;
+        }
+        Ok(client)
+    }
+
+    async fn client(&self, addr: &str, proto: Proto) -> SnmpResult<AsyncSnmpClient> {
+        Ok(AsyncSnmpClient {
+            socket: self.clone(),
+            addr: self.resolve(addr).await?,
+            proto: Arc::new(Mutex::new(proto)),
+        })
+    }
+    // Resolve agent's address, matching socket's family
+    async fn resolve(&self, addr: &str) -> SnmpResult<SocketAddr> {
+        let addr = addr.to_owned();
+        let family = self.inner.cfg.family;
+        let addrs = tokio::task::spawn_blocking(move || resolve(&addr, family))
+            .await
+            .map_err(|e| SnmpError::SocketError(e.to_string()))??;
+        addrs
+            .iter()
+            .filter_map(|x| x.as_socket())
+            .find(|x| x.is_ipv6() == self.inner.ipv6)
+            .map(target_key)
+            .ok_or_else(|| SnmpError::SocketError("invalid address".into()))
+    }
+    // Route replies from `addr` with routing `id` to `tx`.
+    // Returns false if the route is taken by another request.
+    fn register(&self, addr: SocketAddr, id: i64, tx: &UnboundedSender<Vec<u8>>) -> bool {
+        let mut routes = self.inner.routes.lock().unwrap_or_else(|e| e.into_inner());
+        match routes.entry((addr, id)) {
+            Entry::Vacant(e) => {
+                e.insert(tx.clone());
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+    fn unregister(&self, addr: SocketAddr, ids: &[i64]) {
+        let mut routes = self.inner.routes.lock().unwrap_or_else(|e| e.into_inner());
+        for id in ids {
+            routes.remove(&(addr, *id));
+        }
+    }
+}
+
+/// Async SNMP client, bound to the agent.
+///
+/// Values are returned as `OwnedValue`, detached from the received messages,
+/// oids are in numeric format without leading dot.
+/// Clones share the protocol state, so the requests may be issued
+/// concurrently from the several tasks.
+#[derive(Clone)]
+pub struct AsyncSnmpClient {
+    socket: AsyncSnmpSocket,
+    addr: SocketAddr,
+    proto: Arc<Mutex<Proto>>,
+}
+
+// Request in flight.
+// Stops routing and forgets request id when dropped,
+// including the cancellation of the request.
+struct InFlight<'a> {
+    client: &'a AsyncSnmpClient,
+    request_id: i64,
+    routes: Vec<i64>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.client
+            .socket
+            .unregister(self.client.addr, &self.routes);
+        self.client.lock().complete(self.request_id);
+    }
+}
+
+impl AsyncSnmpClient {
+    /// Agent's address
+    pub fn peer_addr(&self) -> String {
+        self.addr.to_string()
+    }
+    /// Effective engine id. SNMPv3 only.
+    pub fn engine_id(&self) -> Option<Vec<u8>> {
+        match &*self.lock() {
+            Proto::V3(proto) => Some(proto.get_engine_id().to_vec()),
+            _ => None,
+        }
+    }
+    /// Send GET request with single oid.
+    /// Returns `NoSuchInstance` error when the oid is not found.
+    pub async fn get(&self, oid: &str) -> SnmpResult<OwnedValue> {
+        let oid = SnmpOid::try_from(oid)?;
+        self.request(
+            |request_id| op::get_request(request_id, vec![oid]),
+            op::get_value,
+        )
+        .await
+    }
+    /// Send GET request with multiple oids.
+    /// Returns found oids along with values, missed ones are skipped.
+    ///
+    /// Oids are split to the several requests to fit agent's msgMaxSize.
+    pub async fn get_many(&self, oids: &[&str]) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let vars = oids
+            .iter()
+            .map(|x| SnmpOid::try_from(*x))
+            .collect::<SnmpResult<Vec<_>>>()?;
+        let size = op::max_varbinds(&self.lock());
+        let mut r = Vec::with_capacity(vars.len());
+        for batch in op::get_batches(&vars, size) {
+            r.extend(
+                self.request(
+                    |request_id| op::get_request(request_id, batch.to_vec()),
+                    op::get_values,
+                )
+                .await?,
+            );
+        }
+        Ok(r)
+    }
+    /// Send GETNEXT request.
+    /// Returns None at the end of MIB view.
+    pub async fn get_next(&self, oid: &str) -> SnmpResult<Option<(String, OwnedValue)>> {
+        let oid = SnmpOid::try_from(oid)?;
+        self.get_next_oid(oid, |_| true).await
+    }
+    /// Send GETBULK request. SNMPv2c and SNMPv3 only.
+    /// Returns up to `max_repetitions` of the following oids along with values.
+    pub async fn get_bulk(
+        &self,
+        oid: &str,
+        max_repetitions: i64,
+    ) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let oid = SnmpOid::try_from(oid)?;
+        self.get_bulk_oid(oid, max_repetitions, |_| true).await
+    }
+    /// Collect the MIB subtree using GETNEXT requests.
+    pub async fn walk(&self, oid: &str) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let mut scope = Scope::new(oid)?;
+        let mut r = Vec::new();
+        loop {
+            let oid = scope.next_oid();
+            match self.get_next_oid(oid, |x| scope.accept(x)).await? {
+                Some(item) => r.push(item),
+                None => return Ok(r),
+            }
+        }
+    }
+    /// Collect the MIB subtree using GETBULK requests.
+    /// SNMPv2c and SNMPv3 only.
+    pub async fn bulk_walk(
+        &self,
+        oid: &str,
+        max_repetitions: i64,
+    ) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let mut scope = Scope::new(oid)?;
+        let mut r = Vec::new();
+        loop {
+            let oid = scope.next_oid();
+            let items = self
+                .get_bulk_oid(oid, max_repetitions, |x| scope.accept(x))
+                .await?;
+            let done = scope.is_done(&items);
+            r.extend(items);
+            if done {
+                return Ok(r);
+            }
+        }
+    }
+    /// Send and receive REPORT to refresh authentication state.
+    /// SNMPv3 only, does nothing for other versions.
+    ///
+    /// Should be called again if over 150 seconds passed
+    /// from the last request.
+    pub async fn refresh(&self) -> SnmpResult<()> {
+        if !matches!(*self.lock(), Proto::V3(_)) {
+            return Ok(());
+        }
+        self.request_pdu(|request_id| op::get_request(request_id, vec![]), |_| Ok(()))
+            .await
+    }
+
+    // GETNEXT with parsed oid.
+    // `accept` checks if the returned oid is still in the scope.
+    async fn get_next_oid(
+        &self,
+        oid: SnmpOid<'static>,
+        accept: impl FnMut(&SnmpOid) -> bool,
+    ) -> SnmpResult<Option<(String, OwnedValue)>> {
+        self.request(
+            |request_id| op::get_next_request(request_id, oid),
+            |resp| op::next_value(resp, accept),
+        )
+        .await
+    }
+
+    // GETBULK with parsed oid.
+    // Stops at the first oid, rejected by `accept`.
+    async fn get_bulk_oid(
+        &self,
+        oid: SnmpOid<'static>,
+        max_repetitions: i64,
+        accept: impl FnMut(&SnmpOid) -> bool,
+    ) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let max_repetitions = op::fit_max_repetitions(&self.lock(), max_repetitions)?;
+        self.request(
+            |request_id| op::get_bulk_request(request_id, oid, max_repetitions),
+            |resp| op::bulk_values(resp, accept),
+        )
+        .await
+    }
+
+    // Send request and receive GETRESPONSE.
+    async fn request<T>(
+        &self,
+        build: impl FnOnce(i64) -> SnmpPdu<'static>,
+        handle: impl FnOnce(&SnmpGetResponse) -> SnmpResult<T>,
+    ) -> SnmpResult<T> {
+        self.request_pdu(build, |pdu| handle(op::as_response(pdu)?))
+            .await
+    }
+
+    // Send request and receive reply to it,
+    // retransmitting the request according to retry policy.
+    async fn request_pdu<T>(
+        &self,
+        build: impl FnOnce(i64) -> SnmpPdu<'static>,
+        handle: impl FnOnce(&SnmpPdu) -> SnmpResult<T>,
+    ) -> SnmpResult<T> {
+        let shared = &self.socket.inner;
+        let (tx, mut rx) = unbounded_channel();
+        let mut flight = self.start(&tx);
+        let pdu = build(flight.request_id);
+        let deadline = Instant::now() + shared.timeout;
+        let policy = RetryPolicy::new(&shared.cfg, shared.timeout);
+        let mut h = get_buffer_pool().acquire();
+        for attempt_deadline in Attempts::new(policy, deadline) {
+            self.police().await;
+            // Each attempt is a separate message
+            self.push(&mut flight, &pdu, &tx, h.as_mut())?;
+            shared
+                .io
+                .send_to(h.as_mut().data(), self.addr)
+                .await
+                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+            // Routed messages still may be unrelated.
+            while let Ok(Some(data)) = time::timeout_at(attempt_deadline.into(), rx.recv()).await {
+                if let Some((id, ref pdu)) = self.lock().unwrap_data(&data)?
+                    && id == flight.request_id
+                {
+                    return handle(pdu);
+                }
+            }
+        }
+        Err(SnmpError::Timeout)
+    }
+
+    // Get request id and route replies to `tx`.
+    // SNMPv3 replies are routed by msgID, which is set on push.
+    fn start(&self, tx: &UnboundedSender<Vec<u8>>) -> InFlight<'_> {
+        let mut proto = self.lock();
+        let is_v3 = matches!(*proto, Proto::V3(_));
+        loop {
+            let request_id = proto.get_request_id().get_next();
+            if is_v3 {
+                return InFlight {
+                    client: self,
+                    request_id,
+                    routes: Vec::new(),
+                };
+            }
+            if self.socket.register(self.addr, request_id, tx) {
+                return InFlight {
+                    client: self,
+                    request_id,
+                    routes: vec![request_id],
+                };
+            }
+            // Same id is used by another client of the agent
+            proto.get_request_id().remove(request_id);
+        }
+    }
+
+    // Encode message and route replies to it.
+    fn push(
+        &self,
+        flight: &mut InFlight,
+        pdu: &SnmpPdu,
+        tx: &UnboundedSender<Vec<u8>>,
+        buf: &mut Buffer,
+    ) -> SnmpResult<()> {
+        let mut proto = self.lock();
+        loop {
+            buf.reset();
+            proto.push_pdu(pdu.clone(), buf)?;
+            let id = proto.routing_id(flight.request_id);
+            if flight.routes.contains(&id) {
+                return Ok(());
+            }
+            if self.socket.register(self.addr, id, tx) {
+                flight.routes.push(id);
+                return Ok(());
+            }
+            // msgID is used by another client of the agent, try next one
+        }
+    }
+
+    // Wait until the policer allows to send the request.
+    async fn police(&self) {
+        if let Some(policer) = &self.socket.inner.cfg.policer {
+            let ready = policer.reserve_at(&self.peer_addr(), Instant::now());
+            time::sleep_until(ready.into()).await;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Proto> {
+        self.proto.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Receive messages and pass them to the waiting requests.
+async fn dispatch(io: Arc<UdpSocket>, routes: Arc<Routes>) {
+    let mut buf = vec![0u8; MAX_SIZE];
+    let mut backoff = Duration::ZERO;
+    loop {
+        let (size, addr) = match io.recv_from(&mut buf).await {
+            Ok(x) => x,
+            // ICMP errors are reported once per received ICMP message
+            Err(e) if is_icmp_error(&e) => continue,
+            // Do not spin on the persistent error
+            Err(_) => {
+                backoff = (backoff * 2).clamp(MIN_RECV_BACKOFF, MAX_RECV_BACKOFF);
+                time::sleep(backoff).await;
+                continue;
+            }
+        };
+        backoff = Duration::ZERO;
+        let data = &buf[..size];
+        let Some(id) = peek_id(data) else {
+            continue;
+        };
+        let tx = routes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(target_key(addr), id))
+            .cloned();
+        if let Some(tx) = tx {
+            // Request may be gone already
+            let _ = tx.send(data.to_vec());
+        }
+    }
+}
+
+// Errors, caused by ICMP messages from agents
+fn is_icmp_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+    )
+}
+
+// Get routing id of the message without protocol state:
+// request id for SNMPv1 and SNMPv2c, msgID for SNMPv3.
+// Only headers are parsed, the message is decoded by the waiting request.
+fn peek_id(data: &[u8]) -> Option<i64> {
+    let (_, envelope) = SnmpSequence::from_ber(data).ok()?;
+    let (tail, version) = SnmpInt::from_ber(envelope.0).ok()?;
+    let id = match u8::try_from(i64::from(version)).ok()? {
+        SNMP_V1 | SNMP_V2C => {
+            // Request id is the first field of PDU
+            let (tail, _) = SnmpOctetString::from_ber(tail).ok()?;
+            let (tail, _) = BerHeader::from_ber(tail).ok()?;
+            SnmpInt::from_ber(tail).ok()?.1
+        }
+        SNMP_V3 => {
+            // msgID is the first field of global header
+            let (_, header) = SnmpSequence::from_ber(tail).ok()?;
+            SnmpInt::from_ber(header.0).ok()?.1
+        }
+        _ => return None,
+    };
+    Some(id.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::UsmUser;
+    use super::super::tests::{DESCR, MIB, run_agent, run_v3_agent};
+    use super::*;
+    use crate::snmp::msg::v3::MIN_MAX_SIZE;
+
+    fn socket() -> SnmpResult<AsyncSnmpSocket> {
+        AsyncSnmpSocket::bind(
+            false,
+            SnmpClientConfig {
+                timeout: Duration::from_secs(5),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn routes(socket: &AsyncSnmpSocket) -> usize {
+        socket.inner.routes.lock().unwrap().len()
+    }
+
+    #[test]
+    fn test_bind_outside_runtime() {
+        assert!(matches!(socket(), Err(SnmpError::SocketError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get() -> SnmpResult<()> {
+        let socket = socket()?;
+        let client = socket.v2c(&run_agent(), "public").await?;
+        assert_eq!(
+            client.get("1.3.6.1.2.1.1.0.0").await?,
+            OwnedValue::OctetString(DESCR.to_vec())
+        );
+        assert!(matches!(
+            client.get("1.3.6.1.2.1.1.9.0").await,
+            Err(SnmpError::NoSuchInstance)
+        ));
+        let r = client
+            .get_many(&["1.3.6.1.2.1.1.1.0", "1.3.6.1.2.1.1.9.0"])
+            .await?;
+        assert_eq!(r, vec![("1.3.6.1.2.1.1.1.0".into(), OwnedValue::Int(1))]);
+        assert_eq!(
+            client.get_next("1.3.6.1.2.1.1.1.0").await?,
+            Some(("1.3.6.1.2.1.1.2.0".into(), OwnedValue::Int(2)))
+        );
+        assert_eq!(routes(&socket), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_many_batches() -> SnmpResult<()> {
+        let (addr, requests) = run_v3_agent(MIN_MAX_SIZE);
+        let socket = socket()?;
+        let client = socket.v3(&addr, None, UsmUser::new("user")).await?;
+        let size = op::max_varbinds(&client.lock());
+        assert!(size > 1);
+        // Two full batches and one more oid
+        let oids = (0..2 * size + 1)
+            .map(|i| MIB[i % MIB.len()].0)
+            .collect::<Vec<_>>();
+        let r = client.get_many(&oids).await?;
+        assert_eq!(r.len(), oids.len());
+        assert_eq!(*requests.lock().unwrap(), vec![size, size, 1]);
+        assert_eq!(routes(&socket), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_walk() -> SnmpResult<()> {
+        let socket = socket()?;
+        let client = socket.v2c(&run_agent(), "public").await?;
+        let r = client.walk("1.3.6.1.2.1.1").await?;
+        assert_eq!(r.len(), 4);
+        assert_eq!(r[1], ("1.3.6.1.2.1.1.1.0".into(), OwnedValue::Int(1)));
+        for max_repetitions in [1, 2, 10] {
+            assert_eq!(client.bulk_walk("1.3.6.1.2.1.1", max_repetitions).await?, r);
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent() -> SnmpResult<()> {
+        let socket = socket()?;
+        // Two agents, sharing one socket
+        let clients = [
+            socket.v2c(&run_agent(), "public").await?,
+            socket.v2c(&run_agent(), "public").await?,
+        ];
+        let mut tasks = Vec::new();
+        for i in 0..64 {
+            let client = clients[i % 2].clone();
+            tasks.push(tokio::spawn(async move {
+                let (oid, value) = MIB[i % MIB.len()];
+                assert_eq!(client.get(oid).await?, OwnedValue::Int(value));
+                Ok::<_, SnmpError>(())
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap()?;
+        }
+        assert_eq!(routes(&socket), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout() -> SnmpResult<()> {
+        // Nobody answers
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let socket = AsyncSnmpSocket::bind(
+            false,
+            SnmpClientConfig {
+                timeout: Duration::from_millis(100),
+                retries: 1,
+                ..Default::default()
+            },
+        )?;
+        let client = socket
+            .v2c(&sock.local_addr().unwrap().to_string(), "public")
+            .await?;
+        assert!(matches!(
+            client.get("1.3.6.1.2.1.1.1.0").await,
+            Err(SnmpError::Timeout)
+        ));
+        assert_eq!(routes(&socket), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel() -> SnmpResult<()> {
+        let sock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let socket = socket()?;
+        let client = socket
+            .v2c(&sock.local_addr().unwrap().to_string(), "public")
+            .await?;
+        let r = time::timeout(Duration::from_millis(50), client.get("1.3.6.1.2.1.1.1.0")).await;
+        assert!(r.is_err());
+        assert_eq!(routes(&socket), 0);
+        assert!(client.lock().get_request_id().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_family_mismatch() -> SnmpResult<()> {
+        let socket = socket()?;
+        assert!(matches!(
+            socket.v2c("[::1]:161", "public").await,
+            Err(SnmpError::SocketError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_peek_id() {
+        let data = [
+            0x30, 0x26, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa2,
+            0x19, 0x02, 0x04, 0x12, 0x34, 0x56, 0x78, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30,
+            0x0b, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x05, 0x00,
+        ];
+        assert_eq!(peek_id(&data), Some(0x12345678));
+        assert_eq!(peek_id(&data[..10]), None);
+        // SNMPv3 message is routed by msgID
+        let data = [
+            0x30, 0x19, 0x02, 0x01, 0x03, 0x30, 0x10, 0x02, 0x04, 0x05, 0x85, 0xc5, 0x8b, 0x02,
+            0x02, 0x05, 0xdc, 0x04, 0x01, 0x07, 0x02, 0x01, 0x03, 0x04, 0x00, 0x30, 0x00,
+        ];
+        assert_eq!(peek_id(&data), Some(0x0585c58b));
+    }
+}
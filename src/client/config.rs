@@ -0,0 +1,97 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Rust client settings
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use crate::hook::PacketHook;
+use crate::policer::Policer;
+use crate::socket::{Family, SocketConfig, Transport};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Client settings
+#[derive(Debug, Clone)]
+pub struct SnmpClientConfig {
+    /// Request timeout, including retransmissions
+    pub timeout: Duration,
+    /// Amount of retransmissions, UDP only
+    pub retries: u32,
+    /// Timeout of the first attempt. None - split `timeout` evenly
+    pub retry_timeout: Option<Duration>,
+    /// Multiplier of the timeout for every next attempt
+    pub backoff: f64,
+    pub transport: Transport,
+    /// Hostname resolution preference
+    pub family: Family,
+    pub tos: u32,
+    /// IPv4 TTL or IPv6 hop limit, 0 - system default
+    pub ttl: u32,
+    /// Send buffer size, 0 - system default
+    pub send_buffer_size: usize,
+    /// Receive buffer size, 0 - system default
+    pub recv_buffer_size: usize,
+    /// Local address to bind
+    pub bind_addr: Option<String>,
+    /// Local port to bind, 0 - ephemeral port
+    pub bind_port: u16,
+    /// Bind to network device
+    pub bind_device: Option<String>,
+    /// SNMPv3 msgMaxSize and the largest TCP message to receive,
+    /// 0 - default
+    pub max_msg_size: usize,
+    /// Shared policer
+    pub policer: Option<Arc<Policer>>,
+    /// Invoked for every sent and received message
+    pub packet_hook: Option<Arc<dyn PacketHook>>,
+}
+
+impl Default for SnmpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            retries: 0,
+            retry_timeout: None,
+            backoff: 1.0,
+            transport: Transport::default(),
+            family: Family::default(),
+            tos: 0,
+            ttl: 0,
+            send_buffer_size: 0,
+            recv_buffer_size: 0,
+            bind_addr: None,
+            bind_port: 0,
+            bind_device: None,
+            max_msg_size: 0,
+            policer: None,
+            packet_hook: None,
+        }
+    }
+}
+
+impl From<SnmpClientConfig> for SocketConfig {
+    fn from(value: SnmpClientConfig) -> Self {
+        SocketConfig {
+            transport: value.transport,
+            tos: value.tos,
+            ttl: value.ttl,
+            send_buffer_size: value.send_buffer_size,
+            recv_buffer_size: value.recv_buffer_size,
+            timeout_ns: value.timeout.as_nanos() as u64,
+            bind_addr: value.bind_addr,
+            bind_port: value.bind_port,
+            bind_device: value.bind_device,
+            family: value.family,
+            retries: value.retries,
+            retry_timeout_ns: value
+                .retry_timeout
+                .map(|x| x.as_nanos() as u64)
+                .unwrap_or(0),
+            backoff: value.backoff,
+            policer: value.policer,
+            packet_hook: value.packet_hook,
+            max_msg_size: value.max_msg_size,
+        }
+    }
+}
@@ -0,0 +1,747 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Rust client
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+#[cfg(feature = "tokio")]
+mod async_client;
+mod config;
+mod op;
+mod usm;
+mod walk;
+
+pub use crate::hook::{Packet, PacketDirection, PacketHook};
+pub use crate::policer::Policer;
+pub use crate::socket::{Family, Transport};
+#[cfg(feature = "tokio")]
+pub use async_client::{AsyncSnmpClient, AsyncSnmpSocket};
+pub use config::SnmpClientConfig;
+pub use usm::{Auth, KeyType, Privacy, UsmUser};
+pub use walk::Walk;
+
+use crate::{
+    ber::SnmpOid,
+    buf::get_buffer_pool,
+    error::{SnmpError, SnmpResult},
+    hook::Tracer,
+    snmp::{getresponse::SnmpGetResponse, pdu::SnmpPdu, value::OwnedValue},
+    socket::{
+        Attempts, Proto, SnmpIo, SnmpV1Proto, SnmpV2cProto, SnmpV3Proto, SocketConfig, fit_max_size,
+    },
+};
+use std::os::fd::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+
+/// Blocking SNMP client.
+///
+/// Values are returned as `OwnedValue`, detached from the received messages,
+/// oids are in numeric format without leading dot.
+pub struct SnmpClient {
+    io: SnmpIo,
+    proto: Proto,
+    timeout: Duration,
+}
+
+impl SnmpClient {
+    /// SNMPv1 client. `addr` is `host:port`.
+    pub fn v1(addr: &str, community: &str, cfg: SnmpClientConfig) -> SnmpResult<Self> {
+        Self::new(addr, Proto::V1(SnmpV1Proto::new(community.into())), cfg)
+    }
+    /// SNMPv2c client. `addr` is `host:port`.
+    pub fn v2c(addr: &str, community: &str, cfg: SnmpClientConfig) -> SnmpResult<Self> {
+        Self::new(addr, Proto::V2c(SnmpV2cProto::new(community.into())), cfg)
+    }
+    /// SNMPv3 client. `addr` is `host:port`.
+    ///
+    /// Engine id is discovered when not set. Engine boots and time
+    /// are requested before returning the client.
+    pub fn v3(
+        addr: &str,
+        engine_id: Option<Vec<u8>>,
+        user: UsmUser,
+        cfg: SnmpClientConfig,
+    ) -> SnmpResult<Self> {
+        let max_size = fit_max_size(cfg.max_msg_size)
+            .ok_or_else(|| SnmpError::SocketError("invalid max message size".into()))?;
+        let discover = engine_id.is_none();
+        // Engine id discovery is not authenticated
+        let initial = if discover {
+            UsmUser::default()
+        } else {
+            user.clone()
+        };
+        let proto = SnmpV3Proto::new(
+            engine_id.unwrap_or_default(),
+            initial.name.clone(),
+            initial.get_auth(),
+            initial.get_privacy(),
+            max_size,
+        )?;
+        let mut client = Self::new(addr, Proto::V3(Box::new(proto)), cfg)?;
+        if discover {
+            client.refresh()?;
+            if let Proto::V3(proto) = &mut client.proto {
+                proto.set_keys(user.name.clone(), user.get_auth(), user.get_privacy())?;
+            }
+        }
+        if discover || user.require_auth() {
+            client.refresh()?;
+        }
+        Ok(client)
+    }
+
+    fn new(addr: &str, proto: Proto, cfg: SnmpClientConfig) -> SnmpResult<Self> {
+        if cfg.timeout.is_zero() {
+            return Err(SnmpError::SocketError("invalid timeout".into()));
+        }
+        let timeout = cfg.timeout;
+        Ok(Self {
+            io: SnmpIo::new(addr.into(), SocketConfig::from(cfg))?,
+            proto,
+            timeout,
+        })
+    }
+    /// Agent's address, actually used
+    pub fn peer_addr(&self) -> String {
+        self.io.peer_addr()
+    }
+    /// Round-trip time of the last completed request
+    pub fn rtt(&self) -> Option<Duration> {
+        self.io.rtt()
+    }
+    /// Effective engine id. SNMPv3 only.
+    pub fn engine_id(&self) -> Option<&[u8]> {
+        match &self.proto {
+            Proto::V3(proto) => Some(proto.get_engine_id()),
+            _ => None,
+        }
+    }
+    /// Send GET request with single oid.
+    /// Returns `NoSuchInstance` error when the oid is not found.
+    pub fn get(&mut self, oid: &str) -> SnmpResult<OwnedValue> {
+        let oid = SnmpOid::try_from(oid)?;
+        self.request(
+            |request_id| op::get_request(request_id, vec![oid]),
+            op::get_value,
+        )
+    }
+    /// Send GET request with multiple oids.
+    /// Returns found oids along with values, missed ones are skipped.
+    ///
+    /// Oids are split to the several requests to fit agent's msgMaxSize.
+    pub fn get_many(&mut self, oids: &[&str]) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let vars = oids
+            .iter()
+            .map(|x| SnmpOid::try_from(*x))
+            .collect::<SnmpResult<Vec<_>>>()?;
+        let mut r = Vec::with_capacity(vars.len());
+        for batch in op::get_batches(&vars, op::max_varbinds(&self.proto)) {
+            r.extend(self.request(
+                |request_id| op::get_request(request_id, batch.to_vec()),
+                op::get_values,
+            )?);
+        }
+        Ok(r)
+    }
+    /// Send GETNEXT request.
+    /// Returns None at the end of MIB view.
+    pub fn get_next(&mut self, oid: &str) -> SnmpResult<Option<(String, OwnedValue)>> {
+        let oid = SnmpOid::try_from(oid)?;
+        self.get_next_oid(oid, |_| true)
+    }
+    /// Send GETBULK request. SNMPv2c and SNMPv3 only.
+    /// Returns up to `max_repetitions` of the following oids along with values.
+    pub fn get_bulk(
+        &mut self,
+        oid: &str,
+        max_repetitions: i64,
+    ) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let oid = SnmpOid::try_from(oid)?;
+        self.get_bulk_oid(oid, max_repetitions, |_| true)
+    }
+    /// Iterate over the MIB subtree using GETNEXT requests.
+    pub fn walk(&mut self, oid: &str) -> SnmpResult<Walk<'_>> {
+        Walk::new(self, oid, None)
+    }
+    /// Iterate over the MIB subtree using GETBULK requests.
+    /// SNMPv2c and SNMPv3 only.
+    pub fn bulk_walk(&mut self, oid: &str, max_repetitions: i64) -> SnmpResult<Walk<'_>> {
+        Walk::new(self, oid, Some(max_repetitions))
+    }
+    /// Send and receive REPORT to refresh authentication state.
+    /// SNMPv3 only, does nothing for other versions.
+    ///
+    /// Should be called again if over 150 seconds passed
+    /// from the last request.
+    pub fn refresh(&mut self) -> SnmpResult<()> {
+        if !matches!(self.proto, Proto::V3(_)) {
+            return Ok(());
+        }
+        self.request_pdu(|request_id| op::get_request(request_id, vec![]), |_| Ok(()))
+    }
+
+    // GETNEXT with parsed oid.
+    // `accept` checks if the returned oid is still in the scope.
+    fn get_next_oid(
+        &mut self,
+        oid: SnmpOid<'static>,
+        accept: impl FnMut(&SnmpOid) -> bool,
+    ) -> SnmpResult<Option<(String, OwnedValue)>> {
+        self.request(
+            |request_id| op::get_next_request(request_id, oid),
+            |resp| op::next_value(resp, accept),
+        )
+    }
+
+    // GETBULK with parsed oid.
+    // Stops at the first oid, rejected by `accept`.
+    fn get_bulk_oid(
+        &mut self,
+        oid: SnmpOid<'static>,
+        max_repetitions: i64,
+        accept: impl FnMut(&SnmpOid) -> bool,
+    ) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        let max_repetitions = op::fit_max_repetitions(&self.proto, max_repetitions)?;
+        self.request(
+            |request_id| op::get_bulk_request(request_id, oid, max_repetitions),
+            |resp| op::bulk_values(resp, accept),
+        )
+    }
+
+    // Send request and receive GETRESPONSE.
+    fn request<T>(
+        &mut self,
+        build: impl FnOnce(i64) -> SnmpPdu<'static>,
+        handle: impl FnOnce(&SnmpGetResponse) -> SnmpResult<T>,
+    ) -> SnmpResult<T> {
+        self.request_pdu(build, |pdu| handle(op::as_response(pdu)?))
+    }
+
+    // Send request and receive reply to it,
+    // retransmitting the request according to retry policy.
+    fn request_pdu<T>(
+        &mut self,
+        build: impl FnOnce(i64) -> SnmpPdu<'static>,
+        handle: impl FnOnce(&SnmpPdu) -> SnmpResult<T>,
+    ) -> SnmpResult<T> {
+        let request_id = self.proto.get_request_id().get_next();
+        let r = self.exchange(request_id, build(request_id), handle);
+        // Late replies are not expected
+        self.proto.complete(request_id);
+        r
+    }
+
+    fn exchange<T>(
+        &mut self,
+        request_id: i64,
+        pdu: SnmpPdu,
+        handle: impl FnOnce(&SnmpPdu) -> SnmpResult<T>,
+    ) -> SnmpResult<T> {
+        let deadline = Instant::now() + self.timeout;
+        let attempts = Attempts::new(self.io.retry_policy(self.timeout), deadline);
+        let mut h = get_buffer_pool().acquire();
+        let buf = h.as_mut();
+        let tracer = self.io.tracer();
+        let summary = tracer.as_ref().and_then(|_| Tracer::summary(&pdu));
+        let trace = |data: &[u8], pdu: Option<&SnmpPdu>, discarded: bool| {
+            if let Some(tracer) = &tracer {
+                tracer.received(data, pdu, discarded);
+            }
+        };
+        for attempt_deadline in attempts {
+            // Apply policer before encoding, so engine time
+            // and authentication are not stale after the wait
+            self.io.police();
+            // Each attempt is a separate message
+            buf.reset();
+            self.proto.push_pdu(pdu.clone(), buf)?;
+            self.io.send(buf.data())?;
+            // Retransmissions restart the round-trip time
+            self.proto
+                .get_request_id()
+                .set_sent_at(request_id, Instant::now());
+            if let Some(tracer) = &tracer {
+                tracer.sent(buf.data(), summary.as_ref());
+            }
+            buf.reset();
+            // We can catch unwanted replies, so do it in a loop.
+            loop {
+                let data = match self
+                    .io
+                    .set_deadline(attempt_deadline)
+                    .and_then(|_| self.io.recv(buf))
+                {
+                    Ok(x) => x,
+                    Err(SnmpError::WouldBlock) => break,
+                    Err(e) => return Err(e),
+                };
+                let rtt = self
+                    .io
+                    .received_at()
+                    .and_then(|t| self.proto.get_request_id().rtt(request_id, t));
+                match self.proto.unwrap_data(data) {
+                    Ok(Some((id, ref pdu))) if id == request_id => {
+                        trace(data, Some(pdu), false);
+                        self.io.set_rtt(rtt);
+                        return handle(pdu);
+                    }
+                    Ok(x) => {
+                        trace(data, x.as_ref().map(|(_, pdu)| pdu), true);
+                        buf.reset();
+                    }
+                    Err(e) => {
+                        trace(data, None, true);
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Err(SnmpError::Timeout)
+    }
+}
+
+impl AsRawFd for SnmpClient {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::{BerEncoder, SnmpInt, SnmpOctetString, objectid::OidStorage};
+    use crate::buf::Buffer;
+    use crate::snmp::{
+        getresponse::SnmpVar,
+        msg::{
+            PduType, SnmpV2cMessage,
+            v3::{MIN_MAX_SIZE, MsgData, ScopedPdu, SnmpV3Message, UsmParameters},
+        },
+        value::SnmpValue,
+    };
+    use std::net::UdpSocket;
+    use std::sync::{Arc, Mutex};
+
+    // (oid, value), ordered
+    pub(super) const MIB: [(&str, i64); 4] = [
+        ("1.3.6.1.2.1.1.1.0", 1),
+        ("1.3.6.1.2.1.1.2.0", 2),
+        ("1.3.6.1.2.1.1.3.0", 3),
+        ("1.3.6.1.2.1.2.1.0", 4),
+    ];
+    pub(super) const DESCR: &[u8] = b"Test agent";
+    const ENGINE_ID: &[u8] = b"agent";
+
+    fn mib() -> Vec<(Vec<u8>, SnmpValue<'static>)> {
+        let mut r: Vec<(Vec<u8>, SnmpValue)> = MIB
+            .iter()
+            .map(|(oid, v)| {
+                let oid = SnmpOid::try_from(*oid).unwrap();
+                ((&oid).into(), SnmpValue::Int(SnmpInt::from(*v)))
+            })
+            .collect();
+        r.insert(
+            0,
+            (
+                (&SnmpOid::try_from("1.3.6.1.2.1.1.0.0").unwrap()).into(),
+                SnmpValue::OctetString(SnmpOctetString(DESCR)),
+            ),
+        );
+        r
+    }
+
+    // GETRESPONSE of the minimal agent to the request
+    fn respond<'a>(
+        mib: &[(Vec<u8>, SnmpValue<'static>)],
+        pdu: &SnmpPdu<'a>,
+    ) -> Option<SnmpPdu<'a>> {
+        let next = |oid: &SnmpOid| mib.iter().find(|(x, _)| x.as_slice() > oid.0.as_ref());
+        let (request_id, vars) = match pdu {
+            SnmpPdu::GetRequest(req) => (
+                req.request_id,
+                req.vars
+                    .iter()
+                    .map(|oid| SnmpVar {
+                        oid: oid.clone(),
+                        value: mib
+                            .iter()
+                            .find(|(x, _)| x.as_slice() == oid.0.as_ref())
+                            .map(|(_, v)| v.clone())
+                            .unwrap_or(SnmpValue::NoSuchInstance),
+                    })
+                    .collect(),
+            ),
+            SnmpPdu::GetNextRequest(req) => (
+                req.request_id,
+                vec![match next(&req.vars[0]) {
+                    Some((oid, value)) => SnmpVar {
+                        oid: oid.as_owned(),
+                        value: value.clone(),
+                    },
+                    None => SnmpVar {
+                        oid: req.vars[0].clone(),
+                        value: SnmpValue::EndOfMibView,
+                    },
+                }],
+            ),
+            SnmpPdu::GetBulkRequest(req) => {
+                let mut vars = Vec::new();
+                let mut oid = req.vars[0].clone();
+                for _ in 0..req.max_repetitions {
+                    match next(&oid) {
+                        Some((x, value)) => {
+                            oid = x.as_owned();
+                            vars.push(SnmpVar {
+                                oid: oid.clone(),
+                                value: value.clone(),
+                            });
+                        }
+                        None => {
+                            vars.push(SnmpVar {
+                                oid,
+                                value: SnmpValue::EndOfMibView,
+                            });
+                            break;
+                        }
+                    }
+                }
+                (req.request_id, vars)
+            }
+            _ => return None,
+        };
+        Some(SnmpPdu::GetResponse(SnmpGetResponse {
+            request_id,
+            error_status: 0,
+            error_index: 0,
+            vars,
+        }))
+    }
+
+    // Minimal SNMPv2c agent.
+    // Returns address to connect.
+    pub(super) fn run_agent() -> String {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let mib = mib();
+            let mut data = [0u8; 1500];
+            loop {
+                let Ok((size, peer)) = sock.recv_from(&mut data) else {
+                    return;
+                };
+                let Ok(msg) = SnmpV2cMessage::try_from(&data[..size]) else {
+                    continue;
+                };
+                let Some(pdu) = respond(&mib, &msg.pdu) else {
+                    continue;
+                };
+                let reply = SnmpV2cMessage {
+                    community: msg.community,
+                    pdu,
+                };
+                let mut buf = Buffer::default();
+                if reply.push_ber(&mut buf).is_ok() {
+                    let _ = sock.send_to(buf.data(), peer);
+                }
+            }
+        });
+        addr
+    }
+
+    // Minimal SNMPv3 agent without authentication,
+    // reporting `max_size` as msgMaxSize.
+    // Returns address to connect and the amounts of oids
+    // in the received GET requests.
+    pub(super) fn run_v3_agent(max_size: i64) -> (String, Arc<Mutex<Vec<usize>>>) {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            let mib = mib();
+            let mut data = [0u8; 1500];
+            loop {
+                let Ok((size, peer)) = sock.recv_from(&mut data) else {
+                    return;
+                };
+                let Ok(msg) = SnmpV3Message::try_from(&data[..size]) else {
+                    continue;
+                };
+                let MsgData::Plaintext(scoped_pdu) = msg.data else {
+                    continue;
+                };
+                // Skip engine discovery and refresh
+                if let SnmpPdu::GetRequest(req) = &scoped_pdu.pdu
+                    && !req.vars.is_empty()
+                {
+                    log.lock().unwrap().push(req.vars.len());
+                }
+                let Some(pdu) = respond(&mib, &scoped_pdu.pdu) else {
+                    continue;
+                };
+                let reply = SnmpV3Message {
+                    msg_id: msg.msg_id,
+                    max_size,
+                    flag_auth: false,
+                    flag_priv: false,
+                    flag_report: false,
+                    usm: UsmParameters {
+                        engine_id: ENGINE_ID,
+                        engine_boots: 1,
+                        engine_time: 0,
+                        user_name: msg.usm.user_name,
+                        auth_params: &[],
+                        privacy_params: &[],
+                    },
+                    data: MsgData::Plaintext(ScopedPdu {
+                        engine_id: ENGINE_ID,
+                        pdu,
+                    }),
+                };
+                let mut buf = Buffer::default();
+                if reply.push_ber(&mut buf).is_ok() {
+                    let _ = sock.send_to(buf.data(), peer);
+                }
+            }
+        });
+        (addr, requests)
+    }
+
+    fn client() -> SnmpResult<SnmpClient> {
+        let cfg = SnmpClientConfig {
+            timeout: Duration::from_secs(5),
+            ..Default::default()
+        };
+        SnmpClient::v2c(&run_agent(), "public", cfg)
+    }
+
+    #[test]
+    fn test_get() -> SnmpResult<()> {
+        let mut client = client()?;
+        assert_eq!(
+            client.get("1.3.6.1.2.1.1.0.0")?,
+            OwnedValue::OctetString(DESCR.to_vec())
+        );
+        assert_eq!(client.get("1.3.6.1.2.1.1.2.0")?, OwnedValue::Int(2));
+        assert!(matches!(
+            client.get("1.3.6.1.2.1.1.9.0"),
+            Err(SnmpError::NoSuchInstance)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rtt() -> SnmpResult<()> {
+        let mut client = client()?;
+        assert!(client.rtt().is_none());
+        client.get("1.3.6.1.2.1.1.2.0")?;
+        let rtt = client.rtt().unwrap();
+        assert!(rtt < Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_many() -> SnmpResult<()> {
+        let mut client = client()?;
+        let r = client.get_many(&[
+            "1.3.6.1.2.1.1.1.0",
+            "1.3.6.1.2.1.1.9.0",
+            "1.3.6.1.2.1.1.3.0",
+        ])?;
+        assert_eq!(
+            r,
+            vec![
+                ("1.3.6.1.2.1.1.1.0".into(), OwnedValue::Int(1)),
+                ("1.3.6.1.2.1.1.3.0".into(), OwnedValue::Int(3)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_many_batches() -> SnmpResult<()> {
+        let (addr, requests) = run_v3_agent(MIN_MAX_SIZE);
+        let cfg = SnmpClientConfig {
+            timeout: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let mut client = SnmpClient::v3(&addr, None, UsmUser::new("user"), cfg)?;
+        assert_eq!(client.engine_id(), Some(ENGINE_ID));
+        let size = op::max_varbinds(&client.proto);
+        assert!(size > 1);
+        // Two full batches and one more oid
+        let expected = (0..2 * size + 1)
+            .map(|i| MIB[i % MIB.len()])
+            .collect::<Vec<_>>();
+        let oids = expected.iter().map(|(oid, _)| *oid).collect::<Vec<_>>();
+        let r = client.get_many(&oids)?;
+        assert_eq!(
+            r,
+            expected
+                .iter()
+                .map(|(oid, v)| (oid.to_string(), OwnedValue::Int(*v)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(*requests.lock().unwrap(), vec![size, size, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_next() -> SnmpResult<()> {
+        let mut client = client()?;
+        assert_eq!(
+            client.get_next("1.3.6.1.2.1.1.1.0")?,
+            Some(("1.3.6.1.2.1.1.2.0".into(), OwnedValue::Int(2)))
+        );
+        assert_eq!(client.get_next("1.3.6.1.2.1.2.1.0")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_bulk() -> SnmpResult<()> {
+        let mut client = client()?;
+        let r = client.get_bulk("1.3.6.1.2.1.1.1.0", 2)?;
+        assert_eq!(
+            r,
+            vec![
+                ("1.3.6.1.2.1.1.2.0".into(), OwnedValue::Int(2)),
+                ("1.3.6.1.2.1.1.3.0".into(), OwnedValue::Int(3)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk() -> SnmpResult<()> {
+        let mut client = client()?;
+        let expected = vec![
+            (
+                "1.3.6.1.2.1.1.0.0".into(),
+                OwnedValue::OctetString(DESCR.to_vec()),
+            ),
+            ("1.3.6.1.2.1.1.1.0".into(), OwnedValue::Int(1)),
+            ("1.3.6.1.2.1.1.2.0".into(), OwnedValue::Int(2)),
+            ("1.3.6.1.2.1.1.3.0".into(), OwnedValue::Int(3)),
+        ];
+        let r = client
+            .walk("1.3.6.1.2.1.1")?
+            .collect::<SnmpResult<Vec<_>>>()?;
+        assert_eq!(r, expected);
+        for max_repetitions in [1, 2, 10] {
+            let r = client
+                .bulk_walk("1.3.6.1.2.1.1", max_repetitions)?
+                .collect::<SnmpResult<Vec<_>>>()?;
+            assert_eq!(r, expected);
+        }
+        // End of MIB
+        let r = client.bulk_walk("1.3.6.1.2.1.2", 10)?.count();
+        assert_eq!(r, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout() -> SnmpResult<()> {
+        // Nobody answers
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let cfg = SnmpClientConfig {
+            timeout: Duration::from_millis(100),
+            retries: 1,
+            ..Default::default()
+        };
+        let mut client = SnmpClient::v2c(&sock.local_addr().unwrap().to_string(), "public", cfg)?;
+        assert!(matches!(
+            client.get("1.3.6.1.2.1.1.1.0"),
+            Err(SnmpError::Timeout)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_v1() -> SnmpResult<()> {
+        let mut client = SnmpClient::v1(&run_agent(), "public", SnmpClientConfig::default())?;
+        assert!(matches!(
+            client.get_bulk("1.3.6.1.2.1.1", 10),
+            Err(SnmpError::NotImplemented)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_packet_hook() -> SnmpResult<()> {
+        // Agent sends stale reply before the matching one
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = sock.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let mut data = [0u8; 1500];
+            let (size, peer) = sock.recv_from(&mut data).unwrap();
+            let msg = SnmpV2cMessage::try_from(&data[..size]).unwrap();
+            for request_id in [msg.pdu.get_request_id() + 1, msg.pdu.get_request_id()] {
+                let reply = SnmpV2cMessage {
+                    community: msg.community,
+                    pdu: SnmpPdu::GetResponse(SnmpGetResponse {
+                        request_id,
+                        error_status: 0,
+                        error_index: 0,
+                        vars: vec![SnmpVar {
+                            oid: SnmpOid::try_from("1.3.6.1.2.1.1.2.0").unwrap(),
+                            value: SnmpValue::Int(SnmpInt::from(2)),
+                        }],
+                    }),
+                };
+                let mut buf = Buffer::default();
+                reply.push_ber(&mut buf).unwrap();
+                sock.send_to(buf.data(), peer).unwrap();
+            }
+        });
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let hook = {
+            let log = log.clone();
+            move |p: &Packet| {
+                log.lock().unwrap().push((
+                    p.direction,
+                    p.peer.to_owned(),
+                    p.pdu.map(|x| (x.pdu_type, x.request_id)),
+                    p.discarded,
+                ))
+            }
+        };
+        let cfg = SnmpClientConfig {
+            timeout: Duration::from_secs(5),
+            packet_hook: Some(Arc::new(hook)),
+            ..Default::default()
+        };
+        let mut client = SnmpClient::v2c(&addr, "public", cfg)?;
+        assert_eq!(client.get("1.3.6.1.2.1.1.2.0")?, OwnedValue::Int(2));
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 3);
+        let (_, _, Some((PduType::GetRequest, request_id)), false) = log[0] else {
+            panic!("invalid request: {:?}", log[0]);
+        };
+        assert_eq!(
+            *log,
+            vec![
+                (
+                    PacketDirection::Sent,
+                    addr.clone(),
+                    Some((PduType::GetRequest, request_id)),
+                    false
+                ),
+                (
+                    PacketDirection::Received,
+                    addr.clone(),
+                    Some((PduType::GetResponse, request_id + 1)),
+                    true
+                ),
+                (
+                    PacketDirection::Received,
+                    addr,
+                    Some((PduType::GetResponse, request_id)),
+                    false
+                ),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Rust client requests and replies
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use crate::{
+    ber::SnmpOid,
+    error::{SnmpError, SnmpResult},
+    snmp::{
+        get::SnmpGet,
+        getbulk::SnmpGetBulk,
+        getresponse::{SnmpGetResponse, SnmpVar},
+        pdu::SnmpPdu,
+        value::{OwnedValue, SnmpValue},
+    },
+    socket::Proto,
+};
+
+pub(crate) type Var = (String, OwnedValue);
+
+/// GET request
+pub(crate) fn get_request(request_id: i64, vars: Vec<SnmpOid<'static>>) -> SnmpPdu<'static> {
+    SnmpPdu::GetRequest(SnmpGet { request_id, vars })
+}
+
+/// GETNEXT request
+pub(crate) fn get_next_request(request_id: i64, oid: SnmpOid<'static>) -> SnmpPdu<'static> {
+    SnmpPdu::GetNextRequest(SnmpGet {
+        request_id,
+        vars: vec![oid],
+    })
+}
+
+/// GETBULK request
+pub(crate) fn get_bulk_request(
+    request_id: i64,
+    oid: SnmpOid<'static>,
+    max_repetitions: i64,
+) -> SnmpPdu<'static> {
+    SnmpPdu::GetBulkRequest(SnmpGetBulk {
+        request_id,
+        non_repeaters: 0,
+        max_repetitions,
+        vars: vec![oid],
+    })
+}
+
+/// Limit max-repetitions according to protocol.
+/// GETBULK is not supported by SNMPv1.
+pub(crate) fn fit_max_repetitions(proto: &Proto, max_repetitions: i64) -> SnmpResult<i64> {
+    match proto {
+        Proto::V1(_) => Err(SnmpError::NotImplemented),
+        Proto::V2c(_) => Ok(max_repetitions),
+        Proto::V3(proto) => Ok(proto.fit_max_repetitions(max_repetitions)),
+    }
+}
+
+/// Maximal amount of varbinds, fitting agent's msgMaxSize.
+/// 0 if unlimited or unknown.
+pub(crate) fn max_varbinds(proto: &Proto) -> usize {
+    match proto {
+        Proto::V3(proto) => proto.max_varbinds(),
+        _ => 0,
+    }
+}
+
+/// Split oids to the batches of up to `size` items, 0 if unlimited.
+pub(crate) fn get_batches<T>(items: &[T], size: usize) -> std::slice::Chunks<'_, T> {
+    match size {
+        0 => items.chunks(items.len().max(1)),
+        n => items.chunks(n),
+    }
+}
+
+/// Get GETRESPONSE from the reply
+pub(crate) fn as_response<'a>(pdu: &'a SnmpPdu<'a>) -> SnmpResult<&'a SnmpGetResponse<'a>> {
+    match pdu {
+        SnmpPdu::GetResponse(resp) => Ok(resp),
+        SnmpPdu::Report(report) => Err(report.report_error()),
+        _ => Err(SnmpError::InvalidPdu),
+    }
+}
+
+/// Value of GET reply
+pub(crate) fn get_value(resp: &SnmpGetResponse) -> SnmpResult<OwnedValue> {
+    resp.check_error()?;
+    match resp.vars.as_slice() {
+        [] => Ok(OwnedValue::Null),
+        [var] => match &var.value {
+            SnmpValue::NoSuchObject | SnmpValue::NoSuchInstance | SnmpValue::EndOfMibView => {
+                Err(SnmpError::NoSuchInstance)
+            }
+            value => OwnedValue::try_from(value),
+        },
+        // Multiple response, surely an error
+        _ => Err(SnmpError::InvalidPdu),
+    }
+}
+
+/// Values of GET reply with multiple oids
+pub(crate) fn get_values(resp: &SnmpGetResponse) -> SnmpResult<Vec<Var>> {
+    resp.check_error()?;
+    resp.vars.iter().filter_map(to_owned_var).collect()
+}
+
+/// Value of GETNEXT reply.
+/// `accept` checks if the returned oid is still in the scope.
+pub(crate) fn next_value(
+    resp: &SnmpGetResponse,
+    mut accept: impl FnMut(&SnmpOid) -> bool,
+) -> SnmpResult<Option<Var>> {
+    match resp.vars.as_slice() {
+        [] => Ok(None),
+        [var] if !accept(&var.oid) => Ok(None),
+        // v1 may return Null at end of mib
+        [var] => match &var.value {
+            SnmpValue::EndOfMibView | SnmpValue::Null => Ok(None),
+            value => Ok(Some((String::try_from(&var.oid)?, value.try_into()?))),
+        },
+        // Multiple response, surely an error
+        _ => Err(SnmpError::InvalidPdu),
+    }
+}
+
+/// Values of GETBULK reply.
+/// Stops at the first oid, rejected by `accept`.
+pub(crate) fn bulk_values(
+    resp: &SnmpGetResponse,
+    mut accept: impl FnMut(&SnmpOid) -> bool,
+) -> SnmpResult<Vec<Var>> {
+    let mut r = Vec::with_capacity(resp.vars.len());
+    for var in resp.vars.iter() {
+        let Some(item) = to_owned_var(var) else {
+            continue;
+        };
+        if !accept(&var.oid) {
+            break;
+        }
+        r.push(item?);
+    }
+    Ok(r)
+}
+
+// Convert varbind to owned oid and value.
+// Returns None for missed values.
+fn to_owned_var(var: &SnmpVar) -> Option<SnmpResult<Var>> {
+    match &var.value {
+        SnmpValue::Null
+        | SnmpValue::NoSuchObject
+        | SnmpValue::NoSuchInstance
+        | SnmpValue::EndOfMibView => None,
+        value => {
+            Some(String::try_from(&var.oid).and_then(|oid| Ok((oid, OwnedValue::try_from(value)?))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_batches() {
+        let items = [1, 2, 3, 4, 5];
+        for (size, expected) in [
+            (0, vec![&items[..]]),
+            (5, vec![&items[..]]),
+            (10, vec![&items[..]]),
+            (2, vec![&items[..2], &items[2..4], &items[4..]]),
+            (1, items.chunks(1).collect()),
+        ] {
+            assert_eq!(get_batches(&items, size).collect::<Vec<_>>(), expected);
+        }
+        // Empty request is not sent
+        assert_eq!(get_batches::<i32>(&[], 0).count(), 0);
+    }
+}
@@ -0,0 +1,121 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: SNMPv3 User
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use crate::auth::{MD5_AUTH, NO_AUTH, SHA1_AUTH};
+
+// Privacy algorithm codes, as expected by PrivKey
+const NO_PRIV: u8 = 0;
+const DES_PRIV: u8 = 1;
+const AES128_PRIV: u8 = 2;
+
+/// Form of the key
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeyType {
+    /// Password, converted to the master key and localized
+    #[default]
+    Password,
+    /// Master key, localized to agent's engine id
+    Master,
+    /// Key, already localized to agent's engine id
+    Localized,
+}
+
+impl KeyType {
+    // Mask, applied to algorithm code
+    fn mask(&self) -> u8 {
+        match self {
+            KeyType::Password => 0,
+            KeyType::Master => 0x40,
+            KeyType::Localized => 0x80,
+        }
+    }
+}
+
+/// Authentication algorithm and key
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Auth {
+    #[default]
+    None,
+    Md5(KeyType, Vec<u8>),
+    Sha1(KeyType, Vec<u8>),
+}
+
+/// Privacy algorithm and key
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Privacy {
+    #[default]
+    None,
+    Des(KeyType, Vec<u8>),
+    Aes128(KeyType, Vec<u8>),
+}
+
+/// SNMPv3 User-based Security Model credentials
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UsmUser {
+    pub name: String,
+    pub auth: Auth,
+    pub privacy: Privacy,
+}
+
+impl UsmUser {
+    /// User without authentication and privacy
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+    /// Set authentication
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+    /// Set privacy
+    pub fn with_privacy(mut self, privacy: Privacy) -> Self {
+        self.privacy = privacy;
+        self
+    }
+    /// Check if user requires authentication
+    pub fn require_auth(&self) -> bool {
+        !matches!(self.auth, Auth::None)
+    }
+    // Auth algorithm code with key type mask, and the key
+    pub(crate) fn get_auth(&self) -> (u8, &[u8]) {
+        match &self.auth {
+            Auth::None => (NO_AUTH, &[]),
+            Auth::Md5(kt, key) => (MD5_AUTH | kt.mask(), key),
+            Auth::Sha1(kt, key) => (SHA1_AUTH | kt.mask(), key),
+        }
+    }
+    // Privacy algorithm code with key type mask, and the key
+    pub(crate) fn get_privacy(&self) -> (u8, &[u8]) {
+        match &self.privacy {
+            Privacy::None => (NO_PRIV, &[]),
+            Privacy::Des(kt, key) => (DES_PRIV | kt.mask(), key),
+            Privacy::Aes128(kt, key) => (AES128_PRIV | kt.mask(), key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        let user = UsmUser::new("user1")
+            .with_auth(Auth::Sha1(KeyType::Master, vec![1, 2]))
+            .with_privacy(Privacy::Aes128(KeyType::Localized, vec![3]));
+        assert!(user.require_auth());
+        assert_eq!(user.get_auth(), (0x42, [1u8, 2].as_ref()));
+        assert_eq!(user.get_privacy(), (0x82, [3u8].as_ref()));
+        let user = UsmUser::new("user2");
+        assert!(!user.require_auth());
+        assert_eq!(user.get_auth(), (0, [].as_ref()));
+        assert_eq!(user.get_privacy(), (0, [].as_ref()));
+    }
+}
@@ -0,0 +1,113 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: MIB subtree iterator
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::SnmpClient;
+use crate::{
+    ber::{SnmpOid, objectid::OidStorage},
+    error::SnmpResult,
+    snmp::value::OwnedValue,
+};
+use std::collections::VecDeque;
+
+/// Position of the walk within the subtree.
+pub(crate) struct Scope {
+    start_oid: Vec<u8>,
+    next_oid: Vec<u8>,
+    out_of_scope: bool,
+}
+
+impl Scope {
+    pub(crate) fn new(oid: &str) -> SnmpResult<Self> {
+        let oid = SnmpOid::try_from(oid)?;
+        Ok(Self {
+            start_oid: (&oid).into(),
+            next_oid: (&oid).into(),
+            out_of_scope: false,
+        })
+    }
+    /// Oid to request next
+    pub(crate) fn next_oid(&self) -> SnmpOid<'static> {
+        self.next_oid.as_owned()
+    }
+    /// Check if we can continue and save oid for next request
+    pub(crate) fn accept(&mut self, oid: &SnmpOid) -> bool {
+        if self.start_oid.as_borrowed().starts_with(oid) {
+            self.next_oid.store(oid);
+            true
+        } else {
+            self.out_of_scope = true;
+            false
+        }
+    }
+    /// Check if the received batch finishes the walk
+    pub(crate) fn is_done<T>(&self, batch: &[T]) -> bool {
+        batch.is_empty() || self.out_of_scope
+    }
+}
+
+/// Iterator over the MIB subtree.
+/// Yields oids along with values, stops on the first error.
+pub struct Walk<'a> {
+    client: &'a mut SnmpClient,
+    scope: Scope,
+    // None - use GETNEXT
+    max_repetitions: Option<i64>,
+    // Received but not yielded yet
+    ready: VecDeque<(String, OwnedValue)>,
+    done: bool,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(
+        client: &'a mut SnmpClient,
+        oid: &str,
+        max_repetitions: Option<i64>,
+    ) -> SnmpResult<Self> {
+        Ok(Self {
+            client,
+            scope: Scope::new(oid)?,
+            max_repetitions,
+            ready: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    // Request next portion of values
+    fn fetch(&mut self) -> SnmpResult<()> {
+        let oid = self.scope.next_oid();
+        let accept = |oid: &SnmpOid| self.scope.accept(oid);
+        match self.max_repetitions {
+            None => match self.client.get_next_oid(oid, accept)? {
+                Some(item) => self.ready.push_back(item),
+                None => self.done = true,
+            },
+            Some(max_repetitions) => {
+                let items = self.client.get_bulk_oid(oid, max_repetitions, accept)?;
+                self.done = self.scope.is_done(&items);
+                self.ready.extend(items);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = SnmpResult<(String, OwnedValue)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() {
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.fetch() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        self.ready.pop_front().map(Ok)
+    }
+}
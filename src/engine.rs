@@ -0,0 +1,891 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: SNMPv3 Authoritative Engine
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use crate::{
+    auth::{AuthKey, SnmpAuth},
+    ber::{BerEncoder, SnmpCounter32, SnmpOid},
+    buf::{Buffer, get_buffer_pool},
+    error::SnmpResult,
+    privacy::{PrivKey, SnmpPriv},
+    snmp::{
+        getresponse::{SnmpGetResponse, SnmpVar},
+        msg::v3::{DEFAULT_MAX_SIZE, MsgData, ScopedPdu, SnmpV3Message, UsmParameters},
+        pdu::SnmpPdu,
+        value::SnmpValue,
+    },
+};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBytes, PyList, PyTuple},
+};
+use std::{collections::HashMap, fs, io, time::Instant};
+
+// Upper limit for snmpEngineBoots and snmpEngineTime, RFC-3414 pp. 2.2.1
+const MAX_ENGINE_VALUE: i64 = 2147483647;
+// Time window, RFC-3414 pp. 2.2.3
+const TIME_WINDOW: i64 = 150;
+// SnmpEngineID size limits, RFC-3411 pp. 5
+const MIN_ENGINE_ID_SIZE: usize = 5;
+const MAX_ENGINE_ID_SIZE: usize = 32;
+const EMPTY: [u8; 0] = [];
+
+// usmStats counters, RFC-3414 pp. 5
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum UsmStat {
+    UnsupportedSecLevels = 0,
+    NotInTimeWindows,
+    UnknownUserNames,
+    UnknownEngineIds,
+    WrongDigests,
+    DecryptionErrors,
+}
+
+const USM_STATS_COUNT: usize = 6;
+
+impl UsmStat {
+    fn oid(&self) -> &'static str {
+        match self {
+            UsmStat::UnsupportedSecLevels => "1.3.6.1.6.3.15.1.1.1.0",
+            UsmStat::NotInTimeWindows => "1.3.6.1.6.3.15.1.1.2.0",
+            UsmStat::UnknownUserNames => "1.3.6.1.6.3.15.1.1.3.0",
+            UsmStat::UnknownEngineIds => "1.3.6.1.6.3.15.1.1.4.0",
+            UsmStat::WrongDigests => "1.3.6.1.6.3.15.1.1.5.0",
+            UsmStat::DecryptionErrors => "1.3.6.1.6.3.15.1.1.6.0",
+        }
+    }
+}
+
+// Entry of usmUserTable
+struct UsmUser {
+    auth_key: AuthKey,
+    // Decrypted PDU refers to the key's buffer,
+    // so separate keys are used for incoming and outgoing messages
+    rx_priv_key: PrivKey,
+    tx_priv_key: PrivKey,
+}
+
+// Local engine parameters for outgoing messages
+#[derive(Clone, Copy)]
+struct EngineState<'a> {
+    engine_id: &'a [u8],
+    boots: i64,
+    time: i64,
+}
+
+// Result of .process(): (reply, notification)
+type ProcessResult = (Option<Py<PyAny>>, Option<Py<PyAny>>);
+
+// Received notification
+pub(crate) struct Notification<'a> {
+    engine_id: &'a [u8],
+    user_name: &'a [u8],
+    vars: Vec<SnmpVar<'a>>,
+}
+
+/// Authoritative USM engine, receiving SNMPv3 traps and informs.
+#[pyclass]
+pub struct SnmpV3Engine {
+    engine_id: Vec<u8>,
+    engine_boots: i64,
+    boots_path: Option<String>,
+    started: Instant,
+    // (user name, engine id) -> keys
+    users: HashMap<(Vec<u8>, Vec<u8>), UsmUser>,
+    // Remote authoritative engine id -> (boots, time)
+    remote_engines: HashMap<Vec<u8>, (i64, i64)>,
+    stats: [u32; USM_STATS_COUNT],
+}
+
+#[pymethods]
+impl SnmpV3Engine {
+    /// Python constructor
+    #[new]
+    #[pyo3(signature = (engine_id, boots_path = None))]
+    fn new(engine_id: Vec<u8>, boots_path: Option<String>) -> PyResult<Self> {
+        if !(MIN_ENGINE_ID_SIZE..=MAX_ENGINE_ID_SIZE).contains(&engine_id.len()) {
+            return Err(PyValueError::new_err("invalid engine id"));
+        }
+        // snmpEngineBoots is incremented on each restart
+        let engine_boots = match &boots_path {
+            Some(path) => Self::next_boots(path)?,
+            None => 1,
+        };
+        Ok(Self::with_boots(engine_id, engine_boots, boots_path))
+    }
+    /// Get engine id
+    fn get_engine_id(&self, py: Python) -> PyResult<Py<PyAny>> {
+        Ok(PyBytes::new(py, &self.engine_id).into())
+    }
+    /// Get snmpEngineBoots
+    fn get_engine_boots(&mut self) -> i64 {
+        self.get_state().boots
+    }
+    /// Get snmpEngineTime
+    fn get_engine_time(&mut self) -> i64 {
+        self.get_state().time
+    }
+    /// Add user.
+    /// Empty engine id means local engine (informs),
+    /// otherwise it is the remote authoritative engine (traps).
+    fn add_user(
+        &mut self,
+        user_name: String,
+        engine_id: Vec<u8>,
+        auth_alg: u8,
+        auth_key: &[u8],
+        priv_alg: u8,
+        priv_key: &[u8],
+    ) -> PyResult<()> {
+        Ok(self.add_user_keys(
+            user_name.into_bytes(),
+            engine_id,
+            (auth_alg, auth_key),
+            (priv_alg, priv_key),
+        )?)
+    }
+    /// Process incoming message.
+    /// Returns tuple of:
+    /// * Reply to be sent back, if any.
+    /// * Tuple of (engine id, user name, varbinds) for received notification, if any.
+    fn process(&mut self, py: Python, data: &[u8]) -> PyResult<ProcessResult> {
+        let mut h = get_buffer_pool().acquire();
+        let buf = h.as_mut();
+        let notification = self.process_message(data, buf)?;
+        let reply = if buf.is_empty() {
+            None
+        } else {
+            Some(PyBytes::new(py, buf.data()).into_any().unbind())
+        };
+        let notification = match notification {
+            Some(n) => {
+                let vars = PyList::empty(py);
+                for var in n.vars.iter() {
+                    let value = match &var.value {
+                        SnmpValue::Null
+                        | SnmpValue::NoSuchObject
+                        | SnmpValue::NoSuchInstance
+                        | SnmpValue::EndOfMibView => py.None().into_bound(py),
+                        v => v.into_pyobject(py)?,
+                    };
+                    vars.append(PyTuple::new(py, [(&var.oid).into_pyobject(py)?, value])?)?;
+                }
+                Some(
+                    (
+                        PyBytes::new(py, n.engine_id),
+                        String::from_utf8_lossy(n.user_name),
+                        vars,
+                    )
+                        .into_pyobject(py)?
+                        .into_any()
+                        .unbind(),
+                )
+            }
+            None => None,
+        };
+        Ok((reply, notification))
+    }
+}
+
+impl SnmpV3Engine {
+    fn with_boots(engine_id: Vec<u8>, engine_boots: i64, boots_path: Option<String>) -> Self {
+        Self {
+            engine_id,
+            engine_boots,
+            boots_path,
+            started: Instant::now(),
+            users: HashMap::new(),
+            remote_engines: HashMap::new(),
+            stats: [0; USM_STATS_COUNT],
+        }
+    }
+    // Increment and store snmpEngineBoots
+    fn next_boots(path: &str) -> io::Result<i64> {
+        let boots = match fs::read_to_string(path) {
+            Ok(s) => s
+                .trim()
+                .parse::<i64>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        let boots = (boots + 1).min(MAX_ENGINE_VALUE);
+        fs::write(path, boots.to_string())?;
+        Ok(boots)
+    }
+    // Add user's keys.
+    // Keys are passed as (algorithm, key)
+    fn add_user_keys(
+        &mut self,
+        user_name: Vec<u8>,
+        engine_id: Vec<u8>,
+        auth: (u8, &[u8]),
+        privacy: (u8, &[u8]),
+    ) -> SnmpResult<()> {
+        let engine_id = if engine_id.is_empty() {
+            self.engine_id.clone()
+        } else {
+            engine_id
+        };
+        // Auth key
+        let mut auth_key = AuthKey::new(auth.0)?;
+        auth_key.as_key_type(auth.0, auth.1, &engine_id)?;
+        // Priv keys
+        let rx_priv_key = Self::get_priv_key(auth.0, privacy.0, privacy.1, &engine_id)?;
+        let tx_priv_key = Self::get_priv_key(auth.0, privacy.0, privacy.1, &engine_id)?;
+        self.users.insert(
+            (user_name, engine_id),
+            UsmUser {
+                auth_key,
+                rx_priv_key,
+                tx_priv_key,
+            },
+        );
+        Ok(())
+    }
+    // Localize privacy key
+    fn get_priv_key(
+        auth_alg: u8,
+        priv_alg: u8,
+        priv_key: &[u8],
+        engine_id: &[u8],
+    ) -> SnmpResult<PrivKey> {
+        let mut pk = PrivKey::new(priv_alg)?;
+        if pk.has_priv() {
+            let mut pk_auth = AuthKey::new(auth_alg)?;
+            pk_auth.as_key_type(priv_alg, priv_key, engine_id)?;
+            pk.as_localized(pk_auth.get_key())?;
+        }
+        Ok(pk)
+    }
+    // Get current engine id, snmpEngineBoots and snmpEngineTime.
+    // Increment boots when time overflows, RFC-3414 pp. 2.2.1
+    fn get_state(&mut self) -> EngineState<'_> {
+        let mut time = self.started.elapsed().as_secs() as i64;
+        if time > MAX_ENGINE_VALUE {
+            self.started = Instant::now();
+            self.engine_boots = (self.engine_boots + 1).min(MAX_ENGINE_VALUE);
+            if let Some(path) = &self.boots_path {
+                // Keep running with in-memory value on failure
+                let _ = fs::write(path, self.engine_boots.to_string());
+            }
+            time = 0;
+        }
+        EngineState {
+            engine_id: &self.engine_id,
+            boots: self.engine_boots,
+            time,
+        }
+    }
+    // Check remote engine's time window, RFC-3414 pp. 3.2.7 (b)
+    // Updates the cached notion of remote boots and time.
+    fn check_remote_time(&mut self, engine_id: &[u8], boots: i64, time: i64) -> bool {
+        if boots >= MAX_ENGINE_VALUE {
+            return false;
+        }
+        match self.remote_engines.get_mut(engine_id) {
+            Some((r_boots, r_time)) => {
+                if boots < *r_boots || (boots == *r_boots && time < *r_time - TIME_WINDOW) {
+                    return false;
+                }
+                if boots > *r_boots || time > *r_time {
+                    *r_boots = boots;
+                    *r_time = time;
+                }
+            }
+            None => {
+                self.remote_engines
+                    .insert(engine_id.to_vec(), (boots, time));
+            }
+        }
+        true
+    }
+    // Process incoming message.
+    // Reply, if any, is placed into buffer.
+    pub(crate) fn process_message<'a>(
+        &'a mut self,
+        data: &'a [u8],
+        buf: &mut Buffer,
+    ) -> SnmpResult<Option<Notification<'a>>> {
+        let msg = SnmpV3Message::try_from(data)?;
+        // Message must not be encrypted without authentication
+        if msg.flag_priv && !msg.flag_auth {
+            return Ok(None);
+        }
+        let authoritative = msg.usm.engine_id == self.engine_id.as_slice();
+        // Check user and security level
+        let key = (msg.usm.user_name.to_vec(), msg.usm.engine_id.to_vec());
+        let check = match self.users.get(&key) {
+            None if authoritative || self.users.keys().any(|(_, e)| e == &key.1) => {
+                Err(UsmStat::UnknownUserNames)
+            }
+            None => Err(UsmStat::UnknownEngineIds),
+            Some(user) => {
+                if user.auth_key.has_auth() != msg.flag_auth
+                    || user.rx_priv_key.has_priv() != msg.flag_priv
+                {
+                    Err(UsmStat::UnsupportedSecLevels)
+                } else if msg.flag_auth
+                    && (msg.usm.auth_params.len() != user.auth_key.placeholder().len()
+                        || user
+                            .auth_key
+                            .verify(
+                                data,
+                                msg.usm.auth_params.as_ptr() as usize - data.as_ptr() as usize,
+                            )
+                            .is_err())
+                {
+                    Err(UsmStat::WrongDigests)
+                } else {
+                    Ok(())
+                }
+            }
+        };
+        // Check time window for authenticated messages
+        let check = match check {
+            Ok(_) if msg.flag_auth => {
+                let in_window = if authoritative {
+                    let state = self.get_state();
+                    state.boots < MAX_ENGINE_VALUE
+                        && msg.usm.engine_boots == state.boots
+                        && (msg.usm.engine_time - state.time).abs() <= TIME_WINDOW
+                } else {
+                    self.check_remote_time(
+                        msg.usm.engine_id,
+                        msg.usm.engine_boots,
+                        msg.usm.engine_time,
+                    )
+                };
+                if in_window {
+                    Ok(())
+                } else {
+                    Err(UsmStat::NotInTimeWindows)
+                }
+            }
+            x => x,
+        };
+        if let Err(stat) = check {
+            self.stats[stat as usize] = self.stats[stat as usize].wrapping_add(1);
+            // Remote engine discovers us or our notion of time
+            if msg.flag_report && (authoritative || msg.usm.engine_id.is_empty()) {
+                let auth = match stat {
+                    UsmStat::NotInTimeWindows => self.users.get(&key).map(|u| &u.auth_key),
+                    _ => None,
+                };
+                let counter = self.stats[stat as usize];
+                let state = EngineState {
+                    engine_id: &self.engine_id,
+                    boots: self.engine_boots,
+                    time: self.started.elapsed().as_secs() as i64,
+                };
+                Self::push_report(buf, state, &msg, stat, counter, auth)?;
+            }
+            return Ok(None);
+        }
+        let state = EngineState {
+            engine_id: &self.engine_id,
+            boots: self.engine_boots,
+            time: self.started.elapsed().as_secs() as i64,
+        };
+        let Some(user) = self.users.get_mut(&key) else {
+            return Ok(None);
+        };
+        let UsmUser {
+            auth_key,
+            rx_priv_key,
+            tx_priv_key,
+        } = user;
+        // Get and decode scoped pdu
+        let scoped_pdu = match msg.data {
+            MsgData::Plaintext(x) => x,
+            MsgData::Encrypted(x) => match rx_priv_key.decrypt(x, &msg.usm) {
+                Ok(x) => x,
+                Err(_) => {
+                    let stat = UsmStat::DecryptionErrors;
+                    self.stats[stat as usize] = self.stats[stat as usize].wrapping_add(1);
+                    if msg.flag_report {
+                        let counter = self.stats[stat as usize];
+                        let msg = SnmpV3Message {
+                            data: MsgData::Encrypted(&EMPTY),
+                            ..msg
+                        };
+                        Self::push_report(buf, state, &msg, stat, counter, None)?;
+                    }
+                    return Ok(None);
+                }
+            },
+        };
+        match scoped_pdu.pdu {
+            SnmpPdu::SnmpV2Trap(trap) => Ok(Some(Notification {
+                engine_id: msg.usm.engine_id,
+                user_name: msg.usm.user_name,
+                vars: trap.vars,
+            })),
+            SnmpPdu::InformRequest(inform) => {
+                // Confirm with RESPONSE, containing the same varbinds, RFC-3416 pp. 4.2.7
+                let response = ScopedPdu {
+                    engine_id: state.engine_id,
+                    pdu: SnmpPdu::GetResponse(SnmpGetResponse {
+                        request_id: inform.request_id,
+                        error_status: 0,
+                        error_index: 0,
+                        vars: inform.vars.clone(),
+                    }),
+                };
+                let (privacy_params, data) = if tx_priv_key.has_priv() {
+                    let (enc_data, privacy_params) =
+                        tx_priv_key.encrypt(&response, state.boots as u32, state.time as u32)?;
+                    (privacy_params, MsgData::Encrypted(enc_data))
+                } else {
+                    (EMPTY.as_ref(), MsgData::Plaintext(response))
+                };
+                let reply = SnmpV3Message {
+                    msg_id: msg.msg_id,
+                    max_size: DEFAULT_MAX_SIZE,
+                    flag_auth: msg.flag_auth,
+                    flag_priv: msg.flag_priv,
+                    flag_report: false,
+                    usm: UsmParameters {
+                        engine_id: state.engine_id,
+                        engine_boots: state.boots,
+                        engine_time: state.time,
+                        user_name: msg.usm.user_name,
+                        auth_params: auth_key.placeholder(),
+                        privacy_params,
+                    },
+                    data,
+                };
+                reply.push_ber(buf)?;
+                if auth_key.has_auth() {
+                    let offset = buf.get_bookmark();
+                    auth_key.sign(buf.data_mut(), offset)?;
+                }
+                Ok(Some(Notification {
+                    engine_id: msg.usm.engine_id,
+                    user_name: msg.usm.user_name,
+                    vars: inform.vars,
+                }))
+            }
+            // Not a notification
+            _ => Ok(None),
+        }
+    }
+    // Build report, RFC-3414 pp. 3.2
+    fn push_report(
+        buf: &mut Buffer,
+        state: EngineState,
+        msg: &SnmpV3Message,
+        stat: UsmStat,
+        counter: u32,
+        auth: Option<&AuthKey>,
+    ) -> SnmpResult<()> {
+        let request_id = match &msg.data {
+            MsgData::Plaintext(x) => x.pdu.get_request_id(),
+            MsgData::Encrypted(_) => 0,
+        };
+        let pdu = SnmpPdu::Report(SnmpGetResponse {
+            request_id,
+            error_status: 0,
+            error_index: 0,
+            vars: vec![SnmpVar {
+                oid: SnmpOid::try_from(stat.oid())?,
+                value: SnmpValue::Counter32(SnmpCounter32(counter)),
+            }],
+        });
+        let report = SnmpV3Message {
+            msg_id: msg.msg_id,
+            max_size: DEFAULT_MAX_SIZE,
+            flag_auth: auth.is_some(),
+            flag_priv: false,
+            flag_report: false,
+            usm: UsmParameters {
+                engine_id: state.engine_id,
+                engine_boots: state.boots,
+                engine_time: state.time,
+                user_name: msg.usm.user_name,
+                auth_params: auth.map(|a| a.placeholder()).unwrap_or(&EMPTY),
+                privacy_params: &EMPTY,
+            },
+            data: MsgData::Plaintext(ScopedPdu {
+                engine_id: state.engine_id,
+                pdu,
+            }),
+        };
+        report.push_ber(buf)?;
+        match auth {
+            Some(a) => {
+                let offset = buf.get_bookmark();
+                a.sign(buf.data_mut(), offset)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{MD5_AUTH, NO_AUTH, SHA1_AUTH};
+    use crate::ber::{SnmpOid, SnmpTimeTicks};
+    use crate::buf::Buffer;
+
+    const ENGINE_ID: [u8; 9] = [0x80, 0, 0x1f, 0x88, 4, 0x67, 0x75, 0x66, 0x6f];
+    const REMOTE_ENGINE_ID: [u8; 9] = [0x80, 0, 0x1f, 0x88, 4, 0x74, 0x72, 0x61, 0x70];
+    const DES: u8 = 1;
+    const AES128: u8 = 2;
+
+    fn get_engine() -> SnmpV3Engine {
+        SnmpV3Engine::with_boots(ENGINE_ID.to_vec(), 1, None)
+    }
+
+    fn get_vars() -> SnmpResult<Vec<SnmpVar<'static>>> {
+        Ok(vec![
+            SnmpVar {
+                oid: SnmpOid::try_from("1.3.6.1.2.1.1.3.0")?,
+                value: SnmpValue::TimeTicks(SnmpTimeTicks(100)),
+            },
+            SnmpVar {
+                oid: SnmpOid::try_from("1.3.6.1.6.3.1.1.4.1.0")?,
+                value: SnmpValue::Oid(SnmpOid::try_from("1.3.6.1.6.3.1.1.5.1")?),
+            },
+        ])
+    }
+
+    // Sender side of the message
+    struct Sender<'a> {
+        engine_id: &'a [u8],
+        boots: i64,
+        time: i64,
+        user_name: &'a [u8],
+        auth_key: AuthKey,
+        priv_key: PrivKey,
+    }
+
+    impl<'a> Sender<'a> {
+        fn new(engine_id: &'a [u8], auth_alg: u8, priv_alg: u8) -> SnmpResult<Sender<'a>> {
+            let mut auth_key = AuthKey::new(auth_alg)?;
+            auth_key.as_key_type(auth_alg, b"authpass", engine_id)?;
+            Ok(Sender {
+                engine_id,
+                boots: 1,
+                time: 0,
+                user_name: b"user",
+                auth_key,
+                priv_key: SnmpV3Engine::get_priv_key(auth_alg, priv_alg, b"privpass", engine_id)?,
+            })
+        }
+        fn encode(&mut self, pdu: SnmpPdu, flag_report: bool) -> SnmpResult<Vec<u8>> {
+            let mut buf = Buffer::default();
+            let scoped_pdu = ScopedPdu {
+                engine_id: self.engine_id,
+                pdu,
+            };
+            let flag_priv = self.priv_key.has_priv();
+            let (privacy_params, data) = if flag_priv {
+                let (enc_data, privacy_params) =
+                    self.priv_key
+                        .encrypt(&scoped_pdu, self.boots as u32, self.time as u32)?;
+                (privacy_params, MsgData::Encrypted(enc_data))
+            } else {
+                (EMPTY.as_ref(), MsgData::Plaintext(scoped_pdu))
+            };
+            let msg = SnmpV3Message {
+                msg_id: 1000,
+                max_size: DEFAULT_MAX_SIZE,
+                flag_auth: self.auth_key.has_auth(),
+                flag_priv,
+                flag_report,
+                usm: UsmParameters {
+                    engine_id: self.engine_id,
+                    engine_boots: self.boots,
+                    engine_time: self.time,
+                    user_name: self.user_name,
+                    auth_params: self.auth_key.placeholder(),
+                    privacy_params,
+                },
+                data,
+            };
+            msg.push_ber(&mut buf)?;
+            if self.auth_key.has_auth() {
+                let offset = buf.get_bookmark();
+                self.auth_key.sign(buf.data_mut(), offset)?;
+            }
+            Ok(buf.data().to_vec())
+        }
+    }
+
+    fn add_user(engine: &mut SnmpV3Engine, engine_id: &[u8], auth_alg: u8, priv_alg: u8) {
+        engine
+            .add_user_keys(
+                b"user".to_vec(),
+                engine_id.to_vec(),
+                (auth_alg, b"authpass"),
+                (priv_alg, b"privpass"),
+            )
+            .unwrap();
+    }
+
+    fn trap() -> SnmpResult<SnmpPdu<'static>> {
+        Ok(SnmpPdu::SnmpV2Trap(SnmpGetResponse {
+            request_id: 15,
+            error_status: 0,
+            error_index: 0,
+            vars: get_vars()?,
+        }))
+    }
+
+    fn inform() -> SnmpResult<SnmpPdu<'static>> {
+        Ok(SnmpPdu::InformRequest(SnmpGetResponse {
+            request_id: 16,
+            error_status: 0,
+            error_index: 0,
+            vars: get_vars()?,
+        }))
+    }
+
+    // Check reply is report with given counter
+    fn assert_report(buf: &Buffer, stat: UsmStat) -> SnmpResult<()> {
+        let msg = SnmpV3Message::try_from(buf.data())?;
+        assert_eq!(msg.usm.engine_id, ENGINE_ID);
+        assert!(!msg.flag_report);
+        let MsgData::Plaintext(scoped_pdu) = msg.data else {
+            panic!("plaintext expected");
+        };
+        let SnmpPdu::Report(report) = scoped_pdu.pdu else {
+            panic!("report expected");
+        };
+        assert_eq!(report.vars.len(), 1);
+        assert_eq!(report.vars[0].oid, SnmpOid::try_from(stat.oid())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_boots() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("gufo_snmp_boots_{}", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let _ = fs::remove_file(&path);
+        assert_eq!(SnmpV3Engine::next_boots(&path)?, 1);
+        assert_eq!(SnmpV3Engine::next_boots(&path)?, 2);
+        fs::remove_file(&path)
+    }
+
+    #[test]
+    fn test_discovery() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        let mut sender = Sender::new(&EMPTY, NO_AUTH, 0)?;
+        let data = sender.encode(
+            SnmpPdu::GetRequest(crate::snmp::get::SnmpGet {
+                request_id: 1,
+                vars: vec![],
+            }),
+            true,
+        )?;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        assert_report(&buf, UsmStat::UnknownEngineIds)?;
+        let msg = SnmpV3Message::try_from(buf.data())?;
+        assert_eq!(msg.usm.engine_boots, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_noauth() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        add_user(&mut engine, &REMOTE_ENGINE_ID, NO_AUTH, 0);
+        let mut sender = Sender::new(&REMOTE_ENGINE_ID, NO_AUTH, 0)?;
+        let data = sender.encode(trap()?, false)?;
+        let mut buf = Buffer::default();
+        let n = engine.process_message(&data, &mut buf)?.unwrap();
+        assert_eq!(n.engine_id, REMOTE_ENGINE_ID);
+        assert_eq!(n.user_name, b"user");
+        assert_eq!(n.vars.len(), 2);
+        assert_eq!(n.vars[0].oid, SnmpOid::try_from("1.3.6.1.2.1.1.3.0")?);
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_auth_priv() -> SnmpResult<()> {
+        for (auth_alg, priv_alg) in [(MD5_AUTH, DES), (SHA1_AUTH, AES128)] {
+            let mut engine = get_engine();
+            add_user(&mut engine, &REMOTE_ENGINE_ID, auth_alg, priv_alg);
+            let mut sender = Sender::new(&REMOTE_ENGINE_ID, auth_alg, priv_alg)?;
+            sender.boots = 5;
+            sender.time = 1000;
+            let data = sender.encode(trap()?, false)?;
+            let mut buf = Buffer::default();
+            let n = engine.process_message(&data, &mut buf)?.unwrap();
+            assert_eq!(n.vars.len(), 2);
+            assert!(buf.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_encode_message() -> SnmpResult<()> {
+        use crate::client::{Auth, KeyType, Privacy, UsmUser};
+        use crate::snmp::msg::{
+            DecodedMessage, DecodedPdu, MsgHeader, MsgVersion, PduType, UsmInfo, encode_message,
+        };
+        use crate::snmp::value::OwnedValue;
+
+        let mut engine = get_engine();
+        add_user(&mut engine, &REMOTE_ENGINE_ID, SHA1_AUTH, AES128);
+        let user = UsmUser::new("user")
+            .with_auth(Auth::Sha1(KeyType::Password, b"authpass".to_vec()))
+            .with_privacy(Privacy::Aes128(KeyType::Password, b"privpass".to_vec()));
+        let msg = DecodedMessage {
+            version: MsgVersion::V3,
+            community: None,
+            header: Some(MsgHeader {
+                msg_id: 1000,
+                max_size: DEFAULT_MAX_SIZE,
+                flag_auth: true,
+                flag_priv: true,
+                flag_report: false,
+            }),
+            usm: Some(UsmInfo {
+                engine_id: REMOTE_ENGINE_ID.to_vec(),
+                engine_boots: 5,
+                engine_time: 1000,
+                user_name: b"user".to_vec(),
+                auth_params: vec![],
+                privacy_params: vec![],
+            }),
+            context_engine_id: None,
+            encrypted: true,
+            pdu: Some(DecodedPdu {
+                pdu_type: PduType::SnmpV2Trap,
+                request_id: 15,
+                error_status: 0,
+                error_index: 0,
+                varbinds: vec![("1.3.6.1.2.1.1.3.0".into(), OwnedValue::TimeTicks(100))],
+                trap: None,
+            }),
+        };
+        let data = encode_message(&msg, Some(&user))?;
+        let mut buf = Buffer::default();
+        let n = engine.process_message(&data, &mut buf)?.unwrap();
+        assert_eq!(n.vars.len(), 1);
+        assert_eq!(engine.stats[UsmStat::WrongDigests as usize], 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_wrong_digest() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        add_user(&mut engine, &REMOTE_ENGINE_ID, MD5_AUTH, 0);
+        let mut sender = Sender::new(&REMOTE_ENGINE_ID, MD5_AUTH, 0)?;
+        let mut data = sender.encode(trap()?, false)?;
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        assert_eq!(engine.stats[UsmStat::WrongDigests as usize], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_unknown_engine() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        let mut sender = Sender::new(&REMOTE_ENGINE_ID, NO_AUTH, 0)?;
+        let data = sender.encode(trap()?, false)?;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        assert_eq!(engine.stats[UsmStat::UnknownEngineIds as usize], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_replay() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        add_user(&mut engine, &REMOTE_ENGINE_ID, MD5_AUTH, 0);
+        let mut sender = Sender::new(&REMOTE_ENGINE_ID, MD5_AUTH, 0)?;
+        sender.boots = 3;
+        let data = sender.encode(trap()?, false)?;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_some());
+        // Message from previous boot
+        sender.boots = 2;
+        let data = sender.encode(trap()?, false)?;
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        assert_eq!(engine.stats[UsmStat::NotInTimeWindows as usize], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inform_unknown_user() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        let mut sender = Sender::new(&ENGINE_ID, NO_AUTH, 0)?;
+        let data = sender.encode(inform()?, true)?;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        assert_report(&buf, UsmStat::UnknownUserNames)
+    }
+
+    #[test]
+    fn test_inform_unsupported_sec_level() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        add_user(&mut engine, &EMPTY, SHA1_AUTH, AES128);
+        let mut sender = Sender::new(&ENGINE_ID, NO_AUTH, 0)?;
+        let data = sender.encode(inform()?, true)?;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        assert_report(&buf, UsmStat::UnsupportedSecLevels)
+    }
+
+    #[test]
+    fn test_inform_not_in_time_window() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        add_user(&mut engine, &EMPTY, SHA1_AUTH, 0);
+        let mut sender = Sender::new(&ENGINE_ID, SHA1_AUTH, 0)?;
+        sender.boots = 0;
+        let data = sender.encode(inform()?, true)?;
+        let mut buf = Buffer::default();
+        assert!(engine.process_message(&data, &mut buf)?.is_none());
+        let msg = SnmpV3Message::try_from(buf.data())?;
+        // Report must be authenticated
+        assert!(msg.flag_auth);
+        assert_eq!(msg.usm.engine_boots, 1);
+        sender.auth_key.verify(
+            buf.data(),
+            msg.usm.auth_params.as_ptr() as usize - buf.data().as_ptr() as usize,
+        )?;
+        assert_report(&buf, UsmStat::NotInTimeWindows)
+    }
+
+    #[test]
+    fn test_inform() -> SnmpResult<()> {
+        let mut engine = get_engine();
+        add_user(&mut engine, &EMPTY, SHA1_AUTH, AES128);
+        let mut sender = Sender::new(&ENGINE_ID, SHA1_AUTH, AES128)?;
+        let data = sender.encode(inform()?, true)?;
+        let mut buf = Buffer::default();
+        let n = engine.process_message(&data, &mut buf)?.unwrap();
+        assert_eq!(n.engine_id, ENGINE_ID);
+        assert_eq!(n.vars.len(), 2);
+        // Check response
+        let msg = SnmpV3Message::try_from(buf.data())?;
+        assert!(msg.flag_auth);
+        assert!(msg.flag_priv);
+        assert_eq!(msg.msg_id, 1000);
+        sender.auth_key.verify(
+            buf.data(),
+            msg.usm.auth_params.as_ptr() as usize - buf.data().as_ptr() as usize,
+        )?;
+        let MsgData::Encrypted(x) = msg.data else {
+            panic!("encrypted expected");
+        };
+        let scoped_pdu = sender.priv_key.decrypt(x, &msg.usm)?;
+        let SnmpPdu::GetResponse(resp) = scoped_pdu.pdu else {
+            panic!("response expected");
+        };
+        assert_eq!(resp.request_id, 16);
+        assert_eq!(resp.vars.len(), 2);
+        Ok(())
+    }
+}
@@ -1,18 +1,24 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SnmpError
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
+use crate::ber::Tag;
+#[cfg(feature = "python")]
 use pyo3::{
-    PyErr, create_exception,
+    PyErr, Python, create_exception,
     exceptions::{
-        PyBlockingIOError, PyException, PyNotImplementedError, PyOSError, PyTimeoutError,
-        PyValueError,
+        PyBlockingIOError, PyConnectionResetError, PyException, PyNotImplementedError, PyOSError,
+        PyTimeoutError, PyValueError,
     },
+    types::PyAnyMethods,
 };
+use std::borrow::Cow;
 use std::convert::Infallible;
+use std::fmt;
+use std::net::IpAddr;
 
 pub type SnmpResult<T> = Result<T, SnmpError>;
 
@@ -21,7 +27,7 @@ pub enum SnmpError {
     /// Too short
     Incomplete,
     /// Other tag is expected
-    UnexpectedTag, // @todo: Expand
+    UnexpectedTag,
     /// Unexpecetd tag format
     InvalidTagFormat,
     /// Unknown PDU type
@@ -48,12 +54,236 @@ pub enum SnmpError {
     SocketError(String),
     /// Blocking operation
     WouldBlock,
+    /// Request timed out
+    Timeout,
     /// Connection refused
     ConnectionRefused,
+    /// Connection reset by peer
+    ConnectionReset,
     /// Unknown Security Model
     UnknownSecurityModel,
     /// Authentication error
     AuthenticationFailed,
+    /// Failed to decrypt message
+    DecryptionFailed,
+    /// ICMP error, reported by network
+    Icmp(IcmpError),
+    /// Decoding error with context
+    Decode(Box<DecodeError>),
+}
+
+/// Decoding error with the position of the failed element
+#[derive(Debug)]
+pub struct DecodeError {
+    /// Underlying error
+    pub error: SnmpError,
+    /// Offset of the failed element from the start of the message
+    pub offset: Option<usize>,
+    /// Expected tag
+    pub expected: Option<Tag>,
+    /// Actual tag
+    pub actual: Option<Tag>,
+    // Path segments, innermost first
+    path: Vec<Cow<'static, str>>,
+    // Address of the failed element, resolved to offset by `locate`
+    addr: Option<usize>,
+}
+
+/// ICMP error kind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IcmpErrorKind {
+    PortUnreachable,
+    HostUnreachable,
+    NetworkUnreachable,
+    AdminProhibited,
+    TtlExceeded,
+}
+
+/// ICMP error, reported by agent or by router on the path
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcmpError {
+    pub kind: IcmpErrorKind,
+    /// Address of the node, sent the ICMP message
+    pub offender: Option<IpAddr>,
+}
+
+impl fmt::Display for IcmpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self.kind {
+            IcmpErrorKind::PortUnreachable => "port unreachable",
+            IcmpErrorKind::HostUnreachable => "host unreachable",
+            IcmpErrorKind::NetworkUnreachable => "network unreachable",
+            IcmpErrorKind::AdminProhibited => "administratively prohibited",
+            IcmpErrorKind::TtlExceeded => "ttl exceeded",
+        };
+        match self.offender {
+            Some(addr) => write!(f, "{} from {}", msg, addr),
+            None => f.write_str(msg),
+        }
+    }
+}
+
+impl fmt::Display for SnmpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnmpError::Incomplete => f.write_str("incomplete"),
+            SnmpError::UnexpectedTag => f.write_str("unexpected tag"),
+            SnmpError::InvalidTagFormat => f.write_str("invalid tag format"),
+            SnmpError::UnknownPdu => f.write_str("unknown pdu"),
+            SnmpError::InvalidPdu => f.write_str("invalid pdu"),
+            SnmpError::InvalidData => f.write_str("invalid data"),
+            SnmpError::InvalidKey => f.write_str("invalid key"),
+            SnmpError::UnsupportedTag(e) => write!(f, "Unsupported tag: {}", e),
+            SnmpError::TrailingData => f.write_str("trailing data"),
+            SnmpError::InvalidVersion(v) => write!(f, "unsupported version: {}", v),
+            SnmpError::OutOfBuffer => f.write_str("out of buffer"),
+            SnmpError::NotImplemented => f.write_str("not implemented"),
+            SnmpError::NoSuchInstance => f.write_str("no such instance"),
+            SnmpError::SocketError(x) => f.write_str(x),
+            SnmpError::WouldBlock => f.write_str("blocked"),
+            SnmpError::Timeout => f.write_str("timed out"),
+            SnmpError::ConnectionRefused => f.write_str("connection refused"),
+            SnmpError::ConnectionReset => f.write_str("connection reset"),
+            SnmpError::UnknownSecurityModel => f.write_str("unknown security model"),
+            SnmpError::AuthenticationFailed => f.write_str("authentication failed"),
+            SnmpError::DecryptionFailed => f.write_str("decryption failed"),
+            SnmpError::Icmp(e) => e.fmt(f),
+            SnmpError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SnmpError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path())?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " (offset {})", offset)?;
+        }
+        match (self.expected, self.actual) {
+            (Some(expected), Some(actual)) => {
+                write!(f, ": expected tag {}, got {}", expected, actual)
+            }
+            (None, Some(actual)) => write!(f, ": tag {}", actual),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<SnmpError> for DecodeError {
+    fn from(value: SnmpError) -> DecodeError {
+        match value {
+            SnmpError::Decode(e) => *e,
+            error => DecodeError {
+                error,
+                offset: None,
+                expected: None,
+                actual: None,
+                path: Vec::new(),
+                addr: None,
+            },
+        }
+    }
+}
+
+impl DecodeError {
+    /// Path to the failed element, like `msg.pdu.varbinds[3].value`
+    pub fn path(&self) -> String {
+        let mut r = String::new();
+        for (n, segment) in self.path.iter().rev().enumerate() {
+            if n > 0 && !segment.starts_with('[') {
+                r.push('.');
+            }
+            r.push_str(segment);
+        }
+        r
+    }
+}
+
+impl SnmpError {
+    /// Underlying error, stripped of decoding context
+    pub fn kind(&self) -> &SnmpError {
+        match self {
+            SnmpError::Decode(e) => &e.error,
+            e => e,
+        }
+    }
+
+    /// Check if the error is raised by decoder
+    pub fn is_decode(&self) -> bool {
+        matches!(
+            self.kind(),
+            SnmpError::Incomplete
+                | SnmpError::UnexpectedTag
+                | SnmpError::InvalidTagFormat
+                | SnmpError::UnknownPdu
+                | SnmpError::InvalidPdu
+                | SnmpError::InvalidData
+                | SnmpError::UnsupportedTag(_)
+                | SnmpError::TrailingData
+                | SnmpError::InvalidVersion(_)
+                | SnmpError::UnknownSecurityModel
+        )
+    }
+
+    // Apply `f` to the decoding context
+    fn with_context(self, f: impl FnOnce(&mut DecodeError)) -> SnmpError {
+        let mut e = DecodeError::from(self);
+        f(&mut e);
+        SnmpError::Decode(Box::new(e))
+    }
+
+    /// Remember the position of the failed element `i`,
+    /// unless already known.
+    pub(crate) fn at(self, i: &[u8]) -> SnmpError {
+        self.with_context(|e| {
+            e.addr.get_or_insert(i.as_ptr() as usize);
+        })
+    }
+
+    /// Set expected and actual tags
+    pub(crate) fn with_tags(self, expected: Option<Tag>, actual: Tag) -> SnmpError {
+        self.with_context(|e| {
+            e.expected = expected;
+            e.actual = Some(actual);
+        })
+    }
+
+    /// Prepend segment to the path
+    pub(crate) fn within(self, segment: impl Into<Cow<'static, str>>) -> SnmpError {
+        self.with_context(|e| e.path.push(segment.into()))
+    }
+
+    /// Resolve the position of the failed element
+    /// to the offset from the start of `data`.
+    pub(crate) fn locate(self, data: &[u8]) -> SnmpError {
+        self.with_context(|e| {
+            let start = data.as_ptr() as usize;
+            if e.offset.is_none()
+                && let Some(addr) = e.addr
+                && (start..=start + data.len()).contains(&addr)
+            {
+                e.offset = Some(addr - start);
+            }
+        })
+    }
+}
+
+/// Prepend path segment to the decoding error
+pub(crate) trait DecodePath<T> {
+    fn within(self, segment: impl Into<Cow<'static, str>>) -> SnmpResult<T>;
+}
+
+impl<T, E: Into<SnmpError>> DecodePath<T> for Result<T, E> {
+    fn within(self, segment: impl Into<Cow<'static, str>>) -> SnmpResult<T> {
+        self.map_err(|e| e.into().within(segment))
+    }
 }
 
 unsafe impl Send for SnmpError {}
@@ -75,61 +305,158 @@ impl From<SnmpError> for nom::Err<SnmpError> {
     }
 }
 
+#[cfg(feature = "python")]
 create_exception!(
     _fast,
     PySnmpError,
     PyException,
     "Base class for Gufo SNMP errors"
 );
+#[cfg(feature = "python")]
 create_exception!(
     _fast,
     PySnmpDecodeError,
     PySnmpError,
     "Message decoding error"
 );
+#[cfg(feature = "python")]
 create_exception!(
     _fast,
     PySnmpEncodeError,
     PySnmpError,
     "Message encoding error"
 );
+#[cfg(feature = "python")]
 create_exception!(
     _fast,
     PyNoSuchInstance,
     PySnmpError,
     "Requested OID is not found"
 );
+#[cfg(feature = "python")]
 create_exception!(_fast, PySnmpAuthError, PySnmpError, "Authentication failed");
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PySnmpDecryptionError,
+    PySnmpAuthError,
+    "Failed to decrypt message"
+);
+// ICMP errors were reported as timeouts before, keep them compatible
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PySnmpIcmpError,
+    PyTimeoutError,
+    "ICMP error received"
+);
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PyPortUnreachableError,
+    PySnmpIcmpError,
+    "ICMP port unreachable"
+);
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PyHostUnreachableError,
+    PySnmpIcmpError,
+    "ICMP host unreachable"
+);
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PyNetworkUnreachableError,
+    PySnmpIcmpError,
+    "ICMP network unreachable"
+);
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PyAdminProhibitedError,
+    PySnmpIcmpError,
+    "ICMP communication administratively prohibited"
+);
+#[cfg(feature = "python")]
+create_exception!(
+    _fast,
+    PyTtlExceededError,
+    PySnmpIcmpError,
+    "ICMP time to live exceeded"
+);
 
+#[cfg(feature = "python")]
+impl From<IcmpError> for PyErr {
+    fn from(value: IcmpError) -> PyErr {
+        let msg = value.to_string();
+        let err = match value.kind {
+            IcmpErrorKind::PortUnreachable => PyPortUnreachableError::new_err(msg),
+            IcmpErrorKind::HostUnreachable => PyHostUnreachableError::new_err(msg),
+            IcmpErrorKind::NetworkUnreachable => PyNetworkUnreachableError::new_err(msg),
+            IcmpErrorKind::AdminProhibited => PyAdminProhibitedError::new_err(msg),
+            IcmpErrorKind::TtlExceeded => PyTtlExceededError::new_err(msg),
+        };
+        // Expose offender's address as `offender` attribute
+        Python::attach(|py| {
+            let offender = value.offender.map(|x| x.to_string());
+            match err.value(py).setattr("offender", offender) {
+                Ok(_) => err,
+                Err(e) => e,
+            }
+        })
+    }
+}
+
+#[cfg(feature = "python")]
 impl From<SnmpError> for PyErr {
     fn from(value: SnmpError) -> PyErr {
+        if value.is_decode() {
+            return DecodeError::from(value).into();
+        }
+        let msg = value.to_string();
         match value {
-            SnmpError::Incomplete => PySnmpDecodeError::new_err("incomplete"),
-            SnmpError::UnexpectedTag => PySnmpDecodeError::new_err("unexpected tag"),
-            SnmpError::InvalidTagFormat => PySnmpDecodeError::new_err("invalid tag format"),
-            SnmpError::UnknownPdu => PySnmpDecodeError::new_err("unknown pdu"),
-            SnmpError::InvalidPdu => PySnmpDecodeError::new_err("invalid pdu"),
-            SnmpError::InvalidData => PySnmpDecodeError::new_err("invalid data"),
-            SnmpError::InvalidKey => PyValueError::new_err("invalid key"),
-            SnmpError::UnsupportedTag(e) => {
-                PySnmpDecodeError::new_err(format!("Unsupported tag: {}", e))
-            }
-            SnmpError::TrailingData => PySnmpDecodeError::new_err("trailing data"),
-            SnmpError::InvalidVersion(v) => {
-                PySnmpDecodeError::new_err(format!("unsupported version: {}", v))
-            }
-            SnmpError::OutOfBuffer => PySnmpEncodeError::new_err("out of buffer"),
-            SnmpError::NotImplemented => PyNotImplementedError::new_err("not implemented"),
-            SnmpError::NoSuchInstance => PyNoSuchInstance::new_err("no such instance"),
-            SnmpError::WouldBlock => PyBlockingIOError::new_err("blocked"),
-            SnmpError::SocketError(x) => PyOSError::new_err(x),
-            SnmpError::ConnectionRefused => PyTimeoutError::new_err("connection refused"),
-            SnmpError::UnknownSecurityModel => PySnmpDecodeError::new_err("unknown security model"),
-            SnmpError::AuthenticationFailed => PySnmpAuthError::new_err("authentication failed"),
+            SnmpError::InvalidKey => PyValueError::new_err(msg),
+            SnmpError::OutOfBuffer => PySnmpEncodeError::new_err(msg),
+            SnmpError::NotImplemented => PyNotImplementedError::new_err(msg),
+            SnmpError::NoSuchInstance => PyNoSuchInstance::new_err(msg),
+            SnmpError::WouldBlock => PyBlockingIOError::new_err(msg),
+            SnmpError::Timeout => PyTimeoutError::new_err(msg),
+            SnmpError::SocketError(_) => PyOSError::new_err(msg),
+            SnmpError::ConnectionRefused => PyTimeoutError::new_err(msg),
+            SnmpError::ConnectionReset => PyConnectionResetError::new_err(msg),
+            SnmpError::AuthenticationFailed => PySnmpAuthError::new_err(msg),
+            SnmpError::DecryptionFailed => PySnmpDecryptionError::new_err(msg),
+            SnmpError::Icmp(e) => e.into(),
+            // Context of non-decoding errors is dropped
+            SnmpError::Decode(e) => e.error.into(),
+            // Decoding errors
+            _ => PySnmpDecodeError::new_err(msg),
         }
     }
 }
 
+// Expose decoding context as `offset`, `expected`, `actual` and `path` attributes
+#[cfg(feature = "python")]
+impl From<DecodeError> for PyErr {
+    fn from(value: DecodeError) -> PyErr {
+        let err = PySnmpDecodeError::new_err(value.to_string());
+        Python::attach(|py| {
+            let v = err.value(py);
+            let path = (!value.path.is_empty()).then(|| value.path());
+            match v
+                .setattr("offset", value.offset)
+                .and_then(|_| v.setattr("expected", value.expected))
+                .and_then(|_| v.setattr("actual", value.actual))
+                .and_then(|_| v.setattr("path", path))
+            {
+                Ok(_) => err,
+                Err(e) => e,
+            }
+        })
+    }
+}
+
 impl From<Infallible> for SnmpError {
     fn from(_value: Infallible) -> Self {
         todo!("Should never happen")
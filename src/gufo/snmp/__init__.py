@@ -13,13 +13,24 @@ Attributes:
 
 # Gufo Labs modules
 from ._fast import (
+    AdminProhibitedError,
+    HostUnreachableError,
+    NetworkUnreachableError,
     NoSuchInstance,
+    PortUnreachableError,
     SnmpAuthError,
     SnmpDecodeError,
+    SnmpDecryptionError,
     SnmpEncodeError,
     SnmpError,
+    SnmpIcmpError,
+    TtlExceededError,
 )
 from .async_client import SnmpSession
+from .family import FamilyPreference
+from .hook import Packet, PacketDirection
+from .message import decode_message, encode_message
+from .transport import Transport
 from .typing import ValueType
 from .user import (
     Aes128Key,
@@ -34,20 +45,33 @@ from .version import SnmpVersion
 
 __version__: str = "0.12.0"
 __all__ = [
+    "AdminProhibitedError",
     "Aes128Key",
     "BaseAuthKey",
     "BasePrivKey",
     "DesKey",
+    "FamilyPreference",
+    "HostUnreachableError",
     "Md5Key",
+    "NetworkUnreachableError",
     "NoSuchInstance",
+    "Packet",
+    "PacketDirection",
+    "PortUnreachableError",
     "Sha1Key",
     "SnmpAuthError",
     "SnmpDecodeError",
+    "SnmpDecryptionError",
     "SnmpEncodeError",
     "SnmpError",
+    "SnmpIcmpError",
     "SnmpSession",
     "SnmpVersion",
+    "Transport",
+    "TtlExceededError",
     "User",
     "ValueType",
     "__version__",
+    "decode_message",
+    "encode_message",
 ]
@@ -1,27 +1,56 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: _fast typing
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-25, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
 # Python modules
-from typing import Dict, List, Optional, Tuple, Union
+from typing import Any, Callable, Dict, List, Optional, Tuple, Union
 
 # Gufo Labs modules
 from .typing import ValueType
 
 class SnmpError(Exception): ...
 class SnmpEncodeError(SnmpError): ...
-class SnmpDecodeError(SnmpError): ...
+class SnmpDecodeError(SnmpError):
+    offset: Optional[int]
+    expected: Optional[int]
+    actual: Optional[int]
+    path: Optional[str]
+
 class SnmpAuthError(SnmpError): ...  # v3 only
+class SnmpDecryptionError(SnmpAuthError): ...  # v3 only
 class NoSuchInstance(SnmpError): ...
 
+# ICMP errors, Linux only
+class SnmpIcmpError(TimeoutError):
+    offender: Optional[str]
+
+class PortUnreachableError(SnmpIcmpError): ...
+class HostUnreachableError(SnmpIcmpError): ...
+class NetworkUnreachableError(SnmpIcmpError): ...
+class AdminProhibitedError(SnmpIcmpError): ...
+class TtlExceededError(SnmpIcmpError): ...
+
+# Default SNMPv3 msgMaxSize
+DEFAULT_MAX_SIZE: int
+
 class GetIter(object):
     def __init__(
         self: "GetIter", oid: str, max_repetitions: Optional[int] = None
     ) -> None: ...
 
+class TokenBucketPolicer(object):
+    def __init__(
+        self: "TokenBucketPolicer",
+        rate: Optional[float] = None,
+        burst: float = 1.0,
+        target_rate: Optional[float] = None,
+        target_burst: float = 1.0,
+    ) -> None: ...
+    def reserve_ns(self: "TokenBucketPolicer", target: str) -> int: ...
+
 class SnmpV1ClientSocket(object):
     def __init__(
         self: "SnmpV1ClientSocket",
@@ -31,45 +60,70 @@ class SnmpV1ClientSocket(object):
         send_buffer_size: int,
         recv_buffer_size: int,
         timeout_ns: int,
+        transport: int = 0,
+        bind_addr: Optional[str] = None,
+        bind_port: int = 0,
+        bind_device: Optional[str] = None,
+        ttl: int = 0,
+        family: int = 0,
+        retries: int = 0,
+        retry_timeout_ns: int = 0,
+        backoff: float = 1.0,
+        policer: Optional["TokenBucketPolicer"] = None,
+        packet_hook: Optional[Callable[..., None]] = None,
+        max_msg_size: int = 0,
     ) -> None: ...
     def get_fd(self: "SnmpV1ClientSocket") -> int: ...
+    def get_peer_addr(self: "SnmpV1ClientSocket") -> str: ...
+    def get_rtt_ns(self: "SnmpV1ClientSocket") -> Optional[int]: ...
 
     # .get()
-    def get(self: "SnmpV1ClientSocket", oid: str) -> ValueType: ...
-    def send_get(self: "SnmpV1ClientSocket", oid: str) -> None: ...
-    def recv_get(self: "SnmpV1ClientSocket") -> ValueType: ...
+    def get(
+        self: "SnmpV1ClientSocket", oid: str, timeout_ns: Optional[int] = None
+    ) -> ValueType: ...
+    def send_get(self: "SnmpV1ClientSocket", oid: str) -> int: ...
+    def recv_get(self: "SnmpV1ClientSocket", request_id: int) -> ValueType: ...
 
     # .get_many()
     def get_many(
-        self: "SnmpV1ClientSocket", oids: List[str]
+        self: "SnmpV1ClientSocket",
+        oids: List[str],
+        timeout_ns: Optional[int] = None,
     ) -> Dict[str, ValueType]: ...
-    def send_get_many(self: "SnmpV1ClientSocket", oids: List[str]) -> None: ...
+    def send_get_many(self: "SnmpV1ClientSocket", oids: List[str]) -> int: ...
     def recv_get_many(
-        self: "SnmpV1ClientSocket",
+        self: "SnmpV1ClientSocket", request_id: int
     ) -> Dict[str, ValueType]: ...
 
     # .get_next
     def get_next(
-        self: "SnmpV1ClientSocket", iter_getnext: GetIter
+        self: "SnmpV1ClientSocket",
+        iter_getnext: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> Tuple[str, ValueType]: ...
     def send_get_next(
         self: "SnmpV1ClientSocket", iter_getnext: GetIter
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_next(
-        self: "SnmpV1ClientSocket", iter_getnext: GetIter
+        self: "SnmpV1ClientSocket", iter_getnext: GetIter, request_id: int
     ) -> Tuple[str, ValueType]: ...
 
     # .get_bulk()
     def get_bulk(
-        self: "SnmpV1ClientSocket", iter_getbulk: GetIter
+        self: "SnmpV1ClientSocket",
+        iter_getbulk: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
     def send_get_bulk(
         self: "SnmpV1ClientSocket", iter_getbulk: GetIter
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_bulk(
-        self: "SnmpV1ClientSocket", iter_getnext: GetIter
+        self: "SnmpV1ClientSocket", iter_getnext: GetIter, request_id: int
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
 
+    # Pipelined requests
+    def recv(self: "SnmpV1ClientSocket") -> Tuple[int, Any]: ...
+
 class SnmpV2cClientSocket(object):
     def __init__(
         self: "SnmpV2cClientSocket",
@@ -79,43 +133,70 @@ class SnmpV2cClientSocket(object):
         send_buffer_size: int,
         recv_buffer_size: int,
         timeout_ns: int,
+        transport: int = 0,
+        bind_addr: Optional[str] = None,
+        bind_port: int = 0,
+        bind_device: Optional[str] = None,
+        ttl: int = 0,
+        family: int = 0,
+        retries: int = 0,
+        retry_timeout_ns: int = 0,
+        backoff: float = 1.0,
+        policer: Optional["TokenBucketPolicer"] = None,
+        packet_hook: Optional[Callable[..., None]] = None,
+        max_msg_size: int = 0,
     ) -> None: ...
     def get_fd(self: "SnmpV2cClientSocket") -> int: ...
+    def get_peer_addr(self: "SnmpV2cClientSocket") -> str: ...
+    def get_rtt_ns(self: "SnmpV2cClientSocket") -> Optional[int]: ...
     # .get()
-    def get(self: "SnmpV2cClientSocket", oid: str) -> ValueType: ...
-    def send_get(self: "SnmpV2cClientSocket", oid: str) -> None: ...
-    def recv_get(self: "SnmpV2cClientSocket") -> ValueType: ...
+    def get(
+        self: "SnmpV2cClientSocket", oid: str, timeout_ns: Optional[int] = None
+    ) -> ValueType: ...
+    def send_get(self: "SnmpV2cClientSocket", oid: str) -> int: ...
+    def recv_get(
+        self: "SnmpV2cClientSocket", request_id: int
+    ) -> ValueType: ...
     # .get_many
     def get_many(
-        self: "SnmpV2cClientSocket", oids: List[str]
+        self: "SnmpV2cClientSocket",
+        oids: List[str],
+        timeout_ns: Optional[int] = None,
     ) -> Dict[str, ValueType]: ...
     def send_get_many(
         self: "SnmpV2cClientSocket", oids: List[str]
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_many(
-        self: "SnmpV2cClientSocket",
+        self: "SnmpV2cClientSocket", request_id: int
     ) -> Dict[str, ValueType]: ...
     # .get_next()
     def get_next(
-        self: "SnmpV2cClientSocket", iter_getnext: GetIter
+        self: "SnmpV2cClientSocket",
+        iter_getnext: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> Tuple[str, ValueType]: ...
     def send_get_next(
         self: "SnmpV2cClientSocket", iter_getnext: GetIter
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_next(
-        self: "SnmpV2cClientSocket", iter_getnext: GetIter
+        self: "SnmpV2cClientSocket", iter_getnext: GetIter, request_id: int
     ) -> Tuple[str, ValueType]: ...
     # .get_bulk()
     def get_bulk(
-        self: "SnmpV2cClientSocket", iter_getbulk: GetIter
+        self: "SnmpV2cClientSocket",
+        iter_getbulk: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
     def send_get_bulk(
         self: "SnmpV2cClientSocket", iter_getbulk: GetIter
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_bulk(
-        self: "SnmpV2cClientSocket", iter_getnext: GetIter
+        self: "SnmpV2cClientSocket", iter_getnext: GetIter, request_id: int
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
 
+    # Pipelined requests
+    def recv(self: "SnmpV2cClientSocket") -> Tuple[int, Any]: ...
+
 class SnmpV3ClientSocket(object):
     def __init__(
         self: "SnmpV3ClientSocket",
@@ -130,6 +211,18 @@ class SnmpV3ClientSocket(object):
         send_buffer_size: int,
         recv_buffer_size: int,
         timeout_ns: int,
+        max_msg_size: int = 0,
+        transport: int = 0,
+        bind_addr: Optional[str] = None,
+        bind_port: int = 0,
+        bind_device: Optional[str] = None,
+        ttl: int = 0,
+        family: int = 0,
+        retries: int = 0,
+        retry_timeout_ns: int = 0,
+        backoff: float = 1.0,
+        policer: Optional["TokenBucketPolicer"] = None,
+        packet_hook: Optional[Callable[..., None]] = None,
     ) -> None: ...
     def set_keys(
         self: "SnmpV3ClientSocket",
@@ -140,44 +233,178 @@ class SnmpV3ClientSocket(object):
         priv_key: bytes,
     ) -> None: ...
     def get_fd(self: "SnmpV3ClientSocket") -> int: ...
+    def get_peer_addr(self: "SnmpV3ClientSocket") -> str: ...
+    def get_rtt_ns(self: "SnmpV3ClientSocket") -> Optional[int]: ...
     def get_engine_id(self: "SnmpV3ClientSocket") -> bytes: ...
+    def get_max_varbinds(self: "SnmpV3ClientSocket") -> int: ...
     # .get()
-    def get(self: "SnmpV3ClientSocket", oid: str) -> ValueType: ...
-    def send_get(self: "SnmpV3ClientSocket", oid: str) -> None: ...
-    def recv_get(self: "SnmpV3ClientSocket") -> ValueType: ...
+    def get(
+        self: "SnmpV3ClientSocket", oid: str, timeout_ns: Optional[int] = None
+    ) -> ValueType: ...
+    def send_get(self: "SnmpV3ClientSocket", oid: str) -> int: ...
+    def recv_get(self: "SnmpV3ClientSocket", request_id: int) -> ValueType: ...
     # .get_many()
-    def send_get_many(self: "SnmpV3ClientSocket", oids: List[str]) -> None: ...
+    def send_get_many(self: "SnmpV3ClientSocket", oids: List[str]) -> int: ...
     def recv_get_many(
-        self: "SnmpV3ClientSocket",
+        self: "SnmpV3ClientSocket", request_id: int
     ) -> Dict[str, ValueType]: ...
     def get_many(
-        self: "SnmpV3ClientSocket", oids: List[str]
+        self: "SnmpV3ClientSocket",
+        oids: List[str],
+        timeout_ns: Optional[int] = None,
     ) -> Dict[str, ValueType]: ...
     # .get_next
     def get_next(
-        self: "SnmpV3ClientSocket", iter_getnext: GetIter
+        self: "SnmpV3ClientSocket",
+        iter_getnext: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> Tuple[str, ValueType]: ...
     def send_get_next(
         self: "SnmpV3ClientSocket", iter_getnext: GetIter
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_next(
-        self: "SnmpV3ClientSocket", iter_getnext: GetIter
+        self: "SnmpV3ClientSocket", iter_getnext: GetIter, request_id: int
     ) -> Tuple[str, ValueType]: ...
     # Rest
     def get_bulk(
-        self: "SnmpV3ClientSocket", iter_getbulk: GetIter
+        self: "SnmpV3ClientSocket",
+        iter_getbulk: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
     def send_get_bulk(
         self: "SnmpV3ClientSocket", iter_getbulk: GetIter
-    ) -> None: ...
+    ) -> int: ...
     def recv_get_bulk(
-        self: "SnmpV3ClientSocket", iter_getnext: GetIter
+        self: "SnmpV3ClientSocket", iter_getnext: GetIter, request_id: int
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
+
+    # Pipelined requests
+    def recv(self: "SnmpV3ClientSocket") -> Tuple[int, Any]: ...
     # .refresh
-    def refresh(self: "SnmpV3ClientSocket") -> None: ...
-    def send_refresh(self: "SnmpV3ClientSocket") -> None: ...
-    def recv_refresh(self: "SnmpV3ClientSocket") -> None: ...
+    def refresh(
+        self: "SnmpV3ClientSocket", timeout_ns: Optional[int] = None
+    ) -> None: ...
+    def send_refresh(self: "SnmpV3ClientSocket") -> int: ...
+    def recv_refresh(
+        self: "SnmpV3ClientSocket", request_id: int
+    ) -> None: ...
 
+class SnmpMultiClientSocket(object):
+    def __init__(
+        self: "SnmpMultiClientSocket",
+        ipv6: bool = False,
+        tos: int = 0,
+        send_buffer_size: int = 0,
+        recv_buffer_size: int = 0,
+        timeout_ns: int = 0,
+        bind_addr: Optional[str] = None,
+        bind_port: int = 0,
+        bind_device: Optional[str] = None,
+        ttl: int = 0,
+        policer: Optional["TokenBucketPolicer"] = None,
+        packet_hook: Optional[Callable[..., None]] = None,
+    ) -> None: ...
+    def get_fd(self: "SnmpMultiClientSocket") -> int: ...
+    def add_v1_target(
+        self: "SnmpMultiClientSocket", addr: str, community: str
+    ) -> None: ...
+    def add_v2c_target(
+        self: "SnmpMultiClientSocket", addr: str, community: str
+    ) -> None: ...
+    def add_v3_target(
+        self: "SnmpMultiClientSocket",
+        addr: str,
+        engine_id: bytes,
+        user_name: str,
+        auth_alg: int,
+        auth_key: bytes,
+        priv_alg: int,
+        priv_key: bytes,
+        max_msg_size: int = 0,
+    ) -> None: ...
+    def remove_target(self: "SnmpMultiClientSocket", addr: str) -> None: ...
+    def set_keys(
+        self: "SnmpMultiClientSocket",
+        addr: str,
+        user_name: str,
+        auth_alg: int,
+        auth_key: bytes,
+        priv_alg: int,
+        priv_key: bytes,
+    ) -> None: ...
+    def get_engine_id(self: "SnmpMultiClientSocket", addr: str) -> bytes: ...
+    def send_get(self: "SnmpMultiClientSocket", addr: str, oid: str) -> None: ...
+    def send_get_many(
+        self: "SnmpMultiClientSocket", addr: str, oids: List[str]
+    ) -> None: ...
+    def send_get_next(
+        self: "SnmpMultiClientSocket", addr: str, iter_getnext: GetIter
+    ) -> None: ...
+    def send_get_bulk(
+        self: "SnmpMultiClientSocket", addr: str, iter_getbulk: GetIter
+    ) -> None: ...
+    def send_refresh(self: "SnmpMultiClientSocket", addr: str) -> None: ...
+    def send_get_batch(
+        self: "SnmpMultiClientSocket", requests: List[Tuple[str, str]]
+    ) -> int: ...
+    def send_get_many_batch(
+        self: "SnmpMultiClientSocket", requests: List[Tuple[str, List[str]]]
+    ) -> int: ...
+    def recv(self: "SnmpMultiClientSocket") -> Tuple[str, Any]: ...
+    def recv_batch(
+        self: "SnmpMultiClientSocket", max_count: int = 64
+    ) -> List[Tuple[str, Any]]: ...
+
+class SnmpV3Engine(object):
+    def __init__(
+        self: "SnmpV3Engine",
+        engine_id: bytes,
+        boots_path: Optional[str] = None,
+    ) -> None: ...
+    def get_engine_id(self: "SnmpV3Engine") -> bytes: ...
+    def get_engine_boots(self: "SnmpV3Engine") -> int: ...
+    def get_engine_time(self: "SnmpV3Engine") -> int: ...
+    def add_user(
+        self: "SnmpV3Engine",
+        user_name: str,
+        engine_id: bytes,
+        auth_alg: int,
+        auth_key: bytes,
+        priv_alg: int,
+        priv_key: bytes,
+    ) -> None: ...
+    def process(
+        self: "SnmpV3Engine", data: bytes
+    ) -> Tuple[
+        Optional[bytes],
+        Optional[Tuple[bytes, str, List[Tuple[str, Optional[ValueType]]]]],
+    ]: ...
+
+def decode_message(
+    data: bytes,
+    auth_alg: int = 0,
+    auth_key: bytes = b"",
+    priv_alg: int = 0,
+    priv_key: bytes = b"",
+) -> Dict[str, Any]: ...
+def encode_message(
+    version: int,
+    pdu_type: str,
+    request_id: int,
+    varbinds: List[Tuple[str, Any]],
+    *,
+    error_status: int = 0,
+    error_index: int = 0,
+    community: bytes = b"public",
+    header: Optional[Tuple[int, int, bool, bool, bool]] = None,
+    usm: Optional[Tuple[bytes, int, int, bytes]] = None,
+    context_engine_id: Optional[bytes] = None,
+    auth_alg: int = 0,
+    auth_key: bytes = b"",
+    priv_alg: int = 0,
+    priv_key: bytes = b"",
+    trap: Optional[Tuple[str, str, int, int, int]] = None,
+) -> bytes: ...
 def get_master_key(auth_alg: int, passwd: bytes) -> bytes: ...
 def get_localized_key(
     auth_alg: int, passwd: bytes, engine_id: bytes
@@ -1,15 +1,16 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: Async SnmpSession
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-25, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
 """SnmpSession implementation."""
 
 # Python modules
-from asyncio import Future, get_running_loop, wait_for
+from asyncio import Future, get_running_loop, sleep, wait_for
 from asyncio import TimeoutError as AIOTimeoutError
+from functools import partial
 from types import TracebackType
 from typing import (
     AsyncIterator,
@@ -30,14 +31,20 @@ from .._fast import (
     SnmpV1ClientSocket,
     SnmpV2cClientSocket,
     SnmpV3ClientSocket,
+    TokenBucketPolicer,
 )
+from ..batch import get_batches, get_max_varbinds
+from ..family import FamilyPreference
+from ..hook import PacketHook, wrap_hook
 from ..policer import BasePolicer, RPSPolicer
 from ..protocol import SnmpClientSocketProtocol
+from ..transport import Transport
 from ..typing import ValueType
 from ..user import User
 from ..version import SnmpVersion
 
 T = TypeVar("T")
+NS = 1_000_000_000.0
 
 
 class SnmpSession(object):
@@ -47,8 +54,12 @@ class SnmpSession(object):
     Should be used either directly or via asynchronous context manager.
 
     Args:
-        addr: SNMP agent address, either IPv4 or IPv6.
-        port: SNMP agent port.
+        addr: SNMP agent address, either IPv4, IPv6, hostname,
+            or an absolute path to the Unix domain socket.
+            Link-local IPv6 addresses may be scoped
+            by interface name, i.e. `fe80::1%eth0`.
+            Unix domain sockets with UDP transport are Linux-only.
+        port: SNMP agent port. Ignored for Unix domain sockets.
         community: SNMP community (v1, v2c).
         engine_id: SNMP Engine id (v3).
         user: User instance (v3).
@@ -59,17 +70,33 @@ class SnmpSession(object):
 
         timeout: Request timeout in seconds.
         tos: Set ToS/DSCP mark on egress packets.
-        send_buffer: Send buffer size for UDP socket.
+            IPv6 traffic class is set for IPv6 agents.
+        send_buffer: Send buffer size for socket.
             0 - use default size.
-        recv_buffer: Receive buffer size for UDP socket.
+        recv_buffer: Receive buffer size for socket.
             0 - use default size.
         max_repetitions: Default max_repetitions for getbulk.
         allow_bulk: Allow using GETBULK in SnmpSession.fetch()
             whenever possible.
-        policer: Optional `BasePolicer` instance to limit
-            outgoing requests. Overrides `limit_rps` parameter.
+        policer: Optional `BasePolicer` or `TokenBucketPolicer`
+            instance to limit outgoing requests.
+            Overrides `limit_rps` parameter.
         limit_rps: Limit outgouing requests to `limit_rps`
             requests per second.
+        max_msg_size: Maximal message size, advertised to agent (v3)
+            and accepted over TCP. 0 - use default size.
+        transport: Transport protocol.
+        bind_addr: Local address to bind the socket.
+            Use the address chosen by the kernel if not set.
+        bind_port: Local port to bind the socket.
+            0 - use ephemeral port.
+        bind_device: Bind the socket to the network device,
+            i.e. VRF device. Linux only.
+        ttl: IPv4 TTL or IPv6 hop limit for egress packets.
+            0 - use system default.
+        family: Address family preference for hostname resolution.
+        packet_hook: Callable, invoked with `Packet` for every
+            sent and received message, including the discarded ones.
 
     Example:
         ``` py
@@ -98,8 +125,16 @@ class SnmpSession(object):
         recv_buffer: int = 0,
         max_repetitions: int = 20,
         allow_bulk: bool = True,
-        policer: Optional[BasePolicer] = None,
+        policer: Optional[Union[BasePolicer, TokenBucketPolicer]] = None,
         limit_rps: Optional[Union[int, float]] = None,
+        max_msg_size: int = 0,
+        transport: Transport = Transport.UDP,
+        bind_addr: Optional[str] = None,
+        bind_port: int = 0,
+        bind_device: Optional[str] = None,
+        ttl: int = 0,
+        family: FamilyPreference = FamilyPreference.ANY,
+        packet_hook: Optional[PacketHook] = None,
     ) -> None:
         # Detect version
         if version is None:
@@ -107,23 +142,46 @@ class SnmpSession(object):
         self._sock: SnmpClientSocketProtocol
         self._to_refresh = False
         self._deferred_user: Optional[User] = None
+        sock_hook = wrap_hook(packet_hook)
+        if addr.startswith("/"):
+            target = addr
+        elif ":" in addr:
+            target = f"[{addr}]:{port}"
+        else:
+            target = f"{addr}:{port}"
         if version == SnmpVersion.v1:
             self._sock = SnmpV1ClientSocket(
-                f"{addr}:{port}",
+                target,
                 community,
                 tos,
                 send_buffer,
                 recv_buffer,
                 0,
+                transport,
+                bind_addr,
+                bind_port,
+                bind_device,
+                ttl,
+                family,
+                packet_hook=sock_hook,
+                max_msg_size=max_msg_size,
             )
         elif version == SnmpVersion.v2c:
             self._sock = SnmpV2cClientSocket(
-                f"{addr}:{port}",
+                target,
                 community,
                 tos,
                 send_buffer,
                 recv_buffer,
                 0,
+                transport,
+                bind_addr,
+                bind_port,
+                bind_device,
+                ttl,
+                family,
+                packet_hook=sock_hook,
+                max_msg_size=max_msg_size,
             )
         elif version == SnmpVersion.v3:
             if not user:
@@ -134,7 +192,7 @@ class SnmpSession(object):
                 self._deferred_user = user
                 user = User.default()
             self._sock = SnmpV3ClientSocket(
-                f"{addr}:{port}",
+                target,
                 engine_id if engine_id else b"",
                 user.name,
                 user.get_auth_alg(),
@@ -145,6 +203,14 @@ class SnmpSession(object):
                 send_buffer,
                 recv_buffer,
                 0,
+                max_msg_size,
+                transport,
+                bind_addr,
+                bind_port,
+                bind_device,
+                ttl,
+                family,
+                packet_hook=sock_hook,
             )
             self._to_refresh = not engine_id or user.require_auth()
         else:
@@ -157,7 +223,7 @@ class SnmpSession(object):
             self._allow_bulk = False
         else:
             self._allow_bulk = allow_bulk
-        self._policer: Optional[BasePolicer] = None
+        self._policer: Optional[Union[BasePolicer, TokenBucketPolicer]] = None
         if policer:
             self._policer = policer
         elif limit_rps:
@@ -176,43 +242,54 @@ class SnmpSession(object):
     ) -> None:
         """Asynchronous context manager exit."""
 
-    async def _send(self, sender: Callable[[], None]) -> None:
+    async def _send(self, sender: Callable[[], int]) -> int:
         """
         Execute callable when socket in writable.
 
         Args:
             sender: Function to execute a write operation.
+
+        Returns:
+            Request id, returned by sender.
         """
 
         def callback() -> None:
             try:
-                sender()
-                future.set_result(None)
+                future.set_result(sender())
             except BaseException as e:  # noqa: BLE001
                 future.set_exception(e)
 
-        if self._policer:
+        if isinstance(self._policer, TokenBucketPolicer):
+            delay = self._policer.reserve_ns(self._sock.get_peer_addr())
+            if delay:
+                await sleep(float(delay) / NS)
+        elif self._policer:
             await self._policer.wait()
         try:
             # Hot path.
             # Usually successful unless output buffer is full
-            sender()
+            return sender()
         except BlockingIOError:
             # Buffer is full, wait
             loop = get_running_loop()
-            future = loop.create_future()
+            future: Future[int] = loop.create_future()
             loop.add_writer(self._fd, callback)
             try:
-                await future
+                return await future
             finally:
                 loop.remove_writer(self._fd)
 
-    async def _recv(self, receiver: Callable[[], T]) -> T:
+    async def _recv(
+        self, receiver: Callable[[], T], timeout: Optional[float] = None
+    ) -> T:
         """
         Execute callable when socket is ready.
 
+        Unrelated replies do not extend the wait.
+
         Args:
             receiver: Function to execute read operation.
+            timeout: Timeout in seconds. Use session's timeout if not set.
         """
 
         def on_readable() -> None:
@@ -220,11 +297,14 @@ class SnmpSession(object):
                 fut.set_result(None)
 
         loop = get_running_loop()
+        if timeout is None:
+            timeout = self._timeout
+        deadline = loop.time() + timeout
         while True:
             fut: Future[None] = loop.create_future()
             loop.add_reader(self._fd, on_readable)
             try:
-                await wait_for(fut, self._timeout)
+                await wait_for(fut, max(deadline - loop.time(), 0))
                 return receiver()
             except BlockingIOError:
                 continue
@@ -233,12 +313,16 @@ class SnmpSession(object):
             finally:
                 loop.remove_reader(self._fd)
 
-    async def get(self: "SnmpSession", oid: str) -> ValueType:
+    async def get(
+        self: "SnmpSession", oid: str, timeout: Optional[float] = None
+    ) -> ValueType:
         """
         Send SNMP GET request and await for response.
 
         Args:
             oid: OID in numeric format, no leading dot.
+            timeout: Request timeout in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Request result. Return type depends on requested oid.
@@ -251,20 +335,26 @@ class SnmpSession(object):
             SnmpError: On other SNMP-related errors.
         """
 
-        def sender() -> None:
-            self._sock.send_get(oid)
+        def sender() -> int:
+            return self._sock.send_get(oid)
 
-        await self._send(sender)
-        return await self._recv(self._sock.recv_get)
+        request_id = await self._send(sender)
+        return await self._recv(
+            partial(self._sock.recv_get, request_id), timeout
+        )
 
     async def get_many(
-        self: "SnmpSession", oids: Iterable[str]
+        self: "SnmpSession",
+        oids: Iterable[str],
+        timeout: Optional[float] = None,
     ) -> Dict[str, ValueType]:
         """
         Send SNMP GET request for multiple oids and await for response.
 
         Args:
             oids: Iterable of oids in numeric format, no leading dots.
+            timeout: Overall timeout in seconds, including all batches.
+                Override the SnmpSession's defaults.
 
         Returns:
             Dict where keys are requested oids, values are returned values.
@@ -281,21 +371,31 @@ class SnmpSession(object):
             RuntimeError: On Python runtime failure.
             SnmpError: On other SNMP-related errors.
         """
-
-        def sender() -> None:
-            self._sock.send_get_many(list(oids))
-
-        await self._send(sender)
-        return await self._recv(self._sock.recv_get_many)
+        r: Dict[str, ValueType] = {}
+        loop = get_running_loop()
+        deadline = None if timeout is None else loop.time() + timeout
+        for batch in get_batches(list(oids), get_max_varbinds(self._sock)):
+            request_id = await self._send(
+                partial(self._sock.send_get_many, batch)
+            )
+            remaining = None if deadline is None else deadline - loop.time()
+            r.update(
+                await self._recv(
+                    partial(self._sock.recv_get_many, request_id), remaining
+                )
+            )
+        return r
 
     def getnext(
-        self: "SnmpSession", oid: str
+        self: "SnmpSession", oid: str, timeout: Optional[float] = None
     ) -> AsyncIterator[Tuple[str, ValueType]]:
         """
         Iterate over oids.
 
         Args:
             oid: Starting oid
+            timeout: Timeout of each request in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Asynchronous iterator yielding pair of (oid, value)
@@ -306,10 +406,13 @@ class SnmpSession(object):
                 print(oid, value)
             ```
         """
-        return GetNextIter(self, oid)
+        return GetNextIter(self, oid, timeout)
 
     def getbulk(
-        self: "SnmpSession", oid: str, max_repetitions: Optional[int] = None
+        self: "SnmpSession",
+        oid: str,
+        max_repetitions: Optional[int] = None,
+        timeout: Optional[float] = None,
     ) -> AsyncIterator[Tuple[str, ValueType]]:
         """
         Iterate over oids.
@@ -318,6 +421,8 @@ class SnmpSession(object):
             oid: Starting oid
             max_repetitions: Maximal amount of items per response.
                 Override the SnmpSession's defaults.
+            timeout: Timeout of each request in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Asynchronous iterator yielding pair of (oid, value)
@@ -332,10 +437,11 @@ class SnmpSession(object):
             self,
             oid,
             max_repetitions or self._max_repetitions,
+            timeout,
         )
 
     def fetch(
-        self: "SnmpSession", oid: str
+        self: "SnmpSession", oid: str, timeout: Optional[float] = None
     ) -> AsyncIterator[Tuple[str, ValueType]]:
         """
         Iterate over oids using fastest method available.
@@ -346,6 +452,8 @@ class SnmpSession(object):
 
         Args:
             oid: Starting oid
+            timeout: Timeout of each request in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Asynchronous iterator yielding pair of (oid, value)
@@ -357,10 +465,12 @@ class SnmpSession(object):
             ```
         """
         if self._allow_bulk:
-            return self.getbulk(oid)
-        return self.getnext(oid)
+            return self.getbulk(oid, timeout=timeout)
+        return self.getnext(oid, timeout=timeout)
 
-    async def refresh(self: "SnmpSession") -> None:
+    async def refresh(
+        self: "SnmpSession", timeout: Optional[float] = None
+    ) -> None:
         """
         Send and receive REPORT to refresh authentication state.
 
@@ -369,6 +479,10 @@ class SnmpSession(object):
         Refresh sent automatically on entering
         the SnmpSession and should be resent manually
         if over 150 seconds left from the last request.
+
+        Args:
+            timeout: Request timeout in seconds.
+                Override the SnmpSession's defaults.
         """
         if (
             not isinstance(self._sock, SnmpV3ClientSocket)
@@ -378,8 +492,10 @@ class SnmpSession(object):
 
         if self._deferred_user:
             # First check runs engine id discovery
-            await self._send(self._sock.send_refresh)
-            await self._recv(self._sock.recv_refresh)
+            request_id = await self._send(self._sock.send_refresh)
+            await self._recv(
+                partial(self._sock.recv_refresh, request_id), timeout
+            )
             # Set and localize actual keys
             self._sock.set_keys(
                 self._deferred_user.name,
@@ -394,8 +510,8 @@ class SnmpSession(object):
             self._deferred_user = None
 
         # Refresh engine boots and time
-        await self._send(self._sock.send_refresh)
-        await self._recv(self._sock.recv_refresh)
+        request_id = await self._send(self._sock.send_refresh)
+        await self._recv(partial(self._sock.recv_refresh, request_id), timeout)
 
     def get_engine_id(self: "SnmpSession") -> bytes:
         """
@@ -409,6 +525,35 @@ class SnmpSession(object):
             raise NotImplementedError(msg)
         return self._sock.get_engine_id()
 
+    def get_peer_addr(self: "SnmpSession") -> str:
+        """
+        Get agent's address, actually used.
+
+        Useful to log the result of hostname resolution.
+
+        Returns:
+            Agent's address as `address:port`, or
+            path to the Unix domain socket.
+        """
+        return self._sock.get_peer_addr()
+
+    def get_rtt(self: "SnmpSession") -> Optional[float]:
+        """
+        Get round-trip time of the last completed request.
+
+        Measured with monotonic clock from sending the request
+        to receiving its own reply, using kernel receive timestamps
+        when available, so event loop delays are not included.
+
+        Returns:
+            Round-trip time in seconds or None,
+            if there is no reply yet.
+        """
+        rtt = self._sock.get_rtt_ns()
+        if rtt is None:
+            return None
+        return rtt / NS
+
 
 class GetNextIter(object):
     """Wrap the series of the GetNext requests.
@@ -416,16 +561,19 @@ class GetNextIter(object):
     Args:
         session: Parent SnmpSession.
         oid: Base oid.
+        timeout: Optional timeout of each request in seconds.
     """
 
     def __init__(
         self: "GetNextIter",
         session: SnmpSession,
         oid: str,
+        timeout: Optional[float] = None,
     ) -> None:
         self._session = session
         self._sock = session._sock
         self._ctx = GetIter(oid)
+        self._timeout = timeout
 
     def __aiter__(self: "GetNextIter") -> "GetNextIter":
         """Return asynchronous iterator."""
@@ -434,14 +582,14 @@ class GetNextIter(object):
     async def __anext__(self: "GetNextIter") -> Tuple[str, ValueType]:
         """Get next value."""
 
-        def sender() -> None:
-            self._sock.send_get_next(self._ctx)
+        def sender() -> int:
+            return self._sock.send_get_next(self._ctx)
 
-        def receiver() -> Tuple[str, ValueType]:
-            return self._sock.recv_get_next(self._ctx)
-
-        await self._session._send(sender)
-        return await self._session._recv(receiver)
+        request_id = await self._session._send(sender)
+        return await self._session._recv(
+            partial(self._sock.recv_get_next, self._ctx, request_id),
+            self._timeout,
+        )
 
 
 class GetBulkIter(object):
@@ -451,6 +599,7 @@ class GetBulkIter(object):
         session: Parent SnmpSession.
         oid: Base oid.
         max_repetitions: Max amount of iterms per response.
+        timeout: Optional timeout of each request in seconds.
     """
 
     def __init__(
@@ -458,12 +607,14 @@ class GetBulkIter(object):
         session: SnmpSession,
         oid: str,
         max_repetitions: int,
+        timeout: Optional[float] = None,
     ) -> None:
         self._session = session
         self._sock = session._sock
         self._ctx = GetIter(oid, max_repetitions)
         self._max_repetitions = max_repetitions
         self._buffer: List[Union[Tuple[str, ValueType], None]] = []
+        self._timeout = timeout
 
     def __aiter__(self: "GetBulkIter") -> "GetBulkIter":
         """Return asynchronous iterator."""
@@ -472,11 +623,8 @@ class GetBulkIter(object):
     async def __anext__(self: "GetBulkIter") -> Tuple[str, ValueType]:
         """Get next value."""
 
-        def sender() -> None:
-            self._sock.send_get_bulk(self._ctx)
-
-        def receiver() -> List[Union[Tuple[str, ValueType], None]]:
-            return self._sock.recv_get_bulk(self._ctx)
+        def sender() -> int:
+            return self._sock.send_get_bulk(self._ctx)
 
         def pop_or_stop() -> Tuple[str, ValueType]:
             v = self._buffer.pop(0)
@@ -487,8 +635,11 @@ class GetBulkIter(object):
         # Return item from buffer, if present
         if self._buffer:
             return pop_or_stop()
-        await self._session._send(sender)
-        self._buffer = await self._session._recv(receiver)
+        request_id = await self._session._send(sender)
+        self._buffer = await self._session._recv(
+            partial(self._sock.recv_get_bulk, self._ctx, request_id),
+            self._timeout,
+        )
         # End?
         if not self._buffer:
             raise StopAsyncIteration  # End of view
@@ -0,0 +1,48 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Request batching
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+"""Split requests to fit agent's message size."""
+
+# Python modules
+from typing import Iterator, List
+
+# Gufo SNMP modules
+from ._fast import SnmpV3ClientSocket
+from .protocol import SnmpClientSocketProtocol
+
+
+def get_max_varbinds(sock: SnmpClientSocketProtocol) -> int:
+    """
+    Get maximal amount of varbinds, fitting agent's msgMaxSize.
+
+    Args:
+        sock: Client socket.
+
+    Returns:
+        Amount of varbinds, 0 if unlimited or unknown.
+    """
+    if isinstance(sock, SnmpV3ClientSocket):
+        return sock.get_max_varbinds()
+    return 0
+
+
+def get_batches(oids: List[str], size: int) -> Iterator[List[str]]:
+    """
+    Split oids to the batches.
+
+    Args:
+        oids: List of oids.
+        size: Maximal amount of oids in batch, 0 if unlimited.
+
+    Returns:
+        Iterator yielding lists of oids.
+    """
+    if not size or len(oids) <= size:
+        yield oids
+        return
+    for i in range(0, len(oids), size):
+        yield oids[i : i + size]
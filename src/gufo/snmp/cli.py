@@ -1,7 +1,7 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: Cli command
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-25, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 """
@@ -39,6 +39,7 @@ from gufo.snmp import (
     Md5Key,
     Sha1Key,
     SnmpAuthError,
+    SnmpDecryptionError,
     SnmpVersion,
     User,
     ValueType,
@@ -561,6 +562,8 @@ class Cli(object):
                 return ExitCode.ERR
         except TimeoutError:
             self.die("ERROR: Timed out")
+        except SnmpDecryptionError:
+            self.die("ERROR: Decryption failed, check privacy key")
         except SnmpAuthError:
             self.die("ERROR: Authentication failed")
 
@@ -0,0 +1,32 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Address family preference
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+"""Address family preference definition."""
+
+# Python modules
+import enum
+
+
+class FamilyPreference(enum.IntEnum):
+    """
+    Address family preference for agent's hostname resolution.
+
+    Resolved addresses are tried in order until the agent replies.
+    The next address is used when the agent is unreachable
+    (ICMP error or refused connection) or, in blocking mode,
+    when the request is timed out. Multi-target sockets and
+    the Rust async client use the first resolved address only.
+
+    Attributes:
+        ANY: Keep resolver's order.
+        IPV4: Try IPv4 addresses first.
+        IPV6: Try IPv6 addresses first.
+    """
+
+    ANY = 0
+    IPV4 = 1
+    IPV6 = 2
@@ -0,0 +1,77 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Packet hook
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+"""Packet hook definition."""
+
+# Python modules
+import enum
+from typing import Any, Callable, Dict, NamedTuple, Optional
+
+
+class PacketDirection(enum.Enum):
+    """
+    Direction of the packet.
+
+    Attributes:
+        SENT: Message is sent to agent.
+        RECEIVED: Message is received from agent.
+    """
+
+    SENT = "send"
+    RECEIVED = "recv"
+
+
+class Packet(NamedTuple):
+    """
+    Message, passed to the packet hook.
+
+    Attributes:
+        direction: Direction of the packet.
+        peer: Agent's address.
+        data: Raw message.
+        pdu: Dict of `type`, `request_id`, `error_status`,
+            `error_index` and `varbinds`, as in `decode_message()`.
+            None if PDU cannot be decoded, i.e. encrypted.
+        discarded: Received message is dropped, as it is not
+            related to the request or cannot be decoded.
+    """
+
+    direction: PacketDirection
+    peer: str
+    data: bytes
+    pdu: Optional[Dict[str, Any]]
+    discarded: bool
+
+
+PacketHook = Callable[[Packet], None]
+
+
+def wrap_hook(
+    hook: Optional[PacketHook],
+) -> Optional[Callable[[str, str, bytes, Optional[Dict[str, Any]], bool], None]]:
+    """
+    Adapt packet hook to the socket's callback.
+
+    Args:
+        hook: Packet hook.
+
+    Returns:
+        Callable, accepting packet fields as arguments.
+    """
+    if hook is None:
+        return None
+
+    def inner(
+        direction: str,
+        peer: str,
+        data: bytes,
+        pdu: Optional[Dict[str, Any]],
+        discarded: bool,
+    ) -> None:
+        hook(Packet(PacketDirection(direction), peer, data, pdu, discarded))
+
+    return inner
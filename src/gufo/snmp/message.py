@@ -0,0 +1,176 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Raw message encoding and decoding
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+"""Raw SNMP messages for the packet analysis and crafting."""
+
+# Python modules
+from typing import Any, Dict, Iterable, Optional, Tuple, Union
+
+# Gufo SNMP modules
+from ._fast import DEFAULT_MAX_SIZE
+from ._fast import decode_message as _decode_message
+from ._fast import encode_message as _encode_message
+from .user import User
+from .version import SnmpVersion
+
+
+def decode_message(data: bytes, user: Optional[User] = None) -> Dict[str, Any]:
+    """
+    Decode SNMP message of any version.
+
+    Protocol version is detected automatically.
+    Encrypted SNMPv3 scoped PDU is decrypted
+    only when `user` is provided, otherwise `pdu`
+    is set to None.
+
+    Args:
+        data: Raw message, i.e. UDP payload.
+        user: SNMPv3 user to decrypt the scoped PDU.
+
+    Returns:
+        Dict with keys:
+
+        * `version`: SnmpVersion value.
+        * `community`: Community (v1 and v2c).
+        * `msg_id`, `max_size`, `flag_auth`, `flag_priv`,
+            `flag_report`: SNMPv3 header fields.
+        * `usm`: Dict of SNMPv3 USM security parameters.
+        * `context_engine_id`: SNMPv3 context engine id.
+        * `encrypted`: True, if SNMPv3 scoped PDU is encrypted.
+        * `pdu`: Dict of `type`, `request_id`, `error_status`,
+            `error_index` and `varbinds` or None. SNMPv1 Trap-PDU
+            also has `enterprise`, `agent_addr`, `generic_trap`,
+            `specific_trap` and `time_stamp`.
+
+        NULL values are returned as None, exceptions as
+        `(type, None)` tuple, like `("NoSuchInstance", None)`.
+
+    Raises:
+        SnmpDecodeError: On malformed message.
+        SnmpDecryptionError: When failed to decrypt the scoped PDU.
+    """
+    if user is None:
+        r = _decode_message(data)
+    else:
+        r = _decode_message(
+            data,
+            user.get_auth_alg(),
+            user.get_auth_key(),
+            user.get_priv_alg(),
+            user.get_priv_key(),
+        )
+    r["version"] = SnmpVersion(r["version"])
+    return r
+
+
+def encode_message(
+    version: SnmpVersion,
+    pdu_type: str,
+    request_id: int,
+    varbinds: Iterable[Tuple[str, Any]],
+    *,
+    community: Union[str, bytes] = b"public",
+    user: Optional[User] = None,
+    error_status: int = 0,
+    error_index: int = 0,
+    msg_id: Optional[int] = None,
+    max_size: int = DEFAULT_MAX_SIZE,
+    flag_auth: Optional[bool] = None,
+    flag_priv: Optional[bool] = None,
+    flag_report: bool = False,
+    engine_id: bytes = b"",
+    engine_boots: int = 0,
+    engine_time: int = 0,
+    context_engine_id: Optional[bytes] = None,
+    enterprise: Optional[str] = None,
+    agent_addr: str = "0.0.0.0",
+    generic_trap: int = 0,
+    specific_trap: int = 0,
+    time_stamp: int = 0,
+) -> bytes:
+    """
+    Encode SNMP message.
+
+    Varbind values of `None`, `int`, `float`, `str` and `bytes`
+    are encoded as NULL, INTEGER, REAL and OCTET STRING.
+    Other types are set explicitly by `(type, value)` tuple,
+    like `("Counter32", 10)`, `("IpAddress", "127.0.0.1")`
+    or `("Oid", "1.3.6.1")`. Request values are ignored.
+
+    SNMPv3 message is signed and the scoped PDU is encrypted
+    with `user` keys, localized with `engine_id`. Header flags
+    follow the user's keys, unless set explicitly.
+
+    Args:
+        version: Protocol version.
+        pdu_type: PDU type, one of `GetRequest`, `GetNextRequest`,
+            `GetResponse`, `SetRequest`, `Trap`, `GetBulkRequest`,
+            `InformRequest`, `SnmpV2Trap` or `Report`.
+        request_id: Request id.
+        varbinds: List of (oid, value).
+        community: Community (v1 and v2c).
+        user: SNMPv3 user.
+        error_status: Error status, non-repeaters for GETBULK.
+        error_index: Error index, max-repetitions for GETBULK.
+        msg_id: SNMPv3 message id, `request_id` by default.
+        max_size: SNMPv3 msgMaxSize.
+        flag_auth: SNMPv3 auth flag.
+        flag_priv: SNMPv3 privacy flag.
+        flag_report: SNMPv3 reportable flag.
+        engine_id: Authoritative engine id.
+        engine_boots: Authoritative engine boots.
+        engine_time: Authoritative engine time.
+        context_engine_id: Context engine id, `engine_id` by default.
+        enterprise: SNMPv1 Trap-PDU enterprise, required for `Trap`.
+        agent_addr: SNMPv1 Trap-PDU agent address.
+        generic_trap: SNMPv1 Trap-PDU generic trap.
+        specific_trap: SNMPv1 Trap-PDU specific trap.
+        time_stamp: SNMPv1 Trap-PDU time stamp.
+
+    Returns:
+        Encoded message.
+
+    Raises:
+        SnmpEncodeError: When message is too large.
+        SnmpDecodeError: When `Trap` has no `enterprise`.
+        ValueError: On invalid varbind value.
+    """
+    if isinstance(community, str):
+        community = community.encode()
+    if user is None:
+        user = User.default()
+    return _encode_message(
+        version,
+        pdu_type,
+        request_id,
+        list(varbinds),
+        error_status=error_status,
+        error_index=error_index,
+        community=community,
+        header=(
+            request_id if msg_id is None else msg_id,
+            max_size,
+            user.require_auth() if flag_auth is None else flag_auth,
+            user.priv_key is not None if flag_priv is None else flag_priv,
+            flag_report,
+        ),
+        usm=(engine_id, engine_boots, engine_time, user.name.encode()),
+        context_engine_id=context_engine_id,
+        auth_alg=user.get_auth_alg(),
+        auth_key=user.get_auth_key(),
+        priv_alg=user.get_priv_alg(),
+        priv_key=user.get_priv_key(),
+        trap=None
+        if enterprise is None
+        else (
+            enterprise,
+            agent_addr,
+            generic_trap,
+            specific_trap,
+            time_stamp,
+        ),
+    )
@@ -1,7 +1,7 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: Query policers
 # ---------------------------------------------------------------------
-# Copyright (C) 2023, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
@@ -13,6 +13,11 @@ from abc import ABC, abstractmethod
 from time import perf_counter_ns, sleep
 from typing import Optional
 
+# Gufo Labs modules
+from ._fast import TokenBucketPolicer
+
+__all__ = ["BasePolicer", "RPSPolicer", "TokenBucketPolicer"]
+
 NS = 1_000_000_000.0
 ZERO = 0.0
 
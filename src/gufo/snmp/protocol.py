@@ -1,12 +1,12 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: Socket protocol definition
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-25, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
 # Python modules
-from typing import Dict, List, Protocol, Tuple, Union
+from typing import Any, Dict, List, Optional, Protocol, Tuple, Union
 
 # Gufo Labs modules
 from ._fast import GetIter
@@ -16,48 +16,67 @@ from .typing import ValueType
 class SnmpClientSocketProtocol(Protocol):
     def get_fd(self: "SnmpClientSocketProtocol") -> int: ...
 
+    def get_peer_addr(self: "SnmpClientSocketProtocol") -> str: ...
+
+    def get_rtt_ns(self: "SnmpClientSocketProtocol") -> Optional[int]: ...
+
     # .get()
-    def get(self: "SnmpClientSocketProtocol", oid: str) -> ValueType: ...
+    def get(
+        self: "SnmpClientSocketProtocol",
+        oid: str,
+        timeout_ns: Optional[int] = None,
+    ) -> ValueType: ...
 
-    def send_get(self: "SnmpClientSocketProtocol", oid: str) -> None: ...
+    def send_get(self: "SnmpClientSocketProtocol", oid: str) -> int: ...
 
-    def recv_get(self: "SnmpClientSocketProtocol") -> ValueType: ...
+    def recv_get(
+        self: "SnmpClientSocketProtocol", request_id: int
+    ) -> ValueType: ...
 
     # .get_many()
     def get_many(
-        self: "SnmpClientSocketProtocol", oids: List[str]
+        self: "SnmpClientSocketProtocol",
+        oids: List[str],
+        timeout_ns: Optional[int] = None,
     ) -> Dict[str, ValueType]: ...
 
     def send_get_many(
         self: "SnmpClientSocketProtocol", oids: List[str]
-    ) -> None: ...
+    ) -> int: ...
 
     def recv_get_many(
-        self: "SnmpClientSocketProtocol",
+        self: "SnmpClientSocketProtocol", request_id: int
     ) -> Dict[str, ValueType]: ...
 
     # .get_next
     def get_next(
-        self: "SnmpClientSocketProtocol", iter_getnext: GetIter
+        self: "SnmpClientSocketProtocol",
+        iter_getnext: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> Tuple[str, ValueType]: ...
 
     def send_get_next(
         self: "SnmpClientSocketProtocol", iter_getnext: GetIter
-    ) -> None: ...
+    ) -> int: ...
 
     def recv_get_next(
-        self: "SnmpClientSocketProtocol", iter_getnext: GetIter
+        self: "SnmpClientSocketProtocol", iter_getnext: GetIter, request_id: int
     ) -> Tuple[str, ValueType]: ...
 
     # .get_bulk
     def get_bulk(
-        self: "SnmpClientSocketProtocol", iter_getbulk: GetIter
+        self: "SnmpClientSocketProtocol",
+        iter_getbulk: GetIter,
+        timeout_ns: Optional[int] = None,
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
 
     def send_get_bulk(
         self: "SnmpClientSocketProtocol", iter_getbulk: GetIter
-    ) -> None: ...
+    ) -> int: ...
 
     def recv_get_bulk(
-        self: "SnmpClientSocketProtocol", iter_getnext: GetIter
+        self: "SnmpClientSocketProtocol", iter_getnext: GetIter, request_id: int
     ) -> List[Union[Tuple[str, ValueType], None]]: ...
+
+    # Pipelined requests
+    def recv(self: "SnmpClientSocketProtocol") -> Tuple[int, Any]: ...
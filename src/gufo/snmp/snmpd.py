@@ -1,7 +1,7 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: Snmpd context manager
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-25, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
@@ -51,6 +51,8 @@ class Snmpd(object):
         path: snmpd path.
         address: Address to listen.
         port: Port to listen.
+        tcp_port: Optional port to listen TCP transport.
+        unix_path: Optional path to listen Unix domain stream socket.
         community: SNMP v1/v2c community.
         location: sysLocation value.
         contact: sysContact value.
@@ -88,6 +90,8 @@ class Snmpd(object):
         path: Optional[str] = None,
         address: str = "127.0.0.1",
         port: int = 10161,
+        tcp_port: Optional[int] = None,
+        unix_path: Optional[str] = None,
         community: str = "public",
         location: str = "Test",
         contact: str = "test <test@example.com>",
@@ -100,6 +104,8 @@ class Snmpd(object):
         self._path = path or self._get_snmpd_path()
         self._address = address
         self._port = port
+        self._tcp_port = tcp_port
+        self._unix_path = unix_path
         self._community = community
         self._location = location
         self._contact = contact
@@ -139,10 +145,15 @@ class Snmpd(object):
         """
         rousers = "\n".join(u.snmpd_rouser for u in self._users)
         create_users = "\n".join(u.snmpd_create_user for u in self._users)
+        listen = f"udp:{self._address}:{self._port}"
+        if self._tcp_port:
+            listen += f",tcp:{self._address}:{self._tcp_port}"
+        if self._unix_path:
+            listen += f",unix:{self._unix_path}"
         return f"""# Gufo SNMP Test Suite
 master agentx
 # Listen address
-agentaddress udp:{self._address}:{self._port}
+agentaddress {listen}
 agentXsocket tcp:{self._address}:{self._port}
 # SNMPv3 engine id
 engineId {self._cfg_engine_id}
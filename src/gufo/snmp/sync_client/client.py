@@ -1,24 +1,38 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: SyncSnmpSession
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-24, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
 """SyncSnmpSession implementation."""
 
 # Python modules
+from time import monotonic
 from types import TracebackType
-from typing import Dict, Iterable, Iterator, Optional, Tuple, Type, Union
+from typing import (
+    Dict,
+    Iterable,
+    Iterator,
+    Optional,
+    Tuple,
+    Type,
+    Union,
+)
 
 # Gufo Labs modules
 from .._fast import (
     SnmpV1ClientSocket,
     SnmpV2cClientSocket,
     SnmpV3ClientSocket,
+    TokenBucketPolicer,
 )
+from ..batch import get_batches, get_max_varbinds
+from ..family import FamilyPreference
+from ..hook import PacketHook, wrap_hook
 from ..policer import BasePolicer, RPSPolicer
 from ..protocol import SnmpClientSocketProtocol
+from ..transport import Transport
 from ..typing import ValueType
 from ..user import User
 from ..version import SnmpVersion
@@ -28,6 +42,13 @@ from .getnext import GetNextIter
 NS = 1_000_000_000.0
 
 
+def _to_ns(timeout: Optional[float]) -> Optional[int]:
+    """Convert optional timeout to nanoseconds."""
+    if timeout is None:
+        return None
+    return max(int(timeout * NS), 0)
+
+
 class SnmpSession(object):
     """
     Synchronous SNMP client session.
@@ -35,8 +56,12 @@ class SnmpSession(object):
     Should be used either directly or via context manager.
 
     Args:
-        addr: SNMP agent address, either IPv4 or IPv6.
-        port: SNMP agent port.
+        addr: SNMP agent address, either IPv4, IPv6, hostname,
+            or an absolute path to the Unix domain socket.
+            Link-local IPv6 addresses may be scoped
+            by interface name, i.e. `fe80::1%eth0`.
+            Unix domain sockets with UDP transport are Linux-only.
+        port: SNMP agent port. Ignored for Unix domain sockets.
         community: SNMP community (v1, v2c).
         engine_id: SNMP Engine id (v3).
         user: User instance (v3).
@@ -47,17 +72,40 @@ class SnmpSession(object):
 
         timeout: Request timeout in seconds.
         tos: Set ToS/DSCP mark on egress packets.
-        send_buffer: Send buffer size for UDP socket.
+            IPv6 traffic class is set for IPv6 agents.
+        send_buffer: Send buffer size for socket.
             0 - use default size.
-        recv_buffer: Receive buffer size for UDP socket.
+        recv_buffer: Receive buffer size for socket.
             0 - use default size.
         max_repetitions: Default max_repetitions for getbulk.
         allow_bulk: Allow using GETBULK in SnmpSession.fetch()
             whenever possible.
-        policer: Optional `BasePolicer` instance to limit
-            outgoing requests. Overrides `limit_rps` parameter.
+        policer: Optional `BasePolicer` or `TokenBucketPolicer`
+            instance to limit outgoing requests.
+            Overrides `limit_rps` parameter.
         limit_rps: Limit outgouing requests to `limit_rps`
             requests per second.
+        max_msg_size: Maximal message size, advertised to agent (v3)
+            and accepted over TCP. 0 - use default size.
+        transport: Transport protocol.
+        bind_addr: Local address to bind the socket.
+            Use the address chosen by the kernel if not set.
+        bind_port: Local port to bind the socket.
+            0 - use ephemeral port.
+        bind_device: Bind the socket to the network device,
+            i.e. VRF device. Linux only.
+        ttl: IPv4 TTL or IPv6 hop limit for egress packets.
+            0 - use system default.
+        family: Address family preference for hostname resolution.
+        retries: Retransmit unanswered UDP requests up to `retries`
+            times. The overall request time is still limited
+            by `timeout`.
+        retry_timeout: Timeout of the first attempt in seconds.
+            Split `timeout` evenly between attempts if not set.
+        backoff: Multiply attempt timeout by `backoff` on every
+            retransmission.
+        packet_hook: Callable, invoked with `Packet` for every
+            sent and received message, including the discarded ones.
 
     Example:
         ``` py
@@ -86,8 +134,19 @@ class SnmpSession(object):
         recv_buffer: int = 0,
         max_repetitions: int = 20,
         allow_bulk: bool = True,
-        policer: Optional[BasePolicer] = None,
+        policer: Optional[Union[BasePolicer, TokenBucketPolicer]] = None,
         limit_rps: Optional[Union[int, float]] = None,
+        max_msg_size: int = 0,
+        transport: Transport = Transport.UDP,
+        bind_addr: Optional[str] = None,
+        bind_port: int = 0,
+        bind_device: Optional[str] = None,
+        ttl: int = 0,
+        family: FamilyPreference = FamilyPreference.ANY,
+        retries: int = 0,
+        retry_timeout: Optional[float] = None,
+        backoff: float = 1.0,
+        packet_hook: Optional[PacketHook] = None,
     ) -> None:
         # Detect version
         if version is None:
@@ -95,24 +154,60 @@ class SnmpSession(object):
         self._sock: SnmpClientSocketProtocol
         self._to_refresh = False
         self._deferred_user: Optional[User] = None
+        sock_hook = wrap_hook(packet_hook)
         timeout_ns = int(timeout * NS)
+        retry_timeout_ns = int(retry_timeout * NS) if retry_timeout else 0
+        # TokenBucketPolicer is applied by socket
+        sock_policer = (
+            policer if isinstance(policer, TokenBucketPolicer) else None
+        )
+        if addr.startswith("/"):
+            target = addr
+        elif ":" in addr:
+            target = f"[{addr}]:{port}"
+        else:
+            target = f"{addr}:{port}"
         if version == SnmpVersion.v1:
             self._sock = SnmpV1ClientSocket(
-                f"{addr}:{port}",
+                target,
                 community,
                 tos,
                 send_buffer,
                 recv_buffer,
                 timeout_ns,
+                transport,
+                bind_addr,
+                bind_port,
+                bind_device,
+                ttl,
+                family,
+                retries,
+                retry_timeout_ns,
+                backoff,
+                sock_policer,
+                packet_hook=sock_hook,
+                max_msg_size=max_msg_size,
             )
         elif version == SnmpVersion.v2c:
             self._sock = SnmpV2cClientSocket(
-                f"{addr}:{port}",
+                target,
                 community,
                 tos,
                 send_buffer,
                 recv_buffer,
                 timeout_ns,
+                transport,
+                bind_addr,
+                bind_port,
+                bind_device,
+                ttl,
+                family,
+                retries,
+                retry_timeout_ns,
+                backoff,
+                sock_policer,
+                packet_hook=sock_hook,
+                max_msg_size=max_msg_size,
             )
         elif version == SnmpVersion.v3:
             if not user:
@@ -123,7 +218,7 @@ class SnmpSession(object):
                 self._deferred_user = user
                 user = User.default()
             self._sock = SnmpV3ClientSocket(
-                f"{addr}:{port}",
+                target,
                 engine_id if engine_id else b"",
                 user.name,
                 user.get_auth_alg(),
@@ -134,6 +229,18 @@ class SnmpSession(object):
                 send_buffer,
                 recv_buffer,
                 timeout_ns,
+                max_msg_size,
+                transport,
+                bind_addr,
+                bind_port,
+                bind_device,
+                ttl,
+                family,
+                retries,
+                retry_timeout_ns,
+                backoff,
+                sock_policer,
+                packet_hook=sock_hook,
             )
             self._to_refresh = not engine_id or user.require_auth()
         else:
@@ -147,7 +254,7 @@ class SnmpSession(object):
         else:
             self._allow_bulk = allow_bulk
         self._policer: Optional[BasePolicer] = None
-        if policer:
+        if isinstance(policer, BasePolicer):
             self._policer = policer
         elif limit_rps:
             self._policer = RPSPolicer(float(limit_rps))
@@ -165,12 +272,16 @@ class SnmpSession(object):
     ) -> None:
         """Asynchronous context manager exit."""
 
-    def get(self: "SnmpSession", oid: str) -> ValueType:
+    def get(
+        self: "SnmpSession", oid: str, timeout: Optional[float] = None
+    ) -> ValueType:
         """
         Send SNMP GET request and await for response.
 
         Args:
             oid: OID in numeric format, no leading dot.
+            timeout: Request timeout in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Request result. Return type depends on requested oid.
@@ -185,18 +296,22 @@ class SnmpSession(object):
         if self._policer:
             self._policer.wait_sync()
         try:
-            return self._sock.get(oid)
+            return self._sock.get(oid, _to_ns(timeout))
         except BlockingIOError as e:
             raise TimeoutError from e
 
     def get_many(
-        self: "SnmpSession", oids: Iterable[str]
+        self: "SnmpSession",
+        oids: Iterable[str],
+        timeout: Optional[float] = None,
     ) -> Dict[str, ValueType]:
         """
         Send SNMP GET request for multiple oids and await for response.
 
         Args:
             oids: Iterable of oids in numeric format, no leading dots.
+            timeout: Overall timeout in seconds, including all batches.
+                Override the SnmpSession's defaults.
 
         Returns:
             Dict where keys are requested oids, values are returned values.
@@ -213,21 +328,28 @@ class SnmpSession(object):
             RuntimeError: On Python runtime failure.
             SnmpError: On other SNMP-related errors.
         """
-        if self._policer:
-            self._policer.wait_sync()
-        try:
-            return self._sock.get_many(list(oids))
-        except BlockingIOError as e:
-            raise TimeoutError from e
+        r: Dict[str, ValueType] = {}
+        deadline = None if timeout is None else monotonic() + timeout
+        for batch in get_batches(list(oids), get_max_varbinds(self._sock)):
+            if self._policer:
+                self._policer.wait_sync()
+            remaining = None if deadline is None else deadline - monotonic()
+            try:
+                r.update(self._sock.get_many(batch, _to_ns(remaining)))
+            except BlockingIOError as e:
+                raise TimeoutError from e
+        return r
 
     def getnext(
-        self: "SnmpSession", oid: str
+        self: "SnmpSession", oid: str, timeout: Optional[float] = None
     ) -> Iterator[Tuple[str, ValueType]]:
         """
         Iterate over oids.
 
         Args:
             oid: Starting oid
+            timeout: Timeout of each request in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Asynchronous iterator yielding pair of (oid, value)
@@ -238,10 +360,13 @@ class SnmpSession(object):
                 print(oid, value)
             ```
         """
-        return GetNextIter(self._sock, oid, self._policer)
+        return GetNextIter(self._sock, oid, self._policer, _to_ns(timeout))
 
     def getbulk(
-        self: "SnmpSession", oid: str, max_repetitions: Optional[int] = None
+        self: "SnmpSession",
+        oid: str,
+        max_repetitions: Optional[int] = None,
+        timeout: Optional[float] = None,
     ) -> Iterator[Tuple[str, ValueType]]:
         """
         Iterate over oids.
@@ -250,6 +375,8 @@ class SnmpSession(object):
             oid: Starting oid
             max_repetitions: Maximal amount of items per response.
                 Override the SnmpSession's defaults.
+            timeout: Timeout of each request in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Asynchronous iterator yielding pair of (oid, value)
@@ -265,10 +392,11 @@ class SnmpSession(object):
             oid,
             max_repetitions or self._max_repetitions,
             self._policer,
+            _to_ns(timeout),
         )
 
     def fetch(
-        self: "SnmpSession", oid: str
+        self: "SnmpSession", oid: str, timeout: Optional[float] = None
     ) -> Iterator[Tuple[str, ValueType]]:
         """
         Iterate over oids using fastest method available.
@@ -279,6 +407,8 @@ class SnmpSession(object):
 
         Args:
             oid: Starting oid
+            timeout: Timeout of each request in seconds.
+                Override the SnmpSession's defaults.
 
         Returns:
             Asynchronous iterator yielding pair of (oid, value)
@@ -290,10 +420,10 @@ class SnmpSession(object):
             ```
         """
         if self._allow_bulk:
-            return self.getbulk(oid)
-        return self.getnext(oid)
+            return self.getbulk(oid, timeout=timeout)
+        return self.getnext(oid, timeout=timeout)
 
-    def refresh(self: "SnmpSession") -> None:
+    def refresh(self: "SnmpSession", timeout: Optional[float] = None) -> None:
         """
         Send and receive REPORT to refresh authentication state.
 
@@ -302,6 +432,10 @@ class SnmpSession(object):
         Refresh sent automatically on entering
         the SnmpSession and should be resent manually
         if over 150 seconds left from the last request.
+
+        Args:
+            timeout: Request timeout in seconds.
+                Override the SnmpSession's defaults.
         """
         if (
             not isinstance(self._sock, SnmpV3ClientSocket)
@@ -311,7 +445,7 @@ class SnmpSession(object):
 
         if self._deferred_user:
             # First check runs engine id discovery
-            self._sock.refresh()
+            self._sock.refresh(_to_ns(timeout))
             # Set and localize actual keys
             self._sock.set_keys(
                 self._deferred_user.name,
@@ -326,7 +460,7 @@ class SnmpSession(object):
             self._deferred_user = None
 
         # Refresh engine boots and time
-        self._sock.refresh()
+        self._sock.refresh(_to_ns(timeout))
 
     def get_engine_id(self: "SnmpSession") -> bytes:
         """
@@ -339,3 +473,33 @@ class SnmpSession(object):
             msg = "Must use SNMPv3"
             raise NotImplementedError(msg)
         return self._sock.get_engine_id()
+
+    def get_peer_addr(self: "SnmpSession") -> str:
+        """
+        Get agent's address, actually used.
+
+        Useful to log the result of hostname resolution.
+
+        Returns:
+            Agent's address as `address:port`, or
+            path to the Unix domain socket.
+        """
+        return self._sock.get_peer_addr()
+
+    def get_rtt(self: "SnmpSession") -> Optional[float]:
+        """
+        Get round-trip time of the last completed request.
+
+        Measured with monotonic clock from sending the request
+        to receiving its own reply, using kernel receive timestamps
+        when available. Retransmitted requests are measured
+        from the last retransmission.
+
+        Returns:
+            Round-trip time in seconds or None,
+            if there is no reply yet.
+        """
+        rtt = self._sock.get_rtt_ns()
+        if rtt is None:
+            return None
+        return rtt / NS
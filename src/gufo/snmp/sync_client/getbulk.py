@@ -25,6 +25,8 @@ class GetBulkIter(object):
         oid: Base oid.
         max_repetitions: Max amount of iterms per response.
         policer: Optional BasePolicer instance to limit requests.
+        timeout_ns: Optional timeout of each request in nanoseconds.
+            Use socket's timeout if not set.
     """
 
     def __init__(
@@ -33,12 +35,14 @@ class GetBulkIter(object):
         oid: str,
         max_repetitions: int,
         policer: Optional[BasePolicer] = None,
+        timeout_ns: Optional[int] = None,
     ) -> None:
         self._sock = sock
         self._ctx = _Iter(oid, max_repetitions)
         self._max_repetitions = max_repetitions
         self._buffer: List[Union[Tuple[str, ValueType], None]] = []
         self._policer = policer
+        self._timeout_ns = timeout_ns
 
     def __iter__(self: "GetBulkIter") -> "GetBulkIter":
         """Return asynchronous iterator."""
@@ -60,7 +64,7 @@ class GetBulkIter(object):
         if self._policer:
             self._policer.wait_sync()
         try:
-            self._buffer = self._sock.get_bulk(self._ctx)
+            self._buffer = self._sock.get_bulk(self._ctx, self._timeout_ns)
         except BlockingIOError as e:
             raise TimeoutError from e
         except StopAsyncIteration as e:
@@ -1,7 +1,7 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: GetNextIter
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-24, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
@@ -25,6 +25,8 @@ class GetNextIter(object):
         oid: Base oid.
         policer: Optional BasePolicer instance to limit
             outgoing requests.
+        timeout_ns: Optional timeout of each request in nanoseconds.
+            Use socket's timeout if not set.
     """
 
     def __init__(
@@ -32,10 +34,12 @@ class GetNextIter(object):
         sock: SnmpClientSocketProtocol,
         oid: str,
         policer: Optional[BasePolicer] = None,
+        timeout_ns: Optional[int] = None,
     ) -> None:
         self._sock = sock
         self._ctx = _Iter(oid)
         self._policer = policer
+        self._timeout_ns = timeout_ns
 
     def __iter__(self: "GetNextIter") -> "GetNextIter":
         """Return iterator."""
@@ -46,7 +50,7 @@ class GetNextIter(object):
         if self._policer:
             self._policer.wait_sync()
         try:
-            return self._sock.get_next(self._ctx)
+            return self._sock.get_next(self._ctx, self._timeout_ns)
         except StopAsyncIteration as e:
             raise StopIteration from e
         except BlockingIOError as e:
@@ -0,0 +1,27 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Transport definition
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+"""Transport definition."""
+
+# Python modules
+import enum
+
+
+class Transport(enum.IntEnum):
+    """
+    SNMP transport protocol.
+
+    Unix domain sockets use datagram socket for `UDP`
+    and stream socket for `TCP`.
+
+    Attributes:
+        UDP: SNMP over UDP, RFC-3417.
+        TCP: SNMP over TCP, RFC-3430.
+    """
+
+    UDP = 0
+    TCP = 1
@@ -0,0 +1,221 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Packet hook
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use crate::snmp::msg::{DecodedPdu, decode_message};
+use crate::snmp::pdu::SnmpPdu;
+#[cfg(feature = "python")]
+use pyo3::{prelude::*, types::PyBytes};
+use std::fmt;
+use std::sync::Arc;
+
+/// Direction of the packet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PacketDirection {
+    Sent,
+    Received,
+}
+
+impl PacketDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PacketDirection::Sent => "send",
+            PacketDirection::Received => "recv",
+        }
+    }
+}
+
+/// Datagram, passed to the packet hook
+#[derive(Debug)]
+pub struct Packet<'a> {
+    pub direction: PacketDirection,
+    /// Agent's address
+    pub peer: &'a str,
+    /// Raw message
+    pub data: &'a [u8],
+    /// Decoded PDU. None if the PDU cannot be decoded,
+    /// i.e. encrypted with unknown keys or malformed.
+    pub pdu: Option<&'a DecodedPdu>,
+    /// Received message is dropped: it is not related to
+    /// the request or cannot be decoded.
+    pub discarded: bool,
+}
+
+/// Hook, invoked for every sent and received message.
+///
+/// Hook is called in the socket's thread, so it must be fast.
+/// Closures `Fn(&Packet)` are hooks too.
+pub trait PacketHook: Send + Sync {
+    fn on_packet(&self, packet: &Packet);
+}
+
+impl<F> PacketHook for F
+where
+    F: Fn(&Packet) + Send + Sync,
+{
+    fn on_packet(&self, packet: &Packet) {
+        self(packet)
+    }
+}
+
+impl fmt::Debug for dyn PacketHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PacketHook")
+    }
+}
+
+/// Packet hook, bound to the agent's address.
+pub(crate) struct Tracer {
+    hook: Arc<dyn PacketHook>,
+    peer: String,
+}
+
+impl Tracer {
+    pub fn new(hook: Arc<dyn PacketHook>, peer: String) -> Self {
+        Self { hook, peer }
+    }
+    /// Summary of the PDU to be sent
+    pub fn summary(pdu: &SnmpPdu) -> Option<DecodedPdu> {
+        DecodedPdu::try_from(pdu).ok()
+    }
+    /// Message is sent
+    pub fn sent(&self, data: &[u8], pdu: Option<&DecodedPdu>) {
+        self.hook.on_packet(&Packet {
+            direction: PacketDirection::Sent,
+            peer: &self.peer,
+            data,
+            pdu,
+            discarded: false,
+        });
+    }
+    /// Message is received.
+    /// `pdu` is the unwrapped PDU, when available,
+    /// message is decoded again otherwise.
+    pub fn received(&self, data: &[u8], pdu: Option<&SnmpPdu>, discarded: bool) {
+        let pdu = match pdu {
+            Some(x) => DecodedPdu::try_from(x).ok(),
+            None => decode_message(data, None).ok().and_then(|x| x.pdu),
+        };
+        self.hook.on_packet(&Packet {
+            direction: PacketDirection::Received,
+            peer: &self.peer,
+            data,
+            pdu: pdu.as_ref(),
+            discarded,
+        });
+    }
+}
+
+/// Python callable as the packet hook.
+///
+/// Called as `hook(direction, peer, data, pdu, discarded)`.
+/// Exceptions are reported as unraisable.
+#[cfg(feature = "python")]
+pub(crate) struct PyPacketHook(Py<PyAny>);
+
+#[cfg(feature = "python")]
+impl PyPacketHook {
+    pub fn shared(hook: Py<PyAny>) -> Arc<dyn PacketHook> {
+        Arc::new(Self(hook))
+    }
+}
+
+#[cfg(feature = "python")]
+impl PacketHook for PyPacketHook {
+    fn on_packet(&self, packet: &Packet) {
+        Python::attach(|py| {
+            let r = packet
+                .pdu
+                .map(|x| x.to_python(py))
+                .transpose()
+                .and_then(|pdu| {
+                    self.0.call1(
+                        py,
+                        (
+                            packet.direction.as_str(),
+                            packet.peer,
+                            PyBytes::new(py, packet.data),
+                            pdu,
+                            packet.discarded,
+                        ),
+                    )
+                });
+            if let Err(e) = r {
+                e.write_unraisable(py, Some(self.0.bind(py)));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::BerEncoder;
+    use crate::ber::SnmpOid;
+    use crate::buf::Buffer;
+    use crate::snmp::get::SnmpGet;
+    use crate::snmp::msg::{PduType, SnmpV2cMessage};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_tracer() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let hook = {
+            let log = log.clone();
+            move |p: &Packet| {
+                log.lock().unwrap().push((
+                    p.direction,
+                    p.peer.to_owned(),
+                    p.data.len(),
+                    p.pdu.map(|x| (x.pdu_type, x.request_id)),
+                    p.discarded,
+                ))
+            }
+        };
+        let tracer = Tracer::new(Arc::new(hook), "127.0.0.1:161".into());
+        let pdu = SnmpPdu::GetRequest(SnmpGet {
+            request_id: 5,
+            vars: vec![SnmpOid::try_from("1.3.6.1.2.1.1.5.0").unwrap()],
+        });
+        let mut buf = Buffer::default();
+        SnmpV2cMessage {
+            community: b"public",
+            pdu: pdu.clone(),
+        }
+        .push_ber(&mut buf)
+        .unwrap();
+        tracer.sent(buf.data(), Tracer::summary(&pdu).as_ref());
+        tracer.received(buf.data(), Some(&pdu), false);
+        // Decoded again
+        tracer.received(buf.data(), None, true);
+        // Malformed
+        tracer.received(b"\x30\x03", None, true);
+        let expected = Some((PduType::GetRequest, 5));
+        let size = buf.data().len();
+        let peer = String::from("127.0.0.1:161");
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                (PacketDirection::Sent, peer.clone(), size, expected, false),
+                (
+                    PacketDirection::Received,
+                    peer.clone(),
+                    size,
+                    expected,
+                    false
+                ),
+                (
+                    PacketDirection::Received,
+                    peer.clone(),
+                    size,
+                    expected,
+                    true
+                ),
+                (PacketDirection::Received, peer, 2, None, true),
+            ]
+        );
+    }
+}
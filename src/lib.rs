@@ -1,22 +1,36 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Module definition
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 pub mod auth;
 pub mod ber;
 pub mod buf;
+pub mod client;
+#[cfg(feature = "python")]
+mod engine;
 pub mod error;
+mod hook;
+mod policer;
 mod privacy;
 pub mod reqid;
 pub mod snmp;
 mod socket;
+#[cfg(feature = "python")]
 mod util;
 
+#[cfg(feature = "tokio")]
+pub use client::{AsyncSnmpClient, AsyncSnmpSocket};
+pub use client::{SnmpClient, SnmpClientConfig, UsmUser};
+pub use snmp::msg::{decode_message, encode_message};
+pub use snmp::value::OwnedValue;
+
 /// Module index
+#[cfg(feature = "python")]
 #[pymodule]
 #[pyo3(name = "_fast")]
 fn gufo_snmp(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
@@ -24,12 +38,43 @@ fn gufo_snmp(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("SnmpEncodeError", py.get_type::<error::PySnmpEncodeError>())?;
     m.add("SnmpDecodeError", py.get_type::<error::PySnmpDecodeError>())?;
     m.add("SnmpAuthError", py.get_type::<error::PySnmpAuthError>())?;
+    m.add(
+        "SnmpDecryptionError",
+        py.get_type::<error::PySnmpDecryptionError>(),
+    )?;
     m.add("NoSuchInstance", py.get_type::<error::PyNoSuchInstance>())?;
+    m.add("SnmpIcmpError", py.get_type::<error::PySnmpIcmpError>())?;
+    m.add(
+        "PortUnreachableError",
+        py.get_type::<error::PyPortUnreachableError>(),
+    )?;
+    m.add(
+        "HostUnreachableError",
+        py.get_type::<error::PyHostUnreachableError>(),
+    )?;
+    m.add(
+        "NetworkUnreachableError",
+        py.get_type::<error::PyNetworkUnreachableError>(),
+    )?;
+    m.add(
+        "AdminProhibitedError",
+        py.get_type::<error::PyAdminProhibitedError>(),
+    )?;
+    m.add(
+        "TtlExceededError",
+        py.get_type::<error::PyTtlExceededError>(),
+    )?;
+    m.add("DEFAULT_MAX_SIZE", snmp::msg::v3::DEFAULT_MAX_SIZE)?;
     m.add_class::<socket::SnmpV1ClientSocket>()?;
     m.add_class::<socket::SnmpV2cClientSocket>()?;
     m.add_class::<socket::SnmpV3ClientSocket>()?;
+    m.add_class::<socket::SnmpMultiClientSocket>()?;
     m.add_class::<snmp::op::GetIter>()?;
+    m.add_class::<engine::SnmpV3Engine>()?;
+    m.add_class::<policer::TokenBucketPolicer>()?;
     m.add_function(wrap_pyfunction!(util::get_master_key, m)?)?;
     m.add_function(wrap_pyfunction!(util::get_localized_key, m)?)?;
+    m.add_function(wrap_pyfunction!(snmp::msg::py_decode_message, m)?)?;
+    m.add_function(wrap_pyfunction!(snmp::msg::py_encode_message, m)?)?;
     Ok(())
 }
@@ -0,0 +1,271 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Token bucket policer
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyValueError, prelude::*};
+#[cfg(feature = "python")]
+use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// Drop idle per-target buckets when the table grows over the limit
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Token bucket
+#[derive(Debug, Clone)]
+struct Bucket {
+    // Tokens per second
+    rate: f64,
+    // Bucket capacity
+    burst: f64,
+    // Available tokens, negative when requests are queued
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(rate: f64, burst: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            burst,
+            tokens: burst,
+            updated: now,
+        }
+    }
+    // Refill bucket
+    fn refill(&mut self, now: Instant) {
+        if now > self.updated {
+            let elapsed = now.duration_since(self.updated).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+            self.updated = now;
+        }
+    }
+    // Take a token, possibly in advance.
+    // Returns the time when the token is available.
+    fn reserve(&mut self, now: Instant) -> Instant {
+        self.refill(now);
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            now
+        } else {
+            now + Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+    // Give back the reserved token
+    fn release(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(self.burst);
+    }
+    // Bucket is full and can be dropped without losing state
+    fn is_idle(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.burst
+    }
+}
+
+#[derive(Debug)]
+struct PolicerState {
+    global: Option<Bucket>,
+    targets: HashMap<String, Bucket>,
+}
+
+/// Token bucket policer with optional global and per-target limits.
+/// Shared between sockets.
+#[derive(Debug)]
+pub struct Policer {
+    // Per-target rate and burst
+    target: Option<(f64, f64)>,
+    state: Mutex<PolicerState>,
+}
+
+impl Policer {
+    /// Limits are given as (rate, burst), where rate is requests
+    /// per second and burst is the bucket capacity, at least 1.
+    pub fn new(global: Option<(f64, f64)>, target: Option<(f64, f64)>) -> Self {
+        let now = Instant::now();
+        Self {
+            target,
+            state: Mutex::new(PolicerState {
+                global: global.map(|(rate, burst)| Bucket::new(rate, burst, now)),
+                targets: HashMap::new(),
+            }),
+        }
+    }
+    /// Reserve a request to the target.
+    /// Returns the time when the request may be sent.
+    pub fn reserve_at(&self, target: &str, now: Instant) -> Instant {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut ready = now;
+        if let Some(bucket) = state.global.as_mut() {
+            ready = ready.max(bucket.reserve(now));
+        }
+        if let Some((rate, burst)) = self.target {
+            if !state.targets.contains_key(target) && state.targets.len() >= PRUNE_THRESHOLD {
+                state.targets.retain(|_, b| !b.is_idle(now));
+            }
+            let bucket = state
+                .targets
+                .entry(target.to_owned())
+                .or_insert_with(|| Bucket::new(rate, burst, now));
+            ready = ready.max(bucket.reserve(now));
+        }
+        ready
+    }
+    /// Give back a reservation for the request, which is not sent.
+    pub fn release(&self, target: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bucket) = state.global.as_mut() {
+            bucket.release();
+        }
+        if let Some(bucket) = state.targets.get_mut(target) {
+            bucket.release();
+        }
+    }
+    /// Reserve a request to the target and wait until it may be sent.
+    pub fn wait(&self, target: &str) {
+        let now = Instant::now();
+        let ready = self.reserve_at(target, now);
+        if ready > now {
+            std::thread::sleep(ready - now);
+        }
+    }
+}
+
+/// Python class wrapping shared policer
+#[cfg(feature = "python")]
+#[pyclass]
+pub struct TokenBucketPolicer {
+    inner: Arc<Policer>,
+}
+
+#[cfg(feature = "python")]
+impl TokenBucketPolicer {
+    pub(crate) fn get_policer(&self) -> Arc<Policer> {
+        self.inner.clone()
+    }
+}
+
+// Validate rate and burst
+#[cfg(feature = "python")]
+fn get_limit(rate: Option<f64>, burst: f64) -> PyResult<Option<(f64, f64)>> {
+    match rate {
+        None => Ok(None),
+        Some(r) if !(r > 0.0 && r.is_finite()) => Err(PyValueError::new_err("invalid rate")),
+        Some(_) if !(burst >= 1.0 && burst.is_finite()) => {
+            Err(PyValueError::new_err("invalid burst"))
+        }
+        Some(r) => Ok(Some((r, burst))),
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TokenBucketPolicer {
+    /// Python constructor
+    #[new]
+    #[pyo3(signature = (rate = None, burst = 1.0, target_rate = None, target_burst = 1.0))]
+    fn new(
+        rate: Option<f64>,
+        burst: f64,
+        target_rate: Option<f64>,
+        target_burst: f64,
+    ) -> PyResult<Self> {
+        let global = get_limit(rate, burst)?;
+        let target = get_limit(target_rate, target_burst)?;
+        if global.is_none() && target.is_none() {
+            return Err(PyValueError::new_err("rate or target_rate must be set"));
+        }
+        Ok(Self {
+            inner: Arc::new(Policer::new(global, target)),
+        })
+    }
+    /// Reserve a request to the target.
+    /// Returns delay before the request may be sent, in nanoseconds.
+    fn reserve_ns(&self, target: &str) -> u64 {
+        let now = Instant::now();
+        self.inner
+            .reserve_at(target, now)
+            .duration_since(now)
+            .as_nanos() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T1: &str = "127.0.0.1:161";
+    const T2: &str = "127.0.0.2:161";
+
+    #[test]
+    fn test_global() {
+        let p = Policer::new(Some((10.0, 1.0)), None);
+        let now = Instant::now();
+        assert_eq!(p.reserve_at(T1, now), now);
+        // Global limit is shared between targets
+        assert_eq!(p.reserve_at(T2, now), now + Duration::from_millis(100));
+        assert_eq!(p.reserve_at(T1, now), now + Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_burst() {
+        let p = Policer::new(Some((10.0, 3.0)), None);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(p.reserve_at(T1, now), now);
+        }
+        assert_eq!(p.reserve_at(T1, now), now + Duration::from_millis(100));
+        // Refilled
+        let later = now + Duration::from_secs(1);
+        assert_eq!(p.reserve_at(T1, later), later);
+    }
+
+    #[test]
+    fn test_target() {
+        let p = Policer::new(None, Some((10.0, 1.0)));
+        let now = Instant::now();
+        assert_eq!(p.reserve_at(T1, now), now);
+        // Independent buckets
+        assert_eq!(p.reserve_at(T2, now), now);
+        assert_eq!(p.reserve_at(T1, now), now + Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_global_and_target() {
+        let p = Policer::new(Some((100.0, 1.0)), Some((10.0, 1.0)));
+        let now = Instant::now();
+        assert_eq!(p.reserve_at(T1, now), now);
+        assert_eq!(p.reserve_at(T2, now), now + Duration::from_millis(10));
+        // Target's limit is stricter
+        assert_eq!(p.reserve_at(T1, now), now + Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_release() {
+        let p = Policer::new(Some((10.0, 1.0)), Some((10.0, 1.0)));
+        let now = Instant::now();
+        assert_eq!(p.reserve_at(T1, now), now);
+        assert_eq!(p.reserve_at(T1, now), now + Duration::from_millis(100));
+        // Unsent request does not delay the next one
+        p.release(T1);
+        assert_eq!(p.reserve_at(T1, now), now + Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_prune() {
+        let p = Policer::new(None, Some((10.0, 1.0)));
+        let now = Instant::now();
+        for i in 0..PRUNE_THRESHOLD {
+            p.reserve_at(&i.to_string(), now);
+        }
+        let later = now + Duration::from_secs(1);
+        p.reserve_at(T1, later);
+        assert_eq!(p.state.lock().unwrap().targets.len(), 1);
+    }
+}
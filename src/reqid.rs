@@ -1,28 +1,117 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Id Generator
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use rand::Rng;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 const MAX_REQUEST_ID: i64 = 0x7fffffff;
+/// Maximal amount of requests in flight.
+/// The oldest requests are abandoned when exceeded.
+pub const MAX_IN_FLIGHT: usize = 1024;
 
-#[derive(Default)]
-pub struct RequestId(i64);
+/// Id generator, tracking ids in flight.
+/// Each id may carry an attached value.
+pub struct RequestId<T = ()> {
+    // Ids in flight, oldest first
+    in_flight: VecDeque<InFlight<T>>,
+}
+
+struct InFlight<T> {
+    id: i64,
+    value: T,
+    // Time when the request has been sent last time
+    sent_at: Option<Instant>,
+}
+
+impl<T> Default for RequestId<T> {
+    fn default() -> Self {
+        Self {
+            in_flight: VecDeque::new(),
+        }
+    }
+}
 
 impl RequestId {
     /// Get next value
     pub fn get_next(&mut self) -> i64 {
+        self.push(())
+    }
+}
+
+impl<T> RequestId<T> {
+    /// Get next value and attach `value` to it
+    pub fn push(&mut self, value: T) -> i64 {
         let mut rng = rand::rng();
-        let x: i64 = rng.random();
-        self.0 = x & MAX_REQUEST_ID;
-        self.0
+        let v = loop {
+            let x: i64 = rng.random();
+            let x = x & MAX_REQUEST_ID;
+            if !self.check(x) {
+                break x;
+            }
+        };
+        if self.in_flight.len() >= MAX_IN_FLIGHT {
+            self.in_flight.pop_front();
+        }
+        self.in_flight.push_back(InFlight {
+            id: v,
+            value,
+            sent_at: None,
+        });
+        v
     }
-    /// Check values for match
+    /// Check value is in flight
     pub fn check(&self, v: i64) -> bool {
-        self.0 == v
+        self.in_flight.iter().any(|x| x.id == v)
+    }
+    /// Get value, attached to id
+    pub fn get(&self, v: i64) -> Option<&T> {
+        self.find(v).map(|x| &x.value)
+    }
+    /// Last generated value, if still in flight
+    pub fn last(&self) -> Option<i64> {
+        self.in_flight.back().map(|x| x.id)
+    }
+    /// Remove value from flight.
+    /// Returns attached value.
+    pub fn remove(&mut self, v: i64) -> Option<T> {
+        let pos = self.in_flight.iter().position(|x| x.id == v)?;
+        self.in_flight.remove(pos).map(|x| x.value)
+    }
+    /// Remember the time when the request is sent.
+    /// Retransmission restarts the measurement.
+    pub fn set_sent_at(&mut self, v: i64, at: Instant) {
+        if let Some(x) = self.in_flight.iter_mut().find(|x| x.id == v) {
+            x.sent_at = Some(at);
+        }
+    }
+    /// Round-trip time of the request, replied at `received_at`
+    pub fn rtt(&self, v: i64, received_at: Instant) -> Option<Duration> {
+        self.find(v)
+            .and_then(|x| x.sent_at)
+            .map(|sent_at| received_at.saturating_duration_since(sent_at))
+    }
+    /// Remove all ids with attached `value`
+    pub fn remove_value(&mut self, value: &T)
+    where
+        T: PartialEq,
+    {
+        self.in_flight.retain(|x| x.value != *value);
+    }
+    fn find(&self, v: i64) -> Option<&InFlight<T>> {
+        self.in_flight.iter().find(|x| x.id == v)
+    }
+    /// Amount of ids in flight
+    pub fn len(&self) -> usize {
+        self.in_flight.len()
+    }
+    /// Check no ids are in flight
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.is_empty()
     }
 }
 
@@ -32,15 +121,17 @@ mod tests {
 
     #[test]
     fn test_default() {
-        let r = RequestId::default();
-        assert!(r.check(0))
+        let r: RequestId = RequestId::default();
+        assert!(!r.check(0));
+        assert_eq!(r.last(), None);
     }
 
     #[test]
     fn test_check() {
         let mut r = RequestId::default();
         let v1 = r.get_next();
-        assert!(r.check(v1))
+        assert!(r.check(v1));
+        assert_eq!(r.last(), Some(v1));
     }
 
     #[test]
@@ -50,4 +141,71 @@ mod tests {
         let v2 = r.get_next();
         assert!(v1 != v2)
     }
+
+    #[test]
+    fn test_in_flight() {
+        let mut r = RequestId::default();
+        let v1 = r.get_next();
+        let v2 = r.get_next();
+        assert!(r.check(v1));
+        assert!(r.check(v2));
+        assert_eq!(r.remove(v1), Some(()));
+        assert!(!r.check(v1));
+        assert!(r.check(v2));
+        assert_eq!(r.remove(v1), None);
+    }
+
+    #[test]
+    fn test_value() {
+        let mut r = RequestId::<i64>::default();
+        let v = r.push(15);
+        assert_eq!(r.get(v), Some(&15));
+        assert_eq!(r.remove(v), Some(15));
+        assert_eq!(r.get(v), None);
+    }
+
+    #[test]
+    fn test_remove_value() {
+        let mut r = RequestId::<i64>::default();
+        let v1 = r.push(15);
+        let v2 = r.push(16);
+        let v3 = r.push(15);
+        r.remove_value(&15);
+        assert!(!r.check(v1));
+        assert!(r.check(v2));
+        assert!(!r.check(v3));
+        assert_eq!(r.len(), 1);
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut r = RequestId::default();
+        let first = r.get_next();
+        for _ in 0..MAX_IN_FLIGHT {
+            r.get_next();
+        }
+        assert_eq!(r.len(), MAX_IN_FLIGHT);
+        // Oldest request is abandoned
+        assert!(!r.check(first));
+    }
+
+    #[test]
+    fn test_rtt() {
+        let mut r = RequestId::default();
+        let v1 = r.get_next();
+        let v2 = r.get_next();
+        let now = Instant::now();
+        // Not sent yet
+        assert_eq!(r.rtt(v1, now), None);
+        r.set_sent_at(v1, now);
+        r.set_sent_at(v2, now + Duration::from_millis(5));
+        let received = now + Duration::from_millis(20);
+        assert_eq!(r.rtt(v1, received), Some(Duration::from_millis(20)));
+        assert_eq!(r.rtt(v2, received), Some(Duration::from_millis(15)));
+        // Retransmission
+        r.set_sent_at(v1, now + Duration::from_millis(10));
+        assert_eq!(r.rtt(v1, received), Some(Duration::from_millis(10)));
+        r.remove(v1);
+        assert_eq!(r.rtt(v1, received), None);
+    }
 }
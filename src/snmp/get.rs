@@ -1,17 +1,17 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: GET PDU Parser
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use crate::ber::{BerDecoder, BerEncoder, SnmpInt, SnmpNull, SnmpOid, SnmpSequence};
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
-use nom::IResult;
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 
 const DOUBLE_ZEROES: [u8; 6] = [2u8, 1, 0, 2, 1, 0];
 
+#[derive(Clone)]
 pub struct SnmpGet<'a> {
     pub request_id: i64,
     pub vars: Vec<SnmpOid<'a>>,
@@ -22,26 +22,27 @@ impl<'a> TryFrom<&'a [u8]> for SnmpGet<'a> {
 
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
         // Request id
-        let (tail, request_id) = SnmpInt::from_ber(value)?;
+        let (tail, request_id) = SnmpInt::from_ber(value).within("request_id")?;
         // error status, must be 0
-        let (tail, error_status) = SnmpInt::from_ber(tail)?;
+        let (tail, error_status) = SnmpInt::from_ber(tail).within("error_status")?;
         if !error_status.is_zero() {
-            return Err(SnmpError::InvalidPdu);
+            return Err(SnmpError::InvalidPdu.within("error_status"));
         }
         // error index, must be 0
-        let (tail, error_index) = SnmpInt::from_ber(tail)?;
+        let (tail, error_index) = SnmpInt::from_ber(tail).within("error_index")?;
         if !error_index.is_zero() {
-            return Err(SnmpError::InvalidPdu);
+            return Err(SnmpError::InvalidPdu.within("error_index"));
         }
         // varbinds
-        let (tail, vb) = SnmpSequence::from_ber(tail)?;
+        let (tail, vb) = SnmpSequence::from_ber(tail).within("varbinds")?;
         if !tail.is_empty() {
-            return Err(SnmpError::TrailingData);
+            return Err(SnmpError::TrailingData.at(tail));
         }
         let mut v_tail = vb.0;
         let mut vars = Vec::<SnmpOid>::new();
         while !v_tail.is_empty() {
-            let (rest, oid) = SnmpGet::parse_var(v_tail)?;
+            let (rest, oid) = SnmpGet::parse_var(v_tail)
+                .map_err(|e| e.within(format!("[{}]", vars.len())).within("varbinds"))?;
             vars.push(oid);
             v_tail = rest;
         }
@@ -79,13 +80,13 @@ impl BerEncoder for SnmpGet<'_> {
 }
 
 impl SnmpGet<'_> {
-    fn parse_var(i: &[u8]) -> IResult<&[u8], SnmpOid<'_>, SnmpError> {
+    fn parse_var(i: &[u8]) -> SnmpResult<(&[u8], SnmpOid<'_>)> {
         // Parse enclosing sequence
         let (rest, vs) = SnmpSequence::from_ber(i)?;
         // Parse oid
-        let (tail, oid) = SnmpOid::from_ber(vs.0)?;
+        let (tail, oid) = SnmpOid::from_ber(vs.0).within("oid")?;
         // Parse null
-        let (_, _) = SnmpNull::from_ber(tail)?;
+        let (_, _) = SnmpNull::from_ber(tail).within("value")?;
         Ok((rest, oid))
     }
 }
@@ -1,15 +1,15 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: GetBulk PDU Parser
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use crate::ber::{BerDecoder, BerEncoder, SnmpInt, SnmpNull, SnmpOid, SnmpSequence};
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
-use nom::IResult;
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 
+#[derive(Clone)]
 pub struct SnmpGetBulk<'a> {
     pub(crate) request_id: i64,
     pub(crate) non_repeaters: i64,
@@ -22,20 +22,21 @@ impl<'a> TryFrom<&'a [u8]> for SnmpGetBulk<'a> {
 
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
         // Request id
-        let (tail, request_id) = SnmpInt::from_ber(value)?;
+        let (tail, request_id) = SnmpInt::from_ber(value).within("request_id")?;
         // non-repeaters
-        let (tail, non_repeaters) = SnmpInt::from_ber(tail)?;
+        let (tail, non_repeaters) = SnmpInt::from_ber(tail).within("non_repeaters")?;
         // max-repetitions
-        let (tail, max_repetitions) = SnmpInt::from_ber(tail)?;
+        let (tail, max_repetitions) = SnmpInt::from_ber(tail).within("max_repetitions")?;
         // varbinds
-        let (tail, vb) = SnmpSequence::from_ber(tail)?;
+        let (tail, vb) = SnmpSequence::from_ber(tail).within("varbinds")?;
         if !tail.is_empty() {
-            return Err(SnmpError::TrailingData);
+            return Err(SnmpError::TrailingData.at(tail));
         }
         let mut v_tail = vb.0;
         let mut vars = Vec::<SnmpOid>::new();
         while !v_tail.is_empty() {
-            let (rest, oid) = SnmpGetBulk::parse_var(v_tail)?;
+            let (rest, oid) = SnmpGetBulk::parse_var(v_tail)
+                .map_err(|e| e.within(format!("[{}]", vars.len())).within("varbinds"))?;
             vars.push(oid);
             v_tail = rest;
         }
@@ -79,13 +80,13 @@ impl BerEncoder for SnmpGetBulk<'_> {
 }
 
 impl SnmpGetBulk<'_> {
-    fn parse_var(i: &[u8]) -> IResult<&[u8], SnmpOid<'_>, SnmpError> {
+    fn parse_var(i: &[u8]) -> SnmpResult<(&[u8], SnmpOid<'_>)> {
         // Parse enclosing sequence
         let (rest, vs) = SnmpSequence::from_ber(i)?;
         // Parse oid
-        let (tail, oid) = SnmpOid::from_ber(vs.0)?;
+        let (tail, oid) = SnmpOid::from_ber(vs.0).within("oid")?;
         // Parse null
-        let (_, _) = SnmpNull::from_ber(tail)?;
+        let (_, _) = SnmpNull::from_ber(tail).within("value")?;
         Ok((rest, oid))
     }
 }
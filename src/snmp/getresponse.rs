@@ -8,12 +8,14 @@
 use super::ERR_AUTHORIZATION_ERROR;
 use super::value::SnmpValue;
 use crate::ber::{
-    BerDecoder, SnmpInt, SnmpOid, SnmpRelativeOid, SnmpSequence, TAG_OBJECT_ID, TAG_RELATIVE_OID,
-    Tag,
+    BerDecoder, BerEncoder, SnmpInt, SnmpOid, SnmpRelativeOid, SnmpSequence, TAG_OBJECT_ID,
+    TAG_RELATIVE_OID, Tag,
 };
-use crate::error::SnmpError;
+use crate::buf::Buffer;
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct SnmpGetResponse<'a> {
     pub(crate) request_id: i64,
     pub(crate) error_status: u8,
@@ -21,6 +23,7 @@ pub struct SnmpGetResponse<'a> {
     pub(crate) vars: Vec<SnmpVar<'a>>,
 }
 
+#[derive(Clone)]
 pub struct SnmpVar<'a> {
     pub oid: SnmpOid<'a>,
     pub value: SnmpValue<'a>,
@@ -31,50 +34,96 @@ impl<'a> TryFrom<&'a [u8]> for SnmpGetResponse<'a> {
 
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
         // Request id
-        let (tail, request_id) = SnmpInt::from_ber(value)?;
+        let (tail, request_id) = SnmpInt::from_ber(value).within("request_id")?;
         // error status
-        let (tail, error_status) = SnmpInt::from_ber(tail)?;
+        let (tail, error_status) = SnmpInt::from_ber(tail).within("error_status")?;
         // error index
-        let (tail, error_index) = SnmpInt::from_ber(tail)?;
+        let (tail, error_index) = SnmpInt::from_ber(tail).within("error_index")?;
         // varbinds
-        let (tail, vb) = SnmpSequence::from_ber(tail)?;
+        let vars = SnmpVar::parse_all(tail)?;
+        Ok(SnmpGetResponse {
+            request_id: request_id.into(),
+            error_status: error_status.into(),
+            error_index: error_index.into(),
+            vars,
+        })
+    }
+}
+
+impl<'a> SnmpVar<'a> {
+    // Parse varbinds sequence, which must end the PDU
+    pub(crate) fn parse_all(i: &'a [u8]) -> SnmpResult<Vec<SnmpVar<'a>>> {
+        let (tail, vb) = SnmpSequence::from_ber(i).within("varbinds")?;
         if !tail.is_empty() {
-            return Err(SnmpError::TrailingData);
+            return Err(SnmpError::TrailingData.at(tail));
         }
         let mut v_tail = vb.0;
         let mut vars: Vec<SnmpVar> = Vec::new();
         while !v_tail.is_empty() {
-            // Parse enclosing sequence
-            let (rest, vs) = SnmpSequence::from_ber(v_tail)?;
-            // Parse oid. May be either absolute or relative
-            let (tail, oid) = match vs.0[0] as Tag {
-                TAG_OBJECT_ID => SnmpOid::from_ber(vs.0)?,
-                TAG_RELATIVE_OID => {
-                    if vars.is_empty() {
-                        // Relative oid must follow absolute one
-                        return Err(SnmpError::UnexpectedTag);
-                    }
-                    // Parse relative oid
-                    let (t, r_oid) = SnmpRelativeOid::from_ber(vs.0)?;
-                    let oid = r_oid.normalize(&vars[vars.len() - 1].oid);
-                    // Apply relative oid
-                    (t, oid)
-                }
-                _ => return Err(SnmpError::UnexpectedTag),
-            };
-            // Parse value
-            let (_, value) = SnmpValue::from_ber(tail)?;
+            let (rest, var) = SnmpVar::parse(v_tail, vars.last())
+                .map_err(|e| e.within(format!("[{}]", vars.len())).within("varbinds"))?;
             // Append an item
-            vars.push(SnmpVar { oid, value });
+            vars.push(var);
             // Shift to the next var
             v_tail = rest;
         }
-        Ok(SnmpGetResponse {
-            request_id: request_id.into(),
-            error_status: error_status.into(),
-            error_index: error_index.into(),
-            vars,
-        })
+        Ok(vars)
+    }
+    // Push varbinds sequence
+    pub(crate) fn push_all(vars: &[SnmpVar], buf: &mut Buffer) -> SnmpResult<()> {
+        // Push all vars in the reversed order
+        let rest = buf.len();
+        for var in vars.iter().rev() {
+            let start = buf.len();
+            // Value
+            var.value.push_ber(buf)?;
+            // OID
+            var.oid.push_ber(buf)?;
+            // Enclosing sequence
+            buf.push_tag_len(0x30, buf.len() - start)?;
+        }
+        // Enclosing sequence for varbinds
+        buf.push_tag_len(0x30, buf.len() - rest)
+    }
+    // Parse varbind, following the `prev` one
+    fn parse(i: &'a [u8], prev: Option<&SnmpVar>) -> SnmpResult<(&'a [u8], SnmpVar<'a>)> {
+        // Parse enclosing sequence
+        let (rest, vs) = SnmpSequence::from_ber(i)?;
+        // Parse oid. May be either absolute or relative
+        let (tail, oid) = match (vs.0.first().map(|&t| t as Tag), prev) {
+            (Some(TAG_OBJECT_ID), _) => SnmpOid::from_ber(vs.0).within("oid")?,
+            // Relative oid must follow absolute one
+            (Some(TAG_RELATIVE_OID), Some(prev)) => {
+                let (t, r_oid) = SnmpRelativeOid::from_ber(vs.0).within("oid")?;
+                // Apply relative oid
+                (t, r_oid.normalize(&prev.oid))
+            }
+            (Some(tag), _) => {
+                return Err(SnmpError::UnexpectedTag
+                    .at(vs.0)
+                    .with_tags(Some(TAG_OBJECT_ID), tag)
+                    .within("oid"));
+            }
+            (None, _) => return Err(SnmpError::Incomplete.at(vs.0).within("oid")),
+        };
+        // Parse value
+        let (_, value) = SnmpValue::from_ber(tail).within("value")?;
+        Ok((rest, SnmpVar { oid, value }))
+    }
+}
+
+impl BerEncoder for SnmpGetResponse<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        SnmpVar::push_all(&self.vars, buf)?;
+        // Error index
+        let error_index: SnmpInt = (self.error_index as i64).into();
+        error_index.push_ber(buf)?;
+        // Error status
+        let error_status: SnmpInt = (self.error_status as i64).into();
+        error_status.push_ber(buf)?;
+        // Request id
+        let r_id: SnmpInt = self.request_id.into();
+        r_id.push_ber(buf)
     }
 }
 
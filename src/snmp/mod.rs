@@ -1,22 +1,24 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP module definition
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use crate::ber::Tag;
 
-const SNMP_V1: u8 = 0;
-const SNMP_V2C: u8 = 1;
-const SNMP_V3: u8 = 3;
+pub(crate) const SNMP_V1: u8 = 0;
+pub(crate) const SNMP_V2C: u8 = 1;
+pub(crate) const SNMP_V3: u8 = 3;
 
 const PDU_GET_REQUEST: Tag = 0;
 const PDU_GETNEXT_REQUEST: Tag = 1;
 const PDU_GET_RESPONSE: Tag = 2;
-// const PDU_SET_REQUEST: Tag = 3;
-// const PDU_TRAP: Tag = 4;
+const PDU_SET_REQUEST: Tag = 3;
+const PDU_TRAP: Tag = 4;
 const PDU_GET_BULK_REQUEST: Tag = 5;
+const PDU_INFORM_REQUEST: Tag = 6;
+const PDU_SNMPV2_TRAP: Tag = 7;
 const PDU_REPORT: Tag = 8;
 
 const ERR_AUTHORIZATION_ERROR: u8 = 16;
@@ -25,7 +27,10 @@ pub mod get;
 pub mod getbulk;
 pub mod getresponse;
 pub mod msg;
+#[cfg(feature = "python")]
 pub mod op;
 pub mod pdu;
 pub mod report;
+pub mod setrequest;
+pub mod trap;
 pub mod value;
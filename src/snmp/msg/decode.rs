@@ -0,0 +1,661 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Generic message decoder
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::v3::{MsgData, ScopedPdu, UsmParameters};
+use super::{SnmpV1Message, SnmpV2cMessage, SnmpV3Message};
+use crate::ber::{BerDecoder, SnmpInt, SnmpOid, SnmpSequence};
+use crate::client::UsmUser;
+use crate::error::{DecodePath, SnmpError, SnmpResult};
+use crate::privacy::SnmpPriv;
+use crate::snmp::getresponse::{SnmpGetResponse, SnmpVar};
+use crate::snmp::pdu::SnmpPdu;
+use crate::snmp::trap::SnmpTrap;
+use crate::snmp::value::OwnedValue;
+use crate::snmp::{SNMP_V1, SNMP_V2C, SNMP_V3};
+use crate::socket::SnmpV3Proto;
+#[cfg(feature = "python")]
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict, PyList},
+};
+use std::net::Ipv4Addr;
+
+/// Protocol version of the message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MsgVersion {
+    V1,
+    V2c,
+    V3,
+}
+
+/// PDU type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PduType {
+    GetRequest,
+    GetNextRequest,
+    GetResponse,
+    SetRequest,
+    Trap,
+    GetBulkRequest,
+    InformRequest,
+    SnmpV2Trap,
+    Report,
+}
+
+/// SNMPv3 global header
+#[derive(Debug, Clone, PartialEq)]
+pub struct MsgHeader {
+    pub msg_id: i64,
+    pub max_size: i64,
+    pub flag_auth: bool,
+    pub flag_priv: bool,
+    pub flag_report: bool,
+}
+
+/// USM security parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsmInfo {
+    pub engine_id: Vec<u8>,
+    pub engine_boots: i64,
+    pub engine_time: i64,
+    pub user_name: Vec<u8>,
+    pub auth_params: Vec<u8>,
+    pub privacy_params: Vec<u8>,
+}
+
+/// SNMPv1 Trap-PDU fields, RFC-1157 pp. 4.1.6
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrapInfo {
+    pub enterprise: String,
+    pub agent_addr: Ipv4Addr,
+    pub generic_trap: i64,
+    pub specific_trap: i64,
+    pub time_stamp: u32,
+}
+
+/// Decoded PDU.
+///
+/// GETBULK keeps non-repeaters and max-repetitions in place of
+/// error status and error index, RFC-3416 pp. 3.
+/// Request varbinds have `Null` values.
+/// SNMPv1 Trap-PDU has zero request id, error status and error index,
+/// its own fields are kept in `trap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedPdu {
+    pub pdu_type: PduType,
+    pub request_id: i64,
+    pub error_status: i64,
+    pub error_index: i64,
+    pub varbinds: Vec<(String, OwnedValue)>,
+    /// SNMPv1 Trap-PDU only
+    pub trap: Option<TrapInfo>,
+}
+
+/// Message, detached from the buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedMessage {
+    pub version: MsgVersion,
+    /// SNMPv1 and SNMPv2c only
+    pub community: Option<Vec<u8>>,
+    /// SNMPv3 only
+    pub header: Option<MsgHeader>,
+    /// SNMPv3 only
+    pub usm: Option<UsmInfo>,
+    /// SNMPv3 only, unknown for encrypted PDU without keys
+    pub context_engine_id: Option<Vec<u8>>,
+    /// Scoped PDU is encrypted
+    pub encrypted: bool,
+    /// None for encrypted PDU without keys
+    pub pdu: Option<DecodedPdu>,
+}
+
+/// Decode message of any version.
+///
+/// Encrypted SNMPv3 PDU is decrypted when `user` is given,
+/// keys are localized with the message's engine id.
+pub fn decode_message(data: &[u8], user: Option<&UsmUser>) -> SnmpResult<DecodedMessage> {
+    decode_with_keys(data, user.map(|u| (u.get_auth(), u.get_privacy())))
+}
+
+// (auth, privacy) algorithms and keys
+pub(super) type Keys<'a> = ((u8, &'a [u8]), (u8, &'a [u8]));
+
+fn decode_with_keys(data: &[u8], keys: Option<Keys>) -> SnmpResult<DecodedMessage> {
+    // Peek version
+    let (_, envelope) = SnmpSequence::from_ber(data).map_err(|e| peek_error(e, data))?;
+    let (_, version) = SnmpInt::from_ber(envelope.0)
+        .within("version")
+        .map_err(|e| peek_error(e, data))?;
+    match u8::from(version) {
+        SNMP_V1 => {
+            let msg = SnmpV1Message::try_from(data)?;
+            Ok(DecodedMessage::community(
+                MsgVersion::V1,
+                msg.community,
+                &msg.pdu,
+            )?)
+        }
+        SNMP_V2C => {
+            let msg = SnmpV2cMessage::try_from(data)?;
+            Ok(DecodedMessage::community(
+                MsgVersion::V2c,
+                msg.community,
+                &msg.pdu,
+            )?)
+        }
+        SNMP_V3 => decode_v3(SnmpV3Message::try_from(data)?, keys),
+        x => Err(SnmpError::InvalidVersion(x)),
+    }
+}
+
+// Error of the version peeking
+fn peek_error(e: impl Into<SnmpError>, data: &[u8]) -> SnmpError {
+    e.into().within("msg").locate(data)
+}
+
+fn decode_v3(msg: SnmpV3Message, keys: Option<Keys>) -> SnmpResult<DecodedMessage> {
+    let mut r = DecodedMessage {
+        version: MsgVersion::V3,
+        community: None,
+        header: Some(MsgHeader {
+            msg_id: msg.msg_id,
+            max_size: msg.max_size,
+            flag_auth: msg.flag_auth,
+            flag_priv: msg.flag_priv,
+            flag_report: msg.flag_report,
+        }),
+        usm: Some((&msg.usm).into()),
+        context_engine_id: None,
+        encrypted: matches!(msg.data, MsgData::Encrypted(_)),
+        pdu: None,
+    };
+    match (msg.data, keys) {
+        (MsgData::Plaintext(scoped), _) => r.set_scoped(&scoped)?,
+        (MsgData::Encrypted(data), Some((auth, privacy))) => {
+            let (_, mut priv_key) = SnmpV3Proto::localize_keys(msg.usm.engine_id, auth, privacy)?;
+            if !priv_key.has_priv() {
+                return Err(SnmpError::DecryptionFailed);
+            }
+            let scoped = priv_key
+                .decrypt(data, &msg.usm)
+                .map_err(|_| SnmpError::DecryptionFailed)?;
+            r.set_scoped(&scoped)?;
+        }
+        (MsgData::Encrypted(_), None) => {}
+    }
+    Ok(r)
+}
+
+impl DecodedMessage {
+    fn community(version: MsgVersion, community: &[u8], pdu: &SnmpPdu) -> SnmpResult<Self> {
+        Ok(Self {
+            version,
+            community: Some(community.to_vec()),
+            header: None,
+            usm: None,
+            context_engine_id: None,
+            encrypted: false,
+            pdu: Some(pdu.try_into()?),
+        })
+    }
+
+    fn set_scoped(&mut self, scoped: &ScopedPdu) -> SnmpResult<()> {
+        self.context_engine_id = Some(scoped.engine_id.to_vec());
+        self.pdu = Some((&scoped.pdu).try_into()?);
+        Ok(())
+    }
+}
+
+impl From<&UsmParameters<'_>> for UsmInfo {
+    fn from(value: &UsmParameters<'_>) -> Self {
+        Self {
+            engine_id: value.engine_id.to_vec(),
+            engine_boots: value.engine_boots,
+            engine_time: value.engine_time,
+            user_name: value.user_name.to_vec(),
+            auth_params: value.auth_params.to_vec(),
+            privacy_params: value.privacy_params.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<&SnmpPdu<'_>> for DecodedPdu {
+    type Error = SnmpError;
+
+    fn try_from(value: &SnmpPdu<'_>) -> SnmpResult<Self> {
+        match value {
+            SnmpPdu::GetRequest(x) => {
+                DecodedPdu::request(PduType::GetRequest, x.request_id, (0, 0), &x.vars)
+            }
+            SnmpPdu::GetNextRequest(x) => {
+                DecodedPdu::request(PduType::GetNextRequest, x.request_id, (0, 0), &x.vars)
+            }
+            SnmpPdu::GetBulkRequest(x) => DecodedPdu::request(
+                PduType::GetBulkRequest,
+                x.request_id,
+                (x.non_repeaters, x.max_repetitions),
+                &x.vars,
+            ),
+            SnmpPdu::GetResponse(x) => DecodedPdu::response(PduType::GetResponse, x),
+            SnmpPdu::SetRequest(x) => DecodedPdu::response(PduType::SetRequest, x),
+            SnmpPdu::Trap(x) => DecodedPdu::trap(x),
+            SnmpPdu::InformRequest(x) => DecodedPdu::response(PduType::InformRequest, x),
+            SnmpPdu::SnmpV2Trap(x) => DecodedPdu::response(PduType::SnmpV2Trap, x),
+            SnmpPdu::Report(x) => DecodedPdu::response(PduType::Report, x),
+        }
+    }
+}
+
+impl DecodedPdu {
+    fn request(
+        pdu_type: PduType,
+        request_id: i64,
+        (error_status, error_index): (i64, i64),
+        vars: &[SnmpOid],
+    ) -> SnmpResult<Self> {
+        Ok(Self {
+            pdu_type,
+            request_id,
+            error_status,
+            error_index,
+            varbinds: vars
+                .iter()
+                .map(|oid| Ok((String::try_from(oid)?, OwnedValue::Null)))
+                .collect::<SnmpResult<_>>()?,
+            trap: None,
+        })
+    }
+
+    fn response(pdu_type: PduType, resp: &SnmpGetResponse) -> SnmpResult<Self> {
+        Ok(Self {
+            pdu_type,
+            request_id: resp.request_id,
+            error_status: resp.error_status.into(),
+            error_index: resp.error_index.into(),
+            varbinds: Self::varbinds(&resp.vars)?,
+            trap: None,
+        })
+    }
+
+    fn trap(trap: &SnmpTrap) -> SnmpResult<Self> {
+        Ok(Self {
+            pdu_type: PduType::Trap,
+            request_id: 0,
+            error_status: 0,
+            error_index: 0,
+            varbinds: Self::varbinds(&trap.vars)?,
+            trap: Some(TrapInfo {
+                enterprise: String::try_from(&trap.enterprise)?,
+                agent_addr: (&trap.agent_addr).into(),
+                generic_trap: trap.generic_trap,
+                specific_trap: trap.specific_trap,
+                time_stamp: trap.time_stamp.0,
+            }),
+        })
+    }
+
+    fn varbinds(vars: &[SnmpVar]) -> SnmpResult<Vec<(String, OwnedValue)>> {
+        vars.iter()
+            .map(|var| Ok((String::try_from(&var.oid)?, (&var.value).try_into()?)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+impl PduType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PduType::GetRequest => "GetRequest",
+            PduType::GetNextRequest => "GetNextRequest",
+            PduType::GetResponse => "GetResponse",
+            PduType::SetRequest => "SetRequest",
+            PduType::Trap => "Trap",
+            PduType::GetBulkRequest => "GetBulkRequest",
+            PduType::InformRequest => "InformRequest",
+            PduType::SnmpV2Trap => "SnmpV2Trap",
+            PduType::Report => "Report",
+        }
+    }
+
+    pub(super) fn from_name(name: &str) -> Option<PduType> {
+        Some(match name {
+            "GetRequest" => PduType::GetRequest,
+            "GetNextRequest" => PduType::GetNextRequest,
+            "GetResponse" => PduType::GetResponse,
+            "SetRequest" => PduType::SetRequest,
+            "Trap" => PduType::Trap,
+            "GetBulkRequest" => PduType::GetBulkRequest,
+            "InformRequest" => PduType::InformRequest,
+            "SnmpV2Trap" => PduType::SnmpV2Trap,
+            "Report" => PduType::Report,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl DecodedPdu {
+    /// Dict of `type`, `request_id`, `error_status`, `error_index` and `varbinds`,
+    /// along with `enterprise`, `agent_addr`, `generic_trap`, `specific_trap`
+    /// and `time_stamp` for SNMPv1 Trap-PDU.
+    pub(crate) fn to_python<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let d = PyDict::new(py);
+        d.set_item("type", self.pdu_type.as_str())?;
+        d.set_item("request_id", self.request_id)?;
+        d.set_item("error_status", self.error_status)?;
+        d.set_item("error_index", self.error_index)?;
+        let vars = PyList::empty(py);
+        for (oid, value) in self.varbinds.iter() {
+            vars.append((oid, value))?;
+        }
+        d.set_item("varbinds", vars)?;
+        if let Some(trap) = &self.trap {
+            d.set_item("enterprise", &trap.enterprise)?;
+            d.set_item("agent_addr", trap.agent_addr.to_string())?;
+            d.set_item("generic_trap", trap.generic_trap)?;
+            d.set_item("specific_trap", trap.specific_trap)?;
+            d.set_item("time_stamp", trap.time_stamp)?;
+        }
+        Ok(d)
+    }
+}
+
+/// Decode message of any version into dict.
+/// Encrypted SNMPv3 PDU is decrypted when `priv_alg` is set.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "decode_message")]
+#[pyo3(signature = (data, auth_alg=0, auth_key=b"".as_slice(), priv_alg=0, priv_key=b"".as_slice()))]
+pub fn py_decode_message<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    auth_alg: u8,
+    auth_key: &[u8],
+    priv_alg: u8,
+    priv_key: &[u8],
+) -> PyResult<Bound<'py, PyDict>> {
+    let keys = (priv_alg != 0).then_some(((auth_alg, auth_key), (priv_alg, priv_key)));
+    let msg = decode_with_keys(data, keys)?;
+    let r = PyDict::new(py);
+    r.set_item(
+        "version",
+        match msg.version {
+            MsgVersion::V1 => SNMP_V1,
+            MsgVersion::V2c => SNMP_V2C,
+            MsgVersion::V3 => SNMP_V3,
+        },
+    )?;
+    if let Some(community) = &msg.community {
+        r.set_item("community", PyBytes::new(py, community))?;
+    }
+    if let Some(header) = &msg.header {
+        r.set_item("msg_id", header.msg_id)?;
+        r.set_item("max_size", header.max_size)?;
+        r.set_item("flag_auth", header.flag_auth)?;
+        r.set_item("flag_priv", header.flag_priv)?;
+        r.set_item("flag_report", header.flag_report)?;
+    }
+    if let Some(usm) = &msg.usm {
+        let d = PyDict::new(py);
+        d.set_item("engine_id", PyBytes::new(py, &usm.engine_id))?;
+        d.set_item("engine_boots", usm.engine_boots)?;
+        d.set_item("engine_time", usm.engine_time)?;
+        d.set_item("user_name", PyBytes::new(py, &usm.user_name))?;
+        d.set_item("auth_params", PyBytes::new(py, &usm.auth_params))?;
+        d.set_item("privacy_params", PyBytes::new(py, &usm.privacy_params))?;
+        r.set_item("usm", d)?;
+    }
+    if let Some(engine_id) = &msg.context_engine_id {
+        r.set_item("context_engine_id", PyBytes::new(py, engine_id))?;
+    }
+    r.set_item("encrypted", msg.encrypted)?;
+    match &msg.pdu {
+        Some(pdu) => r.set_item("pdu", pdu.to_python(py)?)?,
+        None => r.set_item("pdu", py.None())?,
+    }
+    Ok(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buf::Buffer;
+    use crate::client::{Auth, KeyType, Privacy};
+    use crate::snmp::get::SnmpGet;
+    use crate::socket::Proto;
+
+    const ENGINE_ID: &[u8] = b"\x80\x00\x1f\x88\x04test";
+
+    #[test]
+    fn test_v2c_get() -> SnmpResult<()> {
+        let data = [
+            0x30, 0x26, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa0,
+            0x19, 0x02, 0x04, 0x12, 0x34, 0x56, 0x78, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30,
+            0x0b, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x05, 0x00,
+        ];
+        let msg = decode_message(&data, None)?;
+        assert_eq!(msg.version, MsgVersion::V2c);
+        assert_eq!(msg.community.as_deref(), Some(b"public".as_slice()));
+        assert!(msg.header.is_none());
+        assert_eq!(
+            msg.pdu,
+            Some(DecodedPdu {
+                pdu_type: PduType::GetRequest,
+                request_id: 0x12345678,
+                error_status: 0,
+                error_index: 0,
+                varbinds: vec![("1.3.6.1.2.1".into(), OwnedValue::Null)],
+                trap: None,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_v1_response() -> SnmpResult<()> {
+        let data = [
+            0x30, 0x29, 0x02, 0x01, 0x00, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa2,
+            0x1c, 0x02, 0x01, 0x05, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x11, 0x30, 0x0f,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00, 0x43, 0x03, 0x01, 0x02,
+            0x03,
+        ];
+        let msg = decode_message(&data, None)?;
+        assert_eq!(msg.version, MsgVersion::V1);
+        let pdu = msg.pdu.unwrap();
+        assert_eq!(pdu.pdu_type, PduType::GetResponse);
+        assert_eq!(pdu.request_id, 5);
+        assert_eq!(
+            pdu.varbinds,
+            vec![("1.3.6.1.2.1.1.3.0".into(), OwnedValue::TimeTicks(0x010203))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_v1_trap() -> SnmpResult<()> {
+        let data = [
+            0x30, 0x36, 0x02, 0x01, 0x00, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa4,
+            0x29, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x8f, 0x65, 0x01, 0x40, 0x04, 0xc0,
+            0xa8, 0x00, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x00, 0x43, 0x02, 0x30, 0x39, 0x30,
+            0x0d, 0x30, 0x0b, 0x06, 0x06, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x02,
+        ];
+        let msg = decode_message(&data, None)?;
+        assert_eq!(msg.version, MsgVersion::V1);
+        assert_eq!(
+            msg.pdu,
+            Some(DecodedPdu {
+                pdu_type: PduType::Trap,
+                request_id: 0,
+                error_status: 0,
+                error_index: 0,
+                varbinds: vec![("1.3.6.1.2.1.2".into(), OwnedValue::Int(2))],
+                trap: Some(TrapInfo {
+                    enterprise: "1.3.6.1.4.1.2021.1".into(),
+                    agent_addr: Ipv4Addr::new(192, 168, 0, 1),
+                    generic_trap: 2,
+                    specific_trap: 0,
+                    time_stamp: 12345,
+                }),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_v2c_set() -> SnmpResult<()> {
+        let data = [
+            0x30, 0x28, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa3,
+            0x1b, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x10, 0x30, 0x0e,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x05, 0x00, 0x04, 0x02, 0x73, 0x77,
+        ];
+        let msg = decode_message(&data, None)?;
+        let pdu = msg.pdu.unwrap();
+        assert_eq!(pdu.pdu_type, PduType::SetRequest);
+        assert_eq!(pdu.request_id, 1);
+        assert_eq!(
+            pdu.varbinds,
+            vec![(
+                "1.3.6.1.2.1.1.5.0".into(),
+                OwnedValue::OctetString(b"sw".to_vec())
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_version() {
+        let data = [0x30, 0x03, 0x02, 0x01, 0x02];
+        assert!(matches!(
+            decode_message(&data, None),
+            Err(SnmpError::InvalidVersion(2))
+        ));
+    }
+
+    #[test]
+    fn test_malformed() {
+        for data in [
+            b"".as_slice(),
+            b"\x30\x03\x02\x01".as_slice(),
+            b"\x30\x06\x02\x01\x01\x04\x01".as_slice(),
+            b"\x04\x01x".as_slice(),
+        ] {
+            assert!(decode_message(data, None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_error_context() {
+        // GetResponse, second varbind value has unsupported tag 0x0a
+        let data = [
+            0x30, 0x37, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa2,
+            0x2a, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x1f, 0x30, 0x0d,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00, 0x43, 0x01, 0x64, 0x30,
+            0x0e, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x05, 0x00, 0x0a, 0x02, 0x73,
+            0x77,
+        ];
+        let Err(SnmpError::Decode(e)) = decode_message(&data, None) else {
+            panic!("decode error expected");
+        };
+        assert!(matches!(e.error, SnmpError::UnsupportedTag(_)));
+        assert_eq!(e.path(), "msg.pdu.varbinds[1].value");
+        assert_eq!(e.offset, Some(53));
+        assert_eq!(e.expected, None);
+        assert_eq!(e.actual, Some(0x0a));
+        assert_eq!(
+            e.to_string(),
+            "Unsupported tag: Universal primitive at msg.pdu.varbinds[1].value (offset 53): tag 10"
+        );
+    }
+
+    #[test]
+    fn test_error_unexpected_tag() {
+        // Community encoded as INTEGER
+        let data = [
+            0x30, 0x0b, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0xa0, 0x03, 0x02, 0x01, 0x01,
+        ];
+        let err = decode_message(&data, None).unwrap_err();
+        assert!(err.is_decode());
+        assert!(matches!(err.kind(), SnmpError::UnexpectedTag));
+        let SnmpError::Decode(e) = err else {
+            panic!("decode error expected");
+        };
+        assert_eq!(e.path(), "msg.community");
+        assert_eq!(e.offset, Some(5));
+        assert_eq!(e.expected, Some(0x04));
+        assert_eq!(e.actual, Some(0x02));
+    }
+
+    // Encode SNMPv3 GET with user's keys
+    fn v3_get(user: &UsmUser) -> SnmpResult<Buffer> {
+        let mut proto = Proto::V3(Box::new(SnmpV3Proto::new(
+            ENGINE_ID.to_vec(),
+            user.name.clone(),
+            user.get_auth(),
+            user.get_privacy(),
+            1500,
+        )?));
+        let mut buf = Buffer::default();
+        proto.push_pdu(
+            SnmpPdu::GetRequest(SnmpGet {
+                request_id: 7,
+                vars: vec![SnmpOid::try_from("1.3.6.1.2.1.1.5.0")?],
+            }),
+            &mut buf,
+        )?;
+        Ok(buf)
+    }
+
+    #[test]
+    fn test_v3_plaintext() -> SnmpResult<()> {
+        let user =
+            UsmUser::new("user1").with_auth(Auth::Sha1(KeyType::Password, b"secret12".to_vec()));
+        let buf = v3_get(&user)?;
+        let msg = decode_message(buf.data(), None)?;
+        assert_eq!(msg.version, MsgVersion::V3);
+        assert!(!msg.encrypted);
+        let header = msg.header.unwrap();
+        assert!(header.flag_auth);
+        assert!(!header.flag_priv);
+        assert_eq!(header.max_size, 1500);
+        let usm = msg.usm.unwrap();
+        assert_eq!(usm.engine_id, ENGINE_ID);
+        assert_eq!(usm.user_name, b"user1");
+        assert_eq!(usm.auth_params.len(), 12);
+        assert_eq!(msg.context_engine_id.as_deref(), Some(ENGINE_ID));
+        assert_eq!(msg.pdu.unwrap().request_id, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v3_encrypted() -> SnmpResult<()> {
+        let user = UsmUser::new("user1")
+            .with_auth(Auth::Sha1(KeyType::Password, b"secret12".to_vec()))
+            .with_privacy(Privacy::Aes128(KeyType::Password, b"private1".to_vec()));
+        let buf = v3_get(&user)?;
+        // No keys
+        let msg = decode_message(buf.data(), None)?;
+        assert!(msg.encrypted);
+        assert!(msg.pdu.is_none());
+        assert!(msg.context_engine_id.is_none());
+        // Decrypted
+        let msg = decode_message(buf.data(), Some(&user))?;
+        assert!(msg.encrypted);
+        let pdu = msg.pdu.unwrap();
+        assert_eq!(pdu.pdu_type, PduType::GetRequest);
+        assert_eq!(pdu.request_id, 7);
+        assert_eq!(pdu.varbinds[0].0, "1.3.6.1.2.1.1.5.0");
+        // Wrong key
+        let wrong = UsmUser::new("user1")
+            .with_auth(Auth::Sha1(KeyType::Password, b"secret12".to_vec()))
+            .with_privacy(Privacy::Aes128(KeyType::Password, b"private2".to_vec()));
+        assert!(matches!(
+            decode_message(buf.data(), Some(&wrong)),
+            Err(SnmpError::DecryptionFailed)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,524 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Generic message encoder
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::decode::{DecodedMessage, DecodedPdu, Keys, MsgVersion, PduType};
+#[cfg(feature = "python")]
+use super::decode::{MsgHeader, TrapInfo, UsmInfo};
+#[cfg(feature = "python")]
+use super::v3::DEFAULT_MAX_SIZE;
+use super::v3::{MsgData, ScopedPdu, UsmParameters};
+use super::{SnmpV1Message, SnmpV2cMessage, SnmpV3Message};
+use crate::auth::SnmpAuth;
+use crate::ber::{BerEncoder, SnmpOid, SnmpTimeTicks};
+use crate::buf::Buffer;
+use crate::client::UsmUser;
+use crate::error::{SnmpError, SnmpResult};
+use crate::privacy::SnmpPriv;
+use crate::snmp::get::SnmpGet;
+use crate::snmp::getbulk::SnmpGetBulk;
+use crate::snmp::getresponse::{SnmpGetResponse, SnmpVar};
+use crate::snmp::pdu::SnmpPdu;
+use crate::snmp::trap::SnmpTrap;
+#[cfg(feature = "python")]
+use crate::snmp::value::OwnedValue;
+use crate::snmp::value::SnmpValue;
+#[cfg(feature = "python")]
+use crate::snmp::{SNMP_V1, SNMP_V2C, SNMP_V3};
+use crate::socket::SnmpV3Proto;
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+/// Encode message of any version, the reverse of `decode_message`.
+///
+/// SNMPv3 scoped PDU is encrypted and the message is signed
+/// when `user` is given, keys are localized with the USM engine id.
+/// Otherwise USM auth and privacy parameters are encoded as is.
+/// Header flags are never adjusted, so inconsistent messages
+/// may be crafted deliberately. `encrypted` field is ignored.
+pub fn encode_message(msg: &DecodedMessage, user: Option<&UsmUser>) -> SnmpResult<Vec<u8>> {
+    encode_with_keys(msg, user.map(|u| (u.get_auth(), u.get_privacy())))
+}
+
+fn encode_with_keys(msg: &DecodedMessage, keys: Option<Keys>) -> SnmpResult<Vec<u8>> {
+    let pdu = SnmpPdu::try_from(msg.pdu.as_ref().ok_or(SnmpError::InvalidPdu)?)?;
+    let mut buf = Buffer::default();
+    match msg.version {
+        MsgVersion::V1 => SnmpV1Message {
+            community: msg.community.as_deref().ok_or(SnmpError::InvalidData)?,
+            pdu,
+        }
+        .push_ber(&mut buf)?,
+        MsgVersion::V2c => SnmpV2cMessage {
+            community: msg.community.as_deref().ok_or(SnmpError::InvalidData)?,
+            pdu,
+        }
+        .push_ber(&mut buf)?,
+        MsgVersion::V3 => encode_v3(msg, pdu, keys, &mut buf)?,
+    }
+    Ok(buf.data().to_vec())
+}
+
+fn encode_v3(
+    msg: &DecodedMessage,
+    pdu: SnmpPdu,
+    keys: Option<Keys>,
+    buf: &mut Buffer,
+) -> SnmpResult<()> {
+    let (Some(header), Some(usm)) = (&msg.header, &msg.usm) else {
+        return Err(SnmpError::InvalidData);
+    };
+    let (auth, privacy) = keys.unwrap_or(((0, &[]), (0, &[])));
+    let (auth_key, mut priv_key) = SnmpV3Proto::localize_keys(&usm.engine_id, auth, privacy)?;
+    let scoped_pdu = ScopedPdu {
+        engine_id: msg.context_engine_id.as_deref().unwrap_or(&usm.engine_id),
+        pdu,
+    };
+    let (privacy_params, data) = if priv_key.has_priv() {
+        let (enc_data, privacy_params) =
+            priv_key.encrypt(&scoped_pdu, usm.engine_boots as u32, usm.engine_time as u32)?;
+        (privacy_params, MsgData::Encrypted(enc_data))
+    } else {
+        (
+            usm.privacy_params.as_slice(),
+            MsgData::Plaintext(scoped_pdu),
+        )
+    };
+    let msg = SnmpV3Message {
+        msg_id: header.msg_id,
+        max_size: header.max_size,
+        flag_auth: header.flag_auth,
+        flag_priv: header.flag_priv,
+        flag_report: header.flag_report,
+        usm: UsmParameters {
+            engine_id: &usm.engine_id,
+            engine_boots: usm.engine_boots,
+            engine_time: usm.engine_time,
+            user_name: &usm.user_name,
+            auth_params: if auth_key.has_auth() {
+                auth_key.placeholder()
+            } else {
+                &usm.auth_params
+            },
+            privacy_params,
+        },
+        data,
+    };
+    msg.push_ber(buf)?;
+    if auth_key.has_auth() {
+        let offset = buf.get_bookmark();
+        auth_key.sign(buf.data_mut(), offset)?;
+    }
+    Ok(())
+}
+
+impl<'a> TryFrom<&'a DecodedPdu> for SnmpPdu<'a> {
+    type Error = SnmpError;
+
+    fn try_from(value: &'a DecodedPdu) -> SnmpResult<Self> {
+        Ok(match value.pdu_type {
+            PduType::GetRequest => SnmpPdu::GetRequest(value.as_get()?),
+            PduType::GetNextRequest => SnmpPdu::GetNextRequest(value.as_get()?),
+            PduType::GetBulkRequest => SnmpPdu::GetBulkRequest(SnmpGetBulk {
+                request_id: value.request_id,
+                non_repeaters: value.error_status,
+                max_repetitions: value.error_index,
+                vars: value.oids()?,
+            }),
+            PduType::GetResponse => SnmpPdu::GetResponse(value.as_response()?),
+            PduType::SetRequest => SnmpPdu::SetRequest(value.as_response()?),
+            PduType::Trap => SnmpPdu::Trap(value.as_trap()?),
+            PduType::InformRequest => SnmpPdu::InformRequest(value.as_response()?),
+            PduType::SnmpV2Trap => SnmpPdu::SnmpV2Trap(value.as_response()?),
+            PduType::Report => SnmpPdu::Report(value.as_response()?),
+        })
+    }
+}
+
+impl DecodedPdu {
+    // Request values are ignored
+    fn oids(&self) -> SnmpResult<Vec<SnmpOid<'static>>> {
+        self.varbinds
+            .iter()
+            .map(|(oid, _)| SnmpOid::try_from(oid.as_str()))
+            .collect()
+    }
+
+    fn as_get(&self) -> SnmpResult<SnmpGet<'static>> {
+        Ok(SnmpGet {
+            request_id: self.request_id,
+            vars: self.oids()?,
+        })
+    }
+
+    fn as_trap(&self) -> SnmpResult<SnmpTrap<'_>> {
+        let trap = self.trap.as_ref().ok_or(SnmpError::InvalidPdu)?;
+        Ok(SnmpTrap {
+            enterprise: SnmpOid::try_from(trap.enterprise.as_str())?,
+            agent_addr: trap.agent_addr.into(),
+            generic_trap: trap.generic_trap,
+            specific_trap: trap.specific_trap,
+            time_stamp: SnmpTimeTicks(trap.time_stamp),
+            vars: self.vars()?,
+        })
+    }
+
+    fn as_response(&self) -> SnmpResult<SnmpGetResponse<'_>> {
+        Ok(SnmpGetResponse {
+            request_id: self.request_id,
+            error_status: self
+                .error_status
+                .try_into()
+                .map_err(|_| SnmpError::InvalidPdu)?,
+            error_index: self
+                .error_index
+                .try_into()
+                .map_err(|_| SnmpError::InvalidPdu)?,
+            vars: self.vars()?,
+        })
+    }
+
+    fn vars(&self) -> SnmpResult<Vec<SnmpVar<'_>>> {
+        self.varbinds
+            .iter()
+            .map(|(oid, value)| {
+                Ok(SnmpVar {
+                    oid: SnmpOid::try_from(oid.as_str())?,
+                    value: SnmpValue::try_from(value)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Encode message of any version.
+/// SNMPv3 message is signed and encrypted according to the keys.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "encode_message")]
+#[pyo3(signature = (
+    version,
+    pdu_type,
+    request_id,
+    varbinds,
+    *,
+    error_status = 0,
+    error_index = 0,
+    community = b"public".as_slice(),
+    header = None,
+    usm = None,
+    context_engine_id = None,
+    auth_alg = 0,
+    auth_key = b"".as_slice(),
+    priv_alg = 0,
+    priv_key = b"".as_slice(),
+    trap = None,
+))]
+#[allow(clippy::too_many_arguments)] // Internal interface
+pub fn py_encode_message<'py>(
+    py: Python<'py>,
+    version: u8,
+    pdu_type: &str,
+    request_id: i64,
+    varbinds: Vec<(String, Bound<'py, PyAny>)>,
+    error_status: i64,
+    error_index: i64,
+    community: &[u8],
+    header: Option<(i64, i64, bool, bool, bool)>,
+    usm: Option<(Vec<u8>, i64, i64, Vec<u8>)>,
+    context_engine_id: Option<&[u8]>,
+    auth_alg: u8,
+    auth_key: &[u8],
+    priv_alg: u8,
+    priv_key: &[u8],
+    trap: Option<(String, String, i64, i64, u32)>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    // Header flags follow the keys by default
+    let (msg_id, max_size, flag_auth, flag_priv, flag_report) = header.unwrap_or((
+        request_id,
+        DEFAULT_MAX_SIZE,
+        auth_alg != 0,
+        priv_alg != 0,
+        false,
+    ));
+    let (engine_id, engine_boots, engine_time, user_name) = usm.unwrap_or_default();
+    let version = match version {
+        SNMP_V1 => MsgVersion::V1,
+        SNMP_V2C => MsgVersion::V2c,
+        SNMP_V3 => MsgVersion::V3,
+        x => return Err(SnmpError::InvalidVersion(x).into()),
+    };
+    let pdu = DecodedPdu {
+        pdu_type: PduType::from_name(pdu_type)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown pdu type: {}", pdu_type)))?,
+        request_id,
+        error_status,
+        error_index,
+        varbinds: varbinds
+            .iter()
+            .map(|(oid, value)| Ok((oid.clone(), OwnedValue::from_python(value)?)))
+            .collect::<PyResult<_>>()?,
+        trap: match trap {
+            Some((enterprise, agent_addr, generic_trap, specific_trap, time_stamp)) => {
+                Some(TrapInfo {
+                    enterprise,
+                    agent_addr: agent_addr
+                        .parse()
+                        .map_err(|_| PyValueError::new_err("invalid agent address"))?,
+                    generic_trap,
+                    specific_trap,
+                    time_stamp,
+                })
+            }
+            None => None,
+        },
+    };
+    let is_v3 = version == MsgVersion::V3;
+    let msg = DecodedMessage {
+        version,
+        community: (!is_v3).then(|| community.to_vec()),
+        header: is_v3.then_some(MsgHeader {
+            msg_id,
+            max_size,
+            flag_auth,
+            flag_priv,
+            flag_report,
+        }),
+        usm: is_v3.then(|| UsmInfo {
+            engine_id,
+            engine_boots,
+            engine_time,
+            user_name,
+            auth_params: Vec::new(),
+            privacy_params: Vec::new(),
+        }),
+        context_engine_id: context_engine_id.map(|x| x.to_vec()),
+        encrypted: false,
+        pdu: Some(pdu),
+    };
+    let data = encode_with_keys(&msg, Some(((auth_alg, auth_key), (priv_alg, priv_key))))?;
+    Ok(PyBytes::new(py, &data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Auth, KeyType, Privacy};
+    use crate::snmp::msg::{MsgHeader, TrapInfo, UsmInfo, decode_message};
+    use crate::snmp::value::OwnedValue;
+
+    const ENGINE_ID: &[u8] = b"\x80\x00\x1f\x88\x04test";
+
+    fn get_pdu(pdu_type: PduType) -> DecodedPdu {
+        DecodedPdu {
+            pdu_type,
+            request_id: 0x12345678,
+            error_status: 0,
+            error_index: 0,
+            varbinds: vec![("1.3.6.1.2.1".into(), OwnedValue::Null)],
+            trap: None,
+        }
+    }
+
+    fn v3_message(user: &UsmUser) -> DecodedMessage {
+        DecodedMessage {
+            version: MsgVersion::V3,
+            community: None,
+            header: Some(MsgHeader {
+                msg_id: 10,
+                max_size: 1500,
+                flag_auth: true,
+                flag_priv: true,
+                flag_report: true,
+            }),
+            usm: Some(UsmInfo {
+                engine_id: ENGINE_ID.to_vec(),
+                engine_boots: 3,
+                engine_time: 1000,
+                user_name: user.name.as_bytes().to_vec(),
+                auth_params: vec![],
+                privacy_params: vec![],
+            }),
+            context_engine_id: Some(ENGINE_ID.to_vec()),
+            encrypted: true,
+            pdu: Some(DecodedPdu {
+                pdu_type: PduType::GetResponse,
+                request_id: 7,
+                error_status: 0,
+                error_index: 0,
+                varbinds: vec![
+                    ("1.3.6.1.2.1.1.3.0".into(), OwnedValue::TimeTicks(100)),
+                    (
+                        "1.3.6.1.2.1.1.5.0".into(),
+                        OwnedValue::OctetString(b"sw".to_vec()),
+                    ),
+                ],
+                trap: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_v2c_get() -> SnmpResult<()> {
+        let msg = DecodedMessage {
+            version: MsgVersion::V2c,
+            community: Some(b"public".to_vec()),
+            header: None,
+            usm: None,
+            context_engine_id: None,
+            encrypted: false,
+            pdu: Some(get_pdu(PduType::GetRequest)),
+        };
+        let expected = [
+            0x30, 0x26, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa0,
+            0x19, 0x02, 0x04, 0x12, 0x34, 0x56, 0x78, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30,
+            0x0b, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x05, 0x00,
+        ];
+        assert_eq!(encode_message(&msg, None)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v1_roundtrip() -> SnmpResult<()> {
+        let msg = DecodedMessage {
+            version: MsgVersion::V1,
+            community: Some(b"private".to_vec()),
+            header: None,
+            usm: None,
+            context_engine_id: None,
+            encrypted: false,
+            pdu: Some(DecodedPdu {
+                pdu_type: PduType::GetResponse,
+                request_id: 5,
+                error_status: 2,
+                error_index: 1,
+                varbinds: vec![("1.3.6.1.2.1.1.3.0".into(), OwnedValue::Null)],
+                trap: None,
+            }),
+        };
+        let data = encode_message(&msg, None)?;
+        assert_eq!(decode_message(&data, None)?, msg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v1_trap_roundtrip() -> SnmpResult<()> {
+        let msg = DecodedMessage {
+            version: MsgVersion::V1,
+            community: Some(b"public".to_vec()),
+            header: None,
+            usm: None,
+            context_engine_id: None,
+            encrypted: false,
+            pdu: Some(DecodedPdu {
+                pdu_type: PduType::Trap,
+                request_id: 0,
+                error_status: 0,
+                error_index: 0,
+                varbinds: vec![("1.3.6.1.2.1.2.2.1.1.2".into(), OwnedValue::Int(2))],
+                trap: Some(TrapInfo {
+                    enterprise: "1.3.6.1.4.1.2021.1".into(),
+                    agent_addr: "192.168.0.1".parse().unwrap(),
+                    generic_trap: 2,
+                    specific_trap: 0,
+                    time_stamp: 12345,
+                }),
+            }),
+        };
+        let data = encode_message(&msg, None)?;
+        assert_eq!(decode_message(&data, None)?, msg);
+        // Trap fields are required
+        let mut msg = msg;
+        if let Some(pdu) = msg.pdu.as_mut() {
+            pdu.trap = None;
+        }
+        assert!(matches!(
+            encode_message(&msg, None),
+            Err(SnmpError::InvalidPdu)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_getbulk() -> SnmpResult<()> {
+        let msg = DecodedMessage {
+            version: MsgVersion::V2c,
+            community: Some(b"public".to_vec()),
+            header: None,
+            usm: None,
+            context_engine_id: None,
+            encrypted: false,
+            pdu: Some(DecodedPdu {
+                error_status: 0,
+                error_index: 20,
+                ..get_pdu(PduType::GetBulkRequest)
+            }),
+        };
+        let data = encode_message(&msg, None)?;
+        assert_eq!(decode_message(&data, None)?, msg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missed_fields() {
+        let mut msg = DecodedMessage {
+            version: MsgVersion::V2c,
+            community: None,
+            header: None,
+            usm: None,
+            context_engine_id: None,
+            encrypted: false,
+            pdu: Some(get_pdu(PduType::GetRequest)),
+        };
+        assert!(matches!(
+            encode_message(&msg, None),
+            Err(SnmpError::InvalidData)
+        ));
+        msg.version = MsgVersion::V3;
+        assert!(matches!(
+            encode_message(&msg, None),
+            Err(SnmpError::InvalidData)
+        ));
+        msg.pdu = None;
+        assert!(matches!(
+            encode_message(&msg, None),
+            Err(SnmpError::InvalidPdu)
+        ));
+    }
+
+    #[test]
+    fn test_v3_plaintext() -> SnmpResult<()> {
+        let user = UsmUser::new("user1");
+        let mut msg = v3_message(&user);
+        msg.header.as_mut().unwrap().flag_auth = false;
+        msg.header.as_mut().unwrap().flag_priv = false;
+        msg.encrypted = false;
+        let data = encode_message(&msg, None)?;
+        assert_eq!(decode_message(&data, None)?, msg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v3_encrypted() -> SnmpResult<()> {
+        let user = UsmUser::new("user1")
+            .with_auth(Auth::Sha1(KeyType::Password, b"secret12".to_vec()))
+            .with_privacy(Privacy::Aes128(KeyType::Password, b"private1".to_vec()));
+        let msg = v3_message(&user);
+        let data = encode_message(&msg, Some(&user))?;
+        let decoded = decode_message(&data, Some(&user))?;
+        assert!(decoded.encrypted);
+        assert_eq!(decoded.header, msg.header);
+        assert_eq!(decoded.pdu, msg.pdu);
+        assert_eq!(decoded.context_engine_id, msg.context_engine_id);
+        let usm = decoded.usm.unwrap();
+        assert_eq!(usm.engine_boots, 3);
+        assert_eq!(usm.engine_time, 1000);
+        assert_eq!(usm.auth_params.len(), 12);
+        assert_eq!(usm.privacy_params.len(), 8);
+        assert!(decode_message(&data, None)?.pdu.is_none());
+        Ok(())
+    }
+}
@@ -1,14 +1,24 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP Messages
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
+mod decode;
+mod encode;
 mod v1;
 mod v2c;
 pub mod v3;
 pub use super::pdu::SnmpPdu;
+#[cfg(feature = "python")]
+pub(crate) use decode::py_decode_message;
+pub use decode::{
+    DecodedMessage, DecodedPdu, MsgHeader, MsgVersion, PduType, TrapInfo, UsmInfo, decode_message,
+};
+pub use encode::encode_message;
+#[cfg(feature = "python")]
+pub(crate) use encode::py_encode_message;
 pub use v1::SnmpV1Message;
 pub use v2c::SnmpV2cMessage;
 pub use v3::SnmpV3Message;
@@ -1,14 +1,14 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP v1 Message
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 use crate::ber::{
     BerDecoder, BerEncoder, SnmpInt, SnmpOctetString, SnmpSequence, TAG_INT, TAG_OCTET_STRING,
 };
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 use crate::snmp::SNMP_V1;
 use crate::snmp::pdu::SnmpPdu;
 
@@ -23,21 +23,29 @@ impl<'a> TryFrom<&'a [u8]> for SnmpV1Message<'a> {
     type Error = SnmpError;
 
     fn try_from(i: &'a [u8]) -> SnmpResult<SnmpV1Message<'a>> {
+        SnmpV1Message::parse(i).map_err(|e| e.within("msg").locate(i))
+    }
+}
+
+impl<'a> SnmpV1Message<'a> {
+    fn parse(i: &'a [u8]) -> SnmpResult<SnmpV1Message<'a>> {
         // Top-level sequence
         let (tail, envelope) = SnmpSequence::from_ber(i)?;
         if !tail.is_empty() {
-            return Err(SnmpError::TrailingData);
+            return Err(SnmpError::TrailingData.at(tail));
         }
         // Version
-        let (tail, v_code) = SnmpInt::from_ber(envelope.0)?;
+        let (tail, v_code) = SnmpInt::from_ber(envelope.0).within("version")?;
         let vc = v_code.into();
         if vc != SNMP_V1 {
-            return Err(SnmpError::InvalidVersion(vc));
+            return Err(SnmpError::InvalidVersion(vc)
+                .at(envelope.0)
+                .within("version"));
         }
         // Parse community
-        let (tail, community) = SnmpOctetString::from_ber(tail)?;
+        let (tail, community) = SnmpOctetString::from_ber(tail).within("community")?;
         // Parse PDU
-        let pdu = SnmpPdu::try_from(tail)?;
+        let pdu = SnmpPdu::try_from(tail).within("pdu")?;
         //
         Ok(SnmpV1Message {
             community: community.0,
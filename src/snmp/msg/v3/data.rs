@@ -1,14 +1,14 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: MsgData
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use super::scoped::ScopedPdu;
 use crate::ber::{BerDecoder, BerEncoder, SnmpOctetString, TAG_OCTET_STRING};
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 
 pub enum MsgData<'a> {
     Plaintext(ScopedPdu<'a>),
@@ -20,15 +20,15 @@ impl<'a> TryFrom<&'a [u8]> for MsgData<'a> {
 
     fn try_from(i: &'a [u8]) -> SnmpResult<MsgData<'a>> {
         if i.is_empty() {
-            return Err(SnmpError::Incomplete);
+            return Err(SnmpError::Incomplete.at(i).within("scoped_pdu"));
         }
         Ok(if i[0] == TAG_OCTET_STRING {
             // Encryped
-            let (_, os) = SnmpOctetString::from_ber(i)?;
+            let (_, os) = SnmpOctetString::from_ber(i).within("encrypted_pdu")?;
             MsgData::Encrypted(os.0)
         } else {
             // Plaintext
-            MsgData::Plaintext(ScopedPdu::try_from(i)?)
+            MsgData::Plaintext(ScopedPdu::try_from(i).within("scoped_pdu")?)
         })
     }
 }
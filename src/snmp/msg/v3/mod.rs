@@ -10,6 +10,6 @@ mod msg;
 mod scoped;
 mod usm;
 pub use data::MsgData;
-pub use msg::SnmpV3Message;
+pub use msg::{DEFAULT_MAX_SIZE, MIN_MAX_SIZE, SnmpV3Message};
 pub use scoped::ScopedPdu;
 pub use usm::UsmParameters;
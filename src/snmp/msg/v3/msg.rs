@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP v3 Message
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 use super::data::MsgData;
@@ -10,11 +10,12 @@ use crate::ber::{
     BerDecoder, BerEncoder, SnmpInt, SnmpOctetString, SnmpSequence, TAG_INT, TAG_OCTET_STRING,
 };
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 use crate::snmp::SNMP_V3;
 
 pub struct SnmpV3Message<'a> {
     pub msg_id: i64,
+    pub max_size: i64,
     //pub context_engine_id: &'a [u8],
     //pub context_engine_name: &'a [u8],
     pub flag_auth: bool,
@@ -25,7 +26,10 @@ pub struct SnmpV3Message<'a> {
 }
 
 const V3_BER: [u8; 3] = [TAG_INT, 1, SNMP_V3];
-const MAX_SIZE: i64 = 2048;
+/// Default msgMaxSize
+pub const DEFAULT_MAX_SIZE: i64 = 2048;
+/// Minimal msgMaxSize, RFC-3412
+pub const MIN_MAX_SIZE: i64 = 484;
 const USM: u8 = 3;
 const USM_MODEL_BER: [u8; 3] = [TAG_INT, 1, USM];
 // Flags
@@ -37,44 +41,39 @@ impl<'a> TryFrom<&'a [u8]> for SnmpV3Message<'a> {
     type Error = SnmpError;
 
     fn try_from(i: &'a [u8]) -> SnmpResult<SnmpV3Message<'a>> {
+        SnmpV3Message::parse(i).map_err(|e| e.within("msg").locate(i))
+    }
+}
+
+impl<'a> SnmpV3Message<'a> {
+    fn parse(i: &'a [u8]) -> SnmpResult<SnmpV3Message<'a>> {
         // Top-level sequence
         let (tail, envelope) = SnmpSequence::from_ber(i)?;
         if !tail.is_empty() {
-            return Err(SnmpError::TrailingData);
+            return Err(SnmpError::TrailingData.at(tail));
         }
         // Version
-        let (tail, v_code) = SnmpInt::from_ber(envelope.0)?;
+        let (tail, v_code) = SnmpInt::from_ber(envelope.0).within("version")?;
         let vc = v_code.into();
         if vc != SNMP_V3 {
-            return Err(SnmpError::InvalidVersion(vc));
+            return Err(SnmpError::InvalidVersion(vc)
+                .at(envelope.0)
+                .within("version"));
         }
         //
         // Parse global header
         //
-        let (sp_tail, envelope) = SnmpSequence::from_ber(tail)?;
-        // msg id
-        let (tail, msg_id_data) = SnmpInt::from_ber(envelope.0)?;
-        // max_size
-        let (tail, _max_size) = SnmpInt::from_ber(tail)?;
-        // flags
-        let (tail, flags_data) = SnmpOctetString::from_ber(tail)?;
-        if flags_data.0.len() != 1 {
-            return Err(SnmpError::InvalidPdu);
-        }
-        let flags = flags_data.0[0];
-        // security model
-        let (_, security_model) = SnmpInt::from_ber(tail)?;
-        let sm: u8 = security_model.into();
-        if sm != USM {
-            return Err(SnmpError::UnknownSecurityModel);
-        }
+        let (sp_tail, envelope) = SnmpSequence::from_ber(tail).within("header")?;
+        let (msg_id_data, max_size, flags) =
+            SnmpV3Message::parse_header(envelope.0).within("header")?;
         //
         // Process security parameters
         //
-        let (tail, security_parameters) = SnmpOctetString::from_ber(sp_tail)?;
-        let usm = UsmParameters::try_from(security_parameters.0)?;
+        let (tail, security_parameters) = SnmpOctetString::from_ber(sp_tail).within("usm")?;
+        let usm = UsmParameters::try_from(security_parameters.0).within("usm")?;
         Ok(SnmpV3Message {
             msg_id: msg_id_data.into(),
+            max_size: max_size.into(),
             flag_auth: (flags & FLAG_AUTH) != 0,
             flag_priv: (flags & FLAG_PRIV) != 0,
             flag_report: (flags & FLAG_REPORT) != 0,
@@ -82,6 +81,28 @@ impl<'a> TryFrom<&'a [u8]> for SnmpV3Message<'a> {
             data: MsgData::try_from(tail)?,
         })
     }
+
+    // Parse global header: msg id, max size and flags
+    fn parse_header(i: &[u8]) -> SnmpResult<(SnmpInt, SnmpInt, u8)> {
+        // msg id
+        let (tail, msg_id) = SnmpInt::from_ber(i).within("msg_id")?;
+        // max_size
+        let (tail, max_size) = SnmpInt::from_ber(tail).within("max_size")?;
+        // flags
+        let (tail, flags_data) = SnmpOctetString::from_ber(tail).within("flags")?;
+        if flags_data.0.len() != 1 {
+            return Err(SnmpError::InvalidPdu.at(flags_data.0).within("flags"));
+        }
+        // security model
+        let (_, security_model) = SnmpInt::from_ber(tail).within("security_model")?;
+        let sm: u8 = security_model.into();
+        if sm != USM {
+            return Err(SnmpError::UnknownSecurityModel
+                .at(tail)
+                .within("security_model"));
+        }
+        Ok((msg_id, max_size, flags_data.0[0]))
+    }
 }
 
 impl BerEncoder for SnmpV3Message<'_> {
@@ -117,7 +138,7 @@ impl BerEncoder for SnmpV3Message<'_> {
         buf.push_u8(flag)?;
         buf.push_tag_len(TAG_OCTET_STRING, 1)?;
         // Push msg max size
-        let ms: SnmpInt = MAX_SIZE.into();
+        let ms: SnmpInt = self.max_size.into();
         ms.push_ber(buf)?;
         // Push msg id
         let msg_id: SnmpInt = self.msg_id.into();
@@ -152,6 +173,7 @@ mod tests {
         let msg = SnmpV3Message::try_from(data.as_ref())?;
         // Analyze global header
         assert_eq!(msg.msg_id, 37320);
+        assert_eq!(msg.max_size, 1500);
         assert!(!msg.flag_auth);
         assert!(!msg.flag_priv);
         // Analyze security parameters
@@ -185,6 +207,7 @@ mod tests {
         let empty: [u8; 0] = [];
         let msg = SnmpV3Message {
             msg_id: 37320,
+            max_size: DEFAULT_MAX_SIZE,
             flag_auth: false,
             flag_priv: false,
             flag_report: false,
@@ -210,6 +233,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_max_size_roundtrip() -> SnmpResult<()> {
+        let empty: [u8; 0] = [];
+        let msg = SnmpV3Message {
+            msg_id: 37320,
+            max_size: 4000,
+            flag_auth: false,
+            flag_priv: false,
+            flag_report: true,
+            usm: UsmParameters {
+                engine_id: &empty,
+                engine_boots: 0,
+                engine_time: 0,
+                user_name: "admin".as_bytes(),
+                auth_params: &empty,
+                privacy_params: &empty,
+            },
+            data: MsgData::Plaintext(ScopedPdu {
+                engine_id: &empty,
+                pdu: SnmpPdu::GetRequest(SnmpGet {
+                    request_id: 37320,
+                    vars: vec![],
+                }),
+            }),
+        };
+        let mut buf = Buffer::default();
+        msg.push_ber(&mut buf)?;
+        let parsed = SnmpV3Message::try_from(buf.data())?;
+        assert_eq!(parsed.max_size, 4000);
+        assert!(parsed.flag_report);
+        Ok(())
+    }
+
     // #[test]
     // fn test_parse_snmp_getresponse_exception() -> SnmpResult<()> {
     //     let data = [
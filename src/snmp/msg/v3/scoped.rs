@@ -1,13 +1,13 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Scoped PDU
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use crate::ber::{BerDecoder, BerEncoder, SnmpOctetString, SnmpSequence, TAG_OCTET_STRING};
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 use crate::snmp::pdu::SnmpPdu;
 
 pub struct ScopedPdu<'a> {
@@ -23,13 +23,14 @@ impl<'a> TryFrom<&'a [u8]> for ScopedPdu<'a> {
     fn try_from(i: &'a [u8]) -> SnmpResult<ScopedPdu<'a>> {
         let (_, envelope) = SnmpSequence::from_ber(i)?;
         // Context engine id
-        let (tail, engine_id) = SnmpOctetString::from_ber(envelope.0)?;
+        let (tail, engine_id) =
+            SnmpOctetString::from_ber(envelope.0).within("context_engine_id")?;
         // Context engine name
-        let (tail, _ctx_engine_name) = SnmpOctetString::from_ber(tail)?;
+        let (tail, _ctx_engine_name) = SnmpOctetString::from_ber(tail).within("context_name")?;
         // Decode PDU and return
         Ok(ScopedPdu {
             engine_id: engine_id.0,
-            pdu: SnmpPdu::try_from(tail)?,
+            pdu: SnmpPdu::try_from(tail).within("pdu")?,
         })
     }
 }
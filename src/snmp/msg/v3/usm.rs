@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP v3 User-based Security Model (USM)
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -9,7 +9,7 @@ use crate::ber::{
     BerDecoder, BerEncoder, SnmpInt, SnmpOctetString, SnmpSequence, TAG_OCTET_STRING,
 };
 use crate::buf::Buffer;
-use crate::error::{SnmpError, SnmpResult};
+use crate::error::{DecodePath, SnmpError, SnmpResult};
 
 pub struct UsmParameters<'a> {
     pub engine_id: &'a [u8],
@@ -27,20 +27,20 @@ impl<'a> TryFrom<&'a [u8]> for UsmParameters<'a> {
         // Top-level sequence
         let (tail, envelope) = SnmpSequence::from_ber(i)?;
         if !tail.is_empty() {
-            return Err(SnmpError::TrailingData);
+            return Err(SnmpError::TrailingData.at(tail));
         }
         // Engine id
-        let (tail, engine_id) = SnmpOctetString::from_ber(envelope.0)?;
+        let (tail, engine_id) = SnmpOctetString::from_ber(envelope.0).within("engine_id")?;
         // Engine boots
-        let (tail, engine_boots) = SnmpInt::from_ber(tail)?;
+        let (tail, engine_boots) = SnmpInt::from_ber(tail).within("engine_boots")?;
         // Engine time
-        let (tail, engine_time) = SnmpInt::from_ber(tail)?;
+        let (tail, engine_time) = SnmpInt::from_ber(tail).within("engine_time")?;
         // User name
-        let (tail, user_name) = SnmpOctetString::from_ber(tail)?;
+        let (tail, user_name) = SnmpOctetString::from_ber(tail).within("user_name")?;
         // Auth parameters
-        let (tail, auth_parameters) = SnmpOctetString::from_ber(tail)?;
+        let (tail, auth_parameters) = SnmpOctetString::from_ber(tail).within("auth_params")?;
         // Privacy parameters
-        let (_, privacy_parameters) = SnmpOctetString::from_ber(tail)?;
+        let (_, privacy_parameters) = SnmpOctetString::from_ber(tail).within("privacy_params")?;
         Ok(UsmParameters {
             engine_id: engine_id.0,
             engine_boots: engine_boots.into(),
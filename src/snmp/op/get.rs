@@ -52,7 +52,7 @@ impl<'a> PyOp<'a, PyBackedStr> for OpGet {
                     _ => Err(SnmpError::InvalidPdu.into()),
                 }
             }
-            SnmpPdu::Report(_) => Err(SnmpError::AuthenticationFailed.into()),
+            SnmpPdu::Report(report) => Err(report.report_error().into()),
             _ => Err(SnmpError::InvalidPdu.into()),
         }
     }
@@ -69,7 +69,7 @@ impl<'a> PyOp<'a, (SnmpOid<'a>, i64)> for OpGetBulk {
                 }
                 Ok(list.as_any().to_owned())
             }
-            SnmpPdu::Report(_) => Err(SnmpError::AuthenticationFailed.into()),
+            SnmpPdu::Report(report) => Err(report.report_error().into()),
             _ => Err(SnmpError::InvalidPdu.into()),
         }
     }
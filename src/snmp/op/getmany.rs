@@ -48,7 +48,7 @@ impl<'a> PyOp<'a, Vec<PyBackedStr>> for OpGetMany {
                 }
                 Ok(dict.as_any().to_owned())
             }
-            SnmpPdu::Report(_) => Err(SnmpError::AuthenticationFailed.into()),
+            SnmpPdu::Report(report) => Err(report.report_error().into()),
             _ => Err(SnmpError::InvalidPdu.into()),
         }
     }
@@ -62,7 +62,7 @@ impl<'a> PyOp<'a, SnmpOid<'a>> for OpGetNext {
                     _ => Err(SnmpError::InvalidPdu.into()),
                 }
             }
-            SnmpPdu::Report(_) => Err(SnmpError::AuthenticationFailed.into()),
+            SnmpPdu::Report(report) => Err(report.report_error().into()),
             _ => Err(SnmpError::InvalidPdu.into()),
         }
     }
@@ -1,7 +1,7 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SNMP PDU
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
@@ -9,8 +9,11 @@ use super::get::SnmpGet;
 use super::getbulk::SnmpGetBulk;
 use super::getresponse::SnmpGetResponse;
 use super::report::SnmpReport;
+use super::setrequest::SnmpSetRequest;
+use super::trap::SnmpTrap;
 use super::{
-    PDU_GET_BULK_REQUEST, PDU_GET_REQUEST, PDU_GET_RESPONSE, PDU_GETNEXT_REQUEST, PDU_REPORT,
+    PDU_GET_BULK_REQUEST, PDU_GET_REQUEST, PDU_GET_RESPONSE, PDU_GETNEXT_REQUEST,
+    PDU_INFORM_REQUEST, PDU_REPORT, PDU_SET_REQUEST, PDU_SNMPV2_TRAP, PDU_TRAP,
 };
 use crate::ber::{BerDecoder, BerEncoder, SnmpOption};
 use crate::buf::Buffer;
@@ -18,11 +21,16 @@ use crate::error::{SnmpError, SnmpResult};
 use crate::reqid::RequestId;
 
 #[allow(clippy::enum_variant_names)]
+#[derive(Clone)]
 pub enum SnmpPdu<'a> {
     GetRequest(SnmpGet<'a>),
     GetNextRequest(SnmpGet<'a>),
     GetResponse(SnmpGetResponse<'a>),
+    SetRequest(SnmpSetRequest<'a>),
+    Trap(SnmpTrap<'a>),
     GetBulkRequest(SnmpGetBulk<'a>),
+    InformRequest(SnmpGetResponse<'a>),
+    SnmpV2Trap(SnmpGetResponse<'a>),
     Report(SnmpReport<'a>),
 }
 
@@ -35,9 +43,13 @@ impl<'a> TryFrom<&'a [u8]> for SnmpPdu<'a> {
             PDU_GET_REQUEST => SnmpPdu::GetRequest(SnmpGet::try_from(opt.value)?),
             PDU_GETNEXT_REQUEST => SnmpPdu::GetNextRequest(SnmpGet::try_from(opt.value)?),
             PDU_GET_RESPONSE => SnmpPdu::GetResponse(SnmpGetResponse::try_from(opt.value)?),
+            PDU_SET_REQUEST => SnmpPdu::SetRequest(SnmpSetRequest::try_from(opt.value)?),
+            PDU_TRAP => SnmpPdu::Trap(SnmpTrap::try_from(opt.value)?),
             PDU_GET_BULK_REQUEST => SnmpPdu::GetBulkRequest(SnmpGetBulk::try_from(opt.value)?),
+            PDU_INFORM_REQUEST => SnmpPdu::InformRequest(SnmpGetResponse::try_from(opt.value)?),
+            PDU_SNMPV2_TRAP => SnmpPdu::SnmpV2Trap(SnmpGetResponse::try_from(opt.value)?),
             PDU_REPORT => SnmpPdu::Report(SnmpReport::try_from(opt.value)?),
-            _ => return Err(SnmpError::UnknownPdu),
+            _ => return Err(SnmpError::UnknownPdu.at(value).with_tags(None, opt.tag)),
         })
     }
 }
@@ -54,11 +66,34 @@ impl BerEncoder for SnmpPdu<'_> {
                 req.push_ber(buf)?;
                 buf.push_tag_len(161, buf.len() - rest) // Context + Constructed + PDU_GETNEXT_REQUEST(1)
             }
+            SnmpPdu::GetResponse(resp) => {
+                resp.push_ber(buf)?;
+                buf.push_tag_len(162, buf.len() - rest) // Context + Constructed + PDU_GET_RESPONSE(2)
+            }
+            SnmpPdu::SetRequest(req) => {
+                req.push_ber(buf)?;
+                buf.push_tag_len(163, buf.len() - rest) // Context + Constructed + PDU_SET_REQUEST(3)
+            }
+            SnmpPdu::Trap(trap) => {
+                trap.push_ber(buf)?;
+                buf.push_tag_len(164, buf.len() - rest) // Context + Constructed + PDU_TRAP(4)
+            }
             SnmpPdu::GetBulkRequest(req) => {
                 req.push_ber(buf)?;
                 buf.push_tag_len(165, buf.len() - rest) // Context + Constructed + PDU_GETBULK_REQUEST(5)
             }
-            _ => Err(SnmpError::NotImplemented),
+            SnmpPdu::InformRequest(req) => {
+                req.push_ber(buf)?;
+                buf.push_tag_len(166, buf.len() - rest) // Context + Constructed + PDU_INFORM_REQUEST(6)
+            }
+            SnmpPdu::SnmpV2Trap(req) => {
+                req.push_ber(buf)?;
+                buf.push_tag_len(167, buf.len() - rest) // Context + Constructed + PDU_SNMPV2_TRAP(7)
+            }
+            SnmpPdu::Report(report) => {
+                report.push_ber(buf)?;
+                buf.push_tag_len(168, buf.len() - rest) // Context + Constructed + PDU_REPORT(8)
+            }
         }
     }
 }
@@ -71,9 +106,29 @@ impl SnmpPdu<'_> {
             SnmpPdu::GetNextRequest(pdu) => request_id.check(pdu.request_id),
             SnmpPdu::GetBulkRequest(pdu) => request_id.check(pdu.request_id),
             SnmpPdu::GetResponse(pdu) => request_id.check(pdu.request_id),
+            SnmpPdu::SetRequest(pdu) => request_id.check(pdu.request_id),
+            // No request id
+            SnmpPdu::Trap(_) => false,
+            SnmpPdu::InformRequest(pdu) => request_id.check(pdu.request_id),
+            SnmpPdu::SnmpV2Trap(pdu) => request_id.check(pdu.request_id),
             SnmpPdu::Report(_) => true,
         }
     }
+    /// Get request id
+    pub fn get_request_id(&self) -> i64 {
+        match self {
+            SnmpPdu::GetRequest(pdu) => pdu.request_id,
+            SnmpPdu::GetNextRequest(pdu) => pdu.request_id,
+            SnmpPdu::GetBulkRequest(pdu) => pdu.request_id,
+            SnmpPdu::GetResponse(pdu) => pdu.request_id,
+            SnmpPdu::SetRequest(pdu) => pdu.request_id,
+            // No request id
+            SnmpPdu::Trap(_) => 0,
+            SnmpPdu::InformRequest(pdu) => pdu.request_id,
+            SnmpPdu::SnmpV2Trap(pdu) => pdu.request_id,
+            SnmpPdu::Report(pdu) => pdu.request_id,
+        }
+    }
     /// Get GERRESPONSE pdu
     pub fn as_getresponse(&self) -> Option<&SnmpGetResponse<'_>> {
         if let SnmpPdu::GetResponse(pdu) = self {
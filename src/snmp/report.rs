@@ -1,18 +1,64 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Report PDU Parser
 // ------------------------------------------------------------------------
-// Copyright (C) 2023, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
+use super::getresponse::SnmpGetResponse;
 use crate::error::SnmpError;
 
-pub struct SnmpReport<'a>(pub &'a [u8]);
+// RFC-3416: Report-PDU shares the structure with Response-PDU
+pub type SnmpReport<'a> = SnmpGetResponse<'a>;
 
-impl<'a> TryFrom<&'a [u8]> for SnmpReport<'a> {
-    type Error = SnmpError;
+// usmStatsDecryptionErrors.0, RFC-3414 pp. 5, BER-encoded
+const USM_STATS_DECRYPTION_ERRORS: [u8; 10] = [0x2b, 6, 1, 6, 3, 15, 1, 1, 6, 0];
 
-    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        Ok(SnmpReport(value))
+impl SnmpReport<'_> {
+    /// Error, reported by the agent instead of the response.
+    /// Wrong privacy key is reported via usmStatsDecryptionErrors.
+    pub fn report_error(&self) -> SnmpError {
+        match self.vars.first() {
+            Some(var) if var.oid.0.as_ref() == USM_STATS_DECRYPTION_ERRORS => {
+                SnmpError::DecryptionFailed
+            }
+            _ => SnmpError::AuthenticationFailed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber::SnmpOid;
+    use crate::snmp::getresponse::SnmpVar;
+    use crate::snmp::value::SnmpValue;
+
+    fn report(oid: &str) -> SnmpReport<'static> {
+        SnmpReport {
+            request_id: 1,
+            error_status: 0,
+            error_index: 0,
+            vars: vec![SnmpVar {
+                oid: SnmpOid::try_from(oid).unwrap(),
+                value: SnmpValue::Null,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_decryption_error() {
+        assert!(matches!(
+            report("1.3.6.1.6.3.15.1.1.6.0").report_error(),
+            SnmpError::DecryptionFailed
+        ));
+    }
+
+    #[test]
+    fn test_auth_error() {
+        assert!(matches!(
+            report("1.3.6.1.6.3.15.1.1.5.0").report_error(),
+            SnmpError::AuthenticationFailed
+        ));
     }
 }
@@ -0,0 +1,11 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: SetRequest PDU Parser
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::getresponse::SnmpGetResponse;
+
+// RFC-3416: SetRequest-PDU shares the structure with Response-PDU
+pub type SnmpSetRequest<'a> = SnmpGetResponse<'a>;
@@ -0,0 +1,96 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: SNMPv1 Trap PDU Parser
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::getresponse::SnmpVar;
+use crate::ber::{BerDecoder, BerEncoder, SnmpInt, SnmpIpAddress, SnmpOid, SnmpTimeTicks};
+use crate::buf::Buffer;
+use crate::error::{DecodePath, SnmpError, SnmpResult};
+
+/// SNMPv1 Trap-PDU, RFC-1157 pp. 4.1.6
+#[derive(Clone)]
+pub struct SnmpTrap<'a> {
+    pub(crate) enterprise: SnmpOid<'a>,
+    pub(crate) agent_addr: SnmpIpAddress,
+    pub(crate) generic_trap: i64,
+    pub(crate) specific_trap: i64,
+    pub(crate) time_stamp: SnmpTimeTicks,
+    pub(crate) vars: Vec<SnmpVar<'a>>,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SnmpTrap<'a> {
+    type Error = SnmpError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let (tail, enterprise) = SnmpOid::from_ber(value).within("enterprise")?;
+        let (tail, agent_addr) = SnmpIpAddress::from_ber(tail).within("agent_addr")?;
+        let (tail, generic_trap) = SnmpInt::from_ber(tail).within("generic_trap")?;
+        let (tail, specific_trap) = SnmpInt::from_ber(tail).within("specific_trap")?;
+        let (tail, time_stamp) = SnmpTimeTicks::from_ber(tail).within("time_stamp")?;
+        Ok(SnmpTrap {
+            enterprise,
+            agent_addr,
+            generic_trap: generic_trap.into(),
+            specific_trap: specific_trap.into(),
+            time_stamp,
+            vars: SnmpVar::parse_all(tail)?,
+        })
+    }
+}
+
+impl BerEncoder for SnmpTrap<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        SnmpVar::push_all(&self.vars, buf)?;
+        self.time_stamp.push_ber(buf)?;
+        SnmpInt::from(self.specific_trap).push_ber(buf)?;
+        SnmpInt::from(self.generic_trap).push_ber(buf)?;
+        self.agent_addr.push_ber(buf)?;
+        self.enterprise.push_ber(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snmp::value::OwnedValue;
+    use std::net::Ipv4Addr;
+
+    // linkDown of ifIndex 2, from 192.168.0.1
+    const TRAP: [u8; 41] = [
+        0x06, 0x08, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x8f, 0x65, 0x01, 0x40, 0x04, 0xc0, 0xa8, 0x00,
+        0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x00, 0x43, 0x02, 0x30, 0x39, 0x30, 0x0d, 0x30, 0x0b,
+        0x06, 0x06, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x02,
+    ];
+
+    #[test]
+    fn test_parse() -> SnmpResult<()> {
+        let trap = SnmpTrap::try_from(TRAP.as_slice())?;
+        assert_eq!(String::try_from(&trap.enterprise)?, "1.3.6.1.4.1.2021.1");
+        assert_eq!(
+            Ipv4Addr::from(&trap.agent_addr),
+            Ipv4Addr::new(192, 168, 0, 1)
+        );
+        assert_eq!(trap.generic_trap, 2);
+        assert_eq!(trap.specific_trap, 0);
+        assert_eq!(trap.time_stamp.0, 12345);
+        assert_eq!(trap.vars.len(), 1);
+        assert_eq!(String::try_from(&trap.vars[0].oid)?, "1.3.6.1.2.1.2");
+        assert_eq!(
+            OwnedValue::try_from(&trap.vars[0].value)?,
+            OwnedValue::Int(2)
+        );
+        // Encode back
+        let mut buf = Buffer::default();
+        trap.push_ber(&mut buf)?;
+        assert_eq!(buf.data(), TRAP.as_slice());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        assert!(SnmpTrap::try_from(&TRAP[..20]).is_err());
+    }
+}
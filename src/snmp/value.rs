@@ -1,23 +1,35 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SnmpVar struct
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
 use crate::ber::{
-    BerClass, BerDecoder, BerHeader, SnmpBitString, SnmpBool, SnmpCounter32, SnmpCounter64,
-    SnmpGauge32, SnmpInt, SnmpIpAddress, SnmpNull, SnmpObjectDescriptor, SnmpOctetString, SnmpOid,
-    SnmpOpaque, SnmpReal, SnmpTimeTicks, SnmpUInteger32, TAG_APP_COUNTER32, TAG_APP_COUNTER64,
-    TAG_APP_GAUGE32, TAG_APP_IPADDRESS, TAG_APP_OPAQUE, TAG_APP_TIMETICKS, TAG_APP_UINTEGER32,
+    BER_CLASS_CONTEXT, BerClass, BerDecoder, BerEncoder, BerHeader, SnmpBitString, SnmpBool,
+    SnmpCounter32, SnmpCounter64, SnmpGauge32, SnmpInt, SnmpIpAddress, SnmpNsapAddress, SnmpNull,
+    SnmpObjectDescriptor, SnmpOctetString, SnmpOid, SnmpOpaque, SnmpOpaqueValue, SnmpReal,
+    SnmpTimeTicks, SnmpUInteger32, TAG_APP_COUNTER32, TAG_APP_COUNTER64, TAG_APP_GAUGE32,
+    TAG_APP_IPADDRESS, TAG_APP_NSAPADDRESS, TAG_APP_OPAQUE, TAG_APP_TIMETICKS, TAG_APP_UINTEGER32,
     TAG_BIT_STRING, TAG_BOOL, TAG_CTX_END_OF_MIB_VIEW, TAG_CTX_NO_SUCH_INSTANCE,
     TAG_CTX_NO_SUCH_OBJECT, TAG_INT, TAG_NULL, TAG_OBJECT_DESCRIPTOR, TAG_OBJECT_ID,
-    TAG_OCTET_STRING, TAG_REAL,
+    TAG_OCTET_STRING, TAG_REAL, Tag,
 };
-use crate::error::SnmpError;
+use crate::buf::Buffer;
+use crate::error::{SnmpError, SnmpResult};
 use nom::{Err, IResult};
-use pyo3::{Bound, IntoPyObject, PyAny, Python};
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyTypeError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::types::{PyAnyMethods, PyBytes, PyFloat, PyNone, PyString, PyStringMethods, PyTuple};
+#[cfg(feature = "python")]
+use pyo3::{Bound, IntoPyObject, PyAny, PyResult, Python};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::Ipv4Addr;
 
+#[derive(Clone)]
 pub enum SnmpValue<'a> {
     Bool(SnmpBool),
     Int(SnmpInt),
@@ -32,6 +44,8 @@ pub enum SnmpValue<'a> {
     Gauge32(SnmpGauge32),
     TimeTicks(SnmpTimeTicks),
     Opaque(SnmpOpaque<'a>),
+    OpaqueValue(SnmpOpaqueValue),
+    NsapAddress(SnmpNsapAddress<'a>),
     Counter64(SnmpCounter64),
     UInteger32(SnmpUInteger32),
     NoSuchObject,
@@ -41,51 +55,55 @@ pub enum SnmpValue<'a> {
 
 impl SnmpValue<'_> {
     pub fn from_ber(i: &[u8]) -> IResult<&[u8], SnmpValue<'_>, SnmpError> {
-        let (tail, hdr) = BerHeader::from_ber(i)?;
-        let value = match hdr.constructed {
+        let (tail, hdr) =
+            BerHeader::from_ber(i).map_err(|e| Err::Failure(SnmpError::from(e).at(i)))?;
+        let value = SnmpValue::decode(i, tail, &hdr).map_err(|e| Err::Failure(e.at(i)))?;
+        Ok((&tail[hdr.length..], value))
+    }
+
+    fn decode<'a>(i: &'a [u8], tail: &'a [u8], hdr: &BerHeader) -> SnmpResult<SnmpValue<'a>> {
+        Ok(match hdr.constructed {
             // Primitive types
             false => match hdr.class {
                 BerClass::Universal => match hdr.tag {
                     // @todo: TAG_END_OF_CONTENTS
-                    TAG_BOOL => SnmpValue::Bool(SnmpBool::decode(tail, &hdr)?),
-                    TAG_INT => SnmpValue::Int(SnmpInt::decode(tail, &hdr)?),
-                    TAG_BIT_STRING => SnmpValue::BitString(SnmpBitString::decode(tail, &hdr)?),
-                    TAG_OCTET_STRING => {
-                        SnmpValue::OctetString(SnmpOctetString::decode(tail, &hdr)?)
-                    }
+                    TAG_BOOL => SnmpValue::Bool(SnmpBool::decode(tail, hdr)?),
+                    TAG_INT => SnmpValue::Int(SnmpInt::decode(tail, hdr)?),
+                    TAG_BIT_STRING => SnmpValue::BitString(SnmpBitString::decode(tail, hdr)?),
+                    TAG_OCTET_STRING => SnmpValue::OctetString(SnmpOctetString::decode(tail, hdr)?),
                     TAG_NULL => {
-                        SnmpNull::decode(tail, &hdr)?;
+                        SnmpNull::decode(tail, hdr)?;
                         SnmpValue::Null
                     }
-                    TAG_OBJECT_ID => SnmpValue::Oid(SnmpOid::decode(tail, &hdr)?),
+                    TAG_OBJECT_ID => SnmpValue::Oid(SnmpOid::decode(tail, hdr)?),
                     TAG_OBJECT_DESCRIPTOR => {
-                        SnmpValue::ObjectDescriptor(SnmpObjectDescriptor::decode(tail, &hdr)?)
+                        SnmpValue::ObjectDescriptor(SnmpObjectDescriptor::decode(tail, hdr)?)
                     }
-                    TAG_REAL => SnmpValue::Real(SnmpReal::decode(i, &hdr)?),
+                    TAG_REAL => SnmpValue::Real(SnmpReal::decode(i, hdr)?),
                     //
                     _ => {
-                        return Err(Err::Failure(SnmpError::UnsupportedTag(format!(
-                            "Universal primitive tag {}: {:X?}",
-                            hdr.tag, i
-                        ))));
+                        return Err(unsupported_tag("Universal primitive".into(), hdr.tag));
                     }
                 },
                 BerClass::Application => match hdr.tag {
-                    TAG_APP_IPADDRESS => SnmpValue::IpAddress(SnmpIpAddress::decode(tail, &hdr)?),
-                    TAG_APP_COUNTER32 => SnmpValue::Counter32(SnmpCounter32::decode(tail, &hdr)?),
-                    TAG_APP_GAUGE32 => SnmpValue::Gauge32(SnmpGauge32::decode(tail, &hdr)?),
-                    TAG_APP_TIMETICKS => SnmpValue::TimeTicks(SnmpTimeTicks::decode(tail, &hdr)?),
-                    TAG_APP_OPAQUE => SnmpValue::Opaque(SnmpOpaque::decode(tail, &hdr)?),
-                    // TAG_APP_NSAPADDRESS=>{},
-                    TAG_APP_COUNTER64 => SnmpValue::Counter64(SnmpCounter64::decode(tail, &hdr)?),
-                    TAG_APP_UINTEGER32 => {
-                        SnmpValue::UInteger32(SnmpUInteger32::decode(tail, &hdr)?)
+                    TAG_APP_IPADDRESS => SnmpValue::IpAddress(SnmpIpAddress::decode(tail, hdr)?),
+                    TAG_APP_COUNTER32 => SnmpValue::Counter32(SnmpCounter32::decode(tail, hdr)?),
+                    TAG_APP_GAUGE32 => SnmpValue::Gauge32(SnmpGauge32::decode(tail, hdr)?),
+                    TAG_APP_TIMETICKS => SnmpValue::TimeTicks(SnmpTimeTicks::decode(tail, hdr)?),
+                    TAG_APP_OPAQUE => {
+                        let x = SnmpOpaque::decode(tail, hdr)?;
+                        match SnmpOpaqueValue::from_opaque(x.0) {
+                            Some(v) => SnmpValue::OpaqueValue(v),
+                            None => SnmpValue::Opaque(x),
+                        }
                     }
+                    TAG_APP_NSAPADDRESS => {
+                        SnmpValue::NsapAddress(SnmpNsapAddress::decode(tail, hdr)?)
+                    }
+                    TAG_APP_COUNTER64 => SnmpValue::Counter64(SnmpCounter64::decode(tail, hdr)?),
+                    TAG_APP_UINTEGER32 => SnmpValue::UInteger32(SnmpUInteger32::decode(tail, hdr)?),
                     _ => {
-                        return Err(Err::Failure(SnmpError::UnsupportedTag(format!(
-                            "Application primitive tag {}: {:X?}",
-                            hdr.tag, i
-                        ))));
+                        return Err(unsupported_tag("Application primitive".into(), hdr.tag));
                     }
                 },
                 BerClass::Context => match hdr.tag {
@@ -93,28 +111,24 @@ impl SnmpValue<'_> {
                     TAG_CTX_NO_SUCH_INSTANCE => SnmpValue::NoSuchInstance,
                     TAG_CTX_END_OF_MIB_VIEW => SnmpValue::EndOfMibView,
                     _ => {
-                        return Err(Err::Failure(SnmpError::UnsupportedTag(format!(
-                            "Context primitive tag {}: {:X?}",
-                            hdr.tag, i
-                        ))));
+                        return Err(unsupported_tag("Context primitive".into(), hdr.tag));
                     }
                 },
                 _ => {
-                    return Err(Err::Failure(SnmpError::UnsupportedTag(format!(
-                        "{:?} primitive tag {}: {:X?}",
-                        hdr.class, hdr.tag, i
-                    ))));
+                    return Err(unsupported_tag(
+                        format!("{:?} primitive", hdr.class),
+                        hdr.tag,
+                    ));
                 }
             },
             // Constructed types
             true => {
-                return Err(Err::Failure(SnmpError::UnsupportedTag(format!(
-                    "{:?} constructed tag {}: {:X?}",
-                    hdr.class, hdr.tag, i
-                ))));
+                return Err(unsupported_tag(
+                    format!("{:?} constructed", hdr.class),
+                    hdr.tag,
+                ));
             }
-        };
-        Ok((&tail[hdr.length..], value))
+        })
     }
 
     pub fn is_end_of_mib_view(&self) -> bool {
@@ -122,6 +136,198 @@ impl SnmpValue<'_> {
     }
 }
 
+// Unsupported tag with the description
+fn unsupported_tag(description: String, tag: Tag) -> SnmpError {
+    SnmpError::UnsupportedTag(description).with_tags(None, tag)
+}
+
+impl BerEncoder for SnmpValue<'_> {
+    fn push_ber(&self, buf: &mut Buffer) -> SnmpResult<()> {
+        match self {
+            SnmpValue::Bool(x) => x.push_ber(buf),
+            SnmpValue::Int(x) => x.push_ber(buf),
+            SnmpValue::Null => SnmpNull.push_ber(buf),
+            SnmpValue::BitString(x) => x.push_ber(buf),
+            SnmpValue::OctetString(x) => x.push_ber(buf),
+            SnmpValue::Oid(x) => x.push_ber(buf),
+            SnmpValue::ObjectDescriptor(x) => x.push_ber(buf),
+            SnmpValue::Real(x) => x.push_ber(buf),
+            SnmpValue::IpAddress(x) => x.push_ber(buf),
+            SnmpValue::Counter32(x) => x.push_ber(buf),
+            SnmpValue::Gauge32(x) => x.push_ber(buf),
+            SnmpValue::TimeTicks(x) => x.push_ber(buf),
+            SnmpValue::Opaque(x) => x.push_ber(buf),
+            SnmpValue::OpaqueValue(x) => x.push_ber(buf),
+            SnmpValue::NsapAddress(x) => x.push_ber(buf),
+            SnmpValue::Counter64(x) => x.push_ber(buf),
+            SnmpValue::UInteger32(x) => x.push_ber(buf),
+            SnmpValue::NoSuchObject => buf.push(&[BER_CLASS_CONTEXT | TAG_CTX_NO_SUCH_OBJECT, 0]),
+            SnmpValue::NoSuchInstance => {
+                buf.push(&[BER_CLASS_CONTEXT | TAG_CTX_NO_SUCH_INSTANCE, 0])
+            }
+            SnmpValue::EndOfMibView => buf.push(&[BER_CLASS_CONTEXT | TAG_CTX_END_OF_MIB_VIEW, 0]),
+        }
+    }
+}
+
+/// Value, detached from the received message.
+/// Keeps the ASN.1 type of the value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OwnedValue {
+    Bool(bool),
+    Int(i64),
+    Null,
+    BitString(u64),
+    OctetString(Vec<u8>),
+    Oid(String),
+    ObjectDescriptor(Vec<u8>),
+    Real(f64),
+    IpAddress(Ipv4Addr),
+    Counter32(u32),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Opaque(Vec<u8>),
+    OpaqueCounter64(u64),
+    OpaqueFloat(f32),
+    OpaqueDouble(f64),
+    OpaqueInt64(i64),
+    OpaqueUInt64(u64),
+    NsapAddress(Vec<u8>),
+    Counter64(u64),
+    UInteger32(u32),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+}
+
+impl TryFrom<&SnmpValue<'_>> for OwnedValue {
+    type Error = SnmpError;
+
+    fn try_from(value: &SnmpValue<'_>) -> SnmpResult<OwnedValue> {
+        Ok(match value {
+            SnmpValue::Bool(x) => OwnedValue::Bool(x.clone().into()),
+            SnmpValue::Int(x) => OwnedValue::Int(x.clone().into()),
+            SnmpValue::Null => OwnedValue::Null,
+            SnmpValue::BitString(x) => OwnedValue::BitString(x.clone().into()),
+            SnmpValue::OctetString(x) => OwnedValue::OctetString(x.0.to_vec()),
+            SnmpValue::Oid(x) => OwnedValue::Oid(x.try_into()?),
+            SnmpValue::ObjectDescriptor(x) => OwnedValue::ObjectDescriptor(x.0.to_vec()),
+            SnmpValue::Real(x) => OwnedValue::Real(x.clone().into()),
+            SnmpValue::IpAddress(x) => OwnedValue::IpAddress(x.into()),
+            SnmpValue::Counter32(x) => OwnedValue::Counter32(x.0),
+            SnmpValue::Gauge32(x) => OwnedValue::Gauge32(x.0),
+            SnmpValue::TimeTicks(x) => OwnedValue::TimeTicks(x.0),
+            SnmpValue::Opaque(x) => OwnedValue::Opaque(x.0.to_vec()),
+            SnmpValue::OpaqueValue(x) => match *x {
+                SnmpOpaqueValue::Counter64(v) => OwnedValue::OpaqueCounter64(v),
+                SnmpOpaqueValue::Float(v) => OwnedValue::OpaqueFloat(v),
+                SnmpOpaqueValue::Double(v) => OwnedValue::OpaqueDouble(v),
+                SnmpOpaqueValue::Int64(v) => OwnedValue::OpaqueInt64(v),
+                SnmpOpaqueValue::UInt64(v) => OwnedValue::OpaqueUInt64(v),
+            },
+            SnmpValue::NsapAddress(x) => OwnedValue::NsapAddress(x.0.to_vec()),
+            SnmpValue::Counter64(x) => OwnedValue::Counter64(x.0),
+            SnmpValue::UInteger32(x) => OwnedValue::UInteger32(x.0),
+            SnmpValue::NoSuchObject => OwnedValue::NoSuchObject,
+            SnmpValue::NoSuchInstance => OwnedValue::NoSuchInstance,
+            SnmpValue::EndOfMibView => OwnedValue::EndOfMibView,
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a OwnedValue> for SnmpValue<'a> {
+    type Error = SnmpError;
+
+    fn try_from(value: &'a OwnedValue) -> SnmpResult<SnmpValue<'a>> {
+        Ok(match value {
+            OwnedValue::Bool(x) => SnmpValue::Bool((*x).into()),
+            OwnedValue::Int(x) => SnmpValue::Int((*x).into()),
+            OwnedValue::Null => SnmpValue::Null,
+            OwnedValue::BitString(x) => SnmpValue::BitString((*x).into()),
+            OwnedValue::OctetString(x) => SnmpValue::OctetString(SnmpOctetString(x)),
+            OwnedValue::Oid(x) => SnmpValue::Oid(x.as_str().try_into()?),
+            OwnedValue::ObjectDescriptor(x) => SnmpValue::ObjectDescriptor(SnmpObjectDescriptor(x)),
+            OwnedValue::Real(x) => SnmpValue::Real((*x).into()),
+            OwnedValue::IpAddress(x) => SnmpValue::IpAddress((*x).into()),
+            OwnedValue::Counter32(x) => SnmpValue::Counter32(SnmpCounter32(*x)),
+            OwnedValue::Gauge32(x) => SnmpValue::Gauge32(SnmpGauge32(*x)),
+            OwnedValue::TimeTicks(x) => SnmpValue::TimeTicks(SnmpTimeTicks(*x)),
+            OwnedValue::Opaque(x) => SnmpValue::Opaque(SnmpOpaque(x)),
+            OwnedValue::OpaqueCounter64(x) => {
+                SnmpValue::OpaqueValue(SnmpOpaqueValue::Counter64(*x))
+            }
+            OwnedValue::OpaqueFloat(x) => SnmpValue::OpaqueValue(SnmpOpaqueValue::Float(*x)),
+            OwnedValue::OpaqueDouble(x) => SnmpValue::OpaqueValue(SnmpOpaqueValue::Double(*x)),
+            OwnedValue::OpaqueInt64(x) => SnmpValue::OpaqueValue(SnmpOpaqueValue::Int64(*x)),
+            OwnedValue::OpaqueUInt64(x) => SnmpValue::OpaqueValue(SnmpOpaqueValue::UInt64(*x)),
+            OwnedValue::NsapAddress(x) => SnmpValue::NsapAddress(SnmpNsapAddress(x)),
+            OwnedValue::Counter64(x) => SnmpValue::Counter64(SnmpCounter64(*x)),
+            OwnedValue::UInteger32(x) => SnmpValue::UInteger32(SnmpUInteger32(*x)),
+            OwnedValue::NoSuchObject => SnmpValue::NoSuchObject,
+            OwnedValue::NoSuchInstance => SnmpValue::NoSuchInstance,
+            OwnedValue::EndOfMibView => SnmpValue::EndOfMibView,
+        })
+    }
+}
+
+// net-snmp style output, type followed by value
+impl fmt::Display for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnedValue::Bool(x) => write!(f, "BOOLEAN: {x}"),
+            OwnedValue::Int(x) => write!(f, "INTEGER: {x}"),
+            OwnedValue::Null => write!(f, "NULL"),
+            OwnedValue::BitString(x) => write!(f, "BITS: {x:X}"),
+            OwnedValue::OctetString(x) => match std::str::from_utf8(x) {
+                Ok(s) if !s.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+                    write!(f, "STRING: {s:?}")
+                }
+                _ => {
+                    write!(f, "Hex-STRING:")?;
+                    write_hex(f, x)
+                }
+            },
+            OwnedValue::Oid(x) => write!(f, "OID: {x}"),
+            OwnedValue::ObjectDescriptor(x) => {
+                write!(f, "ObjectDescriptor: {}", String::from_utf8_lossy(x))
+            }
+            OwnedValue::Real(x) => write!(f, "REAL: {x}"),
+            OwnedValue::IpAddress(x) => write!(f, "IpAddress: {x}"),
+            OwnedValue::Counter32(x) => write!(f, "Counter32: {x}"),
+            OwnedValue::Gauge32(x) => write!(f, "Gauge32: {x}"),
+            OwnedValue::TimeTicks(x) => write!(f, "Timeticks: ({x})"),
+            OwnedValue::Opaque(x) => {
+                write!(f, "OPAQUE:")?;
+                write_hex(f, x)
+            }
+            OwnedValue::OpaqueCounter64(x) => write!(f, "Opaque: Counter64: {x}"),
+            OwnedValue::OpaqueFloat(x) => write!(f, "Opaque: Float: {x}"),
+            OwnedValue::OpaqueDouble(x) => write!(f, "Opaque: Double: {x}"),
+            OwnedValue::OpaqueInt64(x) => write!(f, "Opaque: Int64: {x}"),
+            OwnedValue::OpaqueUInt64(x) => write!(f, "Opaque: UInt64: {x}"),
+            OwnedValue::NsapAddress(x) => {
+                write!(f, "NsapAddress:")?;
+                write_hex(f, x)
+            }
+            OwnedValue::Counter64(x) => write!(f, "Counter64: {x}"),
+            OwnedValue::UInteger32(x) => write!(f, "UInteger32: {x}"),
+            OwnedValue::NoSuchObject => write!(f, "No Such Object"),
+            OwnedValue::NoSuchInstance => write!(f, "No Such Instance"),
+            OwnedValue::EndOfMibView => write!(f, "End of MIB View"),
+        }
+    }
+}
+
+// Space-separated hex bytes
+fn write_hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    for x in data {
+        write!(f, " {x:02X}")?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "python")]
 impl<'py> IntoPyObject<'py> for &SnmpValue<'_> {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
@@ -142,6 +348,8 @@ impl<'py> IntoPyObject<'py> for &SnmpValue<'_> {
             SnmpValue::Gauge32(x) => x.into_pyobject(py)?,
             SnmpValue::TimeTicks(x) => x.into_pyobject(py)?,
             SnmpValue::Opaque(x) => x.into_pyobject(py)?,
+            SnmpValue::OpaqueValue(x) => x.into_pyobject(py)?,
+            SnmpValue::NsapAddress(x) => x.into_pyobject(py)?,
             SnmpValue::Counter64(x) => x.into_pyobject(py)?,
             SnmpValue::UInteger32(x) => x.into_pyobject(py)?,
             SnmpValue::NoSuchObject | SnmpValue::NoSuchInstance => todo!("never should be passed"),
@@ -150,6 +358,133 @@ impl<'py> IntoPyObject<'py> for &SnmpValue<'_> {
     }
 }
 
+// NULL is converted to None.
+// Exceptions are converted to `(type, None)`, like `("NoSuchInstance", None)`,
+// matching `OwnedValue::from_python`.
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for &OwnedValue {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = SnmpError;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(match self {
+            OwnedValue::Bool(x) => x.into_pyobject(py)?.to_owned().into_any(),
+            OwnedValue::Int(x) => x.into_pyobject(py)?.into_any(),
+            OwnedValue::BitString(x)
+            | OwnedValue::Counter64(x)
+            | OwnedValue::OpaqueCounter64(x)
+            | OwnedValue::OpaqueUInt64(x) => x.into_pyobject(py)?.into_any(),
+            OwnedValue::OpaqueInt64(x) => x.into_pyobject(py)?.into_any(),
+            OwnedValue::OpaqueFloat(x) => crate::ber::opaquevalue::float_to_f64(*x)
+                .into_pyobject(py)?
+                .into_any(),
+            OwnedValue::OctetString(x)
+            | OwnedValue::ObjectDescriptor(x)
+            | OwnedValue::Opaque(x)
+            | OwnedValue::NsapAddress(x) => PyBytes::new(py, x).into_any(),
+            OwnedValue::Oid(x) => PyString::new(py, x).into_any(),
+            OwnedValue::Real(x) | OwnedValue::OpaqueDouble(x) => x.into_pyobject(py)?.into_any(),
+            OwnedValue::IpAddress(x) => PyString::new(py, &x.to_string()).into_any(),
+            OwnedValue::Counter32(x)
+            | OwnedValue::Gauge32(x)
+            | OwnedValue::TimeTicks(x)
+            | OwnedValue::UInteger32(x) => x.into_pyobject(py)?.into_any(),
+            OwnedValue::Null => PyNone::get(py).to_owned().into_any(),
+            OwnedValue::NoSuchObject => py_exception(py, "NoSuchObject")?,
+            OwnedValue::NoSuchInstance => py_exception(py, "NoSuchInstance")?,
+            OwnedValue::EndOfMibView => py_exception(py, "EndOfMibView")?,
+        })
+    }
+}
+
+// `(type, None)` tuple of the exception value
+#[cfg(feature = "python")]
+fn py_exception<'py>(py: Python<'py>, kind: &str) -> SnmpResult<Bound<'py, PyAny>> {
+    Ok((kind, py.None())
+        .into_pyobject(py)
+        .map_err(|_| SnmpError::InvalidData)?
+        .into_any())
+}
+
+#[cfg(feature = "python")]
+impl OwnedValue {
+    /// Convert Python object to value.
+    ///
+    /// `None`, `int`, `float`, `str` and `bytes` are mapped to
+    /// NULL, INTEGER, REAL and OCTET STRING.
+    /// Other types are set explicitly by `(type, value)` tuple,
+    /// where type is the name of the variant, like `("Counter32", 10)`.
+    pub(crate) fn from_python(obj: &Bound<'_, PyAny>) -> PyResult<OwnedValue> {
+        if obj.is_none() {
+            return Ok(OwnedValue::Null);
+        }
+        if let Ok(t) = obj.cast::<PyTuple>() {
+            let (kind, value): (String, Bound<'_, PyAny>) = t.extract()?;
+            return OwnedValue::typed_from_python(&kind, &value);
+        }
+        if obj.is_instance_of::<PyFloat>() {
+            return Ok(OwnedValue::Real(obj.extract()?));
+        }
+        if let Ok(x) = obj.extract::<i64>() {
+            return Ok(OwnedValue::Int(x));
+        }
+        if obj.is_instance_of::<PyString>() || obj.is_instance_of::<PyBytes>() {
+            return Ok(OwnedValue::OctetString(py_octets(obj)?));
+        }
+        Err(PyTypeError::new_err("unsupported value type"))
+    }
+
+    fn typed_from_python(kind: &str, value: &Bound<'_, PyAny>) -> PyResult<OwnedValue> {
+        Ok(match kind {
+            "Bool" => OwnedValue::Bool(value.extract()?),
+            "Int" => OwnedValue::Int(value.extract()?),
+            "Null" => OwnedValue::Null,
+            "BitString" => OwnedValue::BitString(value.extract()?),
+            "OctetString" => OwnedValue::OctetString(py_octets(value)?),
+            "Oid" => OwnedValue::Oid(value.extract()?),
+            "ObjectDescriptor" => OwnedValue::ObjectDescriptor(py_octets(value)?),
+            "Real" => OwnedValue::Real(value.extract()?),
+            "IpAddress" => OwnedValue::IpAddress(
+                value
+                    .extract::<String>()?
+                    .parse()
+                    .map_err(|_| PyValueError::new_err("invalid ip address"))?,
+            ),
+            "Counter32" => OwnedValue::Counter32(value.extract()?),
+            "Gauge32" => OwnedValue::Gauge32(value.extract()?),
+            "TimeTicks" => OwnedValue::TimeTicks(value.extract()?),
+            "Opaque" => OwnedValue::Opaque(py_octets(value)?),
+            "OpaqueCounter64" => OwnedValue::OpaqueCounter64(value.extract()?),
+            "OpaqueFloat" => OwnedValue::OpaqueFloat(value.extract()?),
+            "OpaqueDouble" => OwnedValue::OpaqueDouble(value.extract()?),
+            "OpaqueInt64" => OwnedValue::OpaqueInt64(value.extract()?),
+            "OpaqueUInt64" => OwnedValue::OpaqueUInt64(value.extract()?),
+            "NsapAddress" => OwnedValue::NsapAddress(py_octets(value)?),
+            "Counter64" => OwnedValue::Counter64(value.extract()?),
+            "UInteger32" => OwnedValue::UInteger32(value.extract()?),
+            "NoSuchObject" => OwnedValue::NoSuchObject,
+            "NoSuchInstance" => OwnedValue::NoSuchInstance,
+            "EndOfMibView" => OwnedValue::EndOfMibView,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown value type: {}",
+                    kind
+                )));
+            }
+        })
+    }
+}
+
+// Octets of `str` or `bytes`
+#[cfg(feature = "python")]
+fn py_octets(obj: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    match obj.cast::<PyString>() {
+        Ok(s) => Ok(s.to_str()?.as_bytes().to_vec()),
+        Err(_) => obj.extract(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,6 +660,31 @@ mod tests {
         }
     }
     #[test]
+    fn test_opaque_float() -> SnmpResult<()> {
+        // UCD-SNMP-MIB::laLoadFloat
+        let data = [0x44, 7, 0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a];
+        let (tail, value) = SnmpValue::from_ber(&data)?;
+        assert_eq!(tail.len(), 0);
+        if let SnmpValue::OpaqueValue(x) = value {
+            assert_eq!(x, SnmpOpaqueValue::Float(0.15));
+            Ok(())
+        } else {
+            Err(SnmpError::UnexpectedTag)
+        }
+    }
+    #[test]
+    fn test_nsapaddress() -> SnmpResult<()> {
+        let data = [0x45, 5, 0x47, 0, 5, 0x80, 0xff];
+        let (tail, value) = SnmpValue::from_ber(&data)?;
+        assert_eq!(tail.len(), 0);
+        if let SnmpValue::NsapAddress(x) = value {
+            assert_eq!(x.0, &data[2..]);
+            Ok(())
+        } else {
+            Err(SnmpError::UnexpectedTag)
+        }
+    }
+    #[test]
     fn test_counter64() -> SnmpResult<()> {
         let data = [0x46, 4, 1, 53, 16, 171];
         let (tail, value) = SnmpValue::from_ber(&data)?;
@@ -381,4 +741,167 @@ mod tests {
             Err(SnmpError::UnexpectedTag)
         }
     }
+    #[test]
+    fn test_encode() -> SnmpResult<()> {
+        let samples: [&[u8]; 12] = [
+            &[1u8, 1, 0xff],
+            &[2u8, 1, 10],
+            &[5u8, 0],
+            &[4u8, 5, 0, 1, 2, 3, 4],
+            &[0x6u8, 0x8, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x05, 0x00],
+            &[0x40, 0x4, 127, 0, 0, 1],
+            &[0x41, 4, 1, 53, 16, 171],
+            &[0x44, 7, 0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a],
+            &[0x45, 5, 0x47, 0, 5, 0x80, 0xff],
+            &[0x46, 4, 1, 53, 16, 171],
+            &[0x80u8, 0],
+            &[0x82u8, 0],
+        ];
+        for data in samples {
+            let (_, value) = SnmpValue::from_ber(data)?;
+            let mut buf = Buffer::default();
+            value.push_ber(&mut buf)?;
+            assert_eq!(buf.data(), data);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_owned() -> SnmpResult<()> {
+        let samples: [(&[u8], OwnedValue); 11] = [
+            (&[1u8, 1, 0xff], OwnedValue::Bool(true)),
+            (&[2u8, 1, 10], OwnedValue::Int(10)),
+            (&[4u8, 3, 1, 2, 3], OwnedValue::OctetString(vec![1, 2, 3])),
+            (
+                &[0x6u8, 0x8, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x05, 0x00],
+                OwnedValue::Oid("1.3.6.1.2.1.1.5.0".into()),
+            ),
+            (
+                &[0x40, 0x4, 127, 0, 0, 1],
+                OwnedValue::IpAddress(Ipv4Addr::new(127, 0, 0, 1)),
+            ),
+            (&[0x41, 4, 1, 53, 16, 171], OwnedValue::Counter32(20254891)),
+            (
+                &[0x44, 8, 0x9f, 0x76, 5, 1, 0, 0, 0, 0],
+                OwnedValue::OpaqueCounter64(0x100000000),
+            ),
+            (
+                &[0x44, 7, 0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a],
+                OwnedValue::OpaqueFloat(0.15),
+            ),
+            (
+                &[0x44, 11, 0x9f, 0x79, 8, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+                OwnedValue::OpaqueDouble(1.5),
+            ),
+            (
+                &[0x44, 5, 0x9f, 0x7a, 2, 0xff, 0x7f],
+                OwnedValue::OpaqueInt64(-129),
+            ),
+            (
+                &[0x44, 5, 0x9f, 0x7b, 2, 0, 0x80],
+                OwnedValue::OpaqueUInt64(128),
+            ),
+        ];
+        for (data, expected) in samples {
+            let (_, value) = SnmpValue::from_ber(data)?;
+            assert_eq!(OwnedValue::try_from(&value)?, expected);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_owned_roundtrip() -> SnmpResult<()> {
+        let samples: [&[u8]; 19] = [
+            &[1u8, 1, 0xff],
+            &[2u8, 1, 10],
+            &[5u8, 0],
+            &[4u8, 5, 0, 1, 2, 3, 4],
+            &[0x6u8, 0x8, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x05, 0x00],
+            &[0x40, 0x4, 127, 0, 0, 1],
+            &[0x41, 4, 1, 53, 16, 171],
+            &[0x42, 4, 1, 53, 16, 171],
+            &[67, 4, 1, 53, 16, 171],
+            &[0x44, 2, 1, 2],
+            &[0x44, 8, 0x9f, 0x76, 5, 1, 0, 0, 0, 0],
+            &[0x44, 7, 0x9f, 0x78, 4, 0x3e, 0x19, 0x99, 0x9a],
+            &[0x44, 11, 0x9f, 0x79, 8, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+            &[0x44, 5, 0x9f, 0x7a, 2, 0xff, 0x7f],
+            &[0x44, 5, 0x9f, 0x7b, 2, 0, 0x80],
+            &[0x45, 1, 0x47],
+            &[0x46, 4, 1, 53, 16, 171],
+            &[0x47, 4, 1, 53, 16, 171],
+            &[0x82u8, 0],
+        ];
+        for data in samples {
+            let (_, value) = SnmpValue::from_ber(data)?;
+            let owned = OwnedValue::try_from(&value)?;
+            let value = SnmpValue::try_from(&owned)?;
+            let mut buf = Buffer::default();
+            value.push_ber(&mut buf)?;
+            assert_eq!(buf.data(), data);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_owned_invalid_oid() {
+        let owned = OwnedValue::Oid("1.3.x".into());
+        assert!(SnmpValue::try_from(&owned).is_err());
+    }
+    #[test]
+    fn test_owned_display() {
+        let samples = [
+            (OwnedValue::Int(-5), "INTEGER: -5"),
+            (
+                OwnedValue::OctetString(b"eth0".to_vec()),
+                "STRING: \"eth0\"",
+            ),
+            (
+                OwnedValue::OctetString(vec![0, 0x1b, 0xff]),
+                "Hex-STRING: 00 1B FF",
+            ),
+            (OwnedValue::Oid("1.3.6.1".into()), "OID: 1.3.6.1"),
+            (
+                OwnedValue::IpAddress(Ipv4Addr::new(10, 0, 0, 1)),
+                "IpAddress: 10.0.0.1",
+            ),
+            (OwnedValue::Counter32(7), "Counter32: 7"),
+            (OwnedValue::Gauge32(7), "Gauge32: 7"),
+            (OwnedValue::TimeTicks(100), "Timeticks: (100)"),
+            (
+                OwnedValue::NsapAddress(vec![0x47, 0, 5]),
+                "NsapAddress: 47 00 05",
+            ),
+            (OwnedValue::OpaqueFloat(0.15), "Opaque: Float: 0.15"),
+            (OwnedValue::OpaqueUInt64(5), "Opaque: UInt64: 5"),
+            (OwnedValue::Null, "NULL"),
+            (OwnedValue::EndOfMibView, "End of MIB View"),
+        ];
+        for (value, expected) in samples {
+            assert_eq!(value.to_string(), expected);
+        }
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_owned_serde() {
+        let samples = [
+            (OwnedValue::Counter32(7), r#"{"Counter32":7}"#),
+            (OwnedValue::Gauge32(7), r#"{"Gauge32":7}"#),
+            (OwnedValue::Oid("1.3.6.1".into()), r#"{"Oid":"1.3.6.1"}"#),
+            (
+                OwnedValue::IpAddress(Ipv4Addr::new(10, 0, 0, 1)),
+                r#"{"IpAddress":"10.0.0.1"}"#,
+            ),
+            (
+                OwnedValue::NsapAddress(vec![0x47, 0, 5]),
+                r#"{"NsapAddress":[71,0,5]}"#,
+            ),
+            (OwnedValue::OpaqueFloat(0.5), r#"{"OpaqueFloat":0.5}"#),
+            (OwnedValue::OpaqueInt64(-3), r#"{"OpaqueInt64":-3}"#),
+            (OwnedValue::Null, r#""Null""#),
+        ];
+        for (value, expected) in samples {
+            let s = serde_json::to_string(&value).unwrap();
+            assert_eq!(s, expected);
+            let v: OwnedValue = serde_json::from_str(&s).unwrap();
+            assert_eq!(v, value);
+        }
+    }
 }
@@ -0,0 +1,188 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: ICMP error reporting
+// ------------------------------------------------------------------------
+// Copyright (C) 2023-26, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+use crate::error::IcmpError;
+use crate::error::IcmpErrorKind;
+#[cfg(target_os = "linux")]
+use socket2::Socket;
+#[cfg(target_os = "linux")]
+use std::{
+    io,
+    mem::MaybeUninit,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    os::fd::AsRawFd,
+};
+
+// ICMP types, RFC-792
+const ICMP_DEST_UNREACH: u8 = 3;
+const ICMP_TIME_EXCEEDED: u8 = 11;
+// ICMPv6 types, RFC-4443
+const ICMP6_DST_UNREACH: u8 = 1;
+const ICMP6_TIME_EXCEEDED: u8 = 3;
+
+/// Classify ICMP message
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn classify_icmp(icmp_type: u8, code: u8) -> Option<IcmpErrorKind> {
+    match (icmp_type, code) {
+        // Network unreachable, network unknown, network unreachable for ToS
+        (ICMP_DEST_UNREACH, 0 | 6 | 11) => Some(IcmpErrorKind::NetworkUnreachable),
+        // Host unreachable, source route failed, host unknown,
+        // source host isolated, host unreachable for ToS
+        (ICMP_DEST_UNREACH, 1 | 5 | 7 | 8 | 12) => Some(IcmpErrorKind::HostUnreachable),
+        (ICMP_DEST_UNREACH, 3) => Some(IcmpErrorKind::PortUnreachable),
+        // Network/host prohibited, communication prohibited,
+        // precedence violation, precedence cutoff
+        (ICMP_DEST_UNREACH, 9 | 10 | 13 | 14 | 15) => Some(IcmpErrorKind::AdminProhibited),
+        (ICMP_TIME_EXCEEDED, _) => Some(IcmpErrorKind::TtlExceeded),
+        _ => None,
+    }
+}
+
+/// Classify ICMPv6 message
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn classify_icmp6(icmp_type: u8, code: u8) -> Option<IcmpErrorKind> {
+    match (icmp_type, code) {
+        // No route to destination
+        (ICMP6_DST_UNREACH, 0) => Some(IcmpErrorKind::NetworkUnreachable),
+        // Administratively prohibited, failed ingress/egress policy,
+        // reject route
+        (ICMP6_DST_UNREACH, 1 | 5 | 6) => Some(IcmpErrorKind::AdminProhibited),
+        // Beyond scope of source address, address unreachable
+        (ICMP6_DST_UNREACH, 2 | 3) => Some(IcmpErrorKind::HostUnreachable),
+        (ICMP6_DST_UNREACH, 4) => Some(IcmpErrorKind::PortUnreachable),
+        (ICMP6_TIME_EXCEEDED, _) => Some(IcmpErrorKind::TtlExceeded),
+        _ => None,
+    }
+}
+
+/// Enable extended reliable error reporting, `IP_RECVERR`/`IPV6_RECVERR`.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_recv_err(io: &Socket, ipv6: bool) -> io::Result<()> {
+    let (level, name) = if ipv6 {
+        (libc::IPPROTO_IPV6, libc::IPV6_RECVERR)
+    } else {
+        (libc::IPPROTO_IP, libc::IP_RECVERR)
+    };
+    let on: libc::c_int = 1;
+    let r = unsafe {
+        libc::setsockopt(
+            io.as_raw_fd(),
+            level,
+            name,
+            &on as *const libc::c_int as *const libc::c_void,
+            size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if r < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Drain socket's error queue and return the latest ICMP error, if any.
+#[cfg(target_os = "linux")]
+pub(crate) fn read_icmp_error(io: &Socket) -> Option<IcmpError> {
+    // Original datagram is not interesting
+    let mut data = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr() as *mut libc::c_void,
+        iov_len: data.len(),
+    };
+    // u64 for cmsghdr alignment
+    let mut control = [MaybeUninit::<u64>::uninit(); 64];
+    let mut found = None;
+    loop {
+        let mut hdr: libc::msghdr = unsafe { std::mem::zeroed() };
+        hdr.msg_iov = &mut iov;
+        hdr.msg_iovlen = 1;
+        hdr.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        hdr.msg_controllen = size_of_val(&control) as _;
+        let r = unsafe {
+            libc::recvmsg(
+                io.as_raw_fd(),
+                &mut hdr,
+                libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT,
+            )
+        };
+        if r < 0 {
+            // Queue is empty
+            return found;
+        }
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&hdr) };
+        while !cmsg.is_null() {
+            let (level, kind) = unsafe { ((*cmsg).cmsg_level, (*cmsg).cmsg_type) };
+            if (level == libc::IPPROTO_IP && kind == libc::IP_RECVERR)
+                || (level == libc::IPPROTO_IPV6 && kind == libc::IPV6_RECVERR)
+            {
+                let ee = unsafe { libc::CMSG_DATA(cmsg) } as *const libc::sock_extended_err;
+                if let Some(e) = unsafe { parse_extended_err(ee) } {
+                    found = Some(e);
+                }
+            }
+            cmsg = unsafe { libc::CMSG_NXTHDR(&hdr, cmsg) };
+        }
+    }
+}
+
+// Parse extended error and the offender's address, following it.
+#[cfg(target_os = "linux")]
+unsafe fn parse_extended_err(ee: *const libc::sock_extended_err) -> Option<IcmpError> {
+    let err = unsafe { std::ptr::read_unaligned(ee) };
+    let kind = match err.ee_origin {
+        libc::SO_EE_ORIGIN_ICMP => classify_icmp(err.ee_type, err.ee_code)?,
+        libc::SO_EE_ORIGIN_ICMP6 => classify_icmp6(err.ee_type, err.ee_code)?,
+        _ => return None,
+    };
+    let sa = unsafe { libc::SO_EE_OFFENDER(ee) };
+    let offender = match unsafe { std::ptr::read_unaligned(sa) }.sa_family as libc::c_int {
+        libc::AF_INET => {
+            let sin = unsafe { std::ptr::read_unaligned(sa as *const libc::sockaddr_in) };
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                sin.sin_addr.s_addr,
+            ))))
+        }
+        libc::AF_INET6 => {
+            let sin6 = unsafe { std::ptr::read_unaligned(sa as *const libc::sockaddr_in6) };
+            Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    };
+    Some(IcmpError { kind, offender })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_icmp() {
+        assert_eq!(classify_icmp(3, 0), Some(IcmpErrorKind::NetworkUnreachable));
+        assert_eq!(classify_icmp(3, 1), Some(IcmpErrorKind::HostUnreachable));
+        assert_eq!(classify_icmp(3, 3), Some(IcmpErrorKind::PortUnreachable));
+        assert_eq!(classify_icmp(3, 13), Some(IcmpErrorKind::AdminProhibited));
+        assert_eq!(classify_icmp(11, 0), Some(IcmpErrorKind::TtlExceeded));
+        // Fragmentation needed
+        assert_eq!(classify_icmp(3, 4), None);
+        // Echo reply
+        assert_eq!(classify_icmp(0, 0), None);
+    }
+
+    #[test]
+    fn test_classify_icmp6() {
+        assert_eq!(
+            classify_icmp6(1, 0),
+            Some(IcmpErrorKind::NetworkUnreachable)
+        );
+        assert_eq!(classify_icmp6(1, 1), Some(IcmpErrorKind::AdminProhibited));
+        assert_eq!(classify_icmp6(1, 3), Some(IcmpErrorKind::HostUnreachable));
+        assert_eq!(classify_icmp6(1, 4), Some(IcmpErrorKind::PortUnreachable));
+        assert_eq!(classify_icmp6(3, 0), Some(IcmpErrorKind::TtlExceeded));
+        // Packet too big
+        assert_eq!(classify_icmp6(2, 0), None);
+    }
+}
@@ -0,0 +1,2014 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Transport-level socket I/O
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+use super::icmp::{read_icmp_error, set_recv_err};
+use crate::{
+    ber::BerHeader,
+    buf::Buffer,
+    error::{IcmpError, SnmpError, SnmpResult},
+    hook::{PacketHook, Tracer},
+    policer::Policer,
+};
+use socket2::{Protocol, SockAddr, SockAddrStorage, Socket, Type};
+use std::ffi::CString;
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, ToSocketAddrs};
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Transport protocol
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Transport {
+    #[default]
+    Udp,
+    // RFC-3430
+    Tcp,
+}
+
+// Constructed SEQUENCE, enclosing the whole message
+const MESSAGE_ID: u8 = 0x30;
+// Largest stream message to receive by default
+const MAX_STREAM_SIZE: usize = 65535;
+
+const TRANSPORT_UDP: u8 = 0;
+const TRANSPORT_TCP: u8 = 1;
+
+impl TryFrom<u8> for Transport {
+    type Error = SnmpError;
+
+    fn try_from(value: u8) -> SnmpResult<Transport> {
+        match value {
+            TRANSPORT_UDP => Ok(Transport::Udp),
+            TRANSPORT_TCP => Ok(Transport::Tcp),
+            _ => Err(SnmpError::SocketError("invalid transport".into())),
+        }
+    }
+}
+
+impl Transport {
+    fn is_stream(&self) -> bool {
+        matches!(self, Transport::Tcp)
+    }
+}
+
+/// Address family preference for hostname resolution
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Family {
+    // Resolver's order
+    #[default]
+    Any,
+    PreferIpv4,
+    PreferIpv6,
+}
+
+const FAMILY_ANY: u8 = 0;
+const FAMILY_IPV4: u8 = 1;
+const FAMILY_IPV6: u8 = 2;
+
+impl TryFrom<u8> for Family {
+    type Error = SnmpError;
+
+    fn try_from(value: u8) -> SnmpResult<Family> {
+        match value {
+            FAMILY_ANY => Ok(Family::Any),
+            FAMILY_IPV4 => Ok(Family::PreferIpv4),
+            FAMILY_IPV6 => Ok(Family::PreferIpv6),
+            _ => Err(SnmpError::SocketError("invalid address family".into())),
+        }
+    }
+}
+
+/// Socket settings
+#[derive(Clone, Default)]
+pub(crate) struct SocketConfig {
+    pub transport: Transport,
+    pub tos: u32,
+    // IPv4 TTL or IPv6 hop limit, 0 - system default
+    pub ttl: u32,
+    pub send_buffer_size: usize,
+    pub recv_buffer_size: usize,
+    pub timeout_ns: u64,
+    // Local address to bind
+    pub bind_addr: Option<String>,
+    // Local port to bind, 0 - ephemeral port
+    pub bind_port: u16,
+    // Bind to network device (SO_BINDTODEVICE)
+    pub bind_device: Option<String>,
+    // Hostname resolution preference
+    pub family: Family,
+    // Retransmissions in blocking mode
+    pub retries: u32,
+    // Timeout of the first attempt, 0 - split `timeout_ns` evenly
+    pub retry_timeout_ns: u64,
+    // Multiplier of the timeout for every next attempt
+    pub backoff: f64,
+    // Shared policer, blocking mode only
+    pub policer: Option<Arc<Policer>>,
+    // Invoked for every sent and received message
+    pub packet_hook: Option<Arc<dyn PacketHook>>,
+    // Largest stream message to receive, 0 - MAX_STREAM_SIZE
+    pub max_msg_size: usize,
+}
+
+/// Retransmission schedule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct RetryPolicy {
+    pub retries: u32,
+    // Timeout of the first attempt
+    pub timeout: Duration,
+    pub backoff: f64,
+}
+
+impl RetryPolicy {
+    /// Retransmission schedule for the request timeout.
+    /// None if retransmission is not applicable.
+    pub fn new(cfg: &SocketConfig, timeout: Duration) -> Option<RetryPolicy> {
+        // Stream transport is reliable, RFC-3430 pp. 2.2
+        if cfg.retries == 0 || cfg.timeout_ns == 0 || cfg.transport.is_stream() {
+            return None;
+        }
+        let first = match cfg.retry_timeout_ns {
+            0 => timeout / (cfg.retries + 1),
+            x => Duration::from_nanos(x).min(timeout),
+        };
+        Some(RetryPolicy {
+            retries: cfg.retries,
+            timeout: first.max(Duration::from_nanos(1)),
+            backoff: cfg.backoff,
+        })
+    }
+    /// Timeout of the next attempt
+    pub fn next_timeout(&self, timeout: Duration) -> Duration {
+        timeout.mul_f64(self.backoff.max(1.0))
+    }
+}
+
+/// Deadlines of the request attempts: the first one and retransmissions.
+/// Each attempt starts when its deadline is requested.
+pub(crate) struct Attempts {
+    policy: Option<RetryPolicy>,
+    left: u32,
+    // Timeout of the next attempt
+    timeout: Duration,
+    // Deadline of the whole request
+    deadline: Instant,
+}
+
+impl Attempts {
+    /// Retransmit according to `policy` until the `deadline`.
+    /// Single attempt without policy.
+    pub fn new(policy: Option<RetryPolicy>, deadline: Instant) -> Self {
+        let (left, timeout) = match policy {
+            Some(p) => (p.retries + 1, p.timeout),
+            None => (1, Duration::ZERO),
+        };
+        Self {
+            policy,
+            left,
+            timeout,
+            deadline,
+        }
+    }
+}
+
+impl Iterator for Attempts {
+    type Item = Instant;
+
+    fn next(&mut self) -> Option<Instant> {
+        let now = Instant::now();
+        if self.left == 0 || now >= self.deadline {
+            return None;
+        }
+        self.left -= 1;
+        let Some(policy) = self.policy else {
+            return Some(self.deadline);
+        };
+        let r = (now + self.timeout).min(self.deadline);
+        self.timeout = policy.next_timeout(self.timeout);
+        Some(r)
+    }
+}
+
+/// Connected client socket.
+/// Hides the difference between datagram and stream transports:
+/// every `recv` returns exactly one SNMP message.
+pub(crate) struct SnmpIo {
+    io: Socket,
+    addr: SockAddr,
+    // Local address
+    bind: Option<SockAddr>,
+    cfg: SocketConfig,
+    // Stream data received but not processed yet
+    rx: Vec<u8>,
+    // Rest of the partially sent stream message
+    tx: Vec<u8>,
+    // Stream is closed by peer and must be reestablished
+    broken: bool,
+    // Monotonic time when the last message has been received,
+    // adjusted by kernel timestamp when available
+    received_at: Option<Instant>,
+    // Round-trip time of the last completed request
+    rtt: Option<Duration>,
+    // Resolved addresses to fall back to until the agent replies,
+    // in reverse order
+    fallback: Vec<SockAddr>,
+    // Last sent message, resent to the fallback address
+    last_sent: Vec<u8>,
+    // Deadline of the current receive
+    deadline: Option<Instant>,
+}
+
+// Unconnected socket is used by Python bindings only
+#[cfg_attr(not(feature = "python"), allow(dead_code))]
+impl SnmpIo {
+    pub fn new(addr: String, cfg: SocketConfig) -> SnmpResult<SnmpIo> {
+        check_policer(&cfg)?;
+        Self::connect_any(resolve(&addr, cfg.family)?, cfg)
+    }
+    /// Connect to the first usable address.
+    /// The rest of addresses are kept until the agent replies:
+    /// unreachable or silent agent is replaced by the next address.
+    fn connect_any(addrs: Vec<SockAddr>, cfg: SocketConfig) -> SnmpResult<SnmpIo> {
+        let mut fallback = addrs;
+        fallback.reverse();
+        let mut last_err = None;
+        while let Some(addr) = fallback.pop() {
+            match Self::connect_to(&addr, &cfg) {
+                Ok((io, bind)) => {
+                    return Ok(SnmpIo {
+                        io,
+                        addr,
+                        bind,
+                        cfg,
+                        rx: Vec::new(),
+                        tx: Vec::new(),
+                        broken: false,
+                        received_at: None,
+                        rtt: None,
+                        fallback,
+                        last_sent: Vec::new(),
+                        deadline: None,
+                    });
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| SnmpError::SocketError("invalid address".into())))
+    }
+    /// Bind and connect socket to `addr`
+    fn connect_to(addr: &SockAddr, cfg: &SocketConfig) -> SnmpResult<(Socket, Option<SockAddr>)> {
+        let bind = bind_addr(addr, cfg)?;
+        Ok((Self::connect(addr, bind.as_ref(), cfg)?, bind))
+    }
+    /// Request timeout, either given or configured one.
+    /// None in non-blocking mode.
+    pub fn timeout(&self, timeout_ns: Option<u64>) -> Option<Duration> {
+        match self.cfg.timeout_ns {
+            0 => None,
+            x => Some(Duration::from_nanos(timeout_ns.unwrap_or(x))),
+        }
+    }
+    /// Retransmission schedule for the request timeout.
+    /// None if retransmission is not applicable.
+    pub fn retry_policy(&self, timeout: Duration) -> Option<RetryPolicy> {
+        RetryPolicy::new(&self.cfg, timeout)
+    }
+    /// Limit next receive by the deadline. Blocking mode only.
+    /// Returns WouldBlock if the deadline is already passed.
+    pub fn set_deadline(&mut self, deadline: Instant) -> SnmpResult<()> {
+        self.deadline = Some(deadline);
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        // Zero timeout means blocking forever
+        if remaining < Duration::from_micros(1) {
+            return Err(SnmpError::WouldBlock);
+        }
+        self.io
+            .set_read_timeout(Some(remaining))
+            .map_err(|e| SnmpError::SocketError(e.to_string()))
+    }
+    /// Round-trip time of the last completed request.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+    // Largest stream message to receive
+    fn max_stream_size(&self) -> usize {
+        match self.cfg.max_msg_size {
+            0 => MAX_STREAM_SIZE,
+            x => x,
+        }
+    }
+    /// Time when the last message has been received
+    pub fn received_at(&self) -> Option<Instant> {
+        self.received_at
+    }
+    /// Request is completed, remember its round-trip time
+    pub fn set_rtt(&mut self, rtt: Option<Duration>) {
+        self.rtt = rtt;
+    }
+    /// Agent's address, actually used
+    pub fn peer_addr(&self) -> String {
+        format_addr(&self.addr)
+    }
+    /// Packet hook, bound to the agent's address
+    pub fn tracer(&self) -> Option<Tracer> {
+        self.tracer_to(&self.addr)
+    }
+    /// Packet hook, bound to `addr`
+    pub fn tracer_to(&self, addr: &SockAddr) -> Option<Tracer> {
+        self.cfg
+            .packet_hook
+            .clone()
+            .map(|hook| Tracer::new(hook, format_addr(addr)))
+    }
+    /// Wait until the policer allows to send the request to the agent.
+    pub fn police(&self) {
+        self.police_to(&self.addr);
+    }
+    /// Wait until the policer allows to send the request to `addr`.
+    pub fn police_to(&self, addr: &SockAddr) {
+        if let Some(policer) = &self.cfg.policer {
+            policer.wait(&format_addr(addr));
+        }
+    }
+    /// Send multiple messages, shaped by policer. Unconnected socket only.
+    /// Messages are sent in chunks, as soon as policer allows.
+    pub fn send_to_many_policed(&mut self, msgs: &[(&[u8], &SockAddr)]) -> SnmpResult<usize> {
+        let Some(policer) = self.cfg.policer.clone() else {
+            return self.send_to_many(msgs);
+        };
+        let now = Instant::now();
+        let ready: Vec<Instant> = msgs
+            .iter()
+            .map(|(_, addr)| policer.reserve_at(&format_addr(addr), now))
+            .collect();
+        let mut sent = 0;
+        let mut result = Ok(());
+        while sent < msgs.len() {
+            let now = Instant::now();
+            if ready[sent] > now {
+                std::thread::sleep(ready[sent] - now);
+                continue;
+            }
+            let n = ready[sent..].iter().take_while(|x| **x <= now).count();
+            match self.send_to_many(&msgs[sent..sent + n]) {
+                Ok(r) => {
+                    sent += r;
+                    if r < n {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        // Give back tokens, reserved for unsent messages
+        for (_, addr) in &msgs[sent..] {
+            policer.release(&format_addr(addr));
+        }
+        result.map(|_| sent)
+    }
+    /// Unconnected datagram socket, exchanging messages
+    /// with arbitrary agents of the same address family.
+    pub fn unconnected(ipv6: bool, cfg: SocketConfig) -> SnmpResult<SnmpIo> {
+        check_policer(&cfg)?;
+        if cfg.transport.is_stream() {
+            return Err(SnmpError::SocketError(
+                "stream transport requires connection".into(),
+            ));
+        }
+        let ip: IpAddr = if ipv6 {
+            Ipv6Addr::UNSPECIFIED.into()
+        } else {
+            Ipv4Addr::UNSPECIFIED.into()
+        };
+        let addr = SockAddr::from(SocketAddr::new(ip, 0));
+        let bind = bind_addr(&addr, &cfg)?;
+        Ok(SnmpIo {
+            io: Self::open(&addr, bind.as_ref(), &cfg)?,
+            addr,
+            bind,
+            cfg,
+            rx: Vec::new(),
+            tx: Vec::new(),
+            broken: false,
+            received_at: None,
+            rtt: None,
+            fallback: Vec::new(),
+            last_sent: Vec::new(),
+            deadline: None,
+        })
+    }
+    /// Release underlying socket. Unconnected socket only.
+    #[cfg(feature = "tokio")]
+    pub fn into_socket(self) -> Socket {
+        self.io
+    }
+    /// Create and connect socket
+    fn connect(addr: &SockAddr, bind: Option<&SockAddr>, cfg: &SocketConfig) -> SnmpResult<Socket> {
+        let io = Self::open(addr, bind, cfg)?;
+        if !cfg.transport.is_stream() && !addr.is_unix() {
+            Self::set_recv_err(&io, addr.is_ipv6())?;
+            Self::set_timestamps(&io)?;
+        }
+        match io.connect(addr) {
+            Ok(_) => {}
+            // Non-blocking stream connection is in progress,
+            // the socket becomes writable when connected.
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            Err(e) => return Err(SnmpError::SocketError(e.to_string())),
+        }
+        Ok(io)
+    }
+    /// Create socket, suitable to reach `addr`, and apply settings
+    fn open(addr: &SockAddr, bind: Option<&SockAddr>, cfg: &SocketConfig) -> SnmpResult<Socket> {
+        let is_unix = addr.is_unix();
+        // Create internal socket
+        let (ty, protocol) = match (cfg.transport, is_unix) {
+            (Transport::Udp, false) => (Type::DGRAM, Some(Protocol::UDP)),
+            (Transport::Tcp, false) => (Type::STREAM, Some(Protocol::TCP)),
+            (Transport::Udp, true) => (Type::DGRAM, None),
+            (Transport::Tcp, true) => (Type::STREAM, None),
+        };
+        let io = Socket::new(addr.domain(), ty, protocol)
+            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        if cfg.timeout_ns > 0 {
+            // Blocking mode
+            let timeout = Some(Duration::from_nanos(cfg.timeout_ns));
+            io.set_read_timeout(timeout)
+                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+            io.set_write_timeout(timeout)
+                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        } else {
+            // Mark socket as non-blocking
+            io.set_nonblocking(true)
+                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        }
+        // Set ToS/Traffic class
+        if cfg.tos > 0 {
+            if addr.is_ipv6() {
+                io.set_tclass_v6(cfg.tos)
+            } else if addr.is_ipv4() {
+                io.set_tos_v4(cfg.tos)
+            } else {
+                Ok(())
+            }
+            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        }
+        // Set TTL/Hop limit
+        if cfg.ttl > 0 {
+            if addr.is_ipv6() {
+                io.set_unicast_hops_v6(cfg.ttl)
+            } else if addr.is_ipv4() {
+                io.set_ttl_v4(cfg.ttl)
+            } else {
+                Ok(())
+            }
+            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        }
+        // Set buffers
+        if cfg.send_buffer_size > 0 {
+            Self::set_send_buffer_size(&io, cfg.send_buffer_size)?;
+        }
+        if cfg.recv_buffer_size > 0 {
+            Self::set_recv_buffer_size(&io, cfg.recv_buffer_size)?;
+        }
+        if cfg.transport.is_stream() && !is_unix {
+            io.set_tcp_nodelay(true)
+                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        }
+        // Bind to device
+        if let Some(device) = &cfg.bind_device {
+            Self::bind_device(&io, device)?;
+        }
+        // Bind to local address
+        if let Some(local) = bind {
+            if cfg.transport.is_stream() {
+                // Allow to reuse the same local port on reconnect
+                io.set_reuse_address(true)
+                    .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+            }
+            io.bind(local)
+                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        }
+        Ok(io)
+    }
+    /// Bind socket to the network device
+    #[cfg(target_os = "linux")]
+    fn bind_device(io: &Socket, device: &str) -> SnmpResult<()> {
+        io.bind_device(Some(device.as_bytes()))
+            .map_err(|e| SnmpError::SocketError(e.to_string()))
+    }
+    /// Bind socket to the network device
+    #[cfg(not(target_os = "linux"))]
+    fn bind_device(_io: &Socket, _device: &str) -> SnmpResult<()> {
+        Err(SnmpError::SocketError(
+            "binding to device is not supported".into(),
+        ))
+    }
+    /// Report ICMP errors to connected datagram socket
+    #[cfg(target_os = "linux")]
+    fn set_recv_err(io: &Socket, ipv6: bool) -> SnmpResult<()> {
+        set_recv_err(io, ipv6).map_err(|e| SnmpError::SocketError(e.to_string()))
+    }
+    /// Report ICMP errors to connected datagram socket
+    #[cfg(not(target_os = "linux"))]
+    fn set_recv_err(_io: &Socket, _ipv6: bool) -> SnmpResult<()> {
+        Ok(())
+    }
+    /// Enable kernel receive timestamps
+    #[cfg(target_os = "linux")]
+    fn set_timestamps(io: &Socket) -> SnmpResult<()> {
+        let on: libc::c_int = 1;
+        let r = unsafe {
+            libc::setsockopt(
+                io.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_TIMESTAMPNS,
+                &on as *const libc::c_int as *const libc::c_void,
+                size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if r < 0 {
+            return Err(SnmpError::SocketError(
+                io::Error::last_os_error().to_string(),
+            ));
+        }
+        Ok(())
+    }
+    /// Enable kernel receive timestamps
+    #[cfg(not(target_os = "linux"))]
+    fn set_timestamps(_io: &Socket) -> SnmpResult<()> {
+        Ok(())
+    }
+    /// Receive datagram along with kernel timestamp
+    #[cfg(target_os = "linux")]
+    fn recv_timestamped(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<(usize, SystemTime)> {
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+        // u64 for cmsghdr alignment
+        let mut control = [MaybeUninit::<u64>::uninit(); 8];
+        let mut hdr: libc::msghdr = unsafe { std::mem::zeroed() };
+        hdr.msg_iov = &mut iov;
+        hdr.msg_iovlen = 1;
+        hdr.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        hdr.msg_controllen = size_of_val(&control) as _;
+        let r = unsafe { libc::recvmsg(self.io.as_raw_fd(), &mut hdr, 0) };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut ts = None;
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&hdr) };
+        while !cmsg.is_null() {
+            let (level, kind) = unsafe { ((*cmsg).cmsg_level, (*cmsg).cmsg_type) };
+            if level == libc::SOL_SOCKET && kind == libc::SCM_TIMESTAMPNS {
+                let t = unsafe {
+                    std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::timespec)
+                };
+                ts = Some(UNIX_EPOCH + Duration::new(t.tv_sec as u64, t.tv_nsec as u32));
+            }
+            cmsg = unsafe { libc::CMSG_NXTHDR(&hdr, cmsg) };
+        }
+        Ok((r as usize, ts.unwrap_or_else(SystemTime::now)))
+    }
+    /// Receive datagram along with timestamp
+    #[cfg(not(target_os = "linux"))]
+    fn recv_timestamped(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<(usize, SystemTime)> {
+        let size = self.io.recv(buf)?;
+        Ok((size, SystemTime::now()))
+    }
+    /// Get pending ICMP error
+    #[cfg(target_os = "linux")]
+    fn icmp_error(&self) -> Option<IcmpError> {
+        read_icmp_error(&self.io)
+    }
+    /// Get pending ICMP error
+    #[cfg(not(target_os = "linux"))]
+    fn icmp_error(&self) -> Option<IcmpError> {
+        None
+    }
+    /// Set internal socket's send buffer size
+    fn set_send_buffer_size(io: &Socket, size: usize) -> SnmpResult<()> {
+        // @todo: get wmem_max limit on Linux
+        let mut effective_size = size;
+        while effective_size > 0 {
+            if io.set_send_buffer_size(effective_size).is_ok() {
+                return Ok(());
+            }
+            effective_size >>= 1;
+        }
+        Err(SnmpError::SocketError("unable to set buffer size".into()))
+    }
+    /// Set internal socket's receive buffer size
+    fn set_recv_buffer_size(io: &Socket, size: usize) -> SnmpResult<()> {
+        let mut effective_size = size;
+        while effective_size > 0 {
+            if io.set_recv_buffer_size(effective_size).is_ok() {
+                return Ok(());
+            }
+            effective_size >>= 1;
+        }
+        Err(SnmpError::SocketError("unable to set buffer size".into()))
+    }
+    /// Reestablish the stream connection.
+    /// New connection replaces the old one on the same file descriptor,
+    /// so the descriptor, registered in the event loop, remains valid.
+    fn reconnect(&mut self) -> SnmpResult<()> {
+        let io = Self::connect(&self.addr, self.bind.as_ref(), &self.cfg)?;
+        self.replace(io)
+    }
+    /// Replace the socket, keeping the file descriptor
+    fn replace(&mut self, io: Socket) -> SnmpResult<()> {
+        if unsafe { libc::dup2(io.as_raw_fd(), self.io.as_raw_fd()) } < 0 {
+            return Err(SnmpError::SocketError(
+                io::Error::last_os_error().to_string(),
+            ));
+        }
+        self.rx.clear();
+        self.tx.clear();
+        self.broken = false;
+        Ok(())
+    }
+    /// Switch to the next resolved address, keeping the file descriptor.
+    /// Returns false if no address is left.
+    fn fall_back(&mut self) -> bool {
+        while let Some(addr) = self.fallback.pop() {
+            let Ok((io, bind)) = Self::connect_to(&addr, &self.cfg) else {
+                continue;
+            };
+            if self.replace(io).is_ok() {
+                self.addr = addr;
+                self.bind = bind;
+                return true;
+            }
+        }
+        false
+    }
+    /// Error is caused by the unreachable agent
+    fn is_unreachable(e: &SnmpError) -> bool {
+        matches!(e, SnmpError::Icmp(_) | SnmpError::ConnectionRefused)
+    }
+    /// Send message.
+    /// Unreachable agent is replaced by the next resolved address
+    /// until the agent replies.
+    pub fn send(&mut self, data: &[u8]) -> SnmpResult<()> {
+        if !self.fallback.is_empty() {
+            self.last_sent.clear();
+            self.last_sent.extend_from_slice(data);
+        }
+        loop {
+            match self.send_once(data) {
+                Err(e) if Self::is_unreachable(&e) && self.fall_back() => {}
+                r => return r,
+            }
+        }
+    }
+    fn send_once(&mut self, data: &[u8]) -> SnmpResult<()> {
+        if !self.cfg.transport.is_stream() {
+            return match self.io.send(data) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(self.map_connected_dgram_error(e)),
+            };
+        }
+        if self.broken {
+            self.reconnect()?;
+        }
+        match self.send_stream(data) {
+            Err(SnmpError::ConnectionReset) => {
+                // Connection is closed by peer, retry once
+                self.reconnect()?;
+                self.send_stream(data)
+            }
+            r => r,
+        }
+    }
+    fn send_stream(&mut self, data: &[u8]) -> SnmpResult<()> {
+        // Complete the previous message first to keep stream framing
+        self.flush()?;
+        match self.io.send_with_flags(data, libc::MSG_NOSIGNAL) {
+            Ok(n) if n == data.len() => Ok(()),
+            Ok(n) if self.cfg.timeout_ns == 0 => {
+                // Non-blocking mode. Keep the rest of the message
+                // to be sent before the next one.
+                self.tx.extend_from_slice(&data[n..]);
+                Err(SnmpError::WouldBlock)
+            }
+            Ok(_) => {
+                // Write timeout is expired, framing is lost.
+                // Connection must be reestablished.
+                self.broken = true;
+                Err(SnmpError::Timeout)
+            }
+            Err(e) => Err(Self::map_stream_error(e)),
+        }
+    }
+    // Send the rest of the partially sent message.
+    // Returns WouldBlock until the whole rest is sent.
+    fn flush(&mut self) -> SnmpResult<()> {
+        while !self.tx.is_empty() {
+            match self
+                .io
+                .send_with_flags(&self.tx, libc::MSG_NOSIGNAL | libc::MSG_DONTWAIT)
+            {
+                Ok(n) => {
+                    self.tx.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Err(SnmpError::WouldBlock);
+                }
+                Err(e) => {
+                    // Framing is lost, connection must be reestablished
+                    self.broken = true;
+                    return Err(Self::map_stream_error(e));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn map_stream_error(e: io::Error) -> SnmpError {
+        match e.kind() {
+            io::ErrorKind::WouldBlock => SnmpError::WouldBlock,
+            io::ErrorKind::ConnectionRefused => SnmpError::ConnectionRefused,
+            io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected => SnmpError::ConnectionReset,
+            _ => SnmpError::SocketError(e.to_string()),
+        }
+    }
+    /// Send message to the agent. Unconnected socket only.
+    pub fn send_to(&mut self, data: &[u8], addr: &SockAddr) -> SnmpResult<()> {
+        self.io
+            .send_to(data, addr)
+            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        Ok(())
+    }
+    /// Receive single message along with the sender's address.
+    /// Unconnected socket only.
+    pub fn recv_from<'a>(&mut self, buf: &'a mut Buffer) -> SnmpResult<(&'a [u8], SockAddr)> {
+        match self.io.recv_from(buf.as_mut()) {
+            Ok((s, addr)) => Ok((buf.as_slice(s), addr)),
+            Err(e) => Err(Self::map_dgram_error(e)),
+        }
+    }
+    /// Send multiple messages with a single syscall. Unconnected socket only.
+    /// Returns the number of messages sent, which may be less than requested
+    /// when non-blocking socket's buffer is full.
+    #[cfg(target_os = "linux")]
+    pub fn send_to_many(&mut self, msgs: &[(&[u8], &SockAddr)]) -> SnmpResult<usize> {
+        let mut iov: Vec<libc::iovec> = msgs
+            .iter()
+            .map(|(data, _)| libc::iovec {
+                iov_base: data.as_ptr() as *mut libc::c_void,
+                iov_len: data.len(),
+            })
+            .collect();
+        let mut hdrs: Vec<libc::mmsghdr> = msgs
+            .iter()
+            .zip(iov.iter_mut())
+            .map(|((_, addr), iov)| {
+                let mut hdr: libc::mmsghdr = unsafe { std::mem::zeroed() };
+                hdr.msg_hdr.msg_name = addr.as_ptr() as *mut libc::c_void;
+                hdr.msg_hdr.msg_namelen = addr.len();
+                hdr.msg_hdr.msg_iov = iov;
+                hdr.msg_hdr.msg_iovlen = 1;
+                hdr
+            })
+            .collect();
+        let mut sent = 0;
+        while sent < hdrs.len() {
+            let r = unsafe {
+                libc::sendmmsg(
+                    self.io.as_raw_fd(),
+                    hdrs[sent..].as_mut_ptr(),
+                    (hdrs.len() - sent) as libc::c_uint,
+                    0 as _,
+                )
+            };
+            if r < 0 {
+                let e = io::Error::last_os_error();
+                if sent > 0 && e.kind() == io::ErrorKind::WouldBlock {
+                    break;
+                }
+                return Err(Self::map_dgram_error(e));
+            }
+            sent += r as usize;
+        }
+        Ok(sent)
+    }
+    /// Send multiple messages. Unconnected socket only.
+    /// Returns the number of messages sent, which may be less than requested
+    /// when non-blocking socket's buffer is full.
+    #[cfg(not(target_os = "linux"))]
+    pub fn send_to_many(&mut self, msgs: &[(&[u8], &SockAddr)]) -> SnmpResult<usize> {
+        for (sent, (data, addr)) in msgs.iter().enumerate() {
+            if let Err(e) = self.io.send_to(data, addr) {
+                if sent > 0 && e.kind() == io::ErrorKind::WouldBlock {
+                    return Ok(sent);
+                }
+                return Err(Self::map_dgram_error(e));
+            }
+        }
+        Ok(msgs.len())
+    }
+    /// Receive up to `bufs.len()` messages with a single syscall.
+    /// Waits for the first message only.
+    /// Returns size and sender's address of every received message,
+    /// n-th message is placed into n-th buffer. Unconnected socket only.
+    #[cfg(target_os = "linux")]
+    pub fn recv_from_many(
+        &mut self,
+        bufs: &mut [&mut Buffer],
+    ) -> SnmpResult<Vec<(usize, SockAddr)>> {
+        let mut addrs: Vec<libc::sockaddr_storage> =
+            vec![unsafe { std::mem::zeroed() }; bufs.len()];
+        let mut iov: Vec<libc::iovec> = bufs
+            .iter_mut()
+            .map(|buf| {
+                let data: &mut [MaybeUninit<u8>] = buf.as_mut();
+                libc::iovec {
+                    iov_base: data.as_mut_ptr() as *mut libc::c_void,
+                    iov_len: data.len(),
+                }
+            })
+            .collect();
+        let mut hdrs: Vec<libc::mmsghdr> = addrs
+            .iter_mut()
+            .zip(iov.iter_mut())
+            .map(|(addr, iov)| {
+                let mut hdr: libc::mmsghdr = unsafe { std::mem::zeroed() };
+                hdr.msg_hdr.msg_name = addr as *mut libc::sockaddr_storage as *mut libc::c_void;
+                hdr.msg_hdr.msg_namelen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+                hdr.msg_hdr.msg_iov = iov;
+                hdr.msg_hdr.msg_iovlen = 1;
+                hdr
+            })
+            .collect();
+        let r = unsafe {
+            libc::recvmmsg(
+                self.io.as_raw_fd(),
+                hdrs.as_mut_ptr(),
+                hdrs.len() as libc::c_uint,
+                libc::MSG_WAITFORONE as _,
+                std::ptr::null_mut(),
+            )
+        };
+        if r < 0 {
+            return Err(Self::map_dgram_error(io::Error::last_os_error()));
+        }
+        Ok(hdrs
+            .iter()
+            .zip(addrs.iter())
+            .take(r as usize)
+            .map(|(hdr, addr)| {
+                let mut storage = SockAddrStorage::zeroed();
+                unsafe {
+                    *storage.view_as::<libc::sockaddr_storage>() = *addr;
+                    (
+                        hdr.msg_len as usize,
+                        SockAddr::new(storage, hdr.msg_hdr.msg_namelen),
+                    )
+                }
+            })
+            .collect())
+    }
+    /// Receive single message. Unconnected socket only.
+    #[cfg(not(target_os = "linux"))]
+    pub fn recv_from_many(
+        &mut self,
+        bufs: &mut [&mut Buffer],
+    ) -> SnmpResult<Vec<(usize, SockAddr)>> {
+        let Some(buf) = bufs.first_mut() else {
+            return Ok(Vec::new());
+        };
+        match self.io.recv_from(buf.as_mut()) {
+            Ok((size, addr)) => Ok(vec![(size, addr)]),
+            Err(e) => Err(Self::map_dgram_error(e)),
+        }
+    }
+    fn map_dgram_error(e: io::Error) -> SnmpError {
+        match e.kind() {
+            io::ErrorKind::WouldBlock => SnmpError::WouldBlock,
+            io::ErrorKind::ConnectionRefused => SnmpError::ConnectionRefused,
+            _ => SnmpError::SocketError(e.to_string()),
+        }
+    }
+    // Map error of connected datagram socket.
+    // Pending ICMP error takes precedence, even on WouldBlock:
+    // non-empty error queue keeps the socket readable.
+    fn map_connected_dgram_error(&self, e: io::Error) -> SnmpError {
+        match self.icmp_error() {
+            Some(icmp) => SnmpError::Icmp(icmp),
+            None => Self::map_dgram_error(e),
+        }
+    }
+    /// Receive single message.
+    /// Until the agent replies, unreachable agent is replaced
+    /// by the next resolved address and the last message is resent.
+    /// Timed out agent is replaced as well, so the retransmission
+    /// or the next request goes to the next address.
+    pub fn recv<'a>(&mut self, buf: &'a mut Buffer) -> SnmpResult<&'a [u8]> {
+        loop {
+            match self.recv_once(buf) {
+                Ok(size) => {
+                    // Address is confirmed
+                    self.fallback.clear();
+                    self.last_sent.clear();
+                    return Ok(buf.as_slice(size));
+                }
+                Err(e) => self.recover(e)?,
+            }
+        }
+    }
+    // Fall back to the next address on receive error, if possible.
+    // Returns Ok if the last message is resent and the reply
+    // must be awaited again.
+    fn recover(&mut self, e: SnmpError) -> SnmpResult<()> {
+        if self.last_sent.is_empty() {
+            return Err(e);
+        }
+        match e {
+            e if Self::is_unreachable(&e) && self.fall_back() => {
+                let data = std::mem::take(&mut self.last_sent);
+                self.police();
+                self.send(&data)?;
+                if let Some(tracer) = self.tracer() {
+                    tracer.sent(&data, None);
+                }
+                match self.deadline {
+                    // Replaced socket has the default timeout
+                    Some(d) if self.cfg.timeout_ns > 0 => self.set_deadline(d),
+                    _ => Ok(()),
+                }
+            }
+            // Blocking mode timeout
+            SnmpError::WouldBlock if self.cfg.timeout_ns > 0 => {
+                self.fall_back();
+                Err(SnmpError::WouldBlock)
+            }
+            e => Err(e),
+        }
+    }
+    // Receive single message into the beginning of the buffer.
+    // Returns message size.
+    fn recv_once(&mut self, buf: &mut Buffer) -> SnmpResult<usize> {
+        if !self.cfg.transport.is_stream() {
+            return match self.recv_timestamped(buf.as_mut()) {
+                Ok((s, ts)) => {
+                    // Time spent in the socket's queue
+                    let queued = SystemTime::now().duration_since(ts).unwrap_or_default();
+                    let now = Instant::now();
+                    self.received_at = Some(now.checked_sub(queued).unwrap_or(now));
+                    Ok(s)
+                }
+                Err(e) => Err(self.map_connected_dgram_error(e)),
+            };
+        }
+        loop {
+            // Check if the whole message is already received
+            if let Some(size) = frame_size(&self.rx)? {
+                if size > self.max_stream_size() {
+                    // Cannot be processed, drop the connection
+                    self.rx.clear();
+                    self.broken = true;
+                    return Err(SnmpError::OutOfBuffer);
+                }
+                buf.put_received(&self.rx[..size]);
+                self.rx.drain(..size);
+                self.received_at = Some(Instant::now());
+                return Ok(size);
+            }
+            // Wait for more data
+            match self.io.recv(buf.as_mut()) {
+                Ok(0) => {
+                    self.broken = true;
+                    return Err(SnmpError::SocketError("connection closed by peer".into()));
+                }
+                Ok(s) => self.rx.extend_from_slice(buf.as_slice(s)),
+                Err(e) => {
+                    let e = Self::map_stream_error(e);
+                    if matches!(e, SnmpError::ConnectionReset) {
+                        self.broken = true;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl AsRawFd for SnmpIo {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+// Parse agent address.
+// Absolute path refers to the Unix domain socket,
+// anything else must be an `address:port` pair.
+fn parse_addr(addr: &str) -> SnmpResult<SockAddr> {
+    if addr.starts_with('/') {
+        return SockAddr::unix(addr).map_err(|e| SnmpError::SocketError(e.to_string()));
+    }
+    if let Ok(sock_addr) = addr.parse::<SocketAddr>() {
+        return Ok(SockAddr::from(sock_addr));
+    }
+    // Link-local IPv6 address with the interface name as scope,
+    // i.e. [fe80::1%eth0]:161
+    let invalid = || SnmpError::SocketError("invalid address".into());
+    let (host, port) = addr
+        .strip_prefix('[')
+        .and_then(|x| x.split_once("]:"))
+        .ok_or_else(invalid)?;
+    let (ip, scope) = host.split_once('%').ok_or_else(invalid)?;
+    let ip: Ipv6Addr = ip.parse().map_err(|_| invalid())?;
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    Ok(SockAddr::from(SocketAddrV6::new(
+        ip,
+        port,
+        0,
+        scope_id(scope)?,
+    )))
+}
+
+// Resolve agent's address to the list of candidates.
+// Literal addresses are returned as is, hostnames
+// are resolved and ordered according to family preference.
+pub(crate) fn resolve(addr: &str, family: Family) -> SnmpResult<Vec<SockAddr>> {
+    if let Ok(x) = parse_addr(addr) {
+        return Ok(vec![x]);
+    }
+    let mut addrs: Vec<SocketAddr> = addr
+        .to_socket_addrs()
+        .map_err(|e| SnmpError::SocketError(format!("invalid address: {e}")))?
+        .collect();
+    // Stable sort keeps resolver's order within the family
+    match family {
+        Family::Any => {}
+        Family::PreferIpv4 => addrs.sort_by_key(|x| x.is_ipv6()),
+        Family::PreferIpv6 => addrs.sort_by_key(|x| x.is_ipv4()),
+    }
+    Ok(addrs.into_iter().map(SockAddr::from).collect())
+}
+
+// Normalize address to match the replies.
+// Flow label is not the part of agent's identity.
+#[cfg(any(feature = "python", feature = "tokio"))]
+pub(crate) fn target_key(addr: SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V6(mut x) => {
+            x.set_flowinfo(0);
+            SocketAddr::V6(x)
+        }
+        x => x,
+    }
+}
+
+// Resolve IPv6 scope id, either numeric or interface name.
+fn scope_id(scope: &str) -> SnmpResult<u32> {
+    if let Ok(x) = scope.parse::<u32>() {
+        return Ok(x);
+    }
+    let name =
+        CString::new(scope).map_err(|_| SnmpError::SocketError("invalid interface name".into()))?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(SnmpError::SocketError(format!(
+            "unknown interface: {scope}"
+        ))),
+        x => Ok(x),
+    }
+}
+
+// Policer may sleep, which is not allowed in non-blocking mode
+fn check_policer(cfg: &SocketConfig) -> SnmpResult<()> {
+    if cfg.policer.is_some() && cfg.timeout_ns == 0 {
+        return Err(SnmpError::SocketError(
+            "policer requires blocking mode".into(),
+        ));
+    }
+    Ok(())
+}
+
+// Format address as `address:port` or path to Unix domain socket
+fn format_addr(addr: &SockAddr) -> String {
+    match addr.as_socket() {
+        Some(x) => x.to_string(),
+        None => addr
+            .as_pathname()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+// Local address of the Unix datagram socket.
+// Empty path requests autobind to the unique abstract address.
+#[cfg(target_os = "linux")]
+fn unix_autobind_addr() -> SnmpResult<SockAddr> {
+    SockAddr::unix("").map_err(|e| SnmpError::SocketError(e.to_string()))
+}
+
+// Local address of the Unix datagram socket.
+// Autobind is Linux-only, elsewhere the socket remains unnamed
+// and the agent is unable to reply.
+#[cfg(not(target_os = "linux"))]
+fn unix_autobind_addr() -> SnmpResult<SockAddr> {
+    Err(SnmpError::SocketError(
+        "unix datagram transport is not supported, use TCP".into(),
+    ))
+}
+
+// Get local address to bind the socket.
+fn bind_addr(addr: &SockAddr, cfg: &SocketConfig) -> SnmpResult<Option<SockAddr>> {
+    if addr.is_unix() {
+        if cfg.bind_addr.is_some() || cfg.bind_port > 0 {
+            return Err(SnmpError::SocketError(
+                "cannot bind unix socket to address".into(),
+            ));
+        }
+        if cfg.transport.is_stream() {
+            return Ok(None);
+        }
+        // Unnamed datagram socket cannot receive replies
+        return unix_autobind_addr().map(Some);
+    }
+    let ip: IpAddr = match &cfg.bind_addr {
+        Some(x) => x
+            .parse()
+            .map_err(|_| SnmpError::SocketError("invalid bind address".into()))?,
+        None if cfg.bind_port == 0 => return Ok(None),
+        // Wildcard address of the target's family
+        None if addr.is_ipv6() => Ipv6Addr::UNSPECIFIED.into(),
+        None => Ipv4Addr::UNSPECIFIED.into(),
+    };
+    if ip.is_ipv6() != addr.is_ipv6() {
+        return Err(SnmpError::SocketError(
+            "bind address family mismatch".into(),
+        ));
+    }
+    Ok(Some(SockAddr::from(SocketAddr::new(ip, cfg.bind_port))))
+}
+
+// Get the size of the first complete BER-framed message in the stream,
+// RFC-3430 pp. 2.1.
+// Returns None if more data must be received.
+fn frame_size(data: &[u8]) -> SnmpResult<Option<usize>> {
+    if data.len() < 2 {
+        return Ok(None);
+    }
+    if data[0] != MESSAGE_ID {
+        return Err(SnmpError::UnexpectedTag.with_tags(Some(MESSAGE_ID), data[0]));
+    }
+    // Long form length, X.690 pp 8.1.3.5
+    if data[1] & 0x80 != 0 && data.len() < 2 + (data[1] & 0x7f) as usize {
+        return Ok(None);
+    }
+    match BerHeader::from_ber(data) {
+        Ok((tail, hdr)) => Ok(Some(data.len() - tail.len() + hdr.length)),
+        Err(nom::Err::Incomplete(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport() -> SnmpResult<()> {
+        assert_eq!(Transport::try_from(0)?, Transport::Udp);
+        assert_eq!(Transport::try_from(1)?, Transport::Tcp);
+        assert!(Transport::try_from(2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_size_short() -> SnmpResult<()> {
+        assert_eq!(frame_size(&[])?, None);
+        assert_eq!(frame_size(&[0x30])?, None);
+        assert_eq!(frame_size(&[0x30, 3, 2, 1])?, None);
+        assert_eq!(frame_size(&[0x30, 3, 2, 1, 0])?, Some(5));
+        assert_eq!(frame_size(&[0x30, 3, 2, 1, 0, 0x30])?, Some(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_size_long() -> SnmpResult<()> {
+        let mut data = vec![0x30, 0x82, 0x01];
+        assert_eq!(frame_size(&data)?, None);
+        data.push(0x00);
+        assert_eq!(frame_size(&data)?, None);
+        data.resize(4 + 256, 0);
+        assert_eq!(frame_size(&data)?, Some(260));
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_size_invalid() {
+        assert!(frame_size(&[0x04, 1, 0]).is_err());
+    }
+
+    #[test]
+    fn test_tcp_reassembly() -> SnmpResult<()> {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            // First connection: message is split across writes
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut req = [0u8; 5];
+            conn.read_exact(&mut req).unwrap();
+            conn.write_all(&[0x30, 3, 2]).unwrap();
+            conn.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            conn.write_all(&[1, 1, 0x30, 3, 2, 1, 2]).unwrap();
+            drop(conn);
+            // Second connection after reconnect
+            let (mut conn, _) = listener.accept().unwrap();
+            conn.read_exact(&mut req).unwrap();
+            conn.write_all(&req).unwrap();
+        });
+        let mut io = SnmpIo::new(
+            addr,
+            SocketConfig {
+                transport: Transport::Tcp,
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        let mut buf = Buffer::default();
+        io.send(&[0x30, 3, 2, 1, 0])?;
+        assert_eq!(io.recv(&mut buf)?, &[0x30, 3, 2, 1, 1]);
+        assert_eq!(io.recv(&mut buf)?, &[0x30, 3, 2, 1, 2]);
+        // Connection is closed by peer
+        assert!(io.recv(&mut buf).is_err());
+        // Reconnect on send
+        io.send(&[0x30, 3, 2, 1, 3])?;
+        assert_eq!(io.recv(&mut buf)?, &[0x30, 3, 2, 1, 3]);
+        server.join().unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_tcp_large_frame() -> SnmpResult<()> {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // Larger than the buffer
+        let mut msg = vec![0x30, 0x82, 0x13, 0x88];
+        msg.extend((0..5000).map(|x| x as u8));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let reply = msg.clone();
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut conn, _) = listener.accept().unwrap();
+                let mut req = [0u8; 5];
+                conn.read_exact(&mut req).unwrap();
+                conn.write_all(&reply).unwrap();
+                // Wait for client to close connection
+                let _ = conn.read(&mut req);
+            }
+        });
+        let new = |max_msg_size| {
+            SnmpIo::new(
+                addr.clone(),
+                SocketConfig {
+                    transport: Transport::Tcp,
+                    timeout_ns: 5_000_000_000,
+                    max_msg_size,
+                    ..Default::default()
+                },
+            )
+        };
+        let mut buf = Buffer::default();
+        let mut io = new(0)?;
+        io.send(&[0x30, 3, 2, 1, 0])?;
+        assert_eq!(io.recv(&mut buf)?, msg.as_slice());
+        drop(io);
+        // Limited by max_msg_size
+        buf.reset();
+        let mut io = new(4096)?;
+        io.send(&[0x30, 3, 2, 1, 0])?;
+        assert!(matches!(io.recv(&mut buf), Err(SnmpError::OutOfBuffer)));
+        drop(io);
+        server.join().unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_tcp_send_timeout() -> SnmpResult<()> {
+        use std::net::TcpListener;
+
+        // Peer never reads, so the message doesn't fit socket buffers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut io = SnmpIo::new(
+            listener.local_addr().unwrap().to_string(),
+            SocketConfig {
+                transport: Transport::Tcp,
+                timeout_ns: 200_000_000,
+                ..Default::default()
+            },
+        )?;
+        let (_conn, _) = listener.accept().unwrap();
+        let msg = vec![0u8; 64 << 20];
+        let t0 = Instant::now();
+        assert!(matches!(io.send(&msg), Err(SnmpError::Timeout)));
+        assert!(t0.elapsed() < Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tcp_send_nonblocking() -> SnmpResult<()> {
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut io = SnmpIo::new(
+            listener.local_addr().unwrap().to_string(),
+            SocketConfig {
+                transport: Transport::Tcp,
+                ..Default::default()
+            },
+        )?;
+        let (mut conn, _) = listener.accept().unwrap();
+        // Peer doesn't read yet, so the message doesn't fit socket buffers
+        let msg = vec![0u8; 64 << 20];
+        let small = [0x30, 3, 2, 1, 0];
+        let t0 = Instant::now();
+        assert!(matches!(io.send(&msg), Err(SnmpError::WouldBlock)));
+        assert!(!io.tx.is_empty());
+        // The rest of the message must be sent first
+        assert!(matches!(io.send(&small), Err(SnmpError::WouldBlock)));
+        assert!(t0.elapsed() < Duration::from_secs(1));
+        let total = msg.len() + small.len();
+        let reader = std::thread::spawn(move || {
+            let mut data = Vec::with_capacity(total);
+            let mut chunk = vec![0u8; 1 << 16];
+            while data.len() < total {
+                let n = conn.read(&mut chunk).unwrap();
+                assert!(n > 0);
+                data.extend_from_slice(&chunk[..n]);
+            }
+            data
+        });
+        // Retry when the socket becomes writable
+        loop {
+            match io.send(&small) {
+                Ok(_) => break,
+                Err(SnmpError::WouldBlock) => std::thread::sleep(Duration::from_millis(1)),
+                Err(e) => return Err(e),
+            }
+        }
+        assert!(io.tx.is_empty());
+        let data = reader.join().unwrap();
+        assert_eq!(data.len(), total);
+        assert_eq!(&data[msg.len()..], small.as_slice());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_addr() -> SnmpResult<()> {
+        assert!(parse_addr("127.0.0.1:161")?.is_ipv4());
+        assert!(parse_addr("[::1]:161")?.is_ipv6());
+        let addr = parse_addr("/var/run/snmpd.sock")?;
+        assert!(addr.is_unix());
+        assert_eq!(
+            addr.as_pathname(),
+            Some(std::path::Path::new("/var/run/snmpd.sock"))
+        );
+        assert!(parse_addr("localhost").is_err());
+        // Scoped link-local addresses
+        let addr = parse_addr("[fe80::1%2]:161")?.as_socket_ipv6().unwrap();
+        assert_eq!(addr.scope_id(), 2);
+        let addr = parse_addr("[fe80::1%lo]:161")?.as_socket_ipv6().unwrap();
+        assert_eq!(addr.scope_id(), scope_id("lo")?);
+        assert_eq!(addr.port(), 161);
+        assert!(parse_addr("[fe80::1%nonexistent0]:161").is_err());
+        assert!(parse_addr("[fe80::1%lo]").is_err());
+        Ok(())
+    }
+
+    fn unix_path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("gufo-snmp-{}-{}.sock", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unix_dgram() -> SnmpResult<()> {
+        use std::os::unix::net::UnixDatagram;
+
+        let path = unix_path("dgram");
+        let server = UnixDatagram::bind(&path).unwrap();
+        let mut io = SnmpIo::new(
+            path.clone(),
+            SocketConfig {
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        io.send(&[0x30, 3, 2, 1, 0])?;
+        let mut req = [0u8; 16];
+        let (n, peer) = server.recv_from(&mut req).unwrap();
+        server.send_to_addr(&req[..n], &peer).unwrap();
+        let mut buf = Buffer::default();
+        assert_eq!(io.recv(&mut buf)?, &[0x30, 3, 2, 1, 0]);
+        std::fs::remove_file(&path).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_unix_stream() -> SnmpResult<()> {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let path = unix_path("stream");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut req = [0u8; 5];
+            conn.read_exact(&mut req).unwrap();
+            conn.write_all(&req).unwrap();
+        });
+        let mut io = SnmpIo::new(
+            path.clone(),
+            SocketConfig {
+                transport: Transport::Tcp,
+                tos: 0x10,
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        io.send(&[0x30, 3, 2, 1, 4])?;
+        let mut buf = Buffer::default();
+        assert_eq!(io.recv(&mut buf)?, &[0x30, 3, 2, 1, 4]);
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_addr() -> SnmpResult<()> {
+        let v4 = parse_addr("127.0.0.1:161")?;
+        let v6 = parse_addr("[::1]:161")?;
+        // No bind
+        assert!(bind_addr(&v4, &SocketConfig::default())?.is_none());
+        // Port only
+        let cfg = SocketConfig {
+            bind_port: 1161,
+            ..Default::default()
+        };
+        assert_eq!(
+            bind_addr(&v4, &cfg)?.and_then(|x| x.as_socket()),
+            Some("0.0.0.0:1161".parse().unwrap())
+        );
+        assert_eq!(
+            bind_addr(&v6, &cfg)?.and_then(|x| x.as_socket()),
+            Some("[::]:1161".parse().unwrap())
+        );
+        // Address
+        let cfg = SocketConfig {
+            bind_addr: Some("127.0.0.2".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            bind_addr(&v4, &cfg)?.and_then(|x| x.as_socket()),
+            Some("127.0.0.2:0".parse().unwrap())
+        );
+        assert!(bind_addr(&v6, &cfg).is_err());
+        // Invalid address
+        let cfg = SocketConfig {
+            bind_addr: Some("x".into()),
+            ..Default::default()
+        };
+        assert!(bind_addr(&v4, &cfg).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut io = SnmpIo::new(
+            server.local_addr().unwrap().to_string(),
+            SocketConfig {
+                timeout_ns: 5_000_000_000,
+                bind_addr: Some("127.0.0.1".into()),
+                ..Default::default()
+            },
+        )?;
+        io.send(&[0x30, 3, 2, 1, 0])?;
+        let mut req = [0u8; 16];
+        let (_, peer) = server.recv_from(&mut req).unwrap();
+        assert_eq!(peer.ip(), "127.0.0.1".parse::<IpAddr>().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scope_id() -> SnmpResult<()> {
+        assert_eq!(scope_id("3")?, 3);
+        assert!(scope_id("lo")? > 0);
+        assert!(scope_id("lo\0").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ipv6_options() -> SnmpResult<()> {
+        let server = match std::net::UdpSocket::bind("[::1]:0") {
+            Ok(x) => x,
+            // IPv6 is disabled
+            Err(_) => return Ok(()),
+        };
+        let io = SnmpIo::new(
+            server.local_addr().unwrap().to_string(),
+            SocketConfig {
+                tos: 0x20,
+                ttl: 7,
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(io.io.tclass_v6().unwrap(), 0x20);
+        assert_eq!(io.io.unicast_hops_v6().unwrap(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ipv4_options() -> SnmpResult<()> {
+        let io = SnmpIo::new(
+            "127.0.0.1:161".into(),
+            SocketConfig {
+                tos: 0x20,
+                ttl: 7,
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(io.io.tos_v4().unwrap(), 0x20);
+        assert_eq!(io.io.ttl_v4().unwrap(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unconnected() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+
+        let cfg = SocketConfig {
+            timeout_ns: 5_000_000_000,
+            ..Default::default()
+        };
+        let mut io = SnmpIo::unconnected(false, cfg)?;
+        let s1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let s2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut req = [0u8; 16];
+        for (n, s) in [&s1, &s2].iter().enumerate() {
+            let addr = SockAddr::from(s.local_addr().unwrap());
+            io.send_to(&[0x30, 3, 2, 1, n as u8], &addr)?;
+            let (size, peer) = s.recv_from(&mut req).unwrap();
+            s.send_to(&req[..size], peer).unwrap();
+        }
+        let mut buf = Buffer::default();
+        for (n, s) in [&s1, &s2].iter().enumerate() {
+            let (data, addr) = io.recv_from(&mut buf)?;
+            assert_eq!(data, &[0x30, 3, 2, 1, n as u8]);
+            assert_eq!(addr.as_socket(), Some(s.local_addr().unwrap()));
+            buf.reset();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unconnected_stream() {
+        let cfg = SocketConfig {
+            transport: Transport::Tcp,
+            ..Default::default()
+        };
+        assert!(SnmpIo::unconnected(false, cfg).is_err());
+    }
+
+    #[test]
+    fn test_many() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+
+        let cfg = SocketConfig {
+            timeout_ns: 5_000_000_000,
+            ..Default::default()
+        };
+        let mut io = SnmpIo::unconnected(false, cfg)?;
+        let s1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let s2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let a1 = SockAddr::from(s1.local_addr().unwrap());
+        let a2 = SockAddr::from(s2.local_addr().unwrap());
+        let sent = io.send_to_many(&[
+            (&[0x30, 3, 2, 1, 1], &a1),
+            (&[0x30, 3, 2, 1, 2], &a2),
+            (&[0x30, 3, 2, 1, 3], &a1),
+        ])?;
+        assert_eq!(sent, 3);
+        // Echo
+        let mut req = [0u8; 16];
+        for s in [&s1, &s2, &s1] {
+            let (size, peer) = s.recv_from(&mut req).unwrap();
+            s.send_to(&req[..size], peer).unwrap();
+        }
+        let mut b1 = Buffer::default();
+        let mut b2 = Buffer::default();
+        let mut b3 = Buffer::default();
+        let mut b4 = Buffer::default();
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            let mut bufs = [&mut b1, &mut b2, &mut b3, &mut b4];
+            for (n, (size, addr)) in io.recv_from_many(&mut bufs)?.into_iter().enumerate() {
+                received.push((bufs[n].as_slice(size).to_vec(), addr.as_socket().unwrap()));
+            }
+        }
+        received.sort();
+        assert_eq!(
+            received,
+            vec![
+                (vec![0x30, 3, 2, 1, 1], s1.local_addr().unwrap()),
+                (vec![0x30, 3, 2, 1, 2], s2.local_addr().unwrap()),
+                (vec![0x30, 3, 2, 1, 3], s1.local_addr().unwrap()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_family() -> SnmpResult<()> {
+        assert_eq!(Family::try_from(0)?, Family::Any);
+        assert_eq!(Family::try_from(1)?, Family::PreferIpv4);
+        assert_eq!(Family::try_from(2)?, Family::PreferIpv6);
+        assert!(Family::try_from(3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_literal() -> SnmpResult<()> {
+        let addrs = resolve("127.0.0.1:161", Family::PreferIpv6)?;
+        assert_eq!(addrs.len(), 1);
+        assert_eq!(addrs[0].as_socket(), Some("127.0.0.1:161".parse().unwrap()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_hostname() -> SnmpResult<()> {
+        let addrs = resolve("localhost:161", Family::PreferIpv4)?;
+        assert!(!addrs.is_empty());
+        assert!(addrs[0].is_ipv4() || addrs.iter().all(|x| x.is_ipv6()));
+        assert!(addrs.iter().all(|x| x.as_socket().unwrap().port() == 161));
+        let addrs = resolve("localhost:161", Family::PreferIpv6)?;
+        assert!(addrs[0].is_ipv6() || addrs.iter().all(|x| x.is_ipv4()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_invalid() {
+        assert!(resolve("localhost", Family::Any).is_err());
+        assert!(resolve("localhost:100000", Family::Any).is_err());
+    }
+
+    #[test]
+    fn test_peer_addr() -> SnmpResult<()> {
+        let io = SnmpIo::new(
+            "localhost:161".into(),
+            SocketConfig {
+                family: Family::PreferIpv4,
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        let peer = io.peer_addr();
+        assert!(peer == "127.0.0.1:161" || peer == "[::1]:161");
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_policy() -> SnmpResult<()> {
+        let new = |cfg: SocketConfig| SnmpIo::new("127.0.0.1:161".into(), cfg);
+        let timeout = Duration::from_secs(3);
+        // No retries
+        let io = new(SocketConfig {
+            timeout_ns: 3_000_000_000,
+            ..Default::default()
+        })?;
+        assert_eq!(io.retry_policy(timeout), None);
+        // Split timeout evenly
+        let io = new(SocketConfig {
+            timeout_ns: 3_000_000_000,
+            retries: 2,
+            backoff: 1.0,
+            ..Default::default()
+        })?;
+        assert_eq!(
+            io.retry_policy(timeout),
+            Some(RetryPolicy {
+                retries: 2,
+                timeout: Duration::from_secs(1),
+                backoff: 1.0,
+            })
+        );
+        // Explicit timeout
+        let io = new(SocketConfig {
+            timeout_ns: 3_000_000_000,
+            retries: 2,
+            retry_timeout_ns: 500_000_000,
+            backoff: 2.0,
+            ..Default::default()
+        })?;
+        let policy = io.retry_policy(timeout).unwrap();
+        assert_eq!(policy.timeout, Duration::from_millis(500));
+        assert_eq!(policy.next_timeout(policy.timeout), Duration::from_secs(1));
+        // Limited by request timeout
+        let policy = io.retry_policy(Duration::from_millis(100)).unwrap();
+        assert_eq!(policy.timeout, Duration::from_millis(100));
+        Ok(())
+    }
+
+    #[test]
+    fn test_attempts() {
+        let policy = RetryPolicy {
+            retries: 2,
+            timeout: Duration::from_secs(1),
+            backoff: 2.0,
+        };
+        let t0 = Instant::now();
+        let deadline = t0 + Duration::from_secs(3);
+        let r: Vec<_> = Attempts::new(Some(policy), deadline).collect();
+        assert_eq!(r.len(), 3);
+        // Backoff: 1s, 2s, then limited by the deadline
+        let within = |x: Instant, d: u64| {
+            let d = t0 + Duration::from_secs(d);
+            x >= d && x < d + Duration::from_secs(1)
+        };
+        assert!(within(r[0], 1));
+        assert!(within(r[1], 2));
+        assert_eq!(r[2], deadline);
+        // Single attempt without policy
+        let r: Vec<_> = Attempts::new(None, deadline).collect();
+        assert_eq!(r, vec![deadline]);
+        // Deadline is passed
+        assert_eq!(Attempts::new(Some(policy), t0).count(), 0);
+    }
+
+    #[test]
+    fn test_retry_policy_nonblocking() -> SnmpResult<()> {
+        let io = SnmpIo::new(
+            "127.0.0.1:161".into(),
+            SocketConfig {
+                retries: 2,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(io.timeout(None), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout() -> SnmpResult<()> {
+        let io = SnmpIo::new(
+            "127.0.0.1:161".into(),
+            SocketConfig {
+                timeout_ns: 3_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(io.timeout(None), Some(Duration::from_secs(3)));
+        assert_eq!(
+            io.timeout(Some(500_000_000)),
+            Some(Duration::from_millis(500))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deadline() -> SnmpResult<()> {
+        let mut io = SnmpIo::new(
+            "127.0.0.1:161".into(),
+            SocketConfig {
+                timeout_ns: 3_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        let started = Instant::now();
+        io.set_deadline(started + Duration::from_millis(50))?;
+        let mut buf = Buffer::default();
+        assert!(matches!(io.recv(&mut buf), Err(SnmpError::WouldBlock)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        // Expired
+        assert!(matches!(
+            io.set_deadline(started),
+            Err(SnmpError::WouldBlock)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_icmp_port_unreachable() -> SnmpResult<()> {
+        use crate::error::IcmpErrorKind;
+        use std::net::UdpSocket;
+        // Get free port
+        let port = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut io = SnmpIo::new(
+            format!("127.0.0.1:{}", port),
+            SocketConfig {
+                timeout_ns: 1_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        io.send(b"test")?;
+        let mut buf = Buffer::default();
+        match io.recv(&mut buf) {
+            Err(SnmpError::Icmp(e)) => {
+                assert_eq!(e.kind, IcmpErrorKind::PortUnreachable);
+                assert_eq!(e.offender, Some("127.0.0.1".parse().unwrap()));
+            }
+            _ => panic!("ICMP error expected"),
+        }
+        // Error queue is drained
+        assert!(io.icmp_error().is_none());
+        Ok(())
+    }
+
+    // Echo single datagram in background
+    fn echo(server: std::net::UdpSocket) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            let mut req = [0u8; 16];
+            let (size, peer) = server.recv_from(&mut req).unwrap();
+            server.send_to(&req[..size], peer).unwrap();
+        })
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_fall_back_unreachable() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+        // Get free port
+        let closed = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_addr = server.local_addr().unwrap();
+        let mut io = SnmpIo::connect_any(
+            vec![SockAddr::from(closed), SockAddr::from(server_addr)],
+            SocketConfig {
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(io.peer_addr(), closed.to_string());
+        let handle = echo(server);
+        io.send(b"ping")?;
+        let mut buf = Buffer::default();
+        assert_eq!(io.recv(&mut buf)?, b"ping");
+        assert_eq!(io.peer_addr(), server_addr.to_string());
+        handle.join().unwrap();
+        // Address is confirmed
+        assert!(io.fallback.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fall_back_timeout() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+
+        let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let silent_addr = silent.local_addr().unwrap();
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_addr = server.local_addr().unwrap();
+        let mut io = SnmpIo::connect_any(
+            vec![SockAddr::from(silent_addr), SockAddr::from(server_addr)],
+            SocketConfig {
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        let mut buf = Buffer::default();
+        io.send(b"ping")?;
+        io.set_deadline(Instant::now() + Duration::from_millis(50))?;
+        assert!(matches!(io.recv(&mut buf), Err(SnmpError::WouldBlock)));
+        // Next attempt goes to the next address
+        assert_eq!(io.peer_addr(), server_addr.to_string());
+        let handle = echo(server);
+        io.send(b"pong")?;
+        io.set_deadline(Instant::now() + Duration::from_secs(5))?;
+        assert_eq!(io.recv(&mut buf)?, b"pong");
+        handle.join().unwrap();
+        // First address has received the message
+        let mut req = [0u8; 16];
+        let size = silent.recv(&mut req).unwrap();
+        assert_eq!(&req[..size], b"ping");
+        Ok(())
+    }
+
+    #[test]
+    fn test_received_at() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut io = SnmpIo::new(
+            server.local_addr().unwrap().to_string(),
+            SocketConfig {
+                timeout_ns: 5_000_000_000,
+                ..Default::default()
+            },
+        )?;
+        assert!(io.received_at().is_none());
+        let sent_at = Instant::now();
+        io.send(b"ping")?;
+        let mut req = [0u8; 16];
+        let (size, peer) = server.recv_from(&mut req).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        server.send_to(&req[..size], peer).unwrap();
+        let mut buf = Buffer::default();
+        assert_eq!(io.recv(&mut buf)?, b"ping");
+        let rtt = io.received_at().unwrap().saturating_duration_since(sent_at);
+        assert!(rtt >= Duration::from_millis(10));
+        assert!(rtt < Duration::from_secs(5));
+        // Set by the socket on the matching reply
+        assert!(io.rtt().is_none());
+        io.set_rtt(Some(rtt));
+        assert_eq!(io.rtt(), Some(rtt));
+        Ok(())
+    }
+
+    #[test]
+    fn test_policer_nonblocking() {
+        let r = SnmpIo::new(
+            "127.0.0.1:161".into(),
+            SocketConfig {
+                policer: Some(Arc::new(Policer::new(Some((10.0, 1.0)), None))),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(r, Err(SnmpError::SocketError(_))));
+    }
+
+    #[test]
+    fn test_send_to_many_policed() -> SnmpResult<()> {
+        use std::net::UdpSocket;
+
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = SockAddr::from(server.local_addr().unwrap());
+        let mut io = SnmpIo::unconnected(
+            false,
+            SocketConfig {
+                timeout_ns: 5_000_000_000,
+                policer: Some(Arc::new(Policer::new(None, Some((20.0, 1.0))))),
+                ..Default::default()
+            },
+        )?;
+        let msgs = [(&b"1"[..], &addr), (&b"2"[..], &addr), (&b"3"[..], &addr)];
+        let started = Instant::now();
+        assert_eq!(io.send_to_many_policed(&msgs)?, 3);
+        // 2 messages are delayed by 50ms each
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        let mut buf = [0u8; 16];
+        for expected in [b"1", b"2", b"3"] {
+            let size = server.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..size], expected);
+        }
+        Ok(())
+    }
+
+    #[cfg(any(feature = "python", feature = "tokio"))]
+    #[test]
+    fn test_target_key_v4() {
+        let addr: SocketAddr = "127.0.0.1:161".parse().unwrap();
+        assert_eq!(target_key(addr), addr);
+    }
+
+    #[cfg(any(feature = "python", feature = "tokio"))]
+    #[test]
+    fn test_target_key_v6() {
+        let addr: SocketAddr = "[fe80::1%2]:161".parse().unwrap();
+        let mut flow = match addr {
+            SocketAddr::V6(x) => x,
+            _ => unreachable!(),
+        };
+        flow.set_flowinfo(12345);
+        assert_eq!(target_key(SocketAddr::V6(flow)), addr);
+    }
+}
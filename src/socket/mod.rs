@@ -1,14 +1,33 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Socket classes
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
+mod icmp;
+mod io;
+#[cfg(feature = "python")]
+mod multi;
+mod proto;
+#[cfg(feature = "python")]
 mod snmpsocket;
 mod v1;
 mod v2c;
 mod v3;
+pub(crate) use io::{Attempts, SnmpIo, SocketConfig};
+pub use io::{Family, Transport};
+#[cfg(feature = "tokio")]
+pub(crate) use io::{RetryPolicy, resolve, target_key};
+#[cfg(feature = "python")]
+pub use multi::SnmpMultiClientSocket;
+pub(crate) use proto::Proto;
+#[cfg(feature = "python")]
 pub use v1::SnmpV1ClientSocket;
+pub(crate) use v1::SnmpV1Proto;
+#[cfg(feature = "python")]
 pub use v2c::SnmpV2cClientSocket;
+pub(crate) use v2c::SnmpV2cProto;
+#[cfg(feature = "python")]
 pub use v3::SnmpV3ClientSocket;
+pub(crate) use v3::{SnmpV3Proto, fit_max_size};
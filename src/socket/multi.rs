@@ -0,0 +1,506 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: SnmpMultiClientSocket
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::io::{Family, SnmpIo, SocketConfig, resolve, target_key};
+use super::proto::Proto;
+use super::snmpsocket::Pending;
+use super::v1::SnmpV1Proto;
+use super::v2c::SnmpV2cProto;
+use super::v3::{SnmpV3Proto, get_max_size};
+use crate::{
+    buf::{Buffer, get_buffer_pool},
+    error::{SnmpError, SnmpResult},
+    hook::{PyPacketHook, Tracer},
+    policer::TokenBucketPolicer,
+    snmp::{
+        op::{GetIter, OpGet, OpGetBulk, OpGetMany, OpGetNext, OpRefresh, PyOp},
+        pdu::SnmpPdu,
+    },
+};
+use pyo3::{
+    exceptions::{PyKeyError, PyValueError},
+    prelude::*,
+    pybacked::PyBackedStr,
+    types::PyBytes,
+};
+use socket2::SockAddr;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::os::fd::AsRawFd;
+
+/// Python class wrapping unconnected socket,
+/// polling multiple agents at once.
+#[pyclass]
+pub struct SnmpMultiClientSocket {
+    io: SnmpIo,
+    ipv6: bool,
+    targets: HashMap<SocketAddr, Target>,
+    // Target address, as passed by user -> socket address
+    names: HashMap<String, SocketAddr>,
+}
+
+/// Agent state
+struct Target {
+    name: String,
+    addr: SockAddr,
+    proto: Proto,
+    // Request id and operation, waiting for reply
+    pending: Option<(i64, Pending)>,
+}
+
+// Default amount of replies, received at once
+const BATCH_SIZE: usize = 64;
+
+#[pymethods]
+impl SnmpMultiClientSocket {
+    /// Python constructor
+    #[allow(clippy::too_many_arguments)] // Internal interface
+    #[new]
+    #[pyo3(signature = (
+        ipv6 = false,
+        tos = 0,
+        send_buffer_size = 0,
+        recv_buffer_size = 0,
+        timeout_ns = 0,
+        bind_addr = None,
+        bind_port = 0,
+        bind_device = None,
+        ttl = 0,
+        policer = None,
+        packet_hook = None,
+    ))]
+    fn new(
+        ipv6: bool,
+        tos: u32,
+        send_buffer_size: usize,
+        recv_buffer_size: usize,
+        timeout_ns: u64,
+        bind_addr: Option<String>,
+        bind_port: u16,
+        bind_device: Option<String>,
+        ttl: u32,
+        policer: Option<PyRef<TokenBucketPolicer>>,
+        packet_hook: Option<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        let cfg = SocketConfig {
+            tos,
+            ttl,
+            send_buffer_size,
+            recv_buffer_size,
+            timeout_ns,
+            bind_addr,
+            bind_port,
+            bind_device,
+            policer: policer.map(|p| p.get_policer()),
+            packet_hook: packet_hook.map(PyPacketHook::shared),
+            ..Default::default()
+        };
+        Ok(Self {
+            io: SnmpIo::unconnected(ipv6, cfg)?,
+            ipv6,
+            targets: HashMap::new(),
+            names: HashMap::new(),
+        })
+    }
+    /// Get socket's file descriptor
+    fn get_fd(&self) -> PyResult<i32> {
+        Ok(self.io.as_raw_fd())
+    }
+    /// Add or replace SNMPv1 agent
+    fn add_v1_target(&mut self, addr: String, community: String) -> PyResult<()> {
+        Ok(self.add_target(addr, Proto::V1(SnmpV1Proto::new(community)))?)
+    }
+    /// Add or replace SNMPv2c agent
+    fn add_v2c_target(&mut self, addr: String, community: String) -> PyResult<()> {
+        Ok(self.add_target(addr, Proto::V2c(SnmpV2cProto::new(community)))?)
+    }
+    /// Add or replace SNMPv3 agent
+    #[allow(clippy::too_many_arguments)] // Internal interface
+    #[pyo3(signature = (
+        addr,
+        engine_id,
+        user_name,
+        auth_alg,
+        auth_key,
+        priv_alg,
+        priv_key,
+        max_msg_size = 0,
+    ))]
+    fn add_v3_target(
+        &mut self,
+        addr: String,
+        engine_id: Vec<u8>,
+        user_name: String,
+        auth_alg: u8,
+        auth_key: &[u8],
+        priv_alg: u8,
+        priv_key: &[u8],
+        max_msg_size: usize,
+    ) -> PyResult<()> {
+        let proto = SnmpV3Proto::new(
+            engine_id,
+            user_name,
+            (auth_alg, auth_key),
+            (priv_alg, priv_key),
+            get_max_size(max_msg_size)?,
+        )?;
+        Ok(self.add_target(addr, Proto::V3(Box::new(proto)))?)
+    }
+    /// Remove agent
+    fn remove_target(&mut self, addr: &str) -> PyResult<()> {
+        let key = self
+            .names
+            .remove(addr)
+            .ok_or_else(|| PyKeyError::new_err(addr.to_owned()))?;
+        self.targets.remove(&key);
+        Ok(())
+    }
+    /// Change SNMPv3 agent's user and keys
+    fn set_keys(
+        &mut self,
+        addr: &str,
+        user_name: String,
+        auth_alg: u8,
+        auth_key: &[u8],
+        priv_alg: u8,
+        priv_key: &[u8],
+    ) -> PyResult<()> {
+        match &mut self.get_target(addr)?.proto {
+            Proto::V3(proto) => {
+                Ok(proto.set_keys(user_name, (auth_alg, auth_key), (priv_alg, priv_key))?)
+            }
+            _ => Err(PyValueError::new_err("not an SNMPv3 target")),
+        }
+    }
+    /// Get SNMPv3 agent's engine id
+    fn get_engine_id(&mut self, addr: &str, py: Python) -> PyResult<Py<PyAny>> {
+        match &self.get_target(addr)?.proto {
+            Proto::V3(proto) => Ok(PyBytes::new(py, proto.get_engine_id()).into()),
+            _ => Err(PyValueError::new_err("not an SNMPv3 target")),
+        }
+    }
+    // Send GET request with single oid
+    fn send_get(&mut self, py: Python, addr: &str, oid: PyBackedStr) -> PyResult<()> {
+        self.send_request::<OpGet, _>(addr, oid, Pending::Get, py)
+    }
+    // Send GET request with multiple oids
+    fn send_get_many(&mut self, py: Python, addr: &str, oids: Vec<PyBackedStr>) -> PyResult<()> {
+        self.send_request::<OpGetMany, _>(addr, oids, Pending::GetMany, py)
+    }
+    // Send GETNEXT request according to iter
+    fn send_get_next(&mut self, py: Python, addr: &str, iter: Py<GetIter>) -> PyResult<()> {
+        let oid = iter.borrow(py).get_next_oid();
+        self.send_request::<OpGetNext, _>(addr, oid, Pending::GetNext(iter), py)
+    }
+    // Send GETBULK request according to iter
+    fn send_get_bulk(&mut self, py: Python, addr: &str, iter: Py<GetIter>) -> PyResult<()> {
+        let (oid, max_repetitions) = {
+            let it = iter.borrow(py);
+            (it.get_next_oid(), it.get_max_repetitions())
+        };
+        let max_repetitions = match &self.get_target(addr)?.proto {
+            Proto::V3(proto) => proto.fit_max_repetitions(max_repetitions),
+            _ => max_repetitions,
+        };
+        self.send_request::<OpGetBulk, _>(addr, (oid, max_repetitions), Pending::GetBulk(iter), py)
+    }
+    // Send GET+Report to adjust SNMPv3 boots and time
+    fn send_refresh(&mut self, py: Python, addr: &str) -> PyResult<()> {
+        self.send_request::<OpRefresh, _>(addr, (), Pending::Refresh, py)
+    }
+    // Send GET requests with single oid to multiple agents at once.
+    // Each agent may be requested once per batch.
+    // Returns the number of requests sent.
+    fn send_get_batch(
+        &mut self,
+        py: Python,
+        requests: Vec<(String, PyBackedStr)>,
+    ) -> PyResult<usize> {
+        self.send_batch::<OpGet, _>(requests, || Pending::Get, py)
+    }
+    // Send GET requests with multiple oids to multiple agents at once.
+    // Each agent may be requested once per batch.
+    // Returns the number of requests sent.
+    fn send_get_many_batch(
+        &mut self,
+        py: Python,
+        requests: Vec<(String, Vec<PyBackedStr>)>,
+    ) -> PyResult<usize> {
+        self.send_batch::<OpGetMany, _>(requests, || Pending::GetMany, py)
+    }
+    /// Receive up to `max_count` replies at once.
+    /// Waits for at least one reply. Returns list of (address, result).
+    #[pyo3(signature = (max_count = BATCH_SIZE))]
+    fn recv_batch(&mut self, py: Python, max_count: usize) -> PyResult<Vec<(String, Py<PyAny>)>> {
+        py.detach(|| self._recv_batch_inner(max_count))
+    }
+    /// Receive next reply.
+    /// Returns (address, result) tuple. Errors, related to the particular
+    /// agent, are returned as exception instances instead of raising,
+    /// so the caller is able to match them to the request.
+    fn recv(&mut self, py: Python) -> PyResult<(String, Py<PyAny>)> {
+        py.detach(|| self._recv_inner())
+    }
+}
+
+impl SnmpMultiClientSocket {
+    fn add_target(&mut self, name: String, proto: Proto) -> SnmpResult<()> {
+        // First resolved address of the socket's family
+        let (addr, key) = resolve(&name, Family::Any)?
+            .into_iter()
+            .find_map(|addr| match addr.as_socket() {
+                Some(x) if x.is_ipv6() == self.ipv6 => Some((addr, target_key(x))),
+                _ => None,
+            })
+            .ok_or_else(|| SnmpError::SocketError("address family mismatch".into()))?;
+        if let Some(prev) = self.targets.remove(&key) {
+            self.names.remove(&prev.name);
+        }
+        if let Some(prev) = self.names.remove(&name) {
+            self.targets.remove(&prev);
+        }
+        self.names.insert(name.clone(), key);
+        self.targets.insert(
+            key,
+            Target {
+                name,
+                addr,
+                proto,
+                pending: None,
+            },
+        );
+        Ok(())
+    }
+    fn get_target(&mut self, addr: &str) -> PyResult<&mut Target> {
+        self.names
+            .get(addr)
+            .and_then(|key| self.targets.get_mut(key))
+            .ok_or_else(|| PyKeyError::new_err(addr.to_owned()))
+    }
+    fn send_request<'a, T, V>(
+        &mut self,
+        addr: &str,
+        req: V,
+        pending: Pending,
+        py: Python,
+    ) -> PyResult<()>
+    where
+        T: PyOp<'a, V>,
+        V: 'a,
+    {
+        let target = self
+            .names
+            .get(addr)
+            .and_then(|key| self.targets.get_mut(key))
+            .ok_or_else(|| PyKeyError::new_err(addr.to_owned()))?;
+        // Parse python arguments, under GIL
+        let request_id = target.proto.get_request_id().get_next();
+        let pdu = match T::from_python(req, request_id) {
+            Ok(x) => x,
+            Err(e) => {
+                target.proto.complete(request_id);
+                return Err(e);
+            }
+        };
+        // Release GIL
+        let io = &mut self.io;
+        py.detach(|| -> SnmpResult<()> {
+            let mut pool = get_buffer_pool().acquire();
+            let buf = pool.as_mut();
+            let tracer = io.tracer_to(&target.addr);
+            let summary = tracer.as_ref().and_then(|_| Tracer::summary(&pdu));
+            io.police_to(&target.addr);
+            target.proto.push_pdu(pdu, buf)?;
+            io.send_to(buf.data(), &target.addr)?;
+            if let Some(tracer) = tracer {
+                tracer.sent(buf.data(), summary.as_ref());
+            }
+            Ok(())
+        })?;
+        // Previous request to the agent is abandoned
+        target.abandon();
+        target.pending = Some((request_id, pending));
+        Ok(())
+    }
+    fn _recv_inner(&mut self) -> PyResult<(String, Py<PyAny>)> {
+        // Get buffer from pool
+        let mut h = get_buffer_pool().acquire();
+        let buf = h.as_mut();
+        // We can catch unwanted replies, so do it in a loop
+        loop {
+            let (data, addr) = self.io.recv_from(buf)?;
+            if let Some(r) = self.process_reply(data, &addr) {
+                return Ok(r);
+            }
+            buf.reset();
+        }
+    }
+    fn _recv_batch_inner(&mut self, max_count: usize) -> PyResult<Vec<(String, Py<PyAny>)>> {
+        // Get buffers from pool
+        let mut handles: Vec<_> = (0..max_count.max(1))
+            .map(|_| get_buffer_pool().acquire())
+            .collect();
+        let mut bufs: Vec<&mut Buffer> = handles.iter_mut().map(|h| h.as_mut()).collect();
+        // We can catch unwanted replies, so do it in a loop
+        loop {
+            let received = self.io.recv_from_many(&mut bufs)?;
+            let r: Vec<_> = received
+                .into_iter()
+                .zip(bufs.iter())
+                .filter_map(|((size, addr), buf)| self.process_reply(buf.as_slice(size), &addr))
+                .collect();
+            if !r.is_empty() {
+                return Ok(r);
+            }
+        }
+    }
+    // Match received message against pending requests.
+    // Returns None for unrelated messages.
+    fn process_reply(&mut self, data: &[u8], addr: &SockAddr) -> Option<(String, Py<PyAny>)> {
+        let tracer = self.io.tracer_to(addr);
+        let trace = |pdu: Option<&SnmpPdu>, discarded: bool| {
+            if let Some(tracer) = &tracer {
+                tracer.received(data, pdu, discarded);
+            }
+        };
+        // Reply must come from the address the request was sent to
+        let Some(target) = addr
+            .as_socket()
+            .and_then(|x| self.targets.get_mut(&target_key(x)))
+        else {
+            trace(None, true);
+            return None;
+        };
+        let Some((request_id, pending)) = target.pending.as_ref() else {
+            trace(None, true);
+            return None;
+        };
+        let r = match target.proto.unwrap_data(data) {
+            Ok(Some((id, pdu))) if id == *request_id => {
+                trace(Some(&pdu), false);
+                Python::attach(|py| pending.to_python(&pdu, py).map(|x| x.unbind()))
+            }
+            Ok(x) => {
+                trace(x.as_ref().map(|(_, pdu)| pdu), true);
+                return None;
+            }
+            Err(e) => {
+                trace(None, true);
+                Err(e.into())
+            }
+        };
+        target.abandon();
+        let name = target.name.clone();
+        Python::attach(|py| {
+            Some((
+                name,
+                match r {
+                    Ok(x) => x,
+                    Err(e) => e.into_value(py).into_any(),
+                },
+            ))
+        })
+    }
+    fn send_batch<'a, T, V>(
+        &mut self,
+        requests: Vec<(String, V)>,
+        pending: impl Fn() -> Pending,
+        py: Python,
+    ) -> PyResult<usize>
+    where
+        T: PyOp<'a, V>,
+        V: 'a,
+    {
+        // Parse python arguments, under GIL.
+        // Pending requests are replaced only when the batch is sent.
+        let mut keys: Vec<(SocketAddr, i64)> = Vec::with_capacity(requests.len());
+        let mut pdus = Vec::with_capacity(requests.len());
+        for (addr, req) in requests {
+            let Some(&key) = self.names.get(&addr) else {
+                self.forget(&keys);
+                return Err(PyKeyError::new_err(addr));
+            };
+            // Only one request per target may wait for reply
+            if keys.iter().any(|(x, _)| *x == key) {
+                self.forget(&keys);
+                return Err(PyValueError::new_err(format!("duplicate target: {addr}")));
+            }
+            let target = self.targets.get_mut(&key).unwrap();
+            let request_id = target.proto.get_request_id().get_next();
+            keys.push((key, request_id));
+            match T::from_python(req, request_id) {
+                Ok(pdu) => pdus.push(pdu),
+                Err(e) => {
+                    self.forget(&keys);
+                    return Err(e);
+                }
+            }
+        }
+        // Release GIL
+        let targets = &mut self.targets;
+        let io = &mut self.io;
+        let r = py.detach(|| -> SnmpResult<usize> {
+            // Encode all messages before sending any
+            let mut handles: Vec<_> = pdus.iter().map(|_| get_buffer_pool().acquire()).collect();
+            let tracing = io.tracer().is_some();
+            let mut summaries = Vec::with_capacity(pdus.len());
+            for ((pdu, (key, _)), h) in pdus.into_iter().zip(keys.iter()).zip(handles.iter_mut()) {
+                summaries.push(tracing.then(|| Tracer::summary(&pdu)).flatten());
+                targets
+                    .get_mut(key)
+                    .unwrap()
+                    .proto
+                    .push_pdu(pdu, h.as_mut())?;
+            }
+            let msgs: Vec<_> = handles
+                .iter_mut()
+                .zip(keys.iter())
+                .map(|(h, (key, _))| (h.as_mut().data(), &targets[key].addr))
+                .collect();
+            let sent = io.send_to_many_policed(&msgs)?;
+            for ((data, addr), summary) in msgs.iter().zip(summaries.iter()).take(sent) {
+                if let Some(tracer) = io.tracer_to(addr) {
+                    tracer.sent(data, summary.as_ref());
+                }
+            }
+            Ok(sent)
+        });
+        let sent = match r {
+            Ok(x) => x,
+            Err(e) => {
+                self.forget(&keys);
+                return Err(e.into());
+            }
+        };
+        self.forget(&keys[sent..]);
+        for (key, request_id) in &keys[..sent] {
+            if let Some(target) = self.targets.get_mut(key) {
+                // Previous request to the agent is abandoned
+                target.abandon();
+                target.pending = Some((*request_id, pending()));
+            }
+        }
+        Ok(sent)
+    }
+    // Forget request ids, allocated for the requests which are not sent
+    fn forget(&mut self, keys: &[(SocketAddr, i64)]) {
+        for (key, request_id) in keys {
+            if let Some(target) = self.targets.get_mut(key) {
+                target.proto.complete(*request_id);
+            }
+        }
+    }
+}
+
+impl Target {
+    // Forget request, waiting for reply
+    fn abandon(&mut self) {
+        if let Some((request_id, _)) = self.pending.take() {
+            self.proto.complete(request_id);
+        }
+    }
+}
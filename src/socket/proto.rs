@@ -0,0 +1,94 @@
+// ------------------------------------------------------------------------
+// Gufo SNMP: Protocol state
+// ------------------------------------------------------------------------
+// Copyright (C) 2026, Gufo Labs
+// See LICENSE.md for details
+// ------------------------------------------------------------------------
+
+use super::v1::SnmpV1Proto;
+use super::v2c::SnmpV2cProto;
+use super::v3::SnmpV3Proto;
+use crate::{
+    ber::BerEncoder,
+    buf::Buffer,
+    error::{SnmpError, SnmpResult},
+    reqid::RequestId,
+    snmp::pdu::SnmpPdu,
+};
+
+/// Protocol state, independent of the transport.
+pub(crate) trait SnmpProto
+where
+    Self: Send + Sync,
+{
+    type Message<'a>: TryFrom<&'a [u8], Error = SnmpError> + BerEncoder + Send
+    where
+        Self: 'a;
+
+    fn get_request_id(&mut self) -> &mut RequestId;
+    fn push_pdu(&mut self, pdu: SnmpPdu, buf: &mut Buffer) -> SnmpResult<()>;
+    // Check and unwrap PDU from received message.
+    // Returns request id of the matched request in flight
+    // along with PDU, or None for the unrelated messages.
+    fn unwrap_pdu<'a>(
+        &'a mut self,
+        msg: Self::Message<'a>,
+    ) -> SnmpResult<Option<(i64, SnmpPdu<'a>)>>;
+    // Forget the completed or abandoned request.
+    // Late replies to it are ignored.
+    fn complete(&mut self, request_id: i64) {
+        self.get_request_id().remove(request_id);
+    }
+    // Decode received message and unwrap PDU.
+    fn unwrap_data<'a>(&'a mut self, data: &'a [u8]) -> SnmpResult<Option<(i64, SnmpPdu<'a>)>> {
+        let msg = Self::Message::try_from(data)?;
+        self.unwrap_pdu(msg)
+    }
+}
+
+/// Protocol state of any version
+pub(crate) enum Proto {
+    V1(SnmpV1Proto),
+    V2c(SnmpV2cProto),
+    V3(Box<SnmpV3Proto>),
+}
+
+impl Proto {
+    pub fn get_request_id(&mut self) -> &mut RequestId {
+        match self {
+            Proto::V1(x) => x.get_request_id(),
+            Proto::V2c(x) => x.get_request_id(),
+            Proto::V3(x) => x.get_request_id(),
+        }
+    }
+    pub fn push_pdu(&mut self, pdu: SnmpPdu, buf: &mut Buffer) -> SnmpResult<()> {
+        match self {
+            Proto::V1(x) => x.push_pdu(pdu, buf),
+            Proto::V2c(x) => x.push_pdu(pdu, buf),
+            Proto::V3(x) => x.push_pdu(pdu, buf),
+        }
+    }
+    pub fn complete(&mut self, request_id: i64) {
+        match self {
+            Proto::V1(x) => x.complete(request_id),
+            Proto::V2c(x) => x.complete(request_id),
+            Proto::V3(x) => x.complete(request_id),
+        }
+    }
+    pub fn unwrap_data<'a>(&'a mut self, data: &'a [u8]) -> SnmpResult<Option<(i64, SnmpPdu<'a>)>> {
+        match self {
+            Proto::V1(x) => x.unwrap_data(data),
+            Proto::V2c(x) => x.unwrap_data(data),
+            Proto::V3(x) => x.unwrap_data(data),
+        }
+    }
+    /// Id to match replies before decoding: msgID of the last
+    /// pushed message for SNMPv3, request id otherwise.
+    #[cfg(feature = "tokio")]
+    pub fn routing_id(&self, request_id: i64) -> i64 {
+        match self {
+            Proto::V3(x) => x.last_msg_id().unwrap_or(request_id),
+            _ => request_id,
+        }
+    }
+}
@@ -1,170 +1,350 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: Socket operations
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-24, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use crate::snmp::op::{GetIter, PyOp};
+use super::io::{Attempts, RetryPolicy, SnmpIo};
+use super::proto::SnmpProto;
+use crate::snmp::op::{GetIter, OpGet, OpGetBulk, OpGetMany, OpGetNext, OpRefresh, PyOp};
 use crate::{
-    ber::BerEncoder,
-    buf::{Buffer, get_buffer_pool},
-    error::{SnmpError, SnmpResult},
-    reqid::RequestId,
-    snmp::pdu::SnmpPdu,
+    buf::get_buffer_pool, error::SnmpError, hook::Tracer, reqid::MAX_IN_FLIGHT, snmp::pdu::SnmpPdu,
 };
 use pyo3::prelude::*;
-use socket2::{Domain, Protocol, Socket, Type};
-use std::net::SocketAddr;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Operation, waiting for reply
+pub(crate) enum Pending {
+    Get,
+    GetMany,
+    GetNext(Py<GetIter>),
+    GetBulk(Py<GetIter>),
+    Refresh,
+    // Reply, received while waiting for another request,
+    // along with the time of receiving
+    Replied(PyResult<Py<PyAny>>, Option<Instant>),
+}
+
+// Request id, result and the time of receiving
+type Reply = (i64, PyResult<Py<PyAny>>, Option<Instant>);
+
+impl Pending {
+    pub fn to_python<'py>(&self, pdu: &SnmpPdu, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            Pending::Get => OpGet::to_python(pdu, None, py),
+            Pending::GetMany => OpGetMany::to_python(pdu, None, py),
+            Pending::GetNext(iter) => OpGetNext::to_python(pdu, Some(&mut iter.borrow_mut(py)), py),
+            Pending::GetBulk(iter) => OpGetBulk::to_python(pdu, Some(&mut iter.borrow_mut(py)), py),
+            Pending::Refresh => OpRefresh::to_python(pdu, None, py),
+            // Duplicated reply
+            Pending::Replied(r, _) => Self::clone_result(r, py).map(|x| x.into_bound(py)),
+        }
+    }
+    /// Keep the reply until requested. Duplicated replies are ignored.
+    pub fn store(&mut self, pdu: &SnmpPdu, received_at: Option<Instant>, py: Python) {
+        if !matches!(self, Pending::Replied(..)) {
+            *self = Pending::Replied(self.to_python(pdu, py).map(|x| x.unbind()), received_at);
+        }
+    }
+    fn clone_result(r: &PyResult<Py<PyAny>>, py: Python) -> PyResult<Py<PyAny>> {
+        match r {
+            Ok(x) => Ok(x.clone_ref(py)),
+            Err(e) => Err(e.clone_ref(py)),
+        }
+    }
+}
 
 pub(crate) trait SnmpSocket
 where
     Self: Send + Sync,
 {
-    type Message<'a>: TryFrom<&'a [u8], Error = SnmpError> + BerEncoder + Send
-    where
-        Self: 'a;
+    type Proto: SnmpProto;
 
-    fn get_io(&mut self) -> &mut Socket;
-    fn get_socket(
-        addr: String,
-        tos: u32,
-        send_buffer_size: usize,
-        recv_buffer_size: usize,
-        timeout_ns: u64,
-    ) -> SnmpResult<Socket> {
-        // Parse address
-        let sock_addr = addr
-            .parse()
-            .map_err(|_| SnmpError::SocketError("invalid address".into()))?;
-        // Detect the socket domain
-        let domain = match sock_addr {
-            SocketAddr::V4(_) => Domain::IPV4,
-            SocketAddr::V6(_) => Domain::IPV6,
-        };
-        // Create internal socket
-        let io = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))
-            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
-        if timeout_ns > 0 {
-            // Blocking mode
-            io.set_read_timeout(Some(Duration::from_nanos(timeout_ns)))
-                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
-        } else {
-            // Mark socket as non-blocking
-            io.set_nonblocking(true)
-                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
-        }
-        // Set ToS
-        if tos > 0 {
-            io.set_tos_v4(tos)
-                .map_err(|e| SnmpError::SocketError(e.to_string()))?;
-        }
-        // Set buffers
-        if send_buffer_size > 0 {
-            Self::set_send_buffer_size(&io, send_buffer_size)?;
+    fn get_io(&mut self) -> &mut SnmpIo;
+    fn get_proto(&mut self) -> &mut Self::Proto;
+    // Protocol state along with operations, waiting for replies
+    fn get_pending(&mut self) -> (&mut Self::Proto, &mut HashMap<i64, Pending>);
+    // Remember operation, waiting for reply
+    fn add_pending(&mut self, request_id: i64, op: Pending) {
+        let (proto, pending) = self.get_pending();
+        if pending.len() >= MAX_IN_FLIGHT {
+            // Drop abandoned requests
+            let request_ids = proto.get_request_id();
+            pending.retain(|id, _| request_ids.check(*id));
         }
-        if recv_buffer_size > 0 {
-            Self::set_recv_buffer_size(&io, recv_buffer_size)?;
-        }
-        // Make socket connected
-        io.connect(&sock_addr.into())
-            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
-        Ok(io)
-    }
-    /// Set internal socket's send buffer size
-    fn set_send_buffer_size(io: &Socket, size: usize) -> SnmpResult<()> {
-        // @todo: get wmem_max limit on Linux
-        let mut effective_size = size;
-        while effective_size > 0 {
-            if io.set_send_buffer_size(effective_size).is_ok() {
-                return Ok(());
-            }
-            effective_size >>= 1;
-        }
-        Err(SnmpError::SocketError("unable to set buffer size".into()))
-    }
-    /// Set internal socket's receive buffer size
-    fn set_recv_buffer_size(io: &Socket, size: usize) -> SnmpResult<()> {
-        let mut effective_size = size;
-        while effective_size > 0 {
-            if io.set_recv_buffer_size(effective_size).is_ok() {
-                return Ok(());
-            }
-            effective_size >>= 1;
+        pending.insert(request_id, op);
+    }
+    // Take the reply, received while waiting for another request.
+    // Returns the first available reply if `request_id` is not set.
+    fn take_replied(&mut self, request_id: Option<i64>) -> Option<Reply> {
+        let (_, pending) = self.get_pending();
+        let request_id = request_id.or_else(|| {
+            pending
+                .iter()
+                .find_map(|(id, op)| matches!(op, Pending::Replied(..)).then_some(*id))
+        })?;
+        if !matches!(pending.get(&request_id), Some(Pending::Replied(..))) {
+            return None;
         }
-        Err(SnmpError::SocketError("unable to set buffer size".into()))
-    }
-    fn get_request_id(&mut self) -> &mut RequestId;
-    fn push_pdu(&mut self, pdu: SnmpPdu, buf: &mut Buffer) -> SnmpResult<()>;
-    fn unwrap_pdu<'a>(&'a mut self, msg: Self::Message<'a>) -> Option<SnmpPdu<'a>>;
-    //
-    fn recv_socket<'a>(io: &mut Socket, buf: &'a mut Buffer) -> SnmpResult<&'a [u8]> {
-        match io.recv(buf.as_mut()) {
-            Ok(s) => Ok(buf.as_slice(s)),
-            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Err(SnmpError::WouldBlock),
-            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
-                Err(SnmpError::ConnectionRefused)
-            }
-            Err(e) => Err(SnmpError::SocketError(e.to_string())),
+        match pending.remove(&request_id) {
+            Some(Pending::Replied(r, received_at)) => Some((request_id, r, received_at)),
+            _ => None,
         }
     }
+    // Request is replied at `received_at`, remember its round-trip time.
+    // Must be called before the request is complete.
+    fn replied(&mut self, request_id: i64, received_at: Option<Instant>) {
+        let rtt = received_at.and_then(|t| self.get_proto().get_request_id().rtt(request_id, t));
+        self.get_io().set_rtt(rtt);
+    }
+    // Request is complete or abandoned
+    fn complete(&mut self, request_id: i64) {
+        let (proto, pending) = self.get_pending();
+        proto.complete(request_id);
+        pending.remove(&request_id);
+    }
     // Send section with released GIL
     fn _send_inner(&mut self, pdu: SnmpPdu) -> PyResult<()> {
         // Get buffer for pool
         let mut pool = get_buffer_pool().acquire();
         let buf = pool.as_mut();
-        self.push_pdu(pdu, buf)?;
+        let tracer = self.get_io().tracer();
+        let summary = tracer.as_ref().and_then(|_| Tracer::summary(&pdu));
+        let request_id = pdu.get_request_id();
+        // Apply policer before encoding, so SNMPv3 engine time
+        // and authentication are not stale after the wait
+        self.get_io().police();
+        self.get_proto().push_pdu(pdu, buf)?;
         // Send message
-        self.get_io()
-            .send(buf.data())
-            .map_err(|e| SnmpError::SocketError(e.to_string()))?;
+        self.get_io().send(buf.data())?;
+        // Retransmissions restart the round-trip time
+        self.get_proto()
+            .get_request_id()
+            .set_sent_at(request_id, Instant::now());
+        if let Some(tracer) = tracer {
+            tracer.sent(buf.data(), summary.as_ref());
+        }
         Ok(())
     }
 
-    fn _recv_inner<'a, T, V>(&mut self, iter: Option<&mut GetIter>) -> PyResult<Py<PyAny>>
+    fn _recv_inner<'a, T, V>(
+        &mut self,
+        request_id: i64,
+        iter: Option<&mut GetIter>,
+        deadline: Option<Instant>,
+    ) -> PyResult<Py<PyAny>>
+    where
+        T: PyOp<'a, V>,
+        V: 'a,
+    {
+        match self._try_recv_inner::<T, V>(request_id, iter, deadline)? {
+            Some(r) => Ok(r),
+            None => Err(SnmpError::WouldBlock.into()),
+        }
+    }
+
+    // Receive section with released GIL.
+    // Waits for reply to `request_id`. Replies to other pending
+    // requests are kept until requested, unrelated replies are dropped.
+    // Returns None on timeout.
+    fn _try_recv_inner<'a, T, V>(
+        &mut self,
+        request_id: i64,
+        iter: Option<&mut GetIter>,
+        deadline: Option<Instant>,
+    ) -> PyResult<Option<Py<PyAny>>>
     where
         T: PyOp<'a, V>,
         V: 'a,
     {
+        // Reply may be already received
+        if let Some((_, r, received_at)) = self.take_replied(Some(request_id)) {
+            self.replied(request_id, received_at);
+            self.complete(request_id);
+            return r.map(Some);
+        }
         // Get buffer from pool
         let mut h = get_buffer_pool().acquire();
         let buf = h.as_mut();
-        // We can catch unwanted replies, so do it in a loop
+        let tracer = self.get_io().tracer();
+        let trace = |data: &[u8], pdu: Option<&SnmpPdu>, discarded: bool| {
+            if let Some(tracer) = &tracer {
+                tracer.received(data, pdu, discarded);
+            }
+        };
+        // We can catch unwanted replies, so do it in a loop.
+        // Each unwanted reply consumes the remaining time.
         loop {
-            // Nested scope to release io early after receiving message
-            let msg = {
-                let io = self.get_io();
-                let data = Self::recv_socket(io, buf)?;
-                // Decode message
-                Self::Message::try_from(data)?
+            let r = match deadline {
+                Some(d) => self.get_io().set_deadline(d),
+                None => Ok(()),
+            };
+            let data = match r.and_then(|_| self.get_io().recv(buf)) {
+                Ok(x) => x,
+                Err(SnmpError::WouldBlock) => return Ok(None),
+                Err(e) => return Err(e.into()),
             };
-            match self.unwrap_pdu(msg) {
-                Some(ref pdu) => {
-                    return Python::attach(|py| Ok(T::to_python(pdu, iter, py)?.into()));
+            let received_at = self.get_io().received_at();
+            let (proto, pending) = self.get_pending();
+            let r = match proto.unwrap_data(data) {
+                Ok(Some((id, ref pdu))) if id == request_id => {
+                    trace(data, Some(pdu), false);
+                    Python::attach(|py| T::to_python(pdu, iter, py).map(|x| x.unbind()))
                 }
-                None => {
+                Ok(Some((id, ref pdu))) if pending.contains_key(&id) => {
+                    // Reply to another request in flight
+                    trace(data, Some(pdu), false);
+                    if let Some(op) = pending.get_mut(&id) {
+                        Python::attach(|py| op.store(pdu, received_at, py));
+                    }
                     buf.reset();
                     continue;
                 }
+                Ok(x) => {
+                    trace(data, x.as_ref().map(|(_, pdu)| pdu), true);
+                    buf.reset();
+                    continue;
+                }
+                Err(e) => {
+                    trace(data, None, true);
+                    return Err(e.into());
+                }
+            };
+            self.replied(request_id, received_at);
+            self.complete(request_id);
+            return r.map(Some);
+        }
+    }
+
+    // Receive reply to any pending operation, with released GIL.
+    // Returns request id along with the result.
+    fn _recv_any_inner(&mut self, deadline: Option<Instant>) -> PyResult<(i64, Py<PyAny>)> {
+        let (id, r, received_at) = match self.take_replied(None) {
+            Some(x) => x,
+            None => self._recv_any_reply(deadline)?,
+        };
+        self.replied(id, received_at);
+        self.get_proto().complete(id);
+        // Errors, related to the request, are returned as
+        // exception instances, so the caller is able to match them.
+        Python::attach(|py| {
+            Ok((
+                id,
+                match r {
+                    Ok(x) => x,
+                    Err(e) => e.into_value(py).into_any(),
+                },
+            ))
+        })
+    }
+
+    // Wait for reply to any pending operation.
+    // Returns request id along with the result.
+    fn _recv_any_reply(&mut self, deadline: Option<Instant>) -> PyResult<Reply> {
+        // Get buffer from pool
+        let mut h = get_buffer_pool().acquire();
+        let buf = h.as_mut();
+        let tracer = self.get_io().tracer();
+        let trace = |data: &[u8], pdu: Option<&SnmpPdu>, discarded: bool| {
+            if let Some(tracer) = &tracer {
+                tracer.received(data, pdu, discarded);
+            }
+        };
+        // We can catch unwanted replies, so do it in a loop.
+        loop {
+            if let Some(d) = deadline {
+                self.get_io().set_deadline(d)?;
+            }
+            let data = self.get_io().recv(buf)?;
+            let received_at = self.get_io().received_at();
+            let (proto, pending) = self.get_pending();
+            match proto.unwrap_data(data) {
+                Ok(Some((id, ref pdu))) => match pending.remove(&id) {
+                    Some(op) => {
+                        trace(data, Some(pdu), false);
+                        return Ok((
+                            id,
+                            Python::attach(|py| op.to_python(pdu, py).map(|x| x.unbind())),
+                            received_at,
+                        ));
+                    }
+                    // Abandoned request
+                    None => {
+                        trace(data, Some(pdu), true);
+                        buf.reset();
+                    }
+                },
+                Ok(None) => {
+                    trace(data, None, true);
+                    buf.reset();
+                }
+                Err(e) => {
+                    trace(data, None, true);
+                    return Err(e.into());
+                }
             }
         }
     }
 
-    fn send_request<'a, T, V>(&mut self, req: V, py: Python) -> PyResult<()>
+    // Send request and wait for reply, retransmitting
+    // the request on timeout. Each attempt is a separate message,
+    // so the protocol refreshes its per-message state.
+    fn _retry_inner<'a, T, V>(
+        &mut self,
+        request_id: i64,
+        pdu: SnmpPdu,
+        mut iter: Option<&mut GetIter>,
+        policy: RetryPolicy,
+        deadline: Instant,
+    ) -> PyResult<Py<PyAny>>
+    where
+        T: PyOp<'a, V>,
+        V: 'a,
+    {
+        for attempt_deadline in Attempts::new(Some(policy), deadline) {
+            self._send_inner(pdu.clone())?;
+            if let Some(r) = self._try_recv_inner::<T, V>(
+                request_id,
+                iter.as_deref_mut(),
+                Some(attempt_deadline),
+            )? {
+                return Ok(r);
+            }
+        }
+        Err(SnmpError::WouldBlock.into())
+    }
+
+    // Send request and remember the operation, waiting for reply.
+    // Returns request id.
+    fn send_request<'a, T, V>(&mut self, req: V, op: Pending, py: Python) -> PyResult<i64>
     where
         T: PyOp<'a, V>,
         V: 'a,
     {
         // Parse python arguments, unnder GIL
-        let request_id = self.get_request_id().get_next();
-        let pdu = T::from_python(req, request_id)?;
+        let request_id = self.get_proto().get_request_id().get_next();
+        let pdu = match T::from_python(req, request_id) {
+            Ok(x) => x,
+            Err(e) => {
+                self.complete(request_id);
+                return Err(e);
+            }
+        };
         // Release GIL
-        py.detach(|| self._send_inner(pdu))
+        if let Err(e) = py.detach(|| self._send_inner(pdu)) {
+            self.complete(request_id);
+            return Err(e);
+        }
+        self.add_pending(request_id, op);
+        Ok(request_id)
     }
 
+    // Receive reply to the request, sent by `send_request`
     fn recv_reply<'a, T, V>(
         &mut self,
+        request_id: i64,
         iter: Option<&mut GetIter>,
         py: Python,
     ) -> PyResult<Py<PyAny>>
@@ -172,24 +352,45 @@ where
         T: PyOp<'a, V>,
         V: 'a,
     {
-        py.detach(|| self._recv_inner::<T, V>(iter))
+        let deadline = self.get_io().timeout(None).map(|t| Instant::now() + t);
+        py.detach(|| self._recv_inner::<T, V>(request_id, iter, deadline))
     }
 
+    // Receive reply to any request, sent by `send_request`.
+    // Returns request id along with the result.
+    fn recv_any(&mut self, py: Python) -> PyResult<(i64, Py<PyAny>)> {
+        let deadline = self.get_io().timeout(None).map(|t| Instant::now() + t);
+        py.detach(|| self._recv_any_inner(deadline))
+    }
+
+    // Send request and receive reply.
+    // `timeout_ns` overrides socket's timeout for this request.
     fn send_and_recv<'a, T, V>(
         &mut self,
         req: V,
         iter: Option<&mut GetIter>,
+        timeout_ns: Option<u64>,
         py: Python,
     ) -> PyResult<Py<PyAny>>
     where
         T: PyOp<'a, V>,
         V: 'a,
     {
-        let request_id = self.get_request_id().get_next();
-        let pdu = T::from_python(req, request_id)?;
-        py.detach(|| {
-            self._send_inner(pdu)?;
-            self._recv_inner::<T, V>(iter)
-        })
+        let request_id = self.get_proto().get_request_id().get_next();
+        let timeout = self.get_io().timeout(timeout_ns);
+        let r = T::from_python(req, request_id).and_then(|pdu| {
+            py.detach(|| {
+                let deadline = timeout.map(|t| Instant::now() + t);
+                let policy = timeout.and_then(|t| self.get_io().retry_policy(t));
+                if let (Some(policy), Some(deadline)) = (policy, deadline) {
+                    return self._retry_inner::<T, V>(request_id, pdu, iter, policy, deadline);
+                }
+                self._send_inner(pdu)?;
+                self._recv_inner::<T, V>(request_id, iter, deadline)
+            })
+        });
+        // Late replies are not expected
+        self.complete(request_id);
+        r
     }
 }
@@ -1,122 +1,264 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SnmpV2cClientSocket
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::snmpsocket::SnmpSocket;
+#[cfg(feature = "python")]
+use super::io::{SnmpIo, SocketConfig};
+use super::proto::SnmpProto;
+#[cfg(feature = "python")]
+use super::snmpsocket::{Pending, SnmpSocket};
 use crate::{
     ber::BerEncoder,
     buf::Buffer,
     error::SnmpResult,
     reqid::RequestId,
-    snmp::{
-        msg::SnmpV2cMessage,
-        op::{GetIter, OpGet, OpGetBulk, OpGetMany, OpGetNext},
-        pdu::SnmpPdu,
-    },
+    snmp::{msg::SnmpV2cMessage, pdu::SnmpPdu},
 };
+#[cfg(feature = "python")]
+use crate::{
+    hook::PyPacketHook,
+    policer::TokenBucketPolicer,
+    snmp::op::{GetIter, OpGet, OpGetBulk, OpGetMany, OpGetNext},
+};
+#[cfg(feature = "python")]
 use pyo3::{prelude::*, pybacked::PyBackedStr};
-use socket2::Socket;
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+#[cfg(feature = "python")]
 use std::os::fd::AsRawFd;
 
 /// Python class wrapping socket implementation
+#[cfg(feature = "python")]
 #[pyclass]
 pub struct SnmpV2cClientSocket {
-    io: Socket,
+    io: SnmpIo,
+    proto: SnmpV2cProto,
+    // Operations, waiting for reply, by request id
+    pending: HashMap<i64, Pending>,
+}
+
+/// SNMPv2c protocol state
+pub(crate) struct SnmpV2cProto {
     community: String,
     request_id: RequestId,
 }
 
+impl SnmpV2cProto {
+    pub fn new(community: String) -> Self {
+        Self {
+            community,
+            request_id: RequestId::default(),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
 #[pymethods]
 impl SnmpV2cClientSocket {
+    #[allow(clippy::too_many_arguments)] // Internal interface
     #[new]
+    #[pyo3(signature = (
+        addr,
+        community,
+        tos,
+        send_buffer_size,
+        recv_buffer_size,
+        timeout_ns,
+        transport = 0,
+        bind_addr = None,
+        bind_port = 0,
+        bind_device = None,
+        ttl = 0,
+        family = 0,
+        retries = 0,
+        retry_timeout_ns = 0,
+        backoff = 1.0,
+        policer = None,
+        packet_hook = None,
+        max_msg_size = 0,
+    ))]
     fn new(
+        py: Python,
         addr: String,
         community: String,
         tos: u32,
         send_buffer_size: usize,
         recv_buffer_size: usize,
         timeout_ns: u64,
+        transport: u8,
+        bind_addr: Option<String>,
+        bind_port: u16,
+        bind_device: Option<String>,
+        ttl: u32,
+        family: u8,
+        retries: u32,
+        retry_timeout_ns: u64,
+        backoff: f64,
+        policer: Option<PyRef<TokenBucketPolicer>>,
+        packet_hook: Option<Py<PyAny>>,
+        max_msg_size: usize,
     ) -> PyResult<Self> {
+        let cfg = SocketConfig {
+            transport: transport.try_into()?,
+            tos,
+            ttl,
+            send_buffer_size,
+            recv_buffer_size,
+            timeout_ns,
+            bind_addr,
+            bind_port,
+            bind_device,
+            family: family.try_into()?,
+            retries,
+            retry_timeout_ns,
+            backoff,
+            policer: policer.map(|p| p.get_policer()),
+            packet_hook: packet_hook.map(PyPacketHook::shared),
+            max_msg_size,
+        };
         Ok(Self {
-            io: Self::get_socket(addr, tos, send_buffer_size, recv_buffer_size, timeout_ns)?,
-            community,
-            request_id: RequestId::default(),
+            // Hostname resolution may take a while
+            io: py.detach(|| SnmpIo::new(addr, cfg))?,
+            proto: SnmpV2cProto::new(community),
+            pending: HashMap::new(),
         })
     }
     /// Get socket's file descriptor
     fn get_fd(&self) -> PyResult<i32> {
         Ok(self.io.as_raw_fd())
     }
+    /// Get agent's address, actually used
+    fn get_peer_addr(&self) -> String {
+        self.io.peer_addr()
+    }
+    /// Get round-trip time of the last completed request, in nanoseconds
+    fn get_rtt_ns(&self) -> Option<u64> {
+        self.io.rtt().map(|x| x.as_nanos() as u64)
+    }
     // .get()
     // Prepare send GET request with single oid and receive reply
-    fn get(&mut self, py: Python, oid: PyBackedStr) -> PyResult<Py<PyAny>> {
-        Self::send_and_recv::<OpGet, _>(self, oid, None, py)
+    #[pyo3(signature = (oid, timeout_ns = None))]
+    fn get(
+        &mut self,
+        py: Python,
+        oid: PyBackedStr,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
+        Self::send_and_recv::<OpGet, _>(self, oid, None, timeout_ns, py)
     }
     // Prepare and send GET request with single oid
-    fn send_get(&mut self, py: Python, oid: PyBackedStr) -> PyResult<()> {
-        Self::send_request::<OpGet, _>(self, oid, py)
+    fn send_get(&mut self, py: Python, oid: PyBackedStr) -> PyResult<i64> {
+        Self::send_request::<OpGet, _>(self, oid, Pending::Get, py)
     }
     // Try to receive GETRESPONSE
-    fn recv_get(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGet, _>(self, None, py)
+    fn recv_get(&mut self, py: Python, request_id: i64) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGet, _>(self, request_id, None, py)
     }
     // .get_many()
     // Prepare and send GET request with multiple oids and receive reply
-    fn get_many(&mut self, py: Python, oids: Vec<PyBackedStr>) -> PyResult<Py<PyAny>> {
-        Self::send_and_recv::<OpGetMany, _>(self, oids, None, py)
+    #[pyo3(signature = (oids, timeout_ns = None))]
+    fn get_many(
+        &mut self,
+        py: Python,
+        oids: Vec<PyBackedStr>,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
+        Self::send_and_recv::<OpGetMany, _>(self, oids, None, timeout_ns, py)
     }
     // Prepare and send GET request with multiple oids
-    fn send_get_many(&mut self, py: Python, oids: Vec<PyBackedStr>) -> PyResult<()> {
-        Self::send_request::<OpGetMany, _>(self, oids, py)
+    fn send_get_many(&mut self, py: Python, oids: Vec<PyBackedStr>) -> PyResult<i64> {
+        Self::send_request::<OpGetMany, _>(self, oids, Pending::GetMany, py)
     }
-    fn recv_get_many(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGetMany, _>(self, None, py)
+    fn recv_get_many(&mut self, py: Python, request_id: i64) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGetMany, _>(self, request_id, None, py)
     }
     // .get_next()
-    fn get_next(&mut self, py: Python, iter: &mut GetIter) -> PyResult<Py<PyAny>> {
+    #[pyo3(signature = (iter, timeout_ns = None))]
+    fn get_next(
+        &mut self,
+        py: Python,
+        iter: &mut GetIter,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
         let oid = iter.get_next_oid();
-        Self::send_and_recv::<OpGetNext, _>(self, oid, Some(iter), py)
+        Self::send_and_recv::<OpGetNext, _>(self, oid, Some(iter), timeout_ns, py)
     }
-    fn send_get_next(&mut self, py: Python, iter: &GetIter) -> PyResult<()> {
-        let oid = iter.get_next_oid();
-        Self::send_request::<OpGetNext, _>(self, oid, py)
+    fn send_get_next(&mut self, py: Python, iter: Py<GetIter>) -> PyResult<i64> {
+        let oid = iter.borrow(py).get_next_oid();
+        Self::send_request::<OpGetNext, _>(self, oid, Pending::GetNext(iter), py)
     }
-    fn recv_get_next(&mut self, py: Python, iter: &mut GetIter) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGetNext, _>(self, Some(iter), py)
+    fn recv_get_next(
+        &mut self,
+        py: Python,
+        iter: &mut GetIter,
+        request_id: i64,
+    ) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGetNext, _>(self, request_id, Some(iter), py)
     }
     // .get_bulk()
-    fn get_bulk(&mut self, py: Python, iter: &mut GetIter) -> PyResult<Py<PyAny>> {
+    #[pyo3(signature = (iter, timeout_ns = None))]
+    fn get_bulk(
+        &mut self,
+        py: Python,
+        iter: &mut GetIter,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
         Self::send_and_recv::<OpGetBulk, _>(
             self,
             (iter.get_next_oid(), iter.get_max_repetitions()),
             Some(iter),
+            timeout_ns,
             py,
         )
     }
     // Send GetBulk request according to iter
-    fn send_get_bulk(&mut self, py: Python, iter: &GetIter) -> PyResult<()> {
-        Self::send_request::<OpGetBulk, _>(
-            self,
-            (iter.get_next_oid(), iter.get_max_repetitions()),
-            py,
-        )
+    fn send_get_bulk(&mut self, py: Python, iter: Py<GetIter>) -> PyResult<i64> {
+        let (oid, max_repetitions) = {
+            let it = iter.borrow(py);
+            (it.get_next_oid(), it.get_max_repetitions())
+        };
+        Self::send_request::<OpGetBulk, _>(self, (oid, max_repetitions), Pending::GetBulk(iter), py)
     }
     // Try to receive GETRESPONSE for GETBULK
-    fn recv_get_bulk(&mut self, iter: &mut GetIter, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGetBulk, _>(self, Some(iter), py)
+    fn recv_get_bulk(
+        &mut self,
+        iter: &mut GetIter,
+        request_id: i64,
+        py: Python,
+    ) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGetBulk, _>(self, request_id, Some(iter), py)
+    }
+    // Receive reply to any request, sent by `send_*`.
+    // Returns (request id, result). Errors, related to the particular
+    // request, are returned as exception instances instead of raising.
+    fn recv(&mut self, py: Python) -> PyResult<(i64, Py<PyAny>)> {
+        Self::recv_any(self, py)
     }
 }
 
+#[cfg(feature = "python")]
 impl SnmpSocket for SnmpV2cClientSocket {
-    type Message<'a> = SnmpV2cMessage<'a>;
+    type Proto = SnmpV2cProto;
 
-    fn get_io(&mut self) -> &mut Socket {
+    fn get_io(&mut self) -> &mut SnmpIo {
         &mut self.io
     }
 
+    fn get_proto(&mut self) -> &mut Self::Proto {
+        &mut self.proto
+    }
+
+    fn get_pending(&mut self) -> (&mut Self::Proto, &mut HashMap<i64, Pending>) {
+        (&mut self.proto, &mut self.pending)
+    }
+}
+
+impl SnmpProto for SnmpV2cProto {
+    type Message<'a> = SnmpV2cMessage<'a>;
+
     fn get_request_id(&mut self) -> &mut RequestId {
         &mut self.request_id
     }
@@ -129,16 +271,54 @@ impl SnmpSocket for SnmpV2cClientSocket {
         msg.push_ber(buf)
     }
 
-    fn unwrap_pdu<'a>(&'a mut self, msg: Self::Message<'a>) -> Option<SnmpPdu<'a>> {
+    fn unwrap_pdu<'a>(
+        &'a mut self,
+        msg: Self::Message<'a>,
+    ) -> SnmpResult<Option<(i64, SnmpPdu<'a>)>> {
         // Check communnity
         if msg.community != self.community.as_bytes() {
-            return None;
+            return Ok(None);
         }
         // Check request id
         let pdu = msg.pdu;
         if !pdu.check(&self.request_id) {
-            return None;
+            return Ok(None);
         }
-        Some(pdu)
+        Ok(Some((pdu.get_request_id(), pdu)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snmp::getresponse::SnmpGetResponse;
+
+    fn reply(proto: &mut SnmpV2cProto, request_id: i64) -> SnmpResult<Option<i64>> {
+        let msg = SnmpV2cMessage {
+            community: b"public",
+            pdu: SnmpPdu::GetResponse(SnmpGetResponse {
+                request_id,
+                error_status: 0,
+                error_index: 0,
+                vars: vec![],
+            }),
+        };
+        let mut buf = Buffer::default();
+        msg.push_ber(&mut buf)?;
+        let data = buf.data().to_vec();
+        Ok(proto.unwrap_data(&data)?.map(|(id, _)| id))
+    }
+
+    #[test]
+    fn test_in_flight() -> SnmpResult<()> {
+        let mut proto = SnmpV2cProto::new("public".into());
+        let r1 = proto.get_request_id().get_next();
+        let r2 = proto.get_request_id().get_next();
+        // Replies in any order
+        assert_eq!(reply(&mut proto, r2)?, Some(r2));
+        assert_eq!(reply(&mut proto, r1)?, Some(r1));
+        // Unrelated reply
+        assert_eq!(reply(&mut proto, r1 ^ r2 ^ 1)?, None);
+        Ok(())
     }
 }
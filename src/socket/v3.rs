@@ -1,51 +1,112 @@
 // ------------------------------------------------------------------------
 // Gufo SNMP: SnmpV3ClientSocket
 // ------------------------------------------------------------------------
-// Copyright (C) 2023-25, Gufo Labs
+// Copyright (C) 2023-26, Gufo Labs
 // See LICENSE.md for details
 // ------------------------------------------------------------------------
 
-use super::snmpsocket::SnmpSocket;
+#[cfg(feature = "python")]
+use super::io::{SnmpIo, SocketConfig};
+use super::proto::SnmpProto;
+#[cfg(feature = "python")]
+use super::snmpsocket::{Pending, SnmpSocket};
 use crate::{
     auth::{AuthKey, SnmpAuth},
     ber::BerEncoder,
-    buf::Buffer,
-    error::SnmpResult,
+    buf::{Buffer, MAX_SIZE},
+    error::{SnmpError, SnmpResult},
     privacy::{PrivKey, SnmpPriv},
     reqid::RequestId,
     snmp::{
-        msg::v3::{MsgData, ScopedPdu, SnmpV3Message, UsmParameters},
-        op::{GetIter, OpGet, OpGetBulk, OpGetMany, OpGetNext, OpRefresh},
+        msg::v3::{
+            DEFAULT_MAX_SIZE, MIN_MAX_SIZE, MsgData, ScopedPdu, SnmpV3Message, UsmParameters,
+        },
         pdu::SnmpPdu,
     },
 };
-use pyo3::types::PyBytes;
-use pyo3::{prelude::*, pybacked::PyBackedStr};
-use socket2::Socket;
+#[cfg(feature = "python")]
+use crate::{
+    hook::PyPacketHook,
+    policer::TokenBucketPolicer,
+    snmp::op::{GetIter, OpGet, OpGetBulk, OpGetMany, OpGetNext, OpRefresh},
+};
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyValueError, prelude::*, pybacked::PyBackedStr, types::PyBytes};
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+#[cfg(feature = "python")]
 use std::os::fd::AsRawFd;
+use std::time::Instant;
 
 /// Python class wrapping socket implementation
+#[cfg(feature = "python")]
 #[pyclass]
 pub struct SnmpV3ClientSocket {
-    io: Socket,
+    io: SnmpIo,
+    proto: SnmpV3Proto,
+    // Operations, waiting for reply, by request id
+    pending: HashMap<i64, Pending>,
+}
+
+/// SNMPv3 protocol state
+pub(crate) struct SnmpV3Proto {
     engine_id: Vec<u8>,
     engine_boots: i64,
     engine_time: i64,
+    // When engine_time has been received
+    engine_time_at: Option<Instant>,
     user_name: String,
     auth_key: AuthKey,
     priv_key: PrivKey,
-    msg_id: RequestId,
+    // msgID -> request id
+    msg_id: RequestId<i64>,
     request_id: RequestId,
+    // Our msgMaxSize
+    max_size: i64,
+    // msgMaxSize, reported by agent. 0 - unknown
+    agent_max_size: usize,
 }
 
 const EMPTY: [u8; 0] = [];
+// Estimated size of the message besides varbinds:
+// headers, USM parameters, scoped PDU and padding.
+const MSG_OVERHEAD: usize = 128;
+// Estimated size of the single varbind in response.
+const VAR_SIZE: usize = 64;
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl SnmpV3ClientSocket {
     /// Python constructor
     #[allow(clippy::too_many_arguments)] // Internal interface
     #[new]
+    #[pyo3(signature = (
+        addr,
+        engine_id,
+        user_name,
+        auth_alg,
+        auth_key,
+        priv_alg,
+        priv_key,
+        tos,
+        send_buffer_size,
+        recv_buffer_size,
+        timeout_ns,
+        max_msg_size = 0,
+        transport = 0,
+        bind_addr = None,
+        bind_port = 0,
+        bind_device = None,
+        ttl = 0,
+        family = 0,
+        retries = 0,
+        retry_timeout_ns = 0,
+        backoff = 1.0,
+        policer = None,
+        packet_hook = None,
+    ))]
     fn new(
+        py: Python,
         addr: String,
         engine_id: Vec<u8>,
         user_name: String,
@@ -57,29 +118,49 @@ impl SnmpV3ClientSocket {
         send_buffer_size: usize,
         recv_buffer_size: usize,
         timeout_ns: u64,
+        max_msg_size: usize,
+        transport: u8,
+        bind_addr: Option<String>,
+        bind_port: u16,
+        bind_device: Option<String>,
+        ttl: u32,
+        family: u8,
+        retries: u32,
+        retry_timeout_ns: u64,
+        backoff: f64,
+        policer: Option<PyRef<TokenBucketPolicer>>,
+        packet_hook: Option<Py<PyAny>>,
     ) -> PyResult<Self> {
-        // Auth key
-        let mut auth = AuthKey::new(auth_alg)?;
-        auth.as_key_type(auth_alg, auth_key, &engine_id)?;
-        // Priv key
-        let mut pk = PrivKey::new(priv_alg)?;
-        if pk.has_priv() {
-            // Localize key
-            let mut pk_auth = AuthKey::new(auth_alg)?;
-            pk_auth.as_key_type(priv_alg, priv_key, &engine_id)?;
-            pk.as_localized(pk_auth.get_key())?;
-        }
-        //
-        Ok(Self {
-            io: Self::get_socket(addr, tos, send_buffer_size, recv_buffer_size, timeout_ns)?,
+        let cfg = SocketConfig {
+            transport: transport.try_into()?,
+            tos,
+            ttl,
+            send_buffer_size,
+            recv_buffer_size,
+            timeout_ns,
+            bind_addr,
+            bind_port,
+            bind_device,
+            family: family.try_into()?,
+            retries,
+            retry_timeout_ns,
+            backoff,
+            policer: policer.map(|p| p.get_policer()),
+            packet_hook: packet_hook.map(PyPacketHook::shared),
+            max_msg_size,
+        };
+        let proto = SnmpV3Proto::new(
             engine_id,
-            engine_boots: 0,
-            engine_time: 0,
             user_name,
-            auth_key: auth,
-            priv_key: pk,
-            msg_id: RequestId::default(),
-            request_id: RequestId::default(),
+            (auth_alg, auth_key),
+            (priv_alg, priv_key),
+            get_max_size(max_msg_size)?,
+        )?;
+        Ok(Self {
+            // Hostname resolution may take a while
+            io: py.detach(|| SnmpIo::new(addr, cfg))?,
+            proto,
+            pending: HashMap::new(),
         })
     }
     /// Change keys
@@ -91,142 +172,306 @@ impl SnmpV3ClientSocket {
         priv_alg: u8,
         priv_key: &[u8],
     ) -> PyResult<()> {
-        // Replace user
-        self.user_name = user_name;
-        // Auth key
-        let mut auth = AuthKey::new(auth_alg)?;
-        auth.as_key_type(auth_alg, auth_key, &self.engine_id)?;
-        // Priv key
-        let mut pk = PrivKey::new(priv_alg)?;
-        if pk.has_priv() {
-            // Localize key
-            let mut pk_auth = AuthKey::new(auth_alg)?;
-            pk_auth.as_key_type(priv_alg, priv_key, &self.engine_id)?;
-            pk.as_localized(pk_auth.get_key())?;
-        }
-        self.auth_key = auth;
-        self.priv_key = pk;
-        Ok(())
+        Ok(self
+            .proto
+            .set_keys(user_name, (auth_alg, auth_key), (priv_alg, priv_key))?)
     }
     /// Get socket's file descriptor
     fn get_fd(&self) -> PyResult<i32> {
         Ok(self.io.as_raw_fd())
     }
+    /// Get agent's address, actually used
+    fn get_peer_addr(&self) -> String {
+        self.io.peer_addr()
+    }
+    /// Get round-trip time of the last completed request, in nanoseconds
+    fn get_rtt_ns(&self) -> Option<u64> {
+        self.io.rtt().map(|x| x.as_nanos() as u64)
+    }
     /// Get engine id
     fn get_engine_id(&self, py: Python) -> PyResult<Py<PyAny>> {
-        Ok(PyBytes::new(py, &self.engine_id).into())
+        Ok(PyBytes::new(py, self.proto.get_engine_id()).into())
+    }
+    /// Get maximal amount of varbinds, fitting agent's msgMaxSize.
+    /// 0 - if unknown.
+    fn get_max_varbinds(&self) -> usize {
+        self.proto.max_varbinds()
     }
     // .get()
     // Prepare send GET request with single oid and receive reply
-    fn get(&mut self, py: Python, oid: PyBackedStr) -> PyResult<Py<PyAny>> {
-        Self::send_and_recv::<OpGet, _>(self, oid, None, py)
+    #[pyo3(signature = (oid, timeout_ns = None))]
+    fn get(
+        &mut self,
+        py: Python,
+        oid: PyBackedStr,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
+        Self::send_and_recv::<OpGet, _>(self, oid, None, timeout_ns, py)
     }
     // Prepare and send GET request with single oid
-    fn send_get(&mut self, py: Python, oid: PyBackedStr) -> PyResult<()> {
-        Self::send_request::<OpGet, _>(self, oid, py)
+    fn send_get(&mut self, py: Python, oid: PyBackedStr) -> PyResult<i64> {
+        Self::send_request::<OpGet, _>(self, oid, Pending::Get, py)
     }
     // Try to receive GETRESPONSE
-    fn recv_get(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGet, _>(self, None, py)
+    fn recv_get(&mut self, py: Python, request_id: i64) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGet, _>(self, request_id, None, py)
     }
     // .get_many()
     // Prepare and send GET request with multiple oids and receive reply
-    fn get_many(&mut self, py: Python, oids: Vec<PyBackedStr>) -> PyResult<Py<PyAny>> {
-        Self::send_and_recv::<OpGetMany, _>(self, oids, None, py)
+    #[pyo3(signature = (oids, timeout_ns = None))]
+    fn get_many(
+        &mut self,
+        py: Python,
+        oids: Vec<PyBackedStr>,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
+        Self::send_and_recv::<OpGetMany, _>(self, oids, None, timeout_ns, py)
     }
     // Prepare and send GET request with multiple oids
-    fn send_get_many(&mut self, py: Python, oids: Vec<PyBackedStr>) -> PyResult<()> {
-        Self::send_request::<OpGetMany, _>(self, oids, py)
+    fn send_get_many(&mut self, py: Python, oids: Vec<PyBackedStr>) -> PyResult<i64> {
+        Self::send_request::<OpGetMany, _>(self, oids, Pending::GetMany, py)
     }
-    fn recv_get_many(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGetMany, _>(self, None, py)
+    fn recv_get_many(&mut self, py: Python, request_id: i64) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGetMany, _>(self, request_id, None, py)
     }
     // .get_next()
-    fn get_next(&mut self, py: Python, iter: &mut GetIter) -> PyResult<Py<PyAny>> {
+    #[pyo3(signature = (iter, timeout_ns = None))]
+    fn get_next(
+        &mut self,
+        py: Python,
+        iter: &mut GetIter,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
         let oid = iter.get_next_oid();
-        Self::send_and_recv::<OpGetNext, _>(self, oid, Some(iter), py)
+        Self::send_and_recv::<OpGetNext, _>(self, oid, Some(iter), timeout_ns, py)
     }
-    fn send_get_next(&mut self, py: Python, iter: &GetIter) -> PyResult<()> {
-        let oid = iter.get_next_oid();
-        Self::send_request::<OpGetNext, _>(self, oid, py)
+    fn send_get_next(&mut self, py: Python, iter: Py<GetIter>) -> PyResult<i64> {
+        let oid = iter.borrow(py).get_next_oid();
+        Self::send_request::<OpGetNext, _>(self, oid, Pending::GetNext(iter), py)
     }
-    fn recv_get_next(&mut self, py: Python, iter: &mut GetIter) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGetNext, _>(self, Some(iter), py)
+    fn recv_get_next(
+        &mut self,
+        py: Python,
+        iter: &mut GetIter,
+        request_id: i64,
+    ) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGetNext, _>(self, request_id, Some(iter), py)
     }
     // .get_bulk()
-    fn get_bulk(&mut self, py: Python, iter: &mut GetIter) -> PyResult<Py<PyAny>> {
+    #[pyo3(signature = (iter, timeout_ns = None))]
+    fn get_bulk(
+        &mut self,
+        py: Python,
+        iter: &mut GetIter,
+        timeout_ns: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
+        let max_repetitions = self.proto.fit_max_repetitions(iter.get_max_repetitions());
         Self::send_and_recv::<OpGetBulk, _>(
             self,
-            (iter.get_next_oid(), iter.get_max_repetitions()),
+            (iter.get_next_oid(), max_repetitions),
             Some(iter),
+            timeout_ns,
             py,
         )
     }
     // Send GetBulk request according to iter
-    fn send_get_bulk(&mut self, py: Python, iter: &GetIter) -> PyResult<()> {
-        Self::send_request::<OpGetBulk, _>(
-            self,
-            (iter.get_next_oid(), iter.get_max_repetitions()),
-            py,
-        )
+    fn send_get_bulk(&mut self, py: Python, iter: Py<GetIter>) -> PyResult<i64> {
+        let (oid, max_repetitions) = {
+            let it = iter.borrow(py);
+            (
+                it.get_next_oid(),
+                self.proto.fit_max_repetitions(it.get_max_repetitions()),
+            )
+        };
+        Self::send_request::<OpGetBulk, _>(self, (oid, max_repetitions), Pending::GetBulk(iter), py)
     }
     // Try to receive GETRESPONSE for GETBULK
-    fn recv_get_bulk(&mut self, iter: &mut GetIter, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpGetBulk, _>(self, Some(iter), py)
+    fn recv_get_bulk(
+        &mut self,
+        iter: &mut GetIter,
+        request_id: i64,
+        py: Python,
+    ) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpGetBulk, _>(self, request_id, Some(iter), py)
+    }
+    // Receive reply to any request, sent by `send_*`.
+    // Returns (request id, result). Errors, related to the particular
+    // request, are returned as exception instances instead of raising.
+    fn recv(&mut self, py: Python) -> PyResult<(i64, Py<PyAny>)> {
+        Self::recv_any(self, py)
     }
     // Send GET+Report to adjust boots and time
-    fn refresh(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        Self::send_and_recv::<OpRefresh, _>(self, (), None, py)
+    #[pyo3(signature = (timeout_ns = None))]
+    fn refresh(&mut self, py: Python, timeout_ns: Option<u64>) -> PyResult<Py<PyAny>> {
+        Self::send_and_recv::<OpRefresh, _>(self, (), None, timeout_ns, py)
     }
     //
-    fn send_refresh(&mut self, py: Python) -> PyResult<()> {
-        Self::send_request::<OpRefresh, _>(self, (), py)
+    fn send_refresh(&mut self, py: Python) -> PyResult<i64> {
+        Self::send_request::<OpRefresh, _>(self, (), Pending::Refresh, py)
     }
     //
-    fn recv_refresh(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        Self::recv_reply::<OpRefresh, _>(self, None, py)
+    fn recv_refresh(&mut self, py: Python, request_id: i64) -> PyResult<Py<PyAny>> {
+        Self::recv_reply::<OpRefresh, _>(self, request_id, None, py)
+    }
+}
+
+// msgMaxSize, limited by receive buffer.
+// None if too small.
+pub(crate) fn fit_max_size(max_msg_size: usize) -> Option<i64> {
+    match max_msg_size {
+        0 => Some(DEFAULT_MAX_SIZE),
+        x if (x as i64) < MIN_MAX_SIZE => None,
+        x => Some(x.min(MAX_SIZE) as i64),
+    }
+}
+
+#[cfg(feature = "python")]
+pub(crate) fn get_max_size(max_msg_size: usize) -> PyResult<i64> {
+    fit_max_size(max_msg_size).ok_or_else(|| PyValueError::new_err("invalid max message size"))
+}
+
+impl SnmpV3Proto {
+    pub fn new(
+        engine_id: Vec<u8>,
+        user_name: String,
+        auth: (u8, &[u8]),
+        privacy: (u8, &[u8]),
+        max_size: i64,
+    ) -> SnmpResult<Self> {
+        let (auth_key, priv_key) = Self::localize_keys(&engine_id, auth, privacy)?;
+        Ok(Self {
+            engine_id,
+            engine_boots: 0,
+            engine_time: 0,
+            engine_time_at: None,
+            user_name,
+            auth_key,
+            priv_key,
+            msg_id: RequestId::default(),
+            request_id: RequestId::default(),
+            max_size,
+            agent_max_size: 0,
+        })
+    }
+    /// Change user and keys
+    pub fn set_keys(
+        &mut self,
+        user_name: String,
+        auth: (u8, &[u8]),
+        privacy: (u8, &[u8]),
+    ) -> SnmpResult<()> {
+        let (auth_key, priv_key) = Self::localize_keys(&self.engine_id, auth, privacy)?;
+        self.user_name = user_name;
+        self.auth_key = auth_key;
+        self.priv_key = priv_key;
+        Ok(())
+    }
+    // Get localized auth and privacy keys
+    pub(crate) fn localize_keys(
+        engine_id: &[u8],
+        (auth_alg, auth_key): (u8, &[u8]),
+        (priv_alg, priv_key): (u8, &[u8]),
+    ) -> SnmpResult<(AuthKey, PrivKey)> {
+        // Auth key
+        let mut auth = AuthKey::new(auth_alg)?;
+        auth.as_key_type(auth_alg, auth_key, engine_id)?;
+        // Priv key
+        let mut pk = PrivKey::new(priv_alg)?;
+        if pk.has_priv() {
+            // Localize key
+            let mut pk_auth = AuthKey::new(auth_alg)?;
+            pk_auth.as_key_type(priv_alg, priv_key, engine_id)?;
+            pk.as_localized(pk_auth.get_key())?;
+        }
+        Ok((auth, pk))
+    }
+    // Estimated agent's snmpEngineTime, RFC-3414 pp. 2.3
+    fn get_engine_time(&self) -> i64 {
+        match self.engine_time_at {
+            Some(t) => (self.engine_time + t.elapsed().as_secs() as i64).min(i32::MAX as i64),
+            None => self.engine_time,
+        }
+    }
+    /// Get engine id
+    pub fn get_engine_id(&self) -> &[u8] {
+        &self.engine_id
+    }
+    // Estimate amount of varbinds, fitting agent's msgMaxSize.
+    // Returns 0 if agent's msgMaxSize is not known yet.
+    pub fn max_varbinds(&self) -> usize {
+        if self.agent_max_size == 0 {
+            return 0;
+        }
+        let overhead = MSG_OVERHEAD + 2 * self.engine_id.len() + self.user_name.len();
+        (self.agent_max_size.saturating_sub(overhead) / VAR_SIZE).max(1)
+    }
+    /// msgID of the last pushed message, if still in flight
+    #[cfg(feature = "tokio")]
+    pub fn last_msg_id(&self) -> Option<i64> {
+        self.msg_id.last()
+    }
+    // Limit max-repetitions to fit agent's msgMaxSize
+    pub fn fit_max_repetitions(&self, max_repetitions: i64) -> i64 {
+        match self.max_varbinds() {
+            0 => max_repetitions,
+            n => max_repetitions.min(n as i64),
+        }
     }
 }
 
+#[cfg(feature = "python")]
 impl SnmpSocket for SnmpV3ClientSocket {
-    type Message<'a> = SnmpV3Message<'a>;
+    type Proto = SnmpV3Proto;
 
-    fn get_io(&mut self) -> &mut Socket {
+    fn get_io(&mut self) -> &mut SnmpIo {
         &mut self.io
     }
 
+    fn get_proto(&mut self) -> &mut Self::Proto {
+        &mut self.proto
+    }
+
+    fn get_pending(&mut self) -> (&mut Self::Proto, &mut HashMap<i64, Pending>) {
+        (&mut self.proto, &mut self.pending)
+    }
+}
+
+impl SnmpProto for SnmpV3Proto {
+    type Message<'a> = SnmpV3Message<'a>;
+
     fn get_request_id(&mut self) -> &mut RequestId {
         &mut self.request_id
     }
 
     fn push_pdu(&mut self, pdu: SnmpPdu, buf: &mut Buffer) -> SnmpResult<()> {
         //
+        let request_id = pdu.get_request_id();
         let flag_priv = self.priv_key.has_priv();
+        let engine_time = self.get_engine_time();
         let scoped_pdu = ScopedPdu {
             engine_id: &self.engine_id,
             pdu,
         };
         let (privacy_params, data) = if flag_priv {
             // Encrypted
-            let (enc_data, privacy_params) = self.priv_key.encrypt(
-                &scoped_pdu,
-                self.engine_boots as u32,
-                self.engine_time as u32,
-            )?;
+            let (enc_data, privacy_params) =
+                self.priv_key
+                    .encrypt(&scoped_pdu, self.engine_boots as u32, engine_time as u32)?;
             (privacy_params, MsgData::Encrypted(enc_data))
         } else {
             (EMPTY.as_ref(), MsgData::Plaintext(scoped_pdu))
         };
         // Prepare message
         let msg = SnmpV3Message {
-            msg_id: self.msg_id.get_next(),
+            msg_id: self.msg_id.push(request_id),
+            max_size: self.max_size,
             flag_auth: self.auth_key.has_auth(),
             flag_priv,
             flag_report: true, // Some crazy boxes answer incorrectly if not set
             usm: UsmParameters {
                 engine_id: &self.engine_id,
                 engine_boots: self.engine_boots,
-                engine_time: self.engine_time,
+                engine_time,
                 user_name: self.user_name.as_ref(),
                 auth_params: self.auth_key.placeholder(),
                 privacy_params,
@@ -235,35 +480,236 @@ impl SnmpSocket for SnmpV3ClientSocket {
         };
         // Serialize BER to buffer
         msg.push_ber(buf)?;
-        // Apply auth
+        // Apply auth. Bookmark is set by auth parameters placeholder only.
+        if !self.auth_key.has_auth() {
+            return Ok(());
+        }
         let offset = buf.get_bookmark();
         self.auth_key.sign(buf.data_mut(), offset)
     }
 
-    fn unwrap_pdu<'a>(&'a mut self, msg: Self::Message<'a>) -> Option<SnmpPdu<'a>> {
-        // Get and decode scoped pdu
-        let data = match msg.data {
-            MsgData::Plaintext(x) => x,
-            MsgData::Encrypted(x) => match self.priv_key.decrypt(x, &msg.usm) {
-                Ok(x) => x,
-                Err(_) => return None, // Failed to decrypt
-            },
-        };
+    fn complete(&mut self, request_id: i64) {
+        self.request_id.remove(request_id);
+        // Each retransmission has its own msgID
+        self.msg_id.remove_value(&request_id);
+    }
+
+    fn unwrap_pdu<'a>(
+        &'a mut self,
+        msg: Self::Message<'a>,
+    ) -> SnmpResult<Option<(i64, SnmpPdu<'a>)>> {
         // Global header check
         if !(self.user_name.as_bytes() == msg.usm.user_name
-            && (self.engine_id.is_empty() || msg.usm.engine_id == self.engine_id)
-            && self.msg_id.check(msg.msg_id)
-            && data.pdu.check(&self.request_id))
+            && (self.engine_id.is_empty() || msg.usm.engine_id == self.engine_id))
         {
-            return None;
+            return Ok(None);
+        }
+        // Request, the message is related to
+        let request_id = match self.msg_id.get(msg.msg_id) {
+            Some(&x) if self.request_id.check(x) => x,
+            _ => return Ok(None),
+        };
+        // Get and decode scoped pdu.
+        // Matching message which cannot be decrypted or parsed
+        // usually means wrong privacy key.
+        let data = match msg.data {
+            MsgData::Plaintext(x) => x,
+            MsgData::Encrypted(x) => self
+                .priv_key
+                .decrypt(x, &msg.usm)
+                .map_err(|_| SnmpError::DecryptionFailed)?,
+        };
+        // Reports are matched by msgID only
+        if !matches!(data.pdu, SnmpPdu::Report(_)) && data.pdu.get_request_id() != request_id {
+            return Ok(None);
         }
         // Update engine parameters
         self.engine_boots = msg.usm.engine_boots;
         self.engine_time = msg.usm.engine_time;
+        self.engine_time_at = Some(Instant::now());
+        self.agent_max_size = usize::try_from(msg.max_size).unwrap_or_default();
         if self.engine_id.is_empty() {
             // Auto-detect engine id
             self.engine_id.extend_from_slice(msg.usm.engine_id);
         }
-        Some(data.pdu)
+        self.msg_id.remove(msg.msg_id);
+        Ok(Some((request_id, data.pdu)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::MD5_AUTH;
+    use crate::snmp::{get::SnmpGet, getresponse::SnmpGetResponse};
+    use std::time::Duration;
+
+    #[test]
+    fn test_engine_time() -> SnmpResult<()> {
+        let mut proto = SnmpV3Proto::new(vec![], "user".into(), (0, &[]), (0, &[]), 1500)?;
+        assert_eq!(proto.get_engine_time(), 0);
+        proto.engine_time = 100;
+        assert_eq!(proto.get_engine_time(), 100);
+        proto.engine_time_at = Instant::now().checked_sub(Duration::from_secs(5));
+        assert_eq!(proto.get_engine_time(), 105);
+        Ok(())
+    }
+
+    const AES128: u8 = 2;
+
+    // Agent's reply to the message
+    fn reply(msg_id: i64, request_id: i64) -> SnmpResult<Vec<u8>> {
+        reply_with((0, &[]), (0, &[]), msg_id, request_id)
+    }
+
+    // Agent's reply to the message, protected with the given keys
+    fn reply_with(
+        auth: (u8, &[u8]),
+        privacy: (u8, &[u8]),
+        msg_id: i64,
+        request_id: i64,
+    ) -> SnmpResult<Vec<u8>> {
+        let (auth_key, mut priv_key) = SnmpV3Proto::localize_keys(b"agent", auth, privacy)?;
+        let scoped_pdu = ScopedPdu {
+            engine_id: b"agent",
+            pdu: SnmpPdu::GetResponse(SnmpGetResponse {
+                request_id,
+                error_status: 0,
+                error_index: 0,
+                vars: vec![],
+            }),
+        };
+        let flag_priv = priv_key.has_priv();
+        let (privacy_params, data) = if flag_priv {
+            let (enc_data, privacy_params) = priv_key.encrypt(&scoped_pdu, 1, 0)?;
+            (privacy_params, MsgData::Encrypted(enc_data))
+        } else {
+            (EMPTY.as_ref(), MsgData::Plaintext(scoped_pdu))
+        };
+        let msg = SnmpV3Message {
+            msg_id,
+            max_size: DEFAULT_MAX_SIZE,
+            flag_auth: auth_key.has_auth(),
+            flag_priv,
+            flag_report: false,
+            usm: UsmParameters {
+                engine_id: b"agent",
+                engine_boots: 1,
+                engine_time: 0,
+                user_name: b"user",
+                auth_params: auth_key.placeholder(),
+                privacy_params,
+            },
+            data,
+        };
+        let mut buf = Buffer::default();
+        msg.push_ber(&mut buf)?;
+        if auth_key.has_auth() {
+            let offset = buf.get_bookmark();
+            auth_key.sign(buf.data_mut(), offset)?;
+        }
+        Ok(buf.data().to_vec())
+    }
+
+    #[test]
+    fn test_decryption_failed() -> SnmpResult<()> {
+        let auth = (MD5_AUTH, b"authpass".as_slice());
+        let mut proto = SnmpV3Proto::new(
+            b"agent".to_vec(),
+            "user".into(),
+            auth,
+            (AES128, b"privpass"),
+            1500,
+        )?;
+        let request_id = proto.get_request_id().get_next();
+        let mut buf = Buffer::default();
+        proto.push_pdu(
+            SnmpPdu::GetRequest(SnmpGet {
+                request_id,
+                vars: vec![],
+            }),
+            &mut buf,
+        )?;
+        let msg_id = SnmpV3Message::try_from(buf.data())?.msg_id;
+        // Authenticated reply, encrypted with other key
+        let data = reply_with(auth, (AES128, b"otherpass"), msg_id, request_id)?;
+        assert!(matches!(
+            proto.unwrap_data(&data),
+            Err(SnmpError::DecryptionFailed)
+        ));
+        // Reply, encrypted with the right key
+        let data = reply_with(auth, (AES128, b"privpass"), msg_id, request_id)?;
+        assert!(matches!(proto.unwrap_data(&data)?, Some((id, _)) if id == request_id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_retransmission_msg_ids() -> SnmpResult<()> {
+        let mut proto =
+            SnmpV3Proto::new(b"agent".to_vec(), "user".into(), (0, &[]), (0, &[]), 1500)?;
+        // Request is sent three times, each with own msgID
+        let request_id = proto.get_request_id().get_next();
+        let pdu = SnmpPdu::GetRequest(SnmpGet {
+            request_id,
+            vars: vec![],
+        });
+        let mut msg_ids = Vec::new();
+        for _ in 0..3 {
+            let mut buf = Buffer::default();
+            proto.push_pdu(pdu.clone(), &mut buf)?;
+            msg_ids.push(SnmpV3Message::try_from(buf.data())?.msg_id);
+        }
+        assert_eq!(proto.msg_id.len(), 3);
+        // Reply to the second attempt
+        let data = reply(msg_ids[1], request_id)?;
+        assert!(matches!(proto.unwrap_data(&data)?, Some((id, _)) if id == request_id));
+        proto.complete(request_id);
+        assert!(proto.msg_id.is_empty());
+        assert!(proto.request_id.is_empty());
+        // Late reply to the first attempt
+        let data = reply(msg_ids[0], request_id)?;
+        assert!(proto.unwrap_data(&data)?.is_none());
+        // Request without reply
+        let request_id = proto.get_request_id().get_next();
+        let pdu = SnmpPdu::GetRequest(SnmpGet {
+            request_id,
+            vars: vec![],
+        });
+        for _ in 0..2 {
+            let mut buf = Buffer::default();
+            proto.push_pdu(pdu.clone(), &mut buf)?;
+        }
+        proto.complete(request_id);
+        assert!(proto.msg_id.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_varbinds() -> SnmpResult<()> {
+        let mut proto = SnmpV3Proto::new(vec![], "user".into(), (0, &[]), (0, &[]), 1500)?;
+        let overhead = MSG_OVERHEAD + "user".len();
+        // Unknown
+        assert_eq!(proto.max_varbinds(), 0);
+        // Exactly full
+        proto.agent_max_size = overhead + 3 * VAR_SIZE;
+        assert_eq!(proto.max_varbinds(), 3);
+        // One octet over
+        proto.agent_max_size = overhead + 3 * VAR_SIZE - 1;
+        assert_eq!(proto.max_varbinds(), 2);
+        // Single varbind does not fit, but still sent
+        proto.agent_max_size = overhead;
+        assert_eq!(proto.max_varbinds(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_max_repetitions() -> SnmpResult<()> {
+        let mut proto = SnmpV3Proto::new(vec![], "user".into(), (0, &[]), (0, &[]), 1500)?;
+        // Unknown
+        assert_eq!(proto.fit_max_repetitions(50), 50);
+        proto.agent_max_size = MSG_OVERHEAD + "user".len() + 10 * VAR_SIZE;
+        assert_eq!(proto.fit_max_repetitions(50), 10);
+        assert_eq!(proto.fit_max_repetitions(5), 5);
+        Ok(())
     }
 }
@@ -1,7 +1,7 @@
 # ---------------------------------------------------------------------
 # Gufo SNMP: snmpd fixture
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-24, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
@@ -23,6 +23,8 @@ from .util import (
     SNMPD_ADDRESS,
     SNMPD_PATH,
     SNMPD_PORT,
+    SNMPD_TCP_PORT,
+    SNMPD_UNIX_PATH,
 )
 
 
@@ -34,6 +36,8 @@ def snmpd() -> Iterator[Snmpd]:
         path=SNMPD_PATH,
         address=SNMPD_ADDRESS,
         port=SNMPD_PORT,
+        tcp_port=SNMPD_TCP_PORT,
+        unix_path=SNMPD_UNIX_PATH,
         community=SNMP_COMMUNITY,
         location=SNMP_LOCATION,
         contact=SNMP_CONTACT,
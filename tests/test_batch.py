@@ -0,0 +1,54 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Test request batching
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+# Python modules
+from typing import List
+
+# Third-party modules
+import pytest
+
+# Gufo SNMP modules
+from gufo.snmp._fast import SnmpV2cClientSocket, SnmpV3ClientSocket
+from gufo.snmp.batch import get_batches, get_max_varbinds
+
+OIDS = [f"1.3.6.1.2.1.1.{i}.0" for i in range(1, 5)]
+TIMEOUT_NS = 1_000_000_000
+
+
+@pytest.mark.parametrize(
+    ("oids", "size", "expected"),
+    [
+        # Unlimited
+        (OIDS, 0, [OIDS]),
+        # Exactly full
+        (OIDS, 4, [OIDS]),
+        # One over
+        (OIDS, 3, [OIDS[:3], OIDS[3:]]),
+        # Single oid per message
+        (OIDS, 1, [[x] for x in OIDS]),
+        # Single oversized oid
+        (OIDS[:1], 1, [OIDS[:1]]),
+        ([], 3, [[]]),
+    ],
+)
+def test_get_batches(
+    oids: List[str], size: int, expected: List[List[str]]
+) -> None:
+    assert list(get_batches(oids, size)) == expected
+
+
+def test_max_varbinds_v2c() -> None:
+    sock = SnmpV2cClientSocket("127.0.0.1:161", "public", 0, 0, 0, TIMEOUT_NS)
+    assert get_max_varbinds(sock) == 0
+
+
+def test_max_varbinds_v3_unknown() -> None:
+    sock = SnmpV3ClientSocket(
+        "127.0.0.1:161", b"", "user", 0, b"", 0, b"", 0, 0, 0, TIMEOUT_NS
+    )
+    # Agent's msgMaxSize is not known before the first reply
+    assert get_max_varbinds(sock) == 0
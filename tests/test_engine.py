@@ -0,0 +1,107 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Test SNMPv3 authoritative engine
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+# Gufo SNMP modules
+from gufo.snmp import SnmpVersion, decode_message, encode_message
+from gufo.snmp._fast import SnmpV3Engine
+from gufo.snmp.user import Aes128Key, Sha1Key, User
+
+ENGINE_ID = b"\x80\x00\x1f\x88\x04test"
+USER = User(
+    name="user",
+    auth_key=Sha1Key(b"authpass"),
+    priv_key=Aes128Key(b"privpass"),
+)
+VARBINDS = [
+    ("1.3.6.1.2.1.1.3.0", ("TimeTicks", 100)),
+    ("1.3.6.1.6.3.1.1.4.1.0", ("Oid", "1.3.6.1.6.3.1.1.5.1")),
+]
+
+
+def get_engine() -> SnmpV3Engine:
+    engine = SnmpV3Engine(ENGINE_ID)
+    # Empty engine id for informs, sent to the local engine
+    engine.add_user(
+        USER.name,
+        b"",
+        USER.get_auth_alg(),
+        USER.get_auth_key(),
+        USER.get_priv_alg(),
+        USER.get_priv_key(),
+    )
+    return engine
+
+
+def test_discovery() -> None:
+    engine = get_engine()
+    data = encode_message(
+        SnmpVersion.v3, "GetRequest", 1, [], flag_report=True
+    )
+    reply, notification = engine.process(data)
+    assert notification is None
+    assert reply is not None
+    msg = decode_message(reply)
+    assert msg["usm"]["engine_id"] == ENGINE_ID
+    assert msg["pdu"]["type"] == "Report"
+
+
+def test_inform() -> None:
+    engine = get_engine()
+    data = encode_message(
+        SnmpVersion.v3,
+        "InformRequest",
+        16,
+        VARBINDS,
+        user=USER,
+        engine_id=ENGINE_ID,
+        engine_boots=engine.get_engine_boots(),
+        engine_time=engine.get_engine_time(),
+        flag_report=True,
+    )
+    reply, notification = engine.process(data)
+    assert notification == (
+        ENGINE_ID,
+        USER.name,
+        [
+            ("1.3.6.1.2.1.1.3.0", 100),
+            ("1.3.6.1.6.3.1.1.4.1.0", "1.3.6.1.6.3.1.1.5.1"),
+        ],
+    )
+    # Inform is confirmed by the authenticated and encrypted response
+    assert reply is not None
+    msg = decode_message(reply, USER)
+    assert msg["flag_auth"] is True
+    assert msg["flag_priv"] is True
+    assert msg["msg_id"] == 16
+    assert msg["pdu"]["type"] == "GetResponse"
+    assert msg["pdu"]["request_id"] == 16
+    assert msg["pdu"]["varbinds"] == [
+        ("1.3.6.1.2.1.1.3.0", 100),
+        ("1.3.6.1.6.3.1.1.4.1.0", "1.3.6.1.6.3.1.1.5.1"),
+    ]
+
+
+def test_inform_wrong_engine_time() -> None:
+    engine = get_engine()
+    data = encode_message(
+        SnmpVersion.v3,
+        "InformRequest",
+        16,
+        VARBINDS,
+        user=USER,
+        engine_id=ENGINE_ID,
+        engine_boots=engine.get_engine_boots(),
+        engine_time=engine.get_engine_time() + 1000,
+        flag_report=True,
+    )
+    reply, notification = engine.process(data)
+    # Not confirmed, notInTimeWindows report is sent instead
+    assert notification is None
+    assert reply is not None
+    msg = decode_message(reply)
+    assert msg["pdu"]["type"] == "Report"
+    assert msg["pdu"]["varbinds"][0][0] == "1.3.6.1.6.3.15.1.1.2.0"
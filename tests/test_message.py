@@ -0,0 +1,272 @@
+# ---------------------------------------------------------------------
+# Gufo SNMP: Test raw message encoding and decoding
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+# Python modules
+from typing import Any
+
+# Third-party modules
+import pytest
+
+# Gufo SNMP modules
+from gufo.snmp import (
+    Aes128Key,
+    SnmpDecodeError,
+    SnmpDecryptionError,
+    SnmpVersion,
+    decode_message,
+    encode_message,
+)
+from gufo.snmp._fast import encode_message as _encode_message
+from gufo.snmp.message import DEFAULT_MAX_SIZE
+from gufo.snmp.user import Sha1Key, User
+
+V2C_GET = bytes.fromhex(
+    "3026020101040670756"
+    "26c6963a019020412345678020100020100300b300906052b060102010500"
+)
+V1_RESPONSE = bytes.fromhex(
+    "30290201000406707562"
+    "6c6963a21c020105020100020100301130"
+    "0f06082b060102010103004303010203"
+)
+V3_ENCRYPTED = bytes.fromhex(
+    "307b020103301002040585c58b020205dc040107020103043230300409"
+    "80001f880474657374020100020100040575736572310"
+    "40c5bd29e77e06261df70f2866604"
+    "08153bdcbdf99a6ac60430b3859cd52088cf1ffa5047b445b787681b8b64"
+    "67d1d461105c658af7f12caad5c04b66c602a8e72445c1cf19b59ea649"
+)
+V1_TRAP = bytes.fromhex(
+    "303602010004067075626c6963a42906082b"
+    "060104018f65014004c0a800010201020201"
+    "0043023039300d300b06062b060102010202"
+    "0102"
+)
+USER = User(
+    "user1", auth_key=Sha1Key(b"secret12"), priv_key=Aes128Key(b"private1")
+)
+
+
+def test_v2c() -> None:
+    msg = decode_message(V2C_GET)
+    assert msg["version"] == SnmpVersion.v2c
+    assert msg["community"] == b"public"
+    assert msg["pdu"] == {
+        "type": "GetRequest",
+        "request_id": 0x12345678,
+        "error_status": 0,
+        "error_index": 0,
+        "varbinds": [("1.3.6.1.2.1", None)],
+    }
+
+
+def test_v1() -> None:
+    msg = decode_message(V1_RESPONSE)
+    assert msg["version"] == SnmpVersion.v1
+    pdu = msg["pdu"]
+    assert pdu["type"] == "GetResponse"
+    assert pdu["request_id"] == 5
+    assert pdu["varbinds"] == [("1.3.6.1.2.1.1.3.0", 0x010203)]
+
+
+def test_v1_trap() -> None:
+    msg = decode_message(V1_TRAP)
+    assert msg["version"] == SnmpVersion.v1
+    assert msg["pdu"] == {
+        "type": "Trap",
+        "request_id": 0,
+        "error_status": 0,
+        "error_index": 0,
+        "varbinds": [("1.3.6.1.2.1.2", 2)],
+        "enterprise": "1.3.6.1.4.1.2021.1",
+        "agent_addr": "192.168.0.1",
+        "generic_trap": 2,
+        "specific_trap": 0,
+        "time_stamp": 12345,
+    }
+
+
+def test_set_request() -> None:
+    data = encode_message(
+        SnmpVersion.v2c,
+        "SetRequest",
+        1,
+        [("1.3.6.1.2.1.1.5.0", "sw")],
+    )
+    pdu = decode_message(data)["pdu"]
+    assert pdu["type"] == "SetRequest"
+    assert pdu["varbinds"] == [("1.3.6.1.2.1.1.5.0", b"sw")]
+
+
+def test_encode_trap() -> None:
+    data = encode_message(
+        SnmpVersion.v1,
+        "Trap",
+        0,
+        [("1.3.6.1.2.1.2", 2)],
+        enterprise="1.3.6.1.4.1.2021.1",
+        agent_addr="192.168.0.1",
+        generic_trap=2,
+        time_stamp=12345,
+    )
+    assert data == V1_TRAP
+
+
+def test_encode_trap_no_enterprise() -> None:
+    with pytest.raises(SnmpDecodeError):
+        encode_message(SnmpVersion.v1, "Trap", 0, [])
+
+
+def test_v3_encrypted_no_user() -> None:
+    msg = decode_message(V3_ENCRYPTED)
+    assert msg["version"] == SnmpVersion.v3
+    assert msg["encrypted"] is True
+    assert msg["flag_auth"] is True
+    assert msg["flag_priv"] is True
+    assert msg["max_size"] == 1500
+    assert msg["usm"]["engine_id"] == b"\x80\x00\x1f\x88\x04test"
+    assert msg["usm"]["user_name"] == b"user1"
+    assert msg["pdu"] is None
+
+
+def test_v3_encrypted() -> None:
+    msg = decode_message(V3_ENCRYPTED, USER)
+    pdu = msg["pdu"]
+    assert pdu["type"] == "GetRequest"
+    assert pdu["request_id"] == 7
+    assert pdu["varbinds"] == [("1.3.6.1.2.1.1.5.0", None)]
+
+
+def test_v3_wrong_key() -> None:
+    user = User(
+        "user1",
+        auth_key=Sha1Key(b"secret12"),
+        priv_key=Aes128Key(b"private2"),
+    )
+    with pytest.raises(SnmpDecryptionError):
+        decode_message(V3_ENCRYPTED, user)
+
+
+@pytest.mark.parametrize(
+    "data", [b"", b"\x30\x03\x02\x01\x02", V2C_GET[:-3], b"\x04\x01x"]
+)
+def test_invalid(data: bytes) -> None:
+    with pytest.raises(SnmpDecodeError):
+        decode_message(data)
+
+
+def test_invalid_context() -> None:
+    data = V2C_GET[:5] + b"\x02" + V2C_GET[6:]
+    with pytest.raises(SnmpDecodeError) as exc:
+        decode_message(data)
+    assert exc.value.offset == 5
+    assert exc.value.expected == 0x04
+    assert exc.value.actual == 0x02
+    assert exc.value.path == "msg.community"
+
+
+def test_encode_v2c() -> None:
+    data = encode_message(
+        SnmpVersion.v2c, "GetRequest", 0x12345678, [("1.3.6.1.2.1", None)]
+    )
+    assert data == V2C_GET
+
+
+def test_encode_v1_response() -> None:
+    data = encode_message(
+        SnmpVersion.v1,
+        "GetResponse",
+        5,
+        [("1.3.6.1.2.1.1.3.0", ("TimeTicks", 0x010203))],
+    )
+    assert data == V1_RESPONSE
+
+
+@pytest.mark.parametrize(
+    ("value", "expected"),
+    [
+        (None, None),
+        (10, 10),
+        ("sw", b"sw"),
+        (b"\x00\x01", b"\x00\x01"),
+        (("Counter32", 7), 7),
+        (("Counter64", 2**40), 2**40),
+        (("Gauge32", 8), 8),
+        (("IpAddress", "127.0.0.1"), "127.0.0.1"),
+        (("Oid", "1.3.6.1"), "1.3.6.1"),
+        (("Opaque", b"\x01"), b"\x01"),
+        (("OpaqueFloat", 0.5), 0.5),
+        (("OpaqueFloat", 0.15), 0.15),
+        (("OpaqueDouble", 0.15), 0.15),
+        (("OpaqueCounter64", 2**40), 2**40),
+        (("OpaqueInt64", -(2**40)), -(2**40)),
+        (("OpaqueUInt64", 2**64 - 1), 2**64 - 1),
+        (("NsapAddress", b"\x47\x00\x05"), b"\x47\x00\x05"),
+        (("NoSuchObject", None), ("NoSuchObject", None)),
+        (("NoSuchInstance", None), ("NoSuchInstance", None)),
+        (("EndOfMibView", None), ("EndOfMibView", None)),
+    ],
+)
+def test_encode_values(value: Any, expected: Any) -> None:
+    data = encode_message(
+        SnmpVersion.v2c, "GetResponse", 1, [("1.3.6.1.2.1.1.1.0", value)]
+    )
+    msg = decode_message(data)
+    assert msg["pdu"]["varbinds"] == [("1.3.6.1.2.1.1.1.0", expected)]
+
+
+@pytest.mark.parametrize(
+    "value", [("Counter32", -1), ("IpAddress", "x"), ("Unknown", 1), object()]
+)
+def test_encode_invalid_value(value: Any) -> None:
+    with pytest.raises((ValueError, TypeError, OverflowError)):
+        encode_message(
+            SnmpVersion.v2c, "GetResponse", 1, [("1.3.6.1.2.1.1.1.0", value)]
+        )
+
+
+def test_encode_invalid_pdu_type() -> None:
+    with pytest.raises(ValueError):
+        encode_message(SnmpVersion.v2c, "Unknown", 1, [])
+
+
+def test_encode_v3_encrypted() -> None:
+    engine_id = b"\x80\x00\x1f\x88\x04test"
+    data = encode_message(
+        SnmpVersion.v3,
+        "GetRequest",
+        7,
+        [("1.3.6.1.2.1.1.5.0", None)],
+        user=USER,
+        engine_id=engine_id,
+        engine_boots=2,
+        engine_time=100,
+        flag_report=True,
+    )
+    msg = decode_message(data, USER)
+    assert msg["flag_auth"] is True
+    assert msg["flag_priv"] is True
+    assert msg["flag_report"] is True
+    assert msg["msg_id"] == 7
+    assert msg["usm"]["engine_boots"] == 2
+    assert msg["usm"]["engine_time"] == 100
+    assert msg["context_engine_id"] == engine_id
+    assert msg["pdu"]["varbinds"] == [("1.3.6.1.2.1.1.5.0", None)]
+
+
+def test_encode_v3_default_max_size() -> None:
+    data = encode_message(SnmpVersion.v3, "GetRequest", 7, [])
+    msg = decode_message(data)
+    assert msg["max_size"] == DEFAULT_MAX_SIZE
+
+
+def test_fast_encode_keyword_only() -> None:
+    args: Any = (1, "GetRequest", 0x12345678, [("1.3.6.1.2.1", None)])
+    with pytest.raises(TypeError):
+        _encode_message(*args, 0, 0, b"public")
+    data = _encode_message(*args)
+    assert data == V2C_GET
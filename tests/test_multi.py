@@ -0,0 +1,238 @@
+# ---------------------------------------------------------------------
+# Gufo Labs: Test multi-target socket
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+# Python modules
+import os
+import socket
+from typing import Any, List, Tuple
+
+# Third-party modules
+import pytest
+
+# Gufo Labs modules
+from gufo.snmp import NoSuchInstance
+from gufo.snmp._fast import GetIter, SnmpMultiClientSocket
+from gufo.snmp.hook import Packet, PacketDirection, wrap_hook
+from gufo.snmp.snmpd import Snmpd
+from gufo.snmp.user import User
+
+from .util import (
+    SNMP_COMMUNITY,
+    SNMP_CONTACT,
+    SNMP_CONTACT_OID,
+    SNMP_LOCATION,
+    SNMP_LOCATION_OID,
+    SNMP_USERS,
+    SNMPD_ADDRESS,
+    SNMPD_PORT,
+)
+
+TARGET = f"{SNMPD_ADDRESS}:{SNMPD_PORT}"
+# Nobody listens here
+SILENT_TARGET = f"{SNMPD_ADDRESS}:{SNMPD_PORT + 1}"
+TIMEOUT_NS = 1_000_000_000
+
+
+def get_socket() -> SnmpMultiClientSocket:
+    return SnmpMultiClientSocket(timeout_ns=TIMEOUT_NS)
+
+
+def test_v2c_get(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    assert sock.recv() == (TARGET, SNMP_LOCATION.encode())
+
+
+def test_v2c_get_many(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get_many(TARGET, [SNMP_LOCATION_OID, SNMP_CONTACT_OID])
+    assert sock.recv() == (
+        TARGET,
+        {
+            SNMP_LOCATION_OID: SNMP_LOCATION.encode(),
+            SNMP_CONTACT_OID: SNMP_CONTACT.encode(),
+        },
+    )
+
+
+def test_v2c_get_next(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get_next(TARGET, GetIter("1.3.6.1.2.1.1.6"))
+    assert sock.recv() == (
+        TARGET,
+        (SNMP_LOCATION_OID, SNMP_LOCATION.encode()),
+    )
+
+
+def test_v2c_nosuchinstance(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get(TARGET, "1.3.6.1.2.1.1.6")
+    addr, r = sock.recv()
+    assert addr == TARGET
+    assert isinstance(r, NoSuchInstance)
+
+
+def test_silent_target(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(SILENT_TARGET, SNMP_COMMUNITY)
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get(SILENT_TARGET, SNMP_LOCATION_OID)
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    assert sock.recv() == (TARGET, SNMP_LOCATION.encode())
+    # Timed out
+    with pytest.raises(BlockingIOError):
+        sock.recv()
+
+
+def test_v2c_get_batch(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(SILENT_TARGET, SNMP_COMMUNITY)
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sent = sock.send_get_batch(
+        [(SILENT_TARGET, SNMP_LOCATION_OID), (TARGET, SNMP_LOCATION_OID)]
+    )
+    assert sent == 2
+    assert sock.recv_batch() == [(TARGET, SNMP_LOCATION.encode())]
+
+
+def test_v2c_get_batch_unknown_target(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    with pytest.raises(KeyError):
+        sock.send_get_batch(
+            [(TARGET, SNMP_CONTACT_OID), (SILENT_TARGET, SNMP_CONTACT_OID)]
+        )
+    # Failed batch does not abandon the pending request
+    assert sock.recv() == (TARGET, SNMP_LOCATION.encode())
+
+
+def test_v2c_get_batch_duplicate_target(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    with pytest.raises(ValueError):
+        sock.send_get_batch(
+            [(TARGET, SNMP_CONTACT_OID), (TARGET, SNMP_LOCATION_OID)]
+        )
+    # Failed batch does not abandon the pending request
+    assert sock.recv() == (TARGET, SNMP_LOCATION.encode())
+
+
+def test_v2c_get_many_batch(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sent = sock.send_get_many_batch([(TARGET, [SNMP_CONTACT_OID])])
+    assert sent == 1
+    assert sock.recv_batch(8) == [
+        (TARGET, {SNMP_CONTACT_OID: SNMP_CONTACT.encode()})
+    ]
+
+
+def test_packet_hook(snmpd: Snmpd) -> None:
+    packets: List[Packet] = []
+    sock = SnmpMultiClientSocket(
+        timeout_ns=TIMEOUT_NS, packet_hook=wrap_hook(packets.append)
+    )
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    sock.recv()
+    sent, received = packets
+    assert sent.direction == PacketDirection.SENT
+    assert received.direction == PacketDirection.RECEIVED
+    assert sent.peer == received.peer == TARGET
+    assert sent.pdu is not None
+    assert received.pdu is not None
+    assert received.pdu["request_id"] == sent.pdu["request_id"]
+    assert not received.discarded
+
+
+def test_packet_hook_batch(snmpd: Snmpd) -> None:
+    packets: List[Tuple[Any, ...]] = []
+    sock = SnmpMultiClientSocket(
+        timeout_ns=TIMEOUT_NS, packet_hook=lambda *args: packets.append(args)
+    )
+    sock.add_v2c_target(SILENT_TARGET, SNMP_COMMUNITY)
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.send_get_batch(
+        [(SILENT_TARGET, SNMP_LOCATION_OID), (TARGET, SNMP_LOCATION_OID)]
+    )
+    sock.recv_batch()
+    assert [(x[0], x[1], x[4]) for x in packets] == [
+        ("send", SILENT_TARGET, False),
+        ("send", TARGET, False),
+        ("recv", TARGET, False),
+    ]
+
+
+def test_packet_hook_unknown_target(snmpd: Snmpd) -> None:
+    packets: List[Packet] = []
+    sock = SnmpMultiClientSocket(
+        timeout_ns=TIMEOUT_NS, packet_hook=wrap_hook(packets.append)
+    )
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    with socket.socket(fileno=os.dup(sock.get_fd())) as s:
+        local = s.getsockname()
+    # Stray datagram
+    with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as stray:
+        stray.bind((SNMPD_ADDRESS, 0))
+        stray.sendto(b"\x30\x00", (SNMPD_ADDRESS, local[1]))
+        stray_addr = "{}:{}".format(*stray.getsockname())
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    assert sock.recv() == (TARGET, SNMP_LOCATION.encode())
+    assert [(p.direction, p.peer, p.discarded) for p in packets] == [
+        (PacketDirection.SENT, TARGET, False),
+        (PacketDirection.RECEIVED, stray_addr, True),
+        (PacketDirection.RECEIVED, TARGET, False),
+    ]
+
+
+@pytest.mark.parametrize("user", SNMP_USERS, ids=lambda x: x.name)
+def test_v3_get(user: User, snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.add_v3_target(
+        TARGET,
+        snmpd.engine_id,
+        user.name,
+        user.get_auth_alg(),
+        user.get_auth_key(),
+        user.get_priv_alg(),
+        user.get_priv_key(),
+    )
+    sock.send_refresh(TARGET)
+    assert sock.recv() == (TARGET, None)
+    sock.send_get(TARGET, SNMP_LOCATION_OID)
+    assert sock.recv() == (TARGET, SNMP_LOCATION.encode())
+
+
+def test_remove_target() -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    sock.remove_target(TARGET)
+    with pytest.raises(KeyError):
+        sock.send_get(TARGET, SNMP_LOCATION_OID)
+
+
+def test_unknown_target() -> None:
+    with pytest.raises(KeyError):
+        get_socket().send_get(TARGET, SNMP_LOCATION_OID)
+
+
+def test_family_mismatch() -> None:
+    with pytest.raises(OSError, match="address family mismatch"):
+        get_socket().add_v2c_target("[::1]:161", SNMP_COMMUNITY)
+
+
+def test_not_v3() -> None:
+    sock = get_socket()
+    sock.add_v2c_target(TARGET, SNMP_COMMUNITY)
+    with pytest.raises(ValueError, match="not an SNMPv3 target"):
+        sock.get_engine_id(TARGET)
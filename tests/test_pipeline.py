@@ -0,0 +1,123 @@
+# ---------------------------------------------------------------------
+# Gufo Labs: Test pipelined requests
+# ---------------------------------------------------------------------
+# Copyright (C) 2026, Gufo Labs
+# See LICENSE.md for details
+# ---------------------------------------------------------------------
+
+# Third-party modules
+import pytest
+
+# Gufo Labs modules
+from gufo.snmp import NoSuchInstance
+from gufo.snmp._fast import GetIter, SnmpV2cClientSocket, SnmpV3ClientSocket
+from gufo.snmp.snmpd import Snmpd
+from gufo.snmp.user import User
+
+from .util import (
+    SNMP_COMMUNITY,
+    SNMP_CONTACT,
+    SNMP_CONTACT_OID,
+    SNMP_LOCATION,
+    SNMP_LOCATION_OID,
+    SNMP_USERS,
+    SNMPD_ADDRESS,
+    SNMPD_PORT,
+)
+
+TIMEOUT_NS = 1_000_000_000
+
+
+def get_socket() -> SnmpV2cClientSocket:
+    return SnmpV2cClientSocket(
+        f"{SNMPD_ADDRESS}:{SNMPD_PORT}", SNMP_COMMUNITY, 0, 0, 0, TIMEOUT_NS
+    )
+
+
+def get_v3_socket(user: User, snmpd: Snmpd) -> SnmpV3ClientSocket:
+    sock = SnmpV3ClientSocket(
+        f"{SNMPD_ADDRESS}:{SNMPD_PORT}",
+        snmpd.engine_id,
+        user.name,
+        user.get_auth_alg(),
+        user.get_auth_key(),
+        user.get_priv_alg(),
+        user.get_priv_key(),
+        0,
+        0,
+        0,
+        TIMEOUT_NS,
+    )
+    sock.refresh()
+    return sock
+
+
+def test_pipeline(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    it = GetIter("1.3.6.1.2.1.1.6")
+    expected = {
+        sock.send_get(SNMP_LOCATION_OID): SNMP_LOCATION.encode(),
+        sock.send_get_many([SNMP_CONTACT_OID]): {
+            SNMP_CONTACT_OID: SNMP_CONTACT.encode()
+        },
+        sock.send_get_next(it): (SNMP_LOCATION_OID, SNMP_LOCATION.encode()),
+    }
+    assert len(expected) == 3
+    r = dict(sock.recv() for _ in range(len(expected)))
+    assert r == expected
+
+
+def test_pipeline_error(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    req_id = sock.send_get("1.3.6.1.2.1.1.6")
+    r_id, r = sock.recv()
+    assert r_id == req_id
+    assert isinstance(r, NoSuchInstance)
+
+
+def test_pipeline_timeout(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    sock.send_get(SNMP_LOCATION_OID)
+    sock.recv()
+    with pytest.raises(BlockingIOError):
+        sock.recv()
+
+
+def test_pipeline_kept_reply(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    first = sock.send_get(SNMP_LOCATION_OID)
+    second = sock.send_get(SNMP_CONTACT_OID)
+    # Reply to the first request is kept
+    assert sock.recv_get(second) == SNMP_CONTACT.encode()
+    assert sock.recv() == (first, SNMP_LOCATION.encode())
+
+
+@pytest.mark.parametrize("user", SNMP_USERS, ids=lambda x: x.name)
+def test_v3_pipeline(user: User, snmpd: Snmpd) -> None:
+    sock = get_v3_socket(user, snmpd)
+    expected = {
+        sock.send_get(SNMP_LOCATION_OID): SNMP_LOCATION.encode(),
+        sock.send_get_many([SNMP_CONTACT_OID]): {
+            SNMP_CONTACT_OID: SNMP_CONTACT.encode()
+        },
+    }
+    r = dict(sock.recv() for _ in range(len(expected)))
+    assert r == expected
+
+
+@pytest.mark.parametrize("user", SNMP_USERS, ids=lambda x: x.name)
+def test_v3_pipeline_kept_reply(user: User, snmpd: Snmpd) -> None:
+    sock = get_v3_socket(user, snmpd)
+    first = sock.send_get(SNMP_LOCATION_OID)
+    second = sock.send_get(SNMP_CONTACT_OID)
+    # Reply to the first request is kept, though msgID is released
+    assert sock.recv_get(second) == SNMP_CONTACT.encode()
+    assert sock.recv() == (first, SNMP_LOCATION.encode())
+
+
+def test_pipeline_recv_first(snmpd: Snmpd) -> None:
+    sock = get_socket()
+    first = sock.send_get(SNMP_LOCATION_OID)
+    sock.send_get(SNMP_CONTACT_OID)
+    # Reply to the earlier request
+    assert sock.recv_get(first) == SNMP_LOCATION.encode()
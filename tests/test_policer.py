@@ -1,19 +1,20 @@
 # ---------------------------------------------------------------------
 # Gufo Labs: Test Gufo SNMP
 # ---------------------------------------------------------------------
-# Copyright (C) 2023, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
 # Python modules
 import asyncio
 from time import perf_counter_ns
+from typing import Dict
 
 # Third-party modules
 import pytest
 
 # Gufo Labs modules
-from gufo.snmp.policer import BasePolicer, RPSPolicer
+from gufo.snmp.policer import BasePolicer, RPSPolicer, TokenBucketPolicer
 
 
 def test_base_instance() -> None:
@@ -82,3 +83,34 @@ def test_rps_flood_sync() -> None:
     delta = perf_counter_ns() - t0
     # Check duration
     assert delta >= duration * 1_000_000_000
+
+
+@pytest.mark.parametrize(
+    "kwargs",
+    [
+        {},
+        {"rate": 0.0},
+        {"rate": -1.0},
+        {"rate": 10.0, "burst": 0.5},
+        {"target_rate": 0.0},
+        {"target_rate": 10.0, "target_burst": 0.0},
+    ],
+)
+def test_token_bucket_invalid(kwargs: Dict[str, float]) -> None:
+    with pytest.raises(ValueError):
+        TokenBucketPolicer(**kwargs)
+
+
+def test_token_bucket_global() -> None:
+    p = TokenBucketPolicer(rate=10.0, burst=2.0)
+    assert p.reserve_ns("127.0.0.1:161") == 0
+    assert p.reserve_ns("127.0.0.2:161") == 0
+    # Bucket is empty, shared between targets
+    assert p.reserve_ns("127.0.0.3:161") > 50_000_000
+
+
+def test_token_bucket_target() -> None:
+    p = TokenBucketPolicer(target_rate=10.0)
+    assert p.reserve_ns("127.0.0.1:161") == 0
+    assert p.reserve_ns("127.0.0.2:161") == 0
+    assert p.reserve_ns("127.0.0.1:161") > 50_000_000
@@ -1,35 +1,53 @@
 # ---------------------------------------------------------------------
 # Gufo Labs: Test Gufo SNMP async client
 # ---------------------------------------------------------------------
-# Copyright (C) 2023-24, Gufo Labs
+# Copyright (C) 2023-26, Gufo Labs
 # See LICENSE.md for details
 # ---------------------------------------------------------------------
 
 # Python modules
 import asyncio
 import sys
-from typing import Any, Dict, Optional, cast
+import time
+from typing import Any, Dict, List, Optional, cast
 
 # Third-party modules
 import pytest
 
 # Gufo Labs modules
-from gufo.snmp import NoSuchInstance, SnmpAuthError, ValueType
+from gufo.snmp import (
+    FamilyPreference,
+    NoSuchInstance,
+    Packet,
+    PacketDirection,
+    PortUnreachableError,
+    SnmpAuthError,
+    SnmpDecryptionError,
+    Transport,
+    ValueType,
+)
 from gufo.snmp.async_client import SnmpSession
+from gufo.snmp.policer import TokenBucketPolicer
 from gufo.snmp.snmpd import Snmpd
 
 from .util import (
     ALL,
     SNMP_CONTACT,
+    SNMP_CONTACT_OID,
     SNMP_LOCATION,
     SNMP_LOCATION_OID,
     SNMP_SYSTEM_OID,
     SNMPD_ADDRESS,
     SNMPD_PORT,
+    SNMPD_TCP_PORT,
+    SNMPD_UNIX_PATH,
     UNAUTH_V3_USER,
     V1,
     V2,
     V3,
+    WRONG_PRIV_V3_USER,
+    get_free_port,
+    get_local_addr,
     ids,
 )
 
@@ -70,6 +88,109 @@ def test_timeout_get_many(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
         asyncio.run(inner())
 
 
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_timeout_per_request(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> None:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT + 1,
+            timeout=10.0,
+            **cfg,
+        ) as session:
+            with pytest.raises(TimeoutError):
+                await session.get("1.3.6.1.2.1.1", timeout=0.5)
+            with pytest.raises(TimeoutError):
+                await session.get_many(["1.3.6.1.2.1.1"], timeout=0.5)
+
+    t0 = time.monotonic()
+    asyncio.run(inner())
+    assert time.monotonic() - t0 < 3.0
+
+
+@pytest.mark.skipif(sys.platform != "linux", reason="Linux only")
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_port_unreachable(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> ValueType:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT + 1,
+            timeout=10.0,
+            **cfg,
+        ) as session:
+            return await session.get(SNMP_LOCATION_OID)
+
+    t0 = time.monotonic()
+    with pytest.raises(PortUnreachableError) as e:
+        asyncio.run(inner())
+    assert e.value.offender == SNMPD_ADDRESS
+    # Not waiting for timeout
+    assert time.monotonic() - t0 < 5.0
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_get_rtt(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> Optional[float]:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT,
+            timeout=1.0,
+            engine_id=snmpd.engine_id,
+            **cfg,
+        ) as session:
+            await session.get(SNMP_LOCATION_OID)
+            return session.get_rtt()
+
+    rtt = asyncio.run(inner())
+    assert rtt is not None
+    assert 0.0 < rtt < 1.0
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_packet_hook(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    packets: List[Packet] = []
+
+    async def inner() -> None:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT,
+            timeout=1.0,
+            engine_id=snmpd.engine_id,
+            packet_hook=packets.append,
+            **cfg,
+        ) as session:
+            await session.get(SNMP_LOCATION_OID)
+
+    asyncio.run(inner())
+    sent, received = packets[-2:]
+    assert sent.direction == PacketDirection.SENT
+    assert received.direction == PacketDirection.RECEIVED
+    assert sent.pdu is not None
+    assert received.pdu is not None
+    assert received.pdu["type"] == "GetResponse"
+    assert received.pdu["request_id"] == sent.pdu["request_id"]
+    assert not received.discarded
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_token_bucket_policer(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> float:
+        policer = TokenBucketPolicer(target_rate=10.0)
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT,
+            timeout=1.0,
+            engine_id=snmpd.engine_id,
+            policer=policer,
+            **cfg,
+        ) as session:
+            t0 = time.perf_counter()
+            for _ in range(3):
+                await session.get(SNMP_LOCATION_OID)
+            return time.perf_counter() - t0
+
+    assert asyncio.run(inner()) >= 0.2
+
+
 async def snmp_get(
     cfg: Dict[str, Any], engine_id: Optional[bytes], oid: str
 ) -> ValueType:
@@ -103,6 +224,78 @@ def test_get(
     assert r == expected
 
 
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_get_tcp(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> None:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_TCP_PORT,
+            timeout=1.0,
+            engine_id=snmpd.engine_id,
+            transport=Transport.TCP,
+            **cfg,
+        ) as session:
+            r = await session.get(SNMP_LOCATION_OID)
+            assert r == SNMP_LOCATION.encode()
+            r = await session.get(SNMP_CONTACT_OID)
+            assert r == SNMP_CONTACT.encode()
+
+    asyncio.run(inner())
+
+
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_get_bind(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    port = get_free_port()
+
+    async def inner() -> ValueType:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT,
+            timeout=1.0,
+            bind_addr=SNMPD_ADDRESS,
+            bind_port=port,
+            **cfg,
+        ) as session:
+            # Explicit port is never chosen by kernel
+            assert get_local_addr(session._fd) == (SNMPD_ADDRESS, port)
+            return await session.get(SNMP_LOCATION_OID)
+
+    assert asyncio.run(inner()) == SNMP_LOCATION.encode()
+
+
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_get_hostname(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> None:
+        async with SnmpSession(
+            addr="localhost",
+            port=SNMPD_PORT,
+            timeout=1.0,
+            family=FamilyPreference.IPV4,
+            **cfg,
+        ) as session:
+            assert session.get_peer_addr() == f"127.0.0.1:{SNMPD_PORT}"
+            r = await session.get(SNMP_LOCATION_OID)
+            assert r == SNMP_LOCATION.encode()
+
+    asyncio.run(inner())
+
+
+# Net-SNMP applies community access only to IP transports
+@pytest.mark.parametrize("cfg", V3, ids=ids)
+def test_get_unix(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    async def inner() -> ValueType:
+        async with SnmpSession(
+            addr=SNMPD_UNIX_PATH,
+            timeout=1.0,
+            engine_id=snmpd.engine_id,
+            transport=Transport.TCP,
+            **cfg,
+        ) as session:
+            return await session.get(SNMP_LOCATION_OID)
+
+    assert asyncio.run(inner()) == SNMP_LOCATION.encode()
+
+
 @pytest.mark.parametrize("cfg", V3, ids=ids)
 @pytest.mark.parametrize(
     ("oid", "expected"),
@@ -443,3 +636,17 @@ def test_getmany_auth_error() -> None:
                 await session.get_many([SNMP_LOCATION_OID, SNMP_SYSTEM_OID])
 
     asyncio.run(inner())
+
+
+def test_get_decryption_error(snmpd: Snmpd) -> None:
+    async def inner() -> None:
+        async with SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT,
+            timeout=1.0,
+            user=WRONG_PRIV_V3_USER,
+        ) as session:
+            with pytest.raises(SnmpDecryptionError):
+                await session.get(SNMP_LOCATION_OID)
+
+    asyncio.run(inner())
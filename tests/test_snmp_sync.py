@@ -7,13 +7,25 @@
 
 # Python modules
 import sys
-from typing import Any, Dict, Optional, cast
+import time
+from typing import Any, Dict, List, Optional, cast
 
 # Third-party modules
 import pytest
 
 # Gufo Labs modules
-from gufo.snmp import NoSuchInstance, SnmpAuthError, ValueType
+from gufo.snmp import (
+    FamilyPreference,
+    NoSuchInstance,
+    Packet,
+    PacketDirection,
+    PortUnreachableError,
+    SnmpAuthError,
+    SnmpDecryptionError,
+    Transport,
+    ValueType,
+)
+from gufo.snmp.policer import TokenBucketPolicer
 from gufo.snmp.snmpd import Snmpd
 from gufo.snmp.sync_client import SnmpSession
 
@@ -26,11 +38,16 @@ from .util import (
     SNMP_SYSTEM_OID,
     SNMPD_ADDRESS,
     SNMPD_PORT,
+    SNMPD_TCP_PORT,
+    SNMPD_UNIX_PATH,
     UNAUTH_V3_USER,
     V1,
     V2,
     V3,
+    WRONG_PRIV_V3_USER,
     SyncShiftProxy,
+    get_free_port,
+    get_local_addr,
     ids,
 )
 
@@ -69,6 +86,150 @@ def test_timeout_get_many(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
         session.get_many(["1.3.6.1.2.1.1"])
 
 
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_timeout_retries(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT + 1,
+        timeout=1.0,
+        retries=2,
+        backoff=2.0,
+        engine_id=snmpd.engine_id,
+        **cfg,
+    ) as session:
+        t0 = time.monotonic()
+        with pytest.raises(TimeoutError):
+            session.get("1.3.6.1.2.1.1")
+        # Bounded by overall timeout
+        assert time.monotonic() - t0 < 1.5
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_get_retries(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT,
+        timeout=3.0,
+        retries=2,
+        retry_timeout=0.5,
+        backoff=2.0,
+        engine_id=snmpd.engine_id,
+        **cfg,
+    ) as session:
+        assert session.get(SNMP_LOCATION_OID) == SNMP_LOCATION.encode()
+
+
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_timeout_per_request(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT + 1,
+        timeout=10.0,
+        **cfg,
+    ) as session:
+        t0 = time.monotonic()
+        with pytest.raises(TimeoutError):
+            session.get("1.3.6.1.2.1.1", timeout=0.5)
+        with pytest.raises(TimeoutError):
+            session.get_many(["1.3.6.1.2.1.1"], timeout=0.5)
+        with pytest.raises(TimeoutError):
+            list(session.getnext("1.3.6.1.2.1.1", timeout=0.5))
+        assert time.monotonic() - t0 < 3.0
+
+
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_get_per_request_timeout(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT,
+        timeout=0.001,
+        **cfg,
+    ) as session:
+        assert (
+            session.get(SNMP_LOCATION_OID, timeout=1.0)
+            == SNMP_LOCATION.encode()
+        )
+
+
+@pytest.mark.skipif(sys.platform != "linux", reason="Linux only")
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_port_unreachable(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT + 1,
+        timeout=10.0,
+        **cfg,
+    ) as session:
+        t0 = time.monotonic()
+        with pytest.raises(PortUnreachableError) as e:
+            session.get(SNMP_LOCATION_OID)
+        assert e.value.offender == SNMPD_ADDRESS
+        # Not waiting for timeout
+        assert time.monotonic() - t0 < 5.0
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_get_rtt(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT,
+        timeout=1.0,
+        engine_id=snmpd.engine_id,
+        **cfg,
+    ) as session:
+        session.get(SNMP_LOCATION_OID)
+        rtt = session.get_rtt()
+        assert rtt is not None
+        assert 0.0 < rtt < 1.0
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_packet_hook(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    packets: List[Packet] = []
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT,
+        timeout=1.0,
+        engine_id=snmpd.engine_id,
+        packet_hook=packets.append,
+        **cfg,
+    ) as session:
+        session.get(SNMP_LOCATION_OID)
+    sent, received = packets[-2:]
+    assert sent.direction == PacketDirection.SENT
+    assert received.direction == PacketDirection.RECEIVED
+    assert sent.peer == received.peer == f"{SNMPD_ADDRESS}:{SNMPD_PORT}"
+    assert sent.data
+    assert received.data
+    assert sent.pdu is not None
+    assert sent.pdu["type"] == "GetRequest"
+    assert received.pdu is not None
+    assert received.pdu["type"] == "GetResponse"
+    assert received.pdu["request_id"] == sent.pdu["request_id"]
+    assert received.pdu["varbinds"] == [
+        (SNMP_LOCATION_OID, SNMP_LOCATION.encode())
+    ]
+    assert not sent.discarded
+    assert not received.discarded
+
+
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_token_bucket_policer(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    policer = TokenBucketPolicer(target_rate=10.0)
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT,
+        timeout=1.0,
+        engine_id=snmpd.engine_id,
+        policer=policer,
+        **cfg,
+    ) as session:
+        t0 = time.perf_counter()
+        for _ in range(3):
+            session.get(SNMP_LOCATION_OID)
+        assert time.perf_counter() - t0 >= 0.2
+
+
 def snmp_get(
     cfg: Dict[str, Any], engine_id: Optional[bytes], oid: str
 ) -> ValueType:
@@ -97,6 +258,62 @@ def test_get(
     assert r == expected
 
 
+@pytest.mark.parametrize("cfg", ALL, ids=ids)
+def test_get_tcp(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_TCP_PORT,
+        timeout=1.0,
+        engine_id=snmpd.engine_id,
+        transport=Transport.TCP,
+        **cfg,
+    ) as session:
+        assert session.get(SNMP_LOCATION_OID) == SNMP_LOCATION.encode()
+        assert session.get(SNMP_CONTACT_OID) == SNMP_CONTACT.encode()
+
+
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_get_bind(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    port = get_free_port()
+    with SnmpSession(
+        addr=SNMPD_ADDRESS,
+        port=SNMPD_PORT,
+        timeout=1.0,
+        bind_addr=SNMPD_ADDRESS,
+        bind_port=port,
+        **cfg,
+    ) as session:
+        # Explicit port is never chosen by kernel
+        assert get_local_addr(session._fd) == (SNMPD_ADDRESS, port)
+        assert session.get(SNMP_LOCATION_OID) == SNMP_LOCATION.encode()
+
+
+@pytest.mark.parametrize("cfg", V2, ids=ids)
+def test_get_hostname(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr="localhost",
+        port=SNMPD_PORT,
+        timeout=1.0,
+        family=FamilyPreference.IPV4,
+        **cfg,
+    ) as session:
+        assert session.get_peer_addr() == f"127.0.0.1:{SNMPD_PORT}"
+        assert session.get(SNMP_LOCATION_OID) == SNMP_LOCATION.encode()
+
+
+# Net-SNMP applies community access only to IP transports
+@pytest.mark.parametrize("cfg", V3, ids=ids)
+def test_get_unix(cfg: Dict[str, Any], snmpd: Snmpd) -> None:
+    with SnmpSession(
+        addr=SNMPD_UNIX_PATH,
+        timeout=1.0,
+        engine_id=snmpd.engine_id,
+        transport=Transport.TCP,
+        **cfg,
+    ) as session:
+        assert session.get(SNMP_LOCATION_OID) == SNMP_LOCATION.encode()
+
+
 @pytest.mark.parametrize("cfg", V3, ids=ids)
 @pytest.mark.parametrize(
     ("oid", "expected"),
@@ -380,3 +597,16 @@ def test_getmany_auth_error() -> None:
         pytest.raises(SnmpAuthError),
     ):
         session.get_many([SNMP_LOCATION_OID, SNMP_SYSTEM_OID])
+
+
+def test_get_decryption_error(snmpd: Snmpd) -> None:
+    with (
+        SnmpSession(
+            addr=SNMPD_ADDRESS,
+            port=SNMPD_PORT,
+            timeout=1.0,
+            user=WRONG_PRIV_V3_USER,
+        ) as session,
+        pytest.raises(SnmpDecryptionError),
+    ):
+        session.get(SNMP_LOCATION_OID)
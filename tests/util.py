@@ -6,8 +6,10 @@
 # ---------------------------------------------------------------------
 
 # Python modules
+import os
 import random
 import socket
+import tempfile
 import threading
 import time
 from contextlib import suppress
@@ -30,6 +32,10 @@ from gufo.snmp.user import (
 
 SNMPD_ADDRESS = "127.0.0.1"
 SNMPD_PORT = random.randint(52000, 53999)
+SNMPD_TCP_PORT = SNMPD_PORT + 2
+SNMPD_UNIX_PATH = os.path.join(
+    tempfile.gettempdir(), f"gufo-snmp-{SNMPD_PORT}.sock"
+)
 SNMPD_PATH = "/usr/sbin/snmpd"
 SNMP_COMMUNITY = "public"
 SNMP_LOCATION = "Gufo SNMP Test"
@@ -141,6 +147,12 @@ V3 = [{"version": SnmpVersion.v3, "user": u} for u in SNMP_USERS]
 AUTO_V = [{"community": SNMP_COMMUNITY}, {"user": SNMP_USERS[0]}]
 ALL = V1 + V2 + V3 + AUTO_V
 UNAUTH_V3_USER = User(name="user2121")
+# Valid user and auth key, wrong privacy key
+WRONG_PRIV_V3_USER = User(
+    name="user1020",
+    auth_key=Md5Key(b"user1020pass"),
+    priv_key=Aes128Key(b"WRONGPRIVPASS"),
+)
 
 
 def ids(x: Any) -> str:
@@ -157,6 +169,19 @@ def ids(x: Any) -> str:
     return str(x)
 
 
+def get_free_port() -> int:
+    """Get currently unused local UDP port."""
+    with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+        sock.bind((SNMPD_ADDRESS, 0))
+        return sock.getsockname()[1]
+
+
+def get_local_addr(fd: int) -> Tuple[str, int]:
+    """Get local address of the IPv4 UDP socket by file descriptor."""
+    with socket.fromfd(fd, socket.AF_INET, socket.SOCK_DGRAM) as sock:
+        return sock.getsockname()
+
+
 class SyncShiftProxy(object):
     """
     A shifting proxy, sync version.